@@ -0,0 +1,1030 @@
+use std::{
+    collections::HashMap,
+    ffi::OsStr,
+    fmt::Display,
+    path::{Path, PathBuf},
+};
+
+use unicode_normalization::UnicodeNormalization;
+
+use crate::batch::collisions;
+use crate::file::reg::compile;
+use crate::file::{
+    sanitize::{reserved_on_windows, WINDOWS_INVALID},
+    Case, CaseOptions, ExtensionOptions, File, NameOptions, Options, RegexOptions, ReleaseOptions,
+    ReplaceOptions,
+};
+
+/// Share of the inspected names a pattern must appear in before a rule is suggested.
+const SUGGESTION_THRESHOLD: f64 = 0.5;
+
+/// How two new file names resemble each other.
+/// - `Case` - The names only differ by capitalization, e.g. `Photo.jpg` and `photo.jpg`.
+/// - `Whitespace` - The names only differ by spaces, e.g. `my file.txt` and `myfile.txt`.
+/// - `Normalization` - The names are the same text stored in different Unicode forms (NFC/NFD).
+///
+/// Names differing in several of these ways are reported as `Whitespace`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Similarity {
+    Case,
+    Whitespace,
+    Normalization,
+}
+
+/// Two different source files that will end up with near-duplicate names in the same folder.
+/// `first` and `second` are the original paths of the files.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SimilarityWarning {
+    pub first: PathBuf,
+    pub second: PathBuf,
+    pub similarity: Similarity,
+}
+
+impl Display for SimilarityWarning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let reason = match self.similarity {
+            Similarity::Case => "case",
+            Similarity::Whitespace => "whitespace",
+            Similarity::Normalization => "Unicode normalization",
+        };
+        write!(
+            f,
+            "{} and {} will only differ by {reason}",
+            self.first.display(),
+            self.second.display()
+        )
+    }
+}
+
+/// Find source files whose new names only differ by case, whitespace, or Unicode
+/// normalization form. Such names are technically unique but behave differently
+/// on case-insensitive or normalizing filesystems (Windows, macOS).
+///
+/// `batch` is a list of `(original, new)` paths. Identical new names are collisions,
+/// not near-duplicates, and are not reported here.
+///
+/// # Example
+///
+/// ```
+/// # use mass_renamer_core::analysis::{similar_names, Similarity};
+/// let batch = [("a.txt", "Photo.jpg"), ("b.txt", "photo.jpg"), ("c.txt", "other.jpg")];
+/// let warnings = similar_names(&batch);
+/// assert_eq!(warnings.len(), 1);
+/// assert_eq!(warnings[0].similarity, Similarity::Case);
+/// ```
+pub fn similar_names<P: AsRef<Path>, Q: AsRef<Path>>(batch: &[(P, Q)]) -> Vec<SimilarityWarning> {
+    let mut groups: HashMap<(Option<&Path>, String), Group> = HashMap::new();
+    for (original, new) in batch {
+        let new = new.as_ref();
+        let name = new
+            .file_name()
+            .map(OsStr::to_string_lossy)
+            .unwrap_or_default()
+            .into_owned();
+        groups
+            .entry((new.parent(), similarity_key(&name)))
+            .or_default()
+            .push((original.as_ref(), name));
+    }
+    let mut warnings = vec![];
+    for group in groups.values().filter(|group| group.len() > 1) {
+        for (idx, (first, lhs)) in group.iter().enumerate() {
+            for (second, rhs) in &group[idx + 1..] {
+                if lhs != rhs && first != second {
+                    warnings.push(SimilarityWarning {
+                        first: first.to_path_buf(),
+                        second: second.to_path_buf(),
+                        similarity: similarity(lhs, rhs),
+                    });
+                }
+            }
+        }
+    }
+    warnings.sort_unstable_by(|lhs, rhs| (&lhs.first, &lhs.second).cmp(&(&rhs.first, &rhs.second)));
+    warnings
+}
+
+/// The original paths and new names of files sharing a similarity key.
+type Group<'a> = Vec<(&'a Path, String)>;
+
+/// Reduce a name to the form that case-insensitive, normalizing filesystems compare.
+fn similarity_key(name: &str) -> String {
+    name.nfc()
+        .collect::<String>()
+        .to_lowercase()
+        .chars()
+        .filter(|chr| !chr.is_whitespace())
+        .collect()
+}
+
+fn similarity(lhs: &str, rhs: &str) -> Similarity {
+    let lhs = lhs.nfc().collect::<String>();
+    let rhs = rhs.nfc().collect::<String>();
+    if lhs == rhs {
+        Similarity::Normalization
+    } else if lhs.to_lowercase() == rhs.to_lowercase() {
+        Similarity::Case
+    } else {
+        Similarity::Whitespace
+    }
+}
+
+/// The longest names and paths, in bytes, the filesystem will take.
+/// The default is the current platform's: 255 byte names everywhere, and paths of
+/// 259 bytes on Windows (`MAX_PATH`), 1023 on macOS and 4095 elsewhere.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PathLimits {
+    pub name: usize,
+    pub path: usize,
+}
+
+impl Default for PathLimits {
+    fn default() -> Self {
+        let path = if cfg!(windows) {
+            259
+        } else if cfg!(target_os = "macos") {
+            1023
+        } else {
+            4095
+        };
+        Self { name: 255, path }
+    }
+}
+
+/// A new name or path over the [PathLimits], so the rename would fail.
+/// `original` is the path of the file being renamed, `bytes` the length of the new name
+/// (or the whole new path, including its folder) and `limit` the most allowed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LengthError {
+    pub original: PathBuf,
+    pub whole_path: bool,
+    pub bytes: usize,
+    pub limit: usize,
+}
+
+impl Display for LengthError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let what = if self.whole_path { "path" } else { "name" };
+        write!(
+            f,
+            "{}: new {what} is {} bytes, over the {} byte limit",
+            self.original.display(),
+            self.bytes,
+            self.limit
+        )
+    }
+}
+
+/// Check each new path of a batch against the limits, so names that are too long can be
+/// fixed before anything is renamed. The whole destination path is checked, folder included,
+/// not just the new name. A file over both limits is only reported for its name.
+///
+/// `batch` is a list of `(original, new)` paths.
+///
+/// # Example
+///
+/// ```
+/// # use mass_renamer_core::analysis::{path_lengths, PathLimits};
+/// let limits = PathLimits { name: 255, path: 20 };
+/// let batch = [("/a/b.txt", "/a/c.txt"), ("/a/d.txt", "/a/longer/folder/d.txt")];
+/// let errors = path_lengths(&batch, limits);
+/// assert_eq!(errors[0].to_string(), "/a/d.txt: new path is 22 bytes, over the 20 byte limit");
+/// ```
+pub fn path_lengths<P: AsRef<Path>, Q: AsRef<Path>>(
+    batch: &[(P, Q)],
+    limits: PathLimits,
+) -> Vec<LengthError> {
+    batch
+        .iter()
+        .filter_map(|(original, new)| {
+            let new = new.as_ref();
+            let name = new.file_name().map_or(0, |name| name.len());
+            let path = new.as_os_str().len();
+            let (whole_path, bytes, limit) = if name > limits.name {
+                (false, name, limits.name)
+            } else if path > limits.path {
+                (true, path, limits.path)
+            } else {
+                return None;
+            };
+            Some(LengthError {
+                original: original.as_ref().to_path_buf(),
+                whole_path,
+                bytes,
+                limit,
+            })
+        })
+        .collect()
+}
+
+/// The system whose rules new names are checked against, see [invalid_names]. The
+/// default is the current platform.
+/// - `Windows` - Names can't use `<>:"/\|?*` or control characters, be kept for a device
+///   such as `CON` or `LPT1`, or end with a dot or space.
+/// - `Unix` - Names can't hold a NUL character.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Platform {
+    Windows,
+    Unix,
+}
+
+impl Default for Platform {
+    fn default() -> Self {
+        match cfg!(windows) {
+            true => Platform::Windows,
+            false => Platform::Unix,
+        }
+    }
+}
+
+impl PathLimits {
+    /// The limits on Windows (`MAX_PATH`), e.g. for files synced to it.
+    pub const WINDOWS: PathLimits = PathLimits {
+        name: 255,
+        path: 259,
+    };
+}
+
+/// Why a new name can't be used on a [Platform].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NameProblem {
+    /// The name is kept for the device, e.g. `CON`.
+    Reserved(String),
+    Char(char),
+    /// The name ends with the character, which Windows drops.
+    Trailing(char),
+}
+
+/// A new name the [Platform] won't take, so the rename would fail or give another name.
+/// `original` is the path of the file being renamed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NameError {
+    pub original: PathBuf,
+    pub name: String,
+    pub problem: NameProblem,
+}
+
+impl Display for NameError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let (original, name) = (self.original.display(), &self.name);
+        match &self.problem {
+            NameProblem::Reserved(device) => {
+                write!(f, "{original}: {name} is kept for the {device} device")
+            }
+            NameProblem::Char(c) => write!(f, "{original}: {name} uses {c:?}, not allowed"),
+            NameProblem::Trailing(c) => write!(f, "{original}: {name} ends with {c:?}"),
+        }
+    }
+}
+
+/// Check each new name of a batch against the rules of the `platform`, so names it won't
+/// take can be fixed before anything is renamed, e.g. with the `windows` option of
+/// [crate::file::SanitizeOptions]. A name breaking several rules is reported for the
+/// first. Lengths are checked by [path_lengths].
+///
+/// `batch` is a list of `(original, new)` paths.
+///
+/// # Example
+///
+/// ```
+/// # use mass_renamer_core::analysis::{invalid_names, Platform};
+/// let batch = [("/a/b.txt", "/a/aux.txt"), ("/a/c.txt", "/a/what?.txt")];
+/// let errors = invalid_names(&batch, Platform::Windows);
+/// assert_eq!(errors[0].to_string(), "/a/b.txt: aux.txt is kept for the AUX device");
+/// assert_eq!(errors[1].to_string(), "/a/c.txt: what?.txt uses '?', not allowed");
+/// assert!(invalid_names(&batch, Platform::Unix).is_empty());
+/// ```
+pub fn invalid_names<P: AsRef<Path>, Q: AsRef<Path>>(
+    batch: &[(P, Q)],
+    platform: Platform,
+) -> Vec<NameError> {
+    batch
+        .iter()
+        .filter_map(|(original, new)| {
+            let name = new.as_ref().file_name()?.to_string_lossy().into_owned();
+            let problem = match platform {
+                Platform::Unix => NameProblem::Char(name.chars().find(|c| *c == '\0')?),
+                Platform::Windows if reserved_on_windows(&name) => {
+                    let device = name.split('.').next().unwrap_or_default();
+                    NameProblem::Reserved(device.trim_end().to_uppercase())
+                }
+                Platform::Windows => {
+                    let invalid = |c: &char| WINDOWS_INVALID.contains(c) || c.is_control();
+                    match (name.chars().find(invalid), name.chars().last()) {
+                        (Some(c), _) => NameProblem::Char(c),
+                        (None, Some(c @ ('.' | ' '))) => NameProblem::Trailing(c),
+                        _ => return None,
+                    }
+                }
+            };
+            Some(NameError {
+                original: original.as_ref().to_path_buf(),
+                name,
+                problem,
+            })
+        })
+        .collect()
+}
+
+/// How a batch measures up to a naming scheme, see [conform]. Each list holds the
+/// original paths of the files.
+/// - `already` - The current name fits the scheme.
+/// - `after` - The new name fits the scheme, the current one doesn't.
+/// - `never` - Neither name fits.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Conformance {
+    pub already: Vec<PathBuf>,
+    pub after: Vec<PathBuf>,
+    pub never: Vec<PathBuf>,
+}
+
+/// Check the current and new file names of a batch against a naming scheme, a regular
+/// expression the whole file name, extension included, must match.
+///
+/// `batch` is a list of `(original, new)` paths, files that aren't renamed have the same
+/// path twice.
+///
+/// # Example
+///
+/// ```
+/// # use std::path::PathBuf;
+/// # use mass_renamer_core::analysis::conform;
+/// let batch = [
+///     ("2023-01-02 a.pdf", "2023-01-02 a.pdf"),
+///     ("b.pdf", "2023-03-04 b.pdf"),
+///     ("c.pdf", "c.pdf"),
+/// ];
+/// let report = conform(&batch, r"\d{4}-\d{2}-\d{2} .+\.pdf").unwrap();
+/// assert_eq!(report.after, [PathBuf::from("b.pdf")]);
+/// assert_eq!(report.never, [PathBuf::from("c.pdf")]);
+/// ```
+pub fn conform<P: AsRef<Path>, Q: AsRef<Path>>(
+    batch: &[(P, Q)],
+    scheme: &str,
+) -> Result<Conformance, regex::Error> {
+    let scheme = compile(&format!("^(?:{scheme})$"))?;
+    let fits = |path: &Path| {
+        path.file_name()
+            .is_some_and(|name| scheme.is_match(&name.to_string_lossy()))
+    };
+    let mut report = Conformance::default();
+    for (original, new) in batch {
+        let original = original.as_ref();
+        let list = if fits(original) {
+            &mut report.already
+        } else if fits(new.as_ref()) {
+            &mut report.after
+        } else {
+            &mut report.never
+        };
+        list.push(original.to_path_buf());
+    }
+    Ok(report)
+}
+
+/// A rule that looks useful for the inspected names, ready to be applied to the matching panel.
+#[derive(Debug, Clone)]
+pub struct Suggestion {
+    pub message: String,
+    pub option: Options,
+}
+
+/// Inspect a batch of file names and suggest rules that would tidy up most of them,
+/// e.g. "87% of names contain '_' — replace with space?".
+/// Each suggestion replaces the settings of a single panel when applied.
+///
+/// # Example
+///
+/// ```
+/// # use mass_renamer_core::analysis::suggest;
+/// let suggestions = suggest(&["holiday_01.jpg", "holiday_02.jpg", "beach.jpg"]);
+/// assert_eq!(suggestions[0].message, "67% of names contain '_' — replace with space?");
+/// ```
+pub fn suggest<P: AsRef<Path>>(names: &[P]) -> Vec<Suggestion> {
+    let candidates: [(&str, Applies, Options); 6] = [
+        (
+            "contain '_' — replace with space?",
+            Box::new(|stem, _| stem.contains('_')),
+            Options::Replace(ReplaceOptions {
+                replace: "_".into(),
+                with: " ".into(),
+                case: true,
+            }),
+        ),
+        (
+            "contain '%20' — replace with space?",
+            Box::new(|stem, _| stem.contains("%20")),
+            Options::Replace(ReplaceOptions {
+                replace: "%20".into(),
+                with: " ".into(),
+                case: true,
+            }),
+        ),
+        (
+            "contain dates in DD.MM.YYYY — normalize to ISO?",
+            Box::new(|stem, _| stem.contains(|c: char| c.is_ascii_digit())),
+            Options::Regex(RegexOptions {
+                exp: r"\b(\d{2})[._-](\d{2})[._-](\d{4})\b".into(),
+                rep: "$3-$2-$1".into(),
+                extension: false,
+            }),
+        ),
+        (
+            "are ALL CAPS — convert to title case?",
+            Box::new(|stem, _| {
+                stem.chars().any(char::is_alphabetic) && stem == stem.to_uppercase()
+            }),
+            Options::Case(CaseOptions {
+                case: Case::Title,
+                ..Default::default()
+            }),
+        ),
+        (
+            "have upper case extensions — convert to lower case?",
+            Box::new(|_, ext| ext.is_some_and(|ext| ext != ext.to_lowercase())),
+            Options::Extension(ExtensionOptions::Lower),
+        ),
+        (
+            "contain release tags — clean them up?",
+            Box::new(|_, _| true),
+            Options::Release(ReleaseOptions {
+                resolution: true,
+                source: true,
+                codec: true,
+                audio: true,
+                tags: true,
+                group: true,
+                dots: false,
+            }),
+        ),
+    ];
+    let files = names
+        .iter()
+        .filter_map(|name| {
+            let name = name.as_ref();
+            let stem = name.file_stem()?.to_string_lossy();
+            let extension = name.extension().map(OsStr::to_string_lossy);
+            Some((name, stem, extension))
+        })
+        .collect::<Vec<_>>();
+    if files.is_empty() {
+        return vec![];
+    }
+    let mut suggestions = vec![];
+    for (message, applies, option) in candidates {
+        let matching = files
+            .iter()
+            .filter(|(name, stem, extension)| {
+                applies(stem, extension.as_deref()) && changes(name, &option)
+            })
+            .count();
+        let share = matching as f64 / files.len() as f64;
+        if share >= SUGGESTION_THRESHOLD {
+            suggestions.push(Suggestion {
+                message: format!("{:.0}% of names {message}", share * 100.0),
+                option,
+            });
+        }
+    }
+    suggestions
+}
+
+/// Test on a name's stem and extension deciding whether a suggestion is relevant.
+type Applies = Box<dyn Fn(&str, Option<&str>) -> bool>;
+
+/// Check whether applying the option on its own would change the file's name.
+fn changes(name: &Path, option: &Options) -> bool {
+    match File::new(name) {
+        Ok(file) => file.with_option(option.clone()).preview() != name,
+        Err(_) => false,
+    }
+}
+
+/// Pick up to `size` items to dry-run the rules on, keeping their original order.
+/// Without a `seed` the first `size` items are taken, otherwise the seed drives a
+/// random pick so the same seed always returns the same items.
+///
+/// # Example
+///
+/// ```
+/// # use mass_renamer_core::analysis::sample;
+/// let names = ["a.txt", "b.txt", "c.txt", "d.txt"];
+/// assert_eq!(sample(&names, 2, None), vec!["a.txt", "b.txt"]);
+/// assert_eq!(sample(&names, 2, Some(7)).len(), 2);
+/// ```
+pub fn sample<T: Clone>(items: &[T], size: usize, seed: Option<u64>) -> Vec<T> {
+    let Some(mut state) = seed else {
+        return items.iter().take(size).cloned().collect();
+    };
+    // Partial Fisher-Yates shuffle driven by SplitMix64.
+    let mut indices = (0..items.len()).collect::<Vec<_>>();
+    let size = size.min(items.len());
+    for idx in 0..size {
+        state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut rand = state;
+        rand = (rand ^ (rand >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        rand = (rand ^ (rand >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        rand ^= rand >> 31;
+        let pick = idx + (rand % (items.len() - idx) as u64) as usize;
+        indices.swap(idx, pick);
+    }
+    let mut picked = indices[..size].to_vec();
+    picked.sort_unstable();
+    picked.into_iter().map(|idx| items[idx].clone()).collect()
+}
+
+/// What a batch will do, in a few numbers, see [summarize]. Its text reads e.g.
+/// `142 files will be renamed, 3 conflicts, extensions unchanged`, short enough for a
+/// screen reader to read out in place of the whole table.
+/// - `files` - The files in the batch.
+/// - `renamed` - The files whose path changes.
+/// - `conflicts` - The files whose new path another file in the batch gets too, or a
+///   file that stays is already at.
+/// - `extensions` - The files whose extension changes.
+/// - `moved` - The files moving to another folder.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Summary {
+    pub files: usize,
+    pub renamed: usize,
+    pub conflicts: usize,
+    pub extensions: usize,
+    pub moved: usize,
+}
+
+impl Display for Summary {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let plural = |count: usize, word: &str| match count {
+            1 => format!("1 {word}"),
+            _ => format!("{count} {word}s"),
+        };
+        match self.renamed {
+            0 => write!(f, "Nothing will be renamed")?,
+            1 => write!(f, "1 file will be renamed")?,
+            renamed => write!(f, "{renamed} files will be renamed")?,
+        }
+        if self.renamed < self.files {
+            write!(f, ", {} unchanged", self.files - self.renamed)?;
+        }
+        match self.conflicts {
+            0 => write!(f, ", no conflicts")?,
+            conflicts => write!(f, ", {}", plural(conflicts, "conflict"))?,
+        }
+        match self.extensions {
+            0 => write!(f, ", extensions unchanged")?,
+            extensions => write!(f, ", {} changed", plural(extensions, "extension"))?,
+        }
+        if self.moved > 0 {
+            write!(f, ", {} to another folder", plural(self.moved, "file"))?;
+        }
+        Ok(())
+    }
+}
+
+/// Count what a batch will do, see [Summary]. Conflicts are the [collisions].
+///
+/// `batch` is a list of `(original, new)` paths, files that aren't renamed have the same
+/// path twice.
+///
+/// # Example
+///
+/// ```
+/// # use mass_renamer_core::analysis::summarize;
+/// let batch = [("/d/a.txt", "/d/x.txt"), ("/d/b.txt", "/d/x.txt"), ("/d/c.txt", "/d/c.md")];
+/// assert_eq!(
+///     summarize(&batch).to_string(),
+///     "3 files will be renamed, 2 conflicts, 1 extension changed"
+/// );
+/// ```
+pub fn summarize<P: AsRef<Path>, Q: AsRef<Path>>(batch: &[(P, Q)]) -> Summary {
+    summarize_with(batch, collisions(batch).len())
+}
+
+/// [summarize] a batch whose [collisions] were already found, without looking up the new
+/// paths on disk again.
+pub fn summarize_with<P: AsRef<Path>, Q: AsRef<Path>>(
+    batch: &[(P, Q)],
+    conflicts: usize,
+) -> Summary {
+    let mut summary = Summary {
+        files: batch.len(),
+        conflicts,
+        ..Default::default()
+    };
+    for (original, new) in batch {
+        let (original, new) = (original.as_ref(), new.as_ref());
+        if original == new {
+            continue;
+        }
+        summary.renamed += 1;
+        if original.extension() != new.extension() {
+            summary.extensions += 1;
+        }
+        if original.parent() != new.parent() {
+            summary.moved += 1;
+        }
+    }
+    summary
+}
+
+/// A configured rule that won't do what it looks like it will.
+/// `rule` is the panel the problem is in, e.g. `Replace`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Lint {
+    pub rule: &'static str,
+    pub message: String,
+}
+
+impl Display for Lint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.rule, self.message)
+    }
+}
+
+/// Check a pipeline for rules that have no effect, e.g. a Replace with nothing to find,
+/// and rules that cancel each other out, e.g. changing the case of a name removed after.
+/// The rules are checked in the order given, the order [crate::batch::prepare] applies
+/// them in.
+///
+/// # Example
+///
+/// ```
+/// # use mass_renamer_core::analysis::lint;
+/// # use mass_renamer_core::file::{Options, ReplaceOptions};
+/// let lints = lint(&[Options::Replace(ReplaceOptions {
+///     replace: "".into(),
+///     with: "-".into(),
+///     case: true,
+/// })]);
+/// assert_eq!(lints[0].to_string(), "Replace: Nothing to find, '-' is never used");
+/// ```
+pub fn lint(options: &[Options]) -> Vec<Lint> {
+    let mut lints = vec![];
+    let mut add = |rule, message: String| lints.push(Lint { rule, message });
+    // Whether Name removes the name after the rule at each position, undoing it.
+    let removed_after = |idx: usize| {
+        options[idx + 1..]
+            .iter()
+            .any(|opt| matches!(opt, Options::Name(NameOptions::Remove)))
+    };
+    for (idx, opt) in options.iter().enumerate() {
+        let step = opt.step();
+        if !step.enabled() {
+            add(
+                step.name(),
+                "Not included in this build, has no effect".into(),
+            );
+            continue;
+        }
+        match opt {
+            Options::Regex(opt) => {
+                if opt.exp.is_empty() && !opt.rep.is_empty() {
+                    add(
+                        "Regex",
+                        format!("No expression, '{}' is never used", opt.rep),
+                    );
+                } else if let Err(err) = compile(&opt.exp) {
+                    let reason = err.to_string();
+                    let reason = reason.lines().last().unwrap_or_default();
+                    let reason = reason.trim_start_matches("error: ");
+                    add("Regex", format!("Invalid expression, {reason}"));
+                }
+            }
+            Options::Replace(opt) => {
+                if opt.replace.is_empty() && !opt.with.is_empty() {
+                    add(
+                        "Replace",
+                        format!("Nothing to find, '{}' is never used", opt.with),
+                    );
+                } else if !opt.replace.is_empty() && opt.replace == opt.with {
+                    add(
+                        "Replace",
+                        format!("'{}' is replaced with itself", opt.replace),
+                    );
+                } else if !opt.replace.is_empty() && removed_after(idx) {
+                    add(
+                        "Replace",
+                        "Has no effect, Name removes the name after it".into(),
+                    );
+                }
+            }
+            Options::Case(opt) if (opt.case != Case::Keep || opt.snake) && removed_after(idx) => {
+                add(
+                    "Case",
+                    "Has no effect, Name removes the name after it".into(),
+                );
+            }
+            Options::Lookup(opt) if compile(&opt.key).is_err() => {
+                add(
+                    "Lookup",
+                    "Invalid key expression, no file is looked up".into(),
+                );
+            }
+            Options::Sanitize(opt) if opt.web_safe && !cfg!(feature = "transliterate") => {
+                add(
+                    "Sanitize",
+                    "Web safe isn't included in this build, has no effect".into(),
+                );
+            }
+            Options::Remove(opt) => {
+                let (from, to) = opt.range;
+                if (from, to) != (0, 0) && (from == 0 || to < from) {
+                    add("Remove", format!("Range {from} to {to} is empty"));
+                }
+            }
+            _ => (),
+        }
+    }
+    lints
+}
+
+#[cfg(test)]
+mod analysis_tests {
+    use super::*;
+    use crate::file::RemoveOptions;
+    use crate::tester::temp_folder;
+
+    #[test]
+    fn differ_by_case() {
+        let batch = [
+            ("/dir/a.txt", "/dir/File.txt"),
+            ("/dir/b.txt", "/dir/file.TXT"),
+        ];
+        let warnings = similar_names(&batch);
+        assert_eq!(
+            warnings,
+            vec![SimilarityWarning {
+                first: PathBuf::from("/dir/a.txt"),
+                second: PathBuf::from("/dir/b.txt"),
+                similarity: Similarity::Case
+            }]
+        );
+    }
+
+    #[test]
+    fn conform_whole_names() {
+        let batch = [
+            ("/d/IMG_1.jpg", "/d/IMG_1.jpg.bak"),
+            ("/d/x", "/d/IMG_2.jpg"),
+        ];
+        let report = conform(&batch, r"IMG_\d+\.jpg").unwrap();
+        assert_eq!(report.already, [PathBuf::from("/d/IMG_1.jpg")]);
+        assert_eq!(report.after, [PathBuf::from("/d/x")]);
+        assert!(conform(&batch, "(").is_err());
+    }
+
+    #[test]
+    fn names_windows_wont_take() {
+        let batch = [
+            ("a", "/d/ok.txt"),
+            ("b", "/d/Lpt1 .tar.gz"),
+            ("c", "/d/a|b.txt"),
+            ("d", "/d/tab\there.txt"),
+            ("e", "/d/done."),
+            ("f", "/d/console.txt"),
+        ];
+        let problems = invalid_names(&batch, Platform::Windows)
+            .into_iter()
+            .map(|err| (err.original, err.problem))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            problems,
+            [
+                (PathBuf::from("b"), NameProblem::Reserved("LPT1".into())),
+                (PathBuf::from("c"), NameProblem::Char('|')),
+                (PathBuf::from("d"), NameProblem::Char('\t')),
+                (PathBuf::from("e"), NameProblem::Trailing('.')),
+            ]
+        );
+    }
+
+    #[test]
+    fn lengths_over_limits() {
+        let long = "x".repeat(300);
+        let batch = [
+            ("/d/a".to_owned(), format!("/d/{long}")),
+            ("/d/b".to_owned(), "/d/short".to_owned()),
+            ("/d/c".to_owned(), format!("/{long}/c")),
+        ];
+        let errors = path_lengths(
+            &batch,
+            PathLimits {
+                name: 255,
+                path: 200,
+            },
+        );
+        let found = errors
+            .iter()
+            .map(|err| (err.original.to_str().unwrap(), err.whole_path, err.bytes))
+            .collect::<Vec<_>>();
+        assert_eq!(found, [("/d/a", false, 300), ("/d/c", true, 303)]);
+    }
+
+    #[test]
+    fn differ_by_whitespace() {
+        let batch = [("/dir/a", "/dir/my file"), ("/dir/b", "/dir/myfile")];
+        let warnings = similar_names(&batch);
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].similarity, Similarity::Whitespace);
+    }
+
+    #[test]
+    fn differ_by_normalization() {
+        let batch = [("/dir/a", "/dir/caf\u{e9}"), ("/dir/b", "/dir/cafe\u{301}")];
+        let warnings = similar_names(&batch);
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].similarity, Similarity::Normalization);
+    }
+
+    #[test]
+    fn different_folders_and_identical_names_ignored() {
+        let batch = [
+            ("/one/a", "/one/File"),
+            ("/two/b", "/two/file"),
+            ("/one/c", "/one/same"),
+            ("/one/d", "/one/same"),
+        ];
+        assert!(similar_names(&batch).is_empty());
+    }
+
+    #[test]
+    fn suggest_underscores_and_iso_dates() {
+        let names = [
+            "a_b 01.02.2023.txt",
+            "c_d 24-12-2022.txt",
+            "e_f.txt",
+            "plain.txt",
+        ];
+        let suggestions = suggest(&names);
+        let messages = suggestions
+            .iter()
+            .map(|suggestion| suggestion.message.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            messages,
+            vec![
+                "75% of names contain '_' — replace with space?",
+                "50% of names contain dates in DD.MM.YYYY — normalize to ISO?",
+            ]
+        );
+        let mut file = File::new(Path::new(names[0]))
+            .unwrap()
+            .with_option(suggestions[1].option.clone());
+        assert_eq!(file.preview(), PathBuf::from("a_b 2023-02-01.txt"));
+    }
+
+    #[test]
+    fn suggest_case_and_release_tags() {
+        let names = ["Movie.1080p.x264.MKV", "Show.720p.WEB-DL.MKV"];
+        let suggestions = suggest(&names)
+            .into_iter()
+            .map(|suggestion| suggestion.message)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            suggestions,
+            vec![
+                "100% of names have upper case extensions — convert to lower case?",
+                "100% of names contain release tags — clean them up?",
+            ]
+        );
+    }
+
+    #[test]
+    fn nothing_to_suggest() {
+        let names: [&str; 0] = [];
+        assert!(suggest(&names).is_empty());
+        assert!(suggest(&["tidy name.txt"]).is_empty());
+    }
+
+    #[test]
+    fn sample_first_n() {
+        let items = (0..10).collect::<Vec<_>>();
+        assert_eq!(sample(&items, 3, None), vec![0, 1, 2]);
+        assert_eq!(sample(&items, 20, None), items);
+    }
+
+    #[test]
+    fn sample_random() {
+        let items = (0..1000).collect::<Vec<_>>();
+        let picked = sample(&items, 50, Some(42));
+        assert_eq!(picked.len(), 50);
+        assert!(picked.windows(2).all(|pair| pair[0] < pair[1]));
+        assert_ne!(picked, sample(&items, 50, None));
+        assert_eq!(picked, sample(&items, 50, Some(42)));
+        assert_eq!(sample(&items[..5], 50, Some(42)), items[..5]);
+    }
+
+    #[test]
+    fn lint_defaults_are_clean() {
+        let options = [
+            Options::Regex(RegexOptions::default()),
+            Options::Name(NameOptions::default()),
+            Options::Replace(ReplaceOptions::default()),
+            Options::Case(CaseOptions::default()),
+            Options::Remove(RemoveOptions::default()),
+        ];
+        assert!(lint(&options).is_empty());
+    }
+
+    #[test]
+    fn lint_no_op_rules() {
+        let options = [
+            Options::Regex(RegexOptions {
+                exp: "(".into(),
+                ..Default::default()
+            }),
+            Options::Replace(ReplaceOptions {
+                replace: "a".into(),
+                with: "a".into(),
+                case: true,
+            }),
+            Options::Remove(RemoveOptions {
+                range: (5, 2),
+                ..Default::default()
+            }),
+        ];
+        let lints = lint(&options)
+            .into_iter()
+            .map(|lint| lint.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            lints,
+            vec![
+                "Regex: Invalid expression, unclosed group",
+                "Replace: 'a' is replaced with itself",
+                "Remove: Range 5 to 2 is empty",
+            ]
+        );
+    }
+
+    #[test]
+    fn lint_case_before_removed_name() {
+        let case = Options::Case(CaseOptions {
+            case: Case::Upper,
+            ..Default::default()
+        });
+        let remove = Options::Name(NameOptions::Remove);
+        assert!(lint(std::slice::from_ref(&case)).is_empty());
+        // Case applies to what is added after the name is removed.
+        assert!(lint(&[remove.clone(), case.clone()]).is_empty());
+        assert_eq!(
+            lint(&[case, remove]),
+            vec![Lint {
+                rule: "Case",
+                message: "Has no effect, Name removes the name after it".into()
+            }]
+        );
+    }
+
+    #[test]
+    fn summary_counts() {
+        let batch = [
+            ("/d/a.txt", "/d/a.txt"),
+            ("/d/b.txt", "/d/B.txt"),
+            ("/d/c.txt", "/e/c.txt"),
+        ];
+        let summary = summarize(&batch);
+        assert_eq!(
+            summary,
+            Summary {
+                files: 3,
+                renamed: 2,
+                conflicts: 0,
+                extensions: 0,
+                moved: 1
+            }
+        );
+        assert_eq!(
+            summary.to_string(),
+            "2 files will be renamed, 1 unchanged, no conflicts, extensions unchanged, \
+            1 file to another folder"
+        );
+        let none: [(&str, &str); 0] = [];
+        assert_eq!(
+            summarize(&none).to_string(),
+            "Nothing will be renamed, no conflicts, extensions unchanged"
+        );
+    }
+
+    #[test]
+    fn summary_existing_files_conflict() {
+        let dir = temp_folder("summary_existing");
+        for file in ["a.txt", "b.txt", "c.txt"] {
+            std::fs::write(dir.join(file), file).unwrap();
+        }
+        // b.txt stays so a.txt can't have its name, c.txt is renamed so b.txt can.
+        let batch = [
+            (dir.join("a.txt"), dir.join("b.txt")),
+            (dir.join("c.txt"), dir.join("d.txt")),
+            (dir.join("b.txt"), dir.join("b.txt")),
+        ];
+        let stays = summarize(&batch).conflicts;
+        let batch = [
+            (dir.join("b.txt"), dir.join("c.txt")),
+            (dir.join("c.txt"), dir.join("d.txt")),
+        ];
+        let leaves = summarize(&batch).conflicts;
+        std::fs::remove_dir_all(&dir).ok();
+        assert_eq!(stays, 1);
+        assert_eq!(leaves, 0);
+    }
+}
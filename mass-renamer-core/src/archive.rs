@@ -0,0 +1,234 @@
+//! Rename the entries of a zip archive without extracting it. The rename steps are
+//! applied to each entry's file name, its folders inside the archive are kept, and the
+//! entries are copied to the new archive as they are, without recompressing them.
+
+use std::{
+    collections::{HashMap, HashSet},
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use thiserror::Error;
+use zip::{result::ZipError, ZipArchive, ZipWriter};
+
+use crate::{
+    batch,
+    file::{File, Options},
+};
+
+#[derive(Debug, Error)]
+pub enum ArchiveError {
+    #[error(transparent)]
+    Io(#[from] io::Error),
+    #[error(transparent)]
+    Zip(#[from] ZipError),
+    #[error("More than one entry would be named {0}.")]
+    Duplicate(String),
+    #[error("{0} is the archive itself, the copy needs another name.")]
+    SameArchive(PathBuf),
+}
+
+/// The names of the files in the archive, in archive order. Folders aren't listed.
+pub fn entries<P: AsRef<Path>>(archive: P) -> Result<Vec<String>, ArchiveError> {
+    let mut zip = ZipArchive::new(fs::File::open(archive)?)?;
+    let mut names = vec![];
+    for idx in 0..zip.len() {
+        let entry = zip.by_index_raw(idx)?;
+        if !entry.is_dir() {
+            names.push(entry.name().to_string());
+        }
+    }
+    Ok(names)
+}
+
+/// The new name of each entry with the options, as `(original, new)` pairs.
+/// Entries are previewed together, as a [batch], so Swap and the other batch steps work.
+///
+/// # Example
+///
+/// ```
+/// # use mass_renamer_core::{archive, file::{Case, CaseOptions, Options}};
+/// let entries = ["docs/read_me.txt".to_string()];
+/// let case = CaseOptions { case: Case::Upper, ..Default::default() };
+/// let renames = archive::preview(&entries, &[Options::Case(case)]);
+/// assert_eq!(renames[0].1, "docs/READ_ME.txt");
+/// ```
+pub fn preview(entries: &[String], options: &[Options]) -> Vec<(String, String)> {
+    // Entries without a usable name are left as they are.
+    let (entries, mut files): (Vec<_>, Vec<_>) = entries
+        .iter()
+        .filter_map(|entry| Some((entry, File::new(Path::new(entry)).ok()?)))
+        .unzip();
+    batch::preview(&mut files, options);
+    entries
+        .into_iter()
+        .zip(files)
+        .map(|(entry, file)| {
+            let name = file.new_path();
+            let name = name.file_name().unwrap_or_default().to_string_lossy();
+            // Entry names always use `/`, whatever the platform.
+            let renamed = match entry.rsplit_once('/') {
+                Some((folder, _)) => format!("{folder}/{name}"),
+                None => name.to_string(),
+            };
+            (entry.clone(), renamed)
+        })
+        .collect()
+}
+
+/// Write the archive with its entries renamed, given as `(original, new)` pairs.
+/// Entries not in `renames` keep their names. With a `copy` the archive is left as it
+/// was, otherwise it is replaced once the renamed archive has been written. A `copy` at
+/// the archive's own path fails with [ArchiveError::SameArchive].
+pub fn rewrite<P: AsRef<Path>>(
+    archive: P,
+    renames: &[(String, String)],
+    copy: Option<&Path>,
+) -> Result<(), ArchiveError> {
+    let archive = archive.as_ref();
+    // Creating the copy would empty the archive before it is read.
+    if let Some(copy) = copy {
+        if copy.exists() && fs::canonicalize(copy)? == fs::canonicalize(archive)? {
+            return Err(ArchiveError::SameArchive(copy.to_path_buf()));
+        }
+    }
+    let mut zip = ZipArchive::new(fs::File::open(archive)?)?;
+    let mut new = HashMap::new();
+    for (from, to) in renames {
+        new.entry(from.as_str()).or_insert(to);
+    }
+    let mut names = HashSet::new();
+    let mut new_names = vec![];
+    for idx in 0..zip.len() {
+        let name = zip.by_index_raw(idx)?.name().to_string();
+        let name = new.get(name.as_str()).map_or(name, |to| to.to_string());
+        if !names.insert(name.clone()) {
+            return Err(ArchiveError::Duplicate(name));
+        }
+        new_names.push(name);
+    }
+    let target = match copy {
+        Some(copy) => copy.to_path_buf(),
+        None => temporary(archive),
+    };
+    let written = write(&mut zip, new_names, &target);
+    if written.is_err() && copy.is_none() {
+        fs::remove_file(&target).ok();
+    }
+    written?;
+    if copy.is_none() {
+        fs::rename(&target, archive)?;
+    }
+    Ok(())
+}
+
+fn write(
+    zip: &mut ZipArchive<fs::File>,
+    names: Vec<String>,
+    target: &Path,
+) -> Result<(), ArchiveError> {
+    let mut writer = ZipWriter::new(fs::File::create(target)?);
+    writer.set_raw_comment(zip.comment().to_vec());
+    for (idx, name) in names.into_iter().enumerate() {
+        writer.raw_copy_file_rename(zip.by_index_raw(idx)?, name)?;
+    }
+    writer.finish()?;
+    Ok(())
+}
+
+/// A name beside the archive to write to before replacing it.
+fn temporary(archive: &Path) -> PathBuf {
+    let mut name = archive.file_name().unwrap_or_default().to_os_string();
+    name.push(".mass-renamer.tmp");
+    archive.with_file_name(name)
+}
+
+#[cfg(test)]
+mod archive_tests {
+    use std::io::{Read, Write};
+
+    use zip::{write::FileOptions, CompressionMethod};
+
+    use super::*;
+    use crate::file::{Case, CaseOptions};
+    use crate::tester::temp_folder;
+
+    /// An archive in a folder of its own, removed with the folder.
+    fn fixture(name: &str) -> PathBuf {
+        let path = temp_folder(name).join("archive.zip");
+        let mut writer = ZipWriter::new(fs::File::create(&path).unwrap());
+        let options = FileOptions::default().compression_method(CompressionMethod::Stored);
+        writer.add_directory("docs/", options).unwrap();
+        for entry in ["docs/read_me.txt", "notes.txt"] {
+            writer.start_file(entry, options).unwrap();
+            writer.write_all(entry.as_bytes()).unwrap();
+        }
+        writer.finish().unwrap();
+        path
+    }
+
+    fn contents(path: &Path) -> Vec<(String, String)> {
+        let mut zip = ZipArchive::new(fs::File::open(path).unwrap()).unwrap();
+        (0..zip.len())
+            .map(|idx| {
+                let mut entry = zip.by_index(idx).unwrap();
+                let mut text = String::new();
+                entry.read_to_string(&mut text).unwrap();
+                (entry.name().to_string(), text)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn renames_in_place() {
+        let path = fixture("archive_in_place");
+        let case = CaseOptions {
+            case: Case::Upper,
+            ..Default::default()
+        };
+        let entries = entries(&path).unwrap();
+        let renames = preview(&entries, &[Options::Case(case)]);
+        rewrite(&path, &renames, None).unwrap();
+        let result = contents(&path);
+        fs::remove_dir_all(path.parent().unwrap()).ok();
+        assert_eq!(entries, ["docs/read_me.txt", "notes.txt"]);
+        assert_eq!(
+            result,
+            [
+                ("docs/".to_string(), String::new()),
+                (
+                    "docs/READ_ME.txt".to_string(),
+                    "docs/read_me.txt".to_string()
+                ),
+                ("NOTES.txt".to_string(), "notes.txt".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn copy_leaves_the_original() {
+        let path = fixture("archive_original");
+        let copy = path.with_file_name("copy.zip");
+        let renames = [("notes.txt".to_string(), "docs/read_me.txt".to_string())];
+        let duplicate = rewrite(&path, &renames, Some(&copy));
+        let renames = [("notes.txt".to_string(), "todo.txt".to_string())];
+        rewrite(&path, &renames, Some(&copy)).unwrap();
+        let (original, copied) = (entries(&path).unwrap(), entries(&copy).unwrap());
+        fs::remove_dir_all(path.parent().unwrap()).ok();
+        assert!(matches!(duplicate, Err(ArchiveError::Duplicate(_))));
+        assert_eq!(original, ["docs/read_me.txt", "notes.txt"]);
+        assert_eq!(copied, ["docs/read_me.txt", "todo.txt"]);
+    }
+
+    #[test]
+    fn copy_over_itself_refused() {
+        let path = fixture("archive_itself");
+        let renames = [("notes.txt".to_string(), "todo.txt".to_string())];
+        let itself = path.parent().unwrap().join(".").join("archive.zip");
+        let refused = rewrite(&path, &renames, Some(&itself));
+        let original = entries(&path);
+        fs::remove_dir_all(path.parent().unwrap()).ok();
+        assert!(matches!(refused, Err(ArchiveError::SameArchive(_))));
+        assert_eq!(original.unwrap(), ["docs/read_me.txt", "notes.txt"]);
+    }
+}
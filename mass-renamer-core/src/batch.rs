@@ -0,0 +1,1122 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Display,
+    fs,
+    io::{self, BufRead, Read},
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicBool, Ordering},
+    thread,
+    time::{Duration, Instant},
+};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    file::{File, FileError, Options},
+    guard::Guard,
+};
+
+/// Set to a percentage, e.g. `MASS_RENAMER_CHAOS=10`, to fail that share of renames on
+/// purpose with [FileError::Injected], to test how partial failures are reported and undone.
+/// Only read in builds with the `chaos` feature, which is meant for testing.
+pub const CHAOS_VAR: &str = "MASS_RENAMER_CHAOS";
+
+#[cfg(feature = "chaos")]
+thread_local! {
+    static CHAOS: std::cell::Cell<Option<u64>> = const { std::cell::Cell::new(None) };
+    static ROLL: std::cell::Cell<u64> = std::cell::Cell::new(seed());
+}
+
+/// Fail `percent` of the renames made on this thread, overriding [CHAOS_VAR].
+#[cfg(feature = "chaos")]
+pub fn set_chaos(percent: u8) {
+    CHAOS.with(|chaos| chaos.set(Some(percent.min(100).into())));
+}
+
+#[cfg(feature = "chaos")]
+fn seed() -> u64 {
+    let nanos = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|time| time.as_nanos() as u64)
+        .unwrap_or_default();
+    nanos | 1
+}
+
+/// Whether to fail the next rename on purpose, see [CHAOS_VAR].
+#[cfg(feature = "chaos")]
+fn chaos(path: &Path) -> Result<(), FileError> {
+    let percent = CHAOS.with(|chaos| chaos.get()).unwrap_or_else(|| {
+        std::env::var(CHAOS_VAR)
+            .ok()
+            .and_then(|percent| percent.parse().ok())
+            .unwrap_or(0)
+    });
+    // xorshift64, plenty random enough to pick failures.
+    let roll = ROLL.with(|roll| {
+        let mut next = roll.get();
+        next ^= next << 13;
+        next ^= next >> 7;
+        next ^= next << 17;
+        roll.set(next);
+        next
+    });
+    if roll % 100 < percent {
+        return Err(FileError::Injected(path.to_path_buf()));
+    }
+    Ok(())
+}
+
+#[cfg(not(feature = "chaos"))]
+fn chaos(_path: &Path) -> Result<(), FileError> {
+    Ok(())
+}
+
+/// Limit how many files are renamed each second, e.g. to avoid saturating a NAS during a
+/// very large batch. `per_second` of 0 (the default) doesn't limit anything.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Throttle {
+    pub per_second: u32,
+}
+
+impl Throttle {
+    /// Start pacing a run of operations.
+    pub fn pace(self) -> Pace {
+        Pace {
+            interval: (self.per_second > 0).then(|| Duration::from_secs(1) / self.per_second),
+            next: Instant::now(),
+        }
+    }
+}
+
+/// Spaces out the filesystem operations of a batch to keep to a [Throttle].
+pub struct Pace {
+    interval: Option<Duration>,
+    next: Instant,
+}
+
+impl Pace {
+    /// Wait until the next operation is allowed.
+    pub fn wait(&mut self) {
+        let Some(interval) = self.interval else {
+            return;
+        };
+        let now = Instant::now();
+        if self.next > now {
+            thread::sleep(self.next - now);
+        }
+        self.next = self.next.max(now) + interval;
+    }
+}
+
+/// Preview a batch of files with the options, including the steps that need the whole
+/// batch: the `{dirnum}` and `{filenum}` tokens, the Auto Date `{group_min_date}` and
+/// `{group_max_date}` tokens, Swap and RAW+JPEG Pairs.
+/// Use [File::new_path] for the new names afterwards.
+///
+/// # Example
+///
+/// ```
+/// # use std::path::{Path, PathBuf};
+/// # use mass_renamer_core::{batch, file::{File, Options, SwapMode, SwapOptions}};
+/// let mut files = ["a.txt", "b.txt"].map(|name| File::new(Path::new(name)).unwrap());
+/// let swap = SwapOptions { exp: String::new(), mode: SwapMode::Swap };
+/// batch::preview(&mut files, &[Options::Swap(swap)]);
+/// assert_eq!(files[0].new_path(), PathBuf::from("b.txt"));
+/// ```
+pub fn preview(files: &mut [File], options: &[Options]) {
+    prepare(files, options);
+    for file in files.iter_mut() {
+        file.preview();
+    }
+    finish(files, options);
+}
+
+/// The first part of [preview]: apply the options, in the order given as with
+/// [File::with_pipeline], and add the tokens that need the whole batch. Each file can then
+/// be previewed on its own with [File::preview], in any order, before the batch is
+/// completed with [finish].
+pub fn prepare(files: &mut [File], options: &[Options]) {
+    // Each blacklist is compiled once and shared by the files.
+    let blacklists = options
+        .iter()
+        .filter_map(|opt| match opt {
+            Options::Blacklist(blacklist) => Some(blacklist.compile()),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    for file in files.iter_mut() {
+        *file = std::mem::take(file).with_pipeline(options);
+        file.set_blacklists(blacklists.clone());
+    }
+    // The last of each option wins, as with [File::with_option].
+    let number = options.iter().rev().find_map(|opt| match opt {
+        Options::Number(number) => Some(number.clone()),
+        _ => None,
+    });
+    let number = number.unwrap_or_default();
+    number.sequence(files.iter_mut());
+    number.nested_tokens(files.iter_mut());
+    for opt in options.iter().rev() {
+        if let Options::Date(date) = opt {
+            date.group_tokens(files.iter_mut());
+            break;
+        }
+    }
+}
+
+/// The last part of [preview]: Swap and RAW+JPEG Pairs, which need every file previewed.
+pub fn finish(files: &mut [File], options: &[Options]) {
+    for opt in options.iter().rev() {
+        if let Options::Swap(swap) = opt {
+            swap.apply(files.iter_mut());
+            break;
+        }
+    }
+    // Pairs go last so a JPEG follows its RAW wherever Swap took it.
+    for opt in options.iter().rev() {
+        if let Options::Pair(pair) = opt {
+            pair.apply(files.iter_mut());
+            break;
+        }
+    }
+}
+
+/// What to do about a batch giving files the same name, or the name of a file already there.
+/// - `Refuse` - Rename nothing until the names are fixed (default).
+/// - `Number` - Add ` (2)`, ` (3)` and so on to the names, see [number_collisions].
+/// - `Skip` - Rename the rest of the batch, leaving the files that clash, see [resolve].
+/// - `Overwrite` - Replace the files already there that the batch doesn't rename.
+/// - `Prompt` - Ask about each file whose new name is taken before renaming.
+///
+/// Of files the batch gives the same name, `Skip`, `Overwrite` and `Prompt` let the first
+/// keep it and skip the rest.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum CollisionPolicy {
+    #[default]
+    Refuse,
+    Number,
+    Skip,
+    Overwrite,
+    Prompt,
+}
+
+/// What to do about one file whose new name is taken by a file the batch doesn't rename,
+/// see [resolve].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Resolution {
+    Skip,
+    Overwrite,
+}
+
+/// A batch with its clashing names dealt with by [resolve].
+/// - `renames` - The `(from, to)` paths to rename, in the order given.
+/// - `skipped` - The original paths of the files left out.
+/// - `replace` - The new names to replace the files at, see [rename_watched].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Resolved {
+    pub renames: Vec<(PathBuf, PathBuf)>,
+    pub skipped: Vec<PathBuf>,
+    pub replace: HashSet<PathBuf>,
+}
+
+/// A file of a batch that can't have its new name. `other` is the original path of
+/// another file of the batch given the same name, none when a file that stays where it
+/// is already has it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Collision {
+    pub original: PathBuf,
+    pub new: PathBuf,
+    pub other: Option<PathBuf>,
+}
+
+impl Display for Collision {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = self.new.file_name().unwrap_or_default().to_string_lossy();
+        match &self.other {
+            Some(other) => write!(
+                f,
+                "{}: {name} is also the new name of {}",
+                self.original.display(),
+                other.display()
+            ),
+            None => write!(f, "{}: {name} already exists", self.original.display()),
+        }
+    }
+}
+
+/// Find the files of a batch that can't have their new names, before anything is
+/// renamed: files given the same name, and files given the name of a file the batch
+/// doesn't move out of the way. [rename] would fail them midway through the batch.
+///
+/// `renames` is a list of `(from, to)` pairs, files that aren't renamed may have the same
+/// path twice.
+///
+/// # Example
+///
+/// ```
+/// # use std::path::PathBuf;
+/// # use mass_renamer_core::batch::collisions;
+/// let renames = [("/d/a.txt", "/d/x.txt"), ("/d/b.txt", "/d/x.txt"), ("/d/c.txt", "/d/y.txt")];
+/// let found = collisions(&renames);
+/// assert_eq!(found.len(), 2);
+/// assert_eq!(found[0].other, Some(PathBuf::from("/d/b.txt")));
+/// ```
+pub fn collisions<P: AsRef<Path>, Q: AsRef<Path>>(renames: &[(P, Q)]) -> Vec<Collision> {
+    let leaving = renames
+        .iter()
+        .filter(|(from, to)| from.as_ref() != to.as_ref())
+        .map(|(from, _)| from.as_ref())
+        .collect::<HashSet<_>>();
+    let mut takers: HashMap<&Path, Vec<&Path>> = HashMap::new();
+    for (from, to) in renames {
+        takers.entry(to.as_ref()).or_default().push(from.as_ref());
+    }
+    renames
+        .iter()
+        .filter_map(|(from, to)| {
+            let (from, to) = (from.as_ref(), to.as_ref());
+            if from == to {
+                return None;
+            }
+            let other = if to.exists() && !leaving.contains(to) && !same_file(from, to) {
+                None
+            } else {
+                Some(
+                    takers[to]
+                        .iter()
+                        .find(|other| **other != from)?
+                        .to_path_buf(),
+                )
+            };
+            Some(Collision {
+                original: from.to_path_buf(),
+                new: to.to_path_buf(),
+                other,
+            })
+        })
+        .collect()
+}
+
+/// Leave out the files of a batch [collisions] finds, or let them replace the files at
+/// their new names. `decide` is asked about each file whose new name is taken by a file
+/// the batch doesn't rename. Of files the batch gives the same name the first to keep or
+/// overwrite it does, as only one can have it, and the rest are skipped.
+///
+/// # Example
+///
+/// ```
+/// # use std::{fs, path::PathBuf};
+/// # use mass_renamer_core::batch::{resolve, Resolution};
+/// let dir = std::env::temp_dir().join(format!("resolve_doc_{}", std::process::id()));
+/// fs::create_dir_all(&dir).unwrap();
+/// fs::write(dir.join("taken.txt"), "").unwrap();
+/// let renames = [("a", "x"), ("b", "x"), ("c", "taken.txt"), ("d", "y")]
+///     .map(|(from, to)| (dir.join(from), dir.join(to)));
+/// let resolved = resolve(&renames, |_| Resolution::Overwrite);
+/// fs::remove_dir_all(&dir).ok();
+/// assert_eq!(resolved.renames, [&renames[0], &renames[2], &renames[3]].map(Clone::clone));
+/// assert_eq!(resolved.skipped, [dir.join("b")]);
+/// assert!(resolved.replace.contains(&dir.join("taken.txt")));
+/// ```
+pub fn resolve<P: AsRef<Path>, Q: AsRef<Path>>(
+    renames: &[(P, Q)],
+    mut decide: impl FnMut(&Collision) -> Resolution,
+) -> Resolved {
+    let mut skipped = HashSet::new();
+    let mut replace = HashSet::new();
+    // The new names a file of the batch has already been given.
+    let mut claimed = HashSet::new();
+    for collision in collisions(renames) {
+        let keep = match collision.other {
+            Some(_) => claimed.insert(collision.new.clone()),
+            None => match decide(&collision) {
+                Resolution::Skip => false,
+                Resolution::Overwrite => {
+                    replace.insert(collision.new.clone());
+                    claimed.insert(collision.new.clone())
+                }
+            },
+        };
+        if !keep {
+            skipped.insert(collision.original);
+        }
+    }
+    let mut resolved = Resolved {
+        replace,
+        ..Default::default()
+    };
+    for (from, to) in renames {
+        let (from, to) = (from.as_ref().to_path_buf(), to.as_ref().to_path_buf());
+        match skipped.contains(&from) {
+            true => resolved.skipped.push(from),
+            false => resolved.renames.push((from, to)),
+        }
+    }
+    resolved
+}
+
+/// Add ` (2)`, ` (3)` and so on to the new names of the files [collisions] finds, the
+/// lowest number that frees each name. The first file given a name keeps it as it is.
+/// Use after [preview].
+pub fn number_collisions<'a, I: IntoIterator<Item = &'a mut File>>(files: I) {
+    let mut files = files.into_iter().collect::<Vec<_>>();
+    let renames = files
+        .iter()
+        .map(|file| (file.original().to_path_buf(), file.new_path()))
+        .collect::<Vec<_>>();
+    let collided = collisions(&renames)
+        .into_iter()
+        .map(|collision| collision.original)
+        .collect::<HashSet<_>>();
+    let leaving = renames
+        .iter()
+        .filter(|(from, to)| from != to)
+        .map(|(from, _)| from.clone())
+        .collect::<HashSet<_>>();
+    let mut taken = renames
+        .iter()
+        .filter(|(from, _)| !collided.contains(from))
+        .map(|(_, to)| to.clone())
+        .collect::<HashSet<_>>();
+    let free = |taken: &HashSet<PathBuf>, path: &PathBuf| {
+        !taken.contains(path) && (!path.exists() || leaving.contains(path))
+    };
+    for (file, (from, to)) in files.iter_mut().zip(renames.iter()) {
+        if !collided.contains(from) {
+            continue;
+        }
+        if free(&taken, to) {
+            taken.insert(to.clone());
+            continue;
+        }
+        for number in 2.. {
+            let mut numbered = (*file).clone();
+            numbered.push_stem(&format!(" ({number})"));
+            let path = numbered.new_path();
+            if free(&taken, &path) {
+                taken.insert(path);
+                **file = numbered;
+                break;
+            }
+        }
+    }
+}
+
+/// Rename a batch of files, given as `(from, to)` pairs, returning a result per pair.
+///
+/// Files can take each other's names: a rename waits until the file sitting at its new
+/// name has moved out of the way, and files swapping or rotating names are first moved
+/// to a temporary name to break the cycle. Should a file fail to leave its temporary
+/// name, it is given its old name back if that is still free, or else fails with
+/// [FileError::Parked] saying where it is. An existing file that isn't part of the
+/// batch is never overwritten, the rename fails with [FileError::Exists] instead.
+/// A failed rename doesn't stop the rest of the batch, e.g. a file deleted since the
+/// batch was previewed fails with [FileError::Missing] and the others are still renamed.
+/// Files in the folders of the default [Guard] fail with [FileError::Protected].
+pub fn rename<P: AsRef<Path>, Q: AsRef<Path>>(renames: &[(P, Q)]) -> Vec<Result<(), FileError>> {
+    rename_throttled(renames, Throttle::default())
+}
+
+/// [rename], keeping to the [Throttle]. Moves to and from a temporary name count as
+/// renames too.
+pub fn rename_throttled<P: AsRef<Path>, Q: AsRef<Path>>(
+    renames: &[(P, Q)],
+    throttle: Throttle,
+) -> Vec<Result<(), FileError>> {
+    rename_guarded(renames, throttle, &Guard::default())
+}
+
+/// [rename_throttled], refusing renames from or to the folders the [Guard] protects.
+pub fn rename_guarded<P: AsRef<Path>, Q: AsRef<Path>>(
+    renames: &[(P, Q)],
+    throttle: Throttle,
+    guard: &Guard,
+) -> Vec<Result<(), FileError>> {
+    let cancel = AtomicBool::new(false);
+    rename_watched(
+        renames,
+        throttle,
+        guard,
+        &HashSet::new(),
+        &cancel,
+        |_, _| {},
+    )
+}
+
+/// [rename_guarded], calling `progress` with the index and result of each pair as soon as
+/// it is known, e.g. to report on a batch renamed on another thread.
+///
+/// A file at a new name in `replace` that the batch doesn't rename is replaced, see
+/// [resolve], rather than failing with [FileError::Exists].
+///
+/// Once `cancel` is set no new rename is started, and the files not renamed yet fail with
+/// [FileError::Cancelled]. Files already moved to a temporary name to break a cycle are
+/// still given their new names first, so every file is left at either its old or its new
+/// name.
+///
+/// # Example
+///
+/// ```
+/// # use std::{collections::HashSet, fs, sync::atomic::{AtomicBool, Ordering}};
+/// # use mass_renamer_core::{batch::{rename_watched, Throttle}, guard::Guard};
+/// let dir = std::env::temp_dir().join(format!("rename_watched_doc_{}", std::process::id()));
+/// fs::create_dir_all(&dir).unwrap();
+/// let renames = ["a", "b", "c"].map(|name| {
+///     fs::write(dir.join(name), "").unwrap();
+///     (dir.join(name), dir.join(format!("{name}.txt")))
+/// });
+/// let cancel = AtomicBool::new(false);
+/// let (guard, replace) = (Guard::none(), HashSet::new());
+/// let results = rename_watched(&renames, Throttle::default(), &guard, &replace, &cancel, |_, _| {
+///     cancel.store(true, Ordering::Relaxed);
+/// });
+/// fs::remove_dir_all(&dir).ok();
+/// assert_eq!(results.iter().filter(|result| result.is_ok()).count(), 1);
+/// ```
+pub fn rename_watched<P: AsRef<Path>, Q: AsRef<Path>>(
+    renames: &[(P, Q)],
+    throttle: Throttle,
+    guard: &Guard,
+    replace: &HashSet<PathBuf>,
+    cancel: &AtomicBool,
+    mut progress: impl FnMut(usize, &Result<(), FileError>),
+) -> Vec<Result<(), FileError>> {
+    let mut pace = throttle.pace();
+    let mut results = (0..renames.len()).map(|_| None).collect::<Vec<_>>();
+    let mut settle = |results: &mut Vec<Option<Result<(), FileError>>>, idx: usize, result| {
+        progress(idx, &result);
+        results[idx] = Some(result);
+    };
+    let mut current = renames
+        .iter()
+        .map(|(from, _)| from.as_ref().to_path_buf())
+        .collect::<Vec<_>>();
+    let target = |idx: usize| renames[idx].1.as_ref();
+    // Where the files still to be renamed are, and who is waiting for each path to be free.
+    let mut occupied = HashMap::new();
+    let mut waiting: HashMap<PathBuf, Vec<usize>> = HashMap::new();
+    let mut ready = vec![];
+    // How many files are at a temporary name.
+    let mut parked = 0;
+    for (idx, from) in current.iter().enumerate() {
+        if from.symlink_metadata().is_err() {
+            settle(&mut results, idx, Err(FileError::Missing(from.clone())));
+        } else if guard.protects(from) || guard.protects(target(idx)) {
+            settle(&mut results, idx, Err(FileError::Protected(from.clone())));
+        } else if from == target(idx) {
+            settle(&mut results, idx, Ok(()));
+        } else {
+            occupied.insert(from.clone(), idx);
+        }
+    }
+    for idx in (0..renames.len()).rev() {
+        if results[idx].is_some() {
+            continue;
+        }
+        if occupied.contains_key(target(idx)) {
+            waiting
+                .entry(target(idx).to_path_buf())
+                .or_default()
+                .push(idx);
+        } else {
+            ready.push(idx);
+        }
+    }
+    let cancelled = |parked: usize| parked == 0 && cancel.load(Ordering::Relaxed);
+    loop {
+        while !cancelled(parked) {
+            let Some(idx) = ready.pop() else {
+                break;
+            };
+            occupied.remove(&current[idx]);
+            let original = renames[idx].0.as_ref();
+            let was_parked = current[idx] != original;
+            if was_parked {
+                parked -= 1;
+            }
+            pace.wait();
+            let replacing = replace.contains(target(idx));
+            let mut result = move_file(&current[idx], target(idx), replacing);
+            if was_parked {
+                result = result.map_err(|err| unpark(&current[idx], original, err));
+            }
+            settle(&mut results, idx, result);
+            if let Some(next) = waiting.remove(&current[idx]) {
+                ready.extend(next);
+            }
+        }
+        if cancelled(parked) {
+            break;
+        }
+        // Everyone left is waiting on someone else, so they form cycles.
+        let Some(idx) = waiting.values().flatten().next().copied() else {
+            break;
+        };
+        let from = current[idx].clone();
+        occupied.remove(&from);
+        pace.wait();
+        match park(&from) {
+            Ok(temp) => {
+                occupied.insert(temp.clone(), idx);
+                current[idx] = temp;
+                parked += 1;
+            }
+            Err(err) => {
+                settle(&mut results, idx, Err(err));
+                waiting.retain(|_, queue| {
+                    queue.retain(|other| *other != idx);
+                    !queue.is_empty()
+                });
+            }
+        }
+        if let Some(next) = waiting.remove(&from) {
+            ready.extend(next);
+        }
+    }
+    for idx in 0..renames.len() {
+        if results[idx].is_some() {
+            continue;
+        }
+        let result = match cancel.load(Ordering::Relaxed) {
+            true => Err(FileError::Cancelled(renames[idx].0.as_ref().to_path_buf())),
+            false => Ok(()),
+        };
+        settle(&mut results, idx, result);
+    }
+    results.into_iter().flatten().collect()
+}
+
+/// Copy the files at each `from` path to its `to` path, as [rename_watched] renames them,
+/// leaving the originals where they are, see [crate::file::DestinationOptions]. Copies
+/// never take each other's place, so they are made in order, and one fails with
+/// [FileError::Exists] rather than replace a file. Folders can't be copied.
+pub fn copy_watched<P: AsRef<Path>, Q: AsRef<Path>>(
+    copies: &[(P, Q)],
+    throttle: Throttle,
+    guard: &Guard,
+    cancel: &AtomicBool,
+    mut progress: impl FnMut(usize, &Result<(), FileError>),
+) -> Vec<Result<(), FileError>> {
+    let mut pace = throttle.pace();
+    let mut results = Vec::with_capacity(copies.len());
+    for (idx, (from, to)) in copies.iter().enumerate() {
+        let (from, to) = (from.as_ref(), to.as_ref());
+        let result = if cancel.load(Ordering::Relaxed) {
+            Err(FileError::Cancelled(from.to_path_buf()))
+        } else if from.symlink_metadata().is_err() {
+            Err(FileError::Missing(from.to_path_buf()))
+        } else if guard.protects(to) {
+            Err(FileError::Protected(to.to_path_buf()))
+        } else if from == to {
+            Ok(())
+        } else {
+            pace.wait();
+            copy_file(from, to)
+        };
+        progress(idx, &result);
+        results.push(result);
+    }
+    results
+}
+
+/// Whether the paths are the same file, e.g. they only differ by case on a case-insensitive
+/// file system, so a rename finds itself. Files whose names only differ by case on a
+/// case-sensitive file system are different files.
+fn same_file(from: &Path, to: &Path) -> bool {
+    if from == to {
+        return true;
+    }
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+        match (from.symlink_metadata(), to.symlink_metadata()) {
+            (Ok(from), Ok(to)) => from.dev() == to.dev() && from.ino() == to.ino(),
+            _ => false,
+        }
+    }
+    #[cfg(not(unix))]
+    match (from.canonicalize(), to.canonicalize()) {
+        (Ok(from), Ok(to)) => from == to,
+        _ => false,
+    }
+}
+
+fn move_file(from: &Path, to: &Path, replace: bool) -> Result<(), FileError> {
+    if to.exists() && !same_file(from, to) && !replace {
+        return Err(FileError::Exists(to.to_path_buf()));
+    }
+    chaos(from)?;
+    make_parent(to)?;
+    match fs::rename(from, to) {
+        // A folder on another drive, which a file can't simply be renamed into.
+        Err(err) if err.kind() == io::ErrorKind::CrossesDevices && from.is_file() => {
+            move_across(from, to).map_err(FileError::from)
+        }
+        result => result.map_err(|err| match from.symlink_metadata() {
+            // Deleted while the batch was running.
+            Err(_) => FileError::Missing(from.to_path_buf()),
+            Ok(_) => err.into(),
+        }),
+    }
+}
+
+/// Move a file to another drive by copying it beside `to`, checking the copy against the
+/// original, and only then giving it its name and removing the original. A copy that
+/// fails or doesn't match is removed, leaving both drives as they were.
+fn move_across(from: &Path, to: &Path) -> Result<(), io::Error> {
+    let name = to.file_name().unwrap_or_default().to_string_lossy();
+    let temp = to.with_file_name(format!(".{name}.mass-renamer-move"));
+    let copied = fs::copy(from, &temp).and_then(|_| same_contents(from, &temp));
+    match copied {
+        Ok(true) => {}
+        Ok(false) => {
+            fs::remove_file(&temp).ok();
+            return Err(io::Error::other(format!(
+                "The copy of {} doesn't match it.",
+                from.display()
+            )));
+        }
+        Err(err) => {
+            fs::remove_file(&temp).ok();
+            return Err(err);
+        }
+    }
+    if let Err(err) = fs::rename(&temp, to) {
+        fs::remove_file(&temp).ok();
+        return Err(err);
+    }
+    fs::remove_file(from)
+}
+
+/// Whether the files hold the same bytes, read a block at a time.
+fn same_contents(lhs: &Path, rhs: &Path) -> Result<bool, io::Error> {
+    if fs::metadata(lhs)?.len() != fs::metadata(rhs)?.len() {
+        return Ok(false);
+    }
+    let (mut lhs, mut rhs) = (
+        io::BufReader::new(fs::File::open(lhs)?),
+        io::BufReader::new(fs::File::open(rhs)?),
+    );
+    loop {
+        let block = lhs.fill_buf()?;
+        if block.is_empty() {
+            return Ok(rhs.fill_buf()?.is_empty());
+        }
+        let len = block.len();
+        let mut other = vec![0; len];
+        rhs.read_exact(&mut other)?;
+        if block != other.as_slice() {
+            return Ok(false);
+        }
+        lhs.consume(len);
+    }
+}
+
+fn copy_file(from: &Path, to: &Path) -> Result<(), FileError> {
+    if to.exists() {
+        return Err(FileError::Exists(to.to_path_buf()));
+    }
+    if from.is_dir() {
+        return Err(io::Error::other(format!("{} is a folder.", from.display())).into());
+    }
+    chaos(from)?;
+    make_parent(to)?;
+    fs::copy(from, to)?;
+    Ok(())
+}
+
+/// Create the folder a file is moved to when it is missing, e.g. a new subfolder.
+fn make_parent(to: &Path) -> Result<(), io::Error> {
+    match to.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() && !parent.exists() => {
+            fs::create_dir_all(parent)
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Move a file to an unused temporary name next to it.
+fn park(from: &Path) -> Result<PathBuf, FileError> {
+    let name = from
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let temp = (0..)
+        .map(|idx| from.with_file_name(format!(".{name}.mass-renamer-{idx}")))
+        .find(|temp| !temp.exists())
+        .expect("Ran out of temporary names.");
+    chaos(from)?;
+    fs::rename(from, &temp)?;
+    Ok(temp)
+}
+
+/// Give a file that couldn't leave its temporary name its old name back, if that is still
+/// free. The `err` it failed with is kept either way.
+fn unpark(temp: &Path, original: &Path, err: FileError) -> FileError {
+    if original.symlink_metadata().is_err() && fs::rename(temp, original).is_ok() {
+        err
+    } else {
+        FileError::Parked(original.to_path_buf(), temp.to_path_buf())
+    }
+}
+
+#[cfg(test)]
+mod batch_tests {
+    use super::*;
+    use crate::tester::temp_folder;
+
+    fn write(dir: &Path, files: &[(&str, &str)]) {
+        fs::create_dir_all(dir).unwrap();
+        for (name, text) in files {
+            fs::write(dir.join(name), text).unwrap();
+        }
+    }
+
+    fn read(dir: &Path, name: &str) -> String {
+        fs::read_to_string(dir.join(name)).unwrap_or_default()
+    }
+
+    #[test]
+    fn options_in_order() {
+        use crate::file::{Case, CaseOptions, NumberFormat, NumberMode, NumberOptions};
+        let number = Options::Number(NumberOptions {
+            mode: NumberMode::Prefix,
+            value: 1,
+            sep: "_".into(),
+            format: NumberFormat::AsciiLower,
+            ..Default::default()
+        });
+        let upper = Options::Case(CaseOptions {
+            case: Case::Upper,
+            ..Default::default()
+        });
+        let names = |options: &[Options]| {
+            let mut files = ["x.txt", "y.txt"].map(|name| File::new(Path::new(name)).unwrap());
+            preview(&mut files, options);
+            files.iter().map(File::to_string).collect::<Vec<_>>()
+        };
+        assert_eq!(
+            names(&[upper.clone(), number.clone()]),
+            ["a_X.txt", "b_Y.txt"]
+        );
+        assert_eq!(names(&[number, upper]), ["A_X.txt", "B_Y.txt"]);
+    }
+
+    #[test]
+    fn throttled() {
+        let dir = temp_folder("batch_throttled");
+        write(&dir, &[("a", "A"), ("b", "B"), ("c", "C")]);
+        let renames =
+            [("a", "x"), ("b", "y"), ("c", "z")].map(|(from, to)| (dir.join(from), dir.join(to)));
+        let start = Instant::now();
+        let results = rename_throttled(&renames, Throttle { per_second: 20 });
+        let elapsed = start.elapsed();
+        fs::remove_dir_all(&dir).ok();
+        assert!(results.iter().all(Result::is_ok));
+        // The first rename goes straight away, the other two wait 50ms each.
+        assert!(elapsed >= Duration::from_millis(100));
+    }
+
+    #[cfg(feature = "chaos")]
+    #[test]
+    fn injected_failures() {
+        let dir = temp_folder("batch_chaos");
+        write(&dir, &[("a", "A"), ("b", "B"), ("1", "one"), ("2", "two")]);
+        let renames =
+            [("a", "b"), ("b", "a"), ("1", "x")].map(|(from, to)| (dir.join(from), dir.join(to)));
+        set_chaos(100);
+        let failed = rename(&renames);
+        set_chaos(0);
+        let renamed = rename(&[(dir.join("2"), dir.join("y"))]);
+        let texts = ["a", "b", "1", "y"].map(|name| read(&dir, name));
+        fs::remove_dir_all(&dir).ok();
+        // Whichever of the swapped pair goes first fails, the other finds it still in the way.
+        let swapped = [&failed[0], &failed[1]];
+        assert!(swapped
+            .iter()
+            .any(|result| matches!(result, Err(FileError::Injected(_)))));
+        assert!(swapped
+            .iter()
+            .any(|result| matches!(result, Err(FileError::Exists(_)))));
+        assert!(matches!(failed[2], Err(FileError::Injected(_))));
+        assert!(renamed.iter().all(Result::is_ok));
+        // Nothing moved, not even to a temporary name.
+        assert_eq!(texts, ["A", "B", "one", "two"]);
+    }
+
+    #[test]
+    fn rotate_and_chain() {
+        let dir = temp_folder("batch_rotate");
+        write(
+            &dir,
+            &[
+                ("a", "A"),
+                ("b", "B"),
+                ("c", "C"),
+                ("1", "one"),
+                ("2", "two"),
+            ],
+        );
+        let renames = [
+            ("2", "3"),
+            ("1", "2"),
+            ("a", "b"),
+            ("b", "c"),
+            ("c", "a"),
+            ("missing", "x"),
+        ]
+        .map(|(from, to)| (dir.join(from), dir.join(to)));
+        let results = rename(&renames);
+        let contents = ["a", "b", "c", "2", "3"].map(|name| read(&dir, name));
+        let leftovers = fs::read_dir(&dir).unwrap().count();
+        fs::remove_dir_all(&dir).ok();
+        assert!(results[..5].iter().all(Result::is_ok));
+        assert!(matches!(results[5], Err(FileError::Missing(_))));
+        assert_eq!(contents, ["C", "A", "B", "one", "two"]);
+        assert_eq!(leftovers, 5);
+    }
+
+    #[test]
+    fn parked_files_go_back() {
+        let dir = temp_folder("batch_unpark");
+        write(&dir, &[("a", "A")]);
+        let temp = park(&dir.join("a")).unwrap();
+        let err = unpark(&temp, &dir.join("a"), FileError::Exists(dir.join("x")));
+        let back = read(&dir, "a");
+        let temp = park(&dir.join("a")).unwrap();
+        fs::write(dir.join("a"), "taken").unwrap();
+        let stuck = unpark(&temp, &dir.join("a"), FileError::Exists(dir.join("x")));
+        let left = read(&dir, temp.file_name().unwrap().to_str().unwrap());
+        fs::remove_dir_all(&dir).ok();
+        assert!(matches!(err, FileError::Exists(_)));
+        assert_eq!(back, "A");
+        assert!(
+            matches!(stuck, FileError::Parked(original, at) if original == dir.join("a") && at == temp)
+        );
+        assert_eq!(left, "A");
+    }
+
+    #[test]
+    fn cancel_finishes_cycle() {
+        let dir = temp_folder("batch_cancel");
+        write(&dir, &[("a", "A"), ("b", "B"), ("c", "C")]);
+        let renames =
+            [("a", "b"), ("b", "c"), ("c", "a")].map(|(from, to)| (dir.join(from), dir.join(to)));
+        let cancel = AtomicBool::new(false);
+        let mut reported = 0;
+        // Cancel as soon as the first file of the rotation has its new name.
+        let results = rename_watched(
+            &renames,
+            Throttle::default(),
+            &Guard::none(),
+            &HashSet::new(),
+            &cancel,
+            |_, _| {
+                reported += 1;
+                cancel.store(true, Ordering::Relaxed);
+            },
+        );
+        let contents = ["a", "b", "c"].map(|name| read(&dir, name));
+        let leftovers = fs::read_dir(&dir).unwrap().count();
+        fs::remove_dir_all(&dir).ok();
+        assert_eq!(reported, 3);
+        assert!(results.iter().all(Result::is_ok));
+        assert_eq!(contents, ["C", "A", "B"]);
+        assert_eq!(leftovers, 3);
+    }
+
+    #[test]
+    fn cancel_stops_batch() {
+        let dir = temp_folder("batch_cancel_stops");
+        write(&dir, &[("a", "A"), ("b", "B"), ("c", "C")]);
+        let renames =
+            [("a", "x"), ("b", "y"), ("c", "z")].map(|(from, to)| (dir.join(from), dir.join(to)));
+        let cancel = AtomicBool::new(false);
+        let results = rename_watched(
+            &renames,
+            Throttle::default(),
+            &Guard::none(),
+            &HashSet::new(),
+            &cancel,
+            |_, _| {
+                cancel.store(true, Ordering::Relaxed);
+            },
+        );
+        let contents = ["a", "b", "c", "x"].map(|name| read(&dir, name));
+        fs::remove_dir_all(&dir).ok();
+        assert!(results[0].is_ok());
+        assert!(matches!(results[1], Err(FileError::Cancelled(_))));
+        assert!(matches!(results[2], Err(FileError::Cancelled(_))));
+        assert_eq!(contents, ["", "B", "C", "A"]);
+    }
+
+    #[test]
+    fn sorted_into_new_folders() {
+        let dir = temp_folder("batch_sorted");
+        write(&dir, &[("a", "A"), ("b", "B")]);
+        let renames = [(dir.join("a"), dir.join("2023/07/a"))];
+        let moved = rename(&renames);
+        let copies = [
+            (dir.join("b"), dir.join("copies/b")),
+            (dir.join("b"), dir.join("2023/07/a")),
+        ];
+        let copied = copy_watched(
+            &copies,
+            Throttle::default(),
+            &Guard::none(),
+            &AtomicBool::new(false),
+            |_, _| (),
+        );
+        let contents = ["a", "b", "2023/07/a", "copies/b"].map(|name| read(&dir, name));
+        fs::remove_dir_all(&dir).ok();
+        assert!(moved[0].is_ok());
+        assert!(copied[0].is_ok());
+        assert!(matches!(copied[1], Err(FileError::Exists(_))));
+        assert_eq!(contents, ["", "B", "A", "B"]);
+    }
+
+    #[test]
+    fn skipped_and_overwritten() {
+        let dir = temp_folder("batch_resolved");
+        write(
+            &dir,
+            &[("a", "A"), ("b", "B"), ("c", "C"), ("x", "X"), ("y", "Y")],
+        );
+        let renames =
+            [("a", "x"), ("b", "y"), ("c", "z")].map(|(from, to)| (dir.join(from), dir.join(to)));
+        // x is replaced, y is left alone.
+        let resolved = resolve(&renames, |collision| match collision.new.ends_with("x") {
+            true => Resolution::Overwrite,
+            false => Resolution::Skip,
+        });
+        let results = rename_watched(
+            &resolved.renames,
+            Throttle::default(),
+            &Guard::none(),
+            &resolved.replace,
+            &AtomicBool::new(false),
+            |_, _| (),
+        );
+        let contents = ["b", "x", "y", "z"].map(|name| read(&dir, name));
+        fs::remove_dir_all(&dir).ok();
+        assert_eq!(resolved.skipped, [dir.join("b")]);
+        assert!(results.iter().all(Result::is_ok));
+        assert_eq!(contents, ["B", "A", "Y", "C"]);
+    }
+
+    #[test]
+    fn never_overwrites() {
+        let dir = temp_folder("batch_overwrite");
+        write(&dir, &[("a", "A"), ("b", "B"), ("c", "C")]);
+        let renames = [("a", "c"), ("b", "a")].map(|(from, to)| (dir.join(from), dir.join(to)));
+        let results = rename(&renames);
+        let contents = ["a", "c"].map(|name| read(&dir, name));
+        fs::remove_dir_all(&dir).ok();
+        assert!(matches!(results[0], Err(FileError::Exists(_))));
+        assert!(matches!(results[1], Err(FileError::Exists(_))));
+        assert_eq!(contents, ["A", "C"]);
+    }
+
+    #[test]
+    fn names_differing_by_case_are_other_files() {
+        let dir = temp_folder("batch_case");
+        write(&dir, &[("photo.jpg", "lower"), ("Photo.jpg", "upper")]);
+        // On a case-insensitive file system the second write replaced the first.
+        let case_sensitive = fs::read_dir(&dir).unwrap().count() == 2;
+        let renames = [(dir.join("photo.jpg"), dir.join("Photo.jpg"))];
+        let found = collisions(&renames);
+        let results = rename(&renames);
+        let contents = ["photo.jpg", "Photo.jpg"].map(|name| read(&dir, name));
+        fs::remove_dir_all(&dir).ok();
+        if case_sensitive {
+            assert_eq!(found.len(), 1);
+            assert!(matches!(results[0], Err(FileError::Exists(_))));
+            assert_eq!(contents, ["lower", "upper"]);
+        } else {
+            assert!(found.is_empty());
+            assert!(results[0].is_ok());
+        }
+    }
+
+    #[test]
+    fn moved_across_by_copying() {
+        let dir = temp_folder("batch_across");
+        write(&dir, &[("from.txt", "moved"), ("kept.txt", "kept")]);
+        let moved = move_across(&dir.join("from.txt"), &dir.join("to.txt"));
+        let missing = move_across(&dir.join("gone.txt"), &dir.join("kept.txt"));
+        let contents = ["from.txt", "to.txt", "kept.txt"].map(|name| read(&dir, name));
+        let left = fs::read_dir(&dir).unwrap().count();
+        fs::remove_dir_all(&dir).ok();
+        assert_eq!(left, 2);
+        assert!(moved.is_ok());
+        assert!(missing.is_err());
+        assert_eq!(contents, ["", "moved", "kept"]);
+    }
+
+    #[test]
+    fn collisions_numbered() {
+        let dir = temp_folder("batch_collisions");
+        write(
+            &dir,
+            &[
+                ("a.txt", "A"),
+                ("b.txt", "B"),
+                ("c.txt", "C"),
+                ("k.txt", "K"),
+            ],
+        );
+        let fixed = |name: &str| Options::Name(crate::file::NameOptions::Fixed(name.into()));
+        let mut files = [("a.txt", "x"), ("b.txt", "x"), ("c.txt", "k")]
+            .map(|(from, to)| File::new(&dir.join(from)).unwrap().with_option(fixed(to)));
+        preview(&mut files, &[]);
+        let renames = files
+            .iter()
+            .map(|file| (file.original().to_path_buf(), file.new_path()))
+            .collect::<Vec<_>>();
+        let found = collisions(&renames);
+        number_collisions(files.iter_mut());
+        let names = files.iter().map(File::to_string).collect::<Vec<_>>();
+        fs::remove_dir_all(&dir).ok();
+        assert_eq!(
+            found.iter().map(ToString::to_string).collect::<Vec<_>>(),
+            [
+                format!(
+                    "{}: x.txt is also the new name of {}",
+                    renames[0].0.display(),
+                    renames[1].0.display()
+                ),
+                format!(
+                    "{}: x.txt is also the new name of {}",
+                    renames[1].0.display(),
+                    renames[0].0.display()
+                ),
+                format!("{}: k.txt already exists", renames[2].0.display()),
+            ]
+        );
+        assert_eq!(names, ["x.txt", "x (2).txt", "k (2).txt"]);
+    }
+
+    #[test]
+    fn blacklists_compiled_for_the_batch() {
+        use crate::file::{BlacklistMode, BlacklistOptions};
+        let blacklist = |word: &str| {
+            Options::Blacklist(BlacklistOptions {
+                words: vec![word.into()],
+                mode: BlacklistMode::Remove,
+                case: false,
+            })
+        };
+        // The file's own Blacklist comes before the batch's, each removes its own word.
+        let mut files = ["a-x-y-z.txt", "b-x-y-z.txt"].map(|name| {
+            File::new(Path::new(name))
+                .unwrap()
+                .with_option(blacklist("-x"))
+        });
+        preview(&mut files, &[blacklist("-y"), blacklist("-z")]);
+        let names = files.iter().map(File::to_string).collect::<Vec<_>>();
+        assert_eq!(names, ["a.txt", "b.txt"]);
+    }
+}
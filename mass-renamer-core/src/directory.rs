@@ -0,0 +1,544 @@
+use crate::file::reg::compile;
+use crate::{canonical, file::FileError, File};
+#[cfg(feature = "report")]
+use crate::{
+    file::Options,
+    report::{self, ReportError, ReportFormat},
+};
+use regex::{Regex, RegexBuilder};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, HashSet},
+    env, fs,
+    fs::{canonicalize, read_dir},
+    io,
+    path::{Path, PathBuf},
+};
+use thiserror::Error;
+
+/// The files directly in a folder, in name order.
+#[derive(Default)]
+pub struct Directory {
+    files: Vec<File>,
+}
+
+impl Directory {
+    pub fn new<P: AsRef<Path>>(path: P) -> Result<Self, DirectoryError> {
+        let path = get_directory(path)?;
+        let mut paths = read_dir(&path)?
+            .map(|entry| entry.map(|entry| entry.path()))
+            .collect::<Result<Vec<_>, _>>()?;
+        paths.retain(|path| path.is_file());
+        paths.sort();
+        let files = paths
+            .into_iter()
+            .filter_map(|path| File::try_from(path).ok())
+            .collect();
+        Ok(Self { files })
+    }
+
+    /// The files, in name order.
+    pub fn files(&self) -> &[File] {
+        &self.files
+    }
+
+    /// Preview the files with the options as a [crate::batch] and write what renaming
+    /// them would do to `out`, without renaming anything. See [crate::report].
+    #[cfg(feature = "report")]
+    pub fn export_preview<W: io::Write>(
+        &mut self,
+        options: &[Options],
+        format: ReportFormat,
+        out: W,
+    ) -> Result<(), ReportError> {
+        crate::batch::preview(&mut self.files, options);
+        report::write(&report::rows(&self.files), format, out)
+    }
+}
+
+/// A folder found by [walk], with the files directly in it in name order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Folder {
+    pub path: PathBuf,
+    pub files: Vec<PathBuf>,
+}
+
+/// Every folder under `root`, `root` included, each with its files.
+///
+/// Symbolic links and Windows junctions are skipped unless `follow_links` is set, so a
+/// walk can't leave the tree under `root`. Followed or not, no folder is walked twice,
+/// which stops links from looping forever or listing the same files under two paths.
+pub fn walk<P: AsRef<Path>>(root: P, follow_links: bool) -> Result<Vec<Folder>, io::Error> {
+    let root = root.as_ref();
+    let mut visited = HashSet::from([canonicalize(root)?]);
+    let mut folders = vec![];
+    let mut pending = vec![root.to_path_buf()];
+    while let Some(path) = pending.pop() {
+        let mut files = vec![];
+        for entry in read_dir(&path)? {
+            let entry = entry?;
+            let mut kind = entry.file_type()?;
+            if kind.is_symlink() {
+                // A dangling link has nothing to rename behind it.
+                match fs::metadata(entry.path()) {
+                    Ok(target) if follow_links => kind = target.file_type(),
+                    _ => continue,
+                }
+            }
+            if kind.is_dir() {
+                if !follow_links || visited.insert(canonicalize(entry.path())?) {
+                    pending.push(entry.path());
+                }
+            } else if kind.is_file() {
+                files.push(entry.path());
+            }
+        }
+        files.sort();
+        folders.push(Folder { path, files });
+    }
+    Ok(folders)
+}
+
+/// Which entries of a folder are listed, so rules only apply to the matching ones.
+/// - `mask` - Glob patterns for the name separated by `;`, e.g. `*.jpg;*.png`, where `*`
+///   matches any text and `?` any one character, or `@` and the name of an
+///   [ExtensionGroup], e.g. `@Images`. Case-insensitive. Empty matches all.
+/// - `regex` - A regular expression found somewhere in the name. Empty matches all.
+/// - `skip_hidden` - Leave out hidden entries, those starting with `.` or marked hidden on
+///   Windows.
+/// - `kind` - List files, folders or both.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Filter {
+    pub mask: String,
+    pub regex: String,
+    pub skip_hidden: bool,
+    pub kind: EntryKind,
+}
+
+/// Select from
+/// - `EntryKind::All` for files and folders (default)
+/// - `EntryKind::Files` for files only
+/// - `EntryKind::Folders` for folders only
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum EntryKind {
+    #[default]
+    All,
+    Files,
+    Folders,
+}
+
+impl EntryKind {
+    pub fn iter() -> impl Iterator<Item = EntryKind> {
+        [Self::All, Self::Files, Self::Folders].iter().copied()
+    }
+}
+
+/// A named kind of file, e.g. `Images` for `jpg jpeg png heic raw`, to filter on with
+/// `@Images` in a [Filter] mask.
+/// - `extensions` - With or without the dot, separated by spaces, commas or semicolons.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ExtensionGroup {
+    pub name: String,
+    pub extensions: String,
+}
+
+impl ExtensionGroup {
+    pub fn new(name: &str, extensions: &str) -> Self {
+        Self {
+            name: name.into(),
+            extensions: extensions.into(),
+        }
+    }
+
+    /// The groups there are until they are edited: Images, Audio, Video and Docs.
+    pub fn defaults() -> Vec<Self> {
+        vec![
+            Self::new(
+                "Images",
+                "jpg jpeg png gif bmp tif tiff webp heic heif raw cr2 cr3 nef arw dng orf rw2",
+            ),
+            Self::new("Audio", "mp3 flac wav ogg opus m4a aac wma aiff alac"),
+            Self::new("Video", "mp4 mkv mov avi wmv webm m4v mpg mpeg 3gp"),
+            Self::new(
+                "Docs",
+                "pdf doc docx odt rtf txt md xls xlsx ods ppt pptx odp",
+            ),
+        ]
+    }
+
+    pub fn extensions(&self) -> impl Iterator<Item = &str> {
+        self.extensions
+            .split(|c: char| c == ',' || c == ';' || c.is_whitespace())
+            .map(|ext| ext.trim_start_matches('.'))
+            .filter(|ext| !ext.is_empty())
+    }
+
+    /// Whether the path has one of the extensions, compared case-insensitively.
+    ///
+    /// ```
+    /// # use std::path::Path;
+    /// # use mass_renamer_core::directory::ExtensionGroup;
+    /// let images = ExtensionGroup::new("Images", "jpg, .png");
+    /// assert!(images.contains(Path::new("IMG_1.JPG")));
+    /// assert!(!images.contains(Path::new("notes.txt")));
+    /// ```
+    pub fn contains(&self, path: &Path) -> bool {
+        let Some(ext) = path.extension() else {
+            return false;
+        };
+        let ext = ext.to_string_lossy();
+        self.extensions()
+            .any(|wanted| wanted.eq_ignore_ascii_case(&ext))
+    }
+}
+
+impl Filter {
+    /// Compile the mask and regular expression, failing on an invalid expression.
+    /// Groups in the mask are looked up in the [ExtensionGroup::defaults].
+    ///
+    /// ```
+    /// # use std::path::Path;
+    /// # use mass_renamer_core::directory::Filter;
+    /// let filter = Filter { mask: "*.jpg;*.png".into(), regex: r"^IMG_\d".into(), ..Default::default() };
+    /// let matcher = filter.matcher().unwrap();
+    /// assert!(matcher.matches(Path::new("IMG_1.JPG")));
+    /// assert!(!matcher.matches(Path::new("IMG_1.txt")));
+    /// assert!(!matcher.matches(Path::new("DSC_1.png")));
+    /// ```
+    pub fn matcher(&self) -> Result<Matcher, DirectoryError> {
+        self.matcher_with(&ExtensionGroup::defaults())
+    }
+
+    /// As [Filter::matcher], looking up the groups in the mask in `groups`, e.g. the ones
+    /// edited in the settings. Naming a group that isn't there fails.
+    pub fn matcher_with(&self, groups: &[ExtensionGroup]) -> Result<Matcher, DirectoryError> {
+        let mut globs = vec![];
+        for glob in self.mask.split(';').map(str::trim) {
+            match glob.strip_prefix('@') {
+                Some(name) => {
+                    let group = groups
+                        .iter()
+                        .find(|group| group.name.eq_ignore_ascii_case(name.trim()))
+                        .ok_or_else(|| DirectoryError::UnknownGroup(name.trim().into()))?;
+                    globs.extend(
+                        group
+                            .extensions()
+                            .map(|ext| glob_to_regex(&format!("*.{ext}"))),
+                    );
+                }
+                None if !glob.is_empty() => globs.push(glob_to_regex(glob)),
+                None => (),
+            }
+        }
+        let mask = (!globs.is_empty())
+            .then(|| {
+                RegexBuilder::new(&format!("^(?:{})$", globs.join("|")))
+                    .case_insensitive(true)
+                    .build()
+            })
+            .transpose()?;
+        let regex = (!self.regex.is_empty())
+            .then(|| compile(&self.regex))
+            .transpose()?;
+        Ok(Matcher {
+            mask,
+            regex,
+            skip_hidden: self.skip_hidden,
+            kind: self.kind,
+        })
+    }
+}
+
+/// A compiled [Filter].
+#[derive(Debug, Clone)]
+pub struct Matcher {
+    mask: Option<Regex>,
+    regex: Option<Regex>,
+    skip_hidden: bool,
+    kind: EntryKind,
+}
+
+impl Matcher {
+    /// Whether the entry at `path` is listed. Only its name is matched, and the file
+    /// system is only read to tell files from folders and to find hidden entries.
+    pub fn matches(&self, path: &Path) -> bool {
+        let name = path.file_name().unwrap_or_default().to_string_lossy();
+        let kind_matches = match self.kind {
+            EntryKind::All => true,
+            EntryKind::Files => !path.is_dir(),
+            EntryKind::Folders => path.is_dir(),
+        };
+        kind_matches
+            && !(self.skip_hidden && is_hidden(path))
+            && self.mask.iter().all(|mask| mask.is_match(&name))
+            && self.regex.iter().all(|regex| regex.is_match(&name))
+    }
+}
+
+fn glob_to_regex(glob: &str) -> String {
+    glob.chars()
+        .map(|chr| match chr {
+            '*' => String::from(".*"),
+            '?' => String::from("."),
+            _ => regex::escape(&chr.to_string()),
+        })
+        .collect()
+}
+
+fn is_hidden(path: &Path) -> bool {
+    #[cfg(windows)]
+    {
+        use std::os::windows::fs::MetadataExt;
+        const FILE_ATTRIBUTE_HIDDEN: u32 = 0x2;
+        if fs::metadata(path).is_ok_and(|meta| meta.file_attributes() & FILE_ATTRIBUTE_HIDDEN != 0)
+        {
+            return true;
+        }
+    }
+    path.file_name()
+        .is_some_and(|name| name.to_string_lossy().starts_with('.'))
+}
+
+/// A change made to one file by hand, instead of what the options do to it.
+/// - `Name` - Give the file this name, extension included.
+/// - `Exclude` - Leave the file out of the batch, even when it is selected.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Override {
+    Name(String),
+    Exclude,
+}
+
+/// The [Override]s made by hand, kept by canonical original path so changing the options,
+/// filtering or re-reading a folder never loses them.
+///
+/// ```
+/// # use std::path::Path;
+/// # use mass_renamer_core::directory::{Override, Overrides};
+/// let mut overrides = Overrides::default();
+/// overrides.set(Path::new("a.txt"), Some(Override::Name("b.txt".into())));
+/// assert_eq!(overrides.get(Path::new("a.txt")), Some(&Override::Name("b.txt".into())));
+/// overrides.set(Path::new("a.txt"), None);
+/// assert!(overrides.is_empty());
+/// ```
+#[derive(Debug, Default, Clone)]
+pub struct Overrides {
+    overrides: BTreeMap<PathBuf, Override>,
+}
+
+impl Overrides {
+    pub fn clear(&mut self) {
+        self.overrides.clear()
+    }
+
+    /// Override the file, or clear its override with `None`.
+    pub fn set(&mut self, file: &Path, over: Option<Override>) {
+        match over {
+            Some(over) => self.overrides.insert(canonical(file), over),
+            None => self.overrides.remove(&canonical(file)),
+        };
+    }
+
+    /// Clear the file's override, returning it.
+    pub fn take(&mut self, file: &Path) -> Option<Override> {
+        self.overrides.remove(&canonical(file))
+    }
+
+    pub fn get(&self, file: &Path) -> Option<&Override> {
+        self.overrides.get(&canonical(file))
+    }
+
+    pub fn len(&self) -> usize {
+        self.overrides.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.overrides.is_empty()
+    }
+
+    /// The overridden files, by path.
+    pub fn iter(&self) -> impl Iterator<Item = (&Path, &Override)> {
+        self.overrides
+            .iter()
+            .map(|(path, over)| (path.as_path(), over))
+    }
+}
+
+/// Get the full path of a directory falling back to the home directory
+/// if nothing is provided. If the provided path is a file, the file's parent
+/// is returned.
+pub fn get_initial_directory<P: AsRef<Path>>(path: Option<P>) -> Result<PathBuf, DirectoryError> {
+    if let Some(arg) = path {
+        get_directory(arg)
+    } else {
+        match env::current_dir() {
+            Ok(dir) => Ok(dir),
+            Err(_) => {
+                let d = home::home_dir();
+                match d {
+                    Some(path) => Ok(path),
+                    None => Err(DirectoryError::NoHome),
+                }
+            }
+        }
+    }
+}
+
+/// Get the full path of a directory.
+/// If the provided path is a file, the file's parent is returned.
+pub fn get_directory<P: AsRef<Path>>(path: P) -> Result<PathBuf, DirectoryError> {
+    let p = canonicalize(path)?;
+    if p.is_file() {
+        Ok(p.parent().unwrap().into())
+    } else {
+        Ok(p)
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum DirectoryError {
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error("No home directory could be found")]
+    NoHome,
+    #[error(transparent)]
+    File(#[from] FileError),
+    #[error(transparent)]
+    Regex(#[from] regex::Error),
+    #[error("There is no extension group called {0}")]
+    UnknownGroup(String),
+}
+
+#[cfg(test)]
+mod directory_tests {
+    use super::*;
+    use crate::tester::temp_folder;
+
+    #[cfg(unix)]
+    #[test]
+    fn links_are_not_followed_out_or_round() {
+        use std::os::unix::fs::symlink;
+        let base = temp_folder("walk_links");
+        let (root, outside) = (base.join("root"), base.join("outside"));
+        fs::create_dir_all(root.join("sub")).unwrap();
+        fs::create_dir_all(&outside).unwrap();
+        fs::write(root.join("sub").join("a.txt"), "a").unwrap();
+        fs::write(outside.join("b.txt"), "b").unwrap();
+        symlink(&root, root.join("sub").join("loop")).unwrap();
+        symlink(&outside, root.join("out")).unwrap();
+        let files = |follow_links| {
+            let mut files = walk(&root, follow_links)
+                .unwrap()
+                .into_iter()
+                .flat_map(|folder| folder.files)
+                .map(|file| file.file_name().unwrap().to_string_lossy().into_owned())
+                .collect::<Vec<_>>();
+            files.sort();
+            files
+        };
+        let (skipped, followed) = (files(false), files(true));
+        fs::remove_dir_all(&base).ok();
+        assert_eq!(skipped, ["a.txt"]);
+        assert_eq!(followed, ["a.txt", "b.txt"]);
+    }
+
+    #[test]
+    fn filter_by_kind_and_hidden() {
+        let base = temp_folder("filter_entries");
+        fs::create_dir_all(base.join("photos.jpg")).unwrap();
+        for name in ["a.JPG", "b.png", "c.txt", ".d.jpg"] {
+            fs::write(base.join(name), name).unwrap();
+        }
+        let listed = |filter: Filter| {
+            let matcher = filter.matcher().unwrap();
+            let mut names = read_dir(&base)
+                .unwrap()
+                .flatten()
+                .map(|entry| entry.path())
+                .filter(|path| matcher.matches(path))
+                .map(|path| path.file_name().unwrap().to_string_lossy().into_owned())
+                .collect::<Vec<_>>();
+            names.sort();
+            names
+        };
+        let mask = String::from("*.jpg; *.png");
+        let all = listed(Filter {
+            mask: mask.clone(),
+            skip_hidden: true,
+            ..Default::default()
+        });
+        let files = listed(Filter {
+            mask: mask.clone(),
+            kind: EntryKind::Files,
+            ..Default::default()
+        });
+        let folders = listed(Filter {
+            mask,
+            kind: EntryKind::Folders,
+            ..Default::default()
+        });
+        let invalid = Filter {
+            regex: "(".into(),
+            ..Default::default()
+        };
+        let images = listed(Filter {
+            mask: "@images".into(),
+            ..Default::default()
+        });
+        let docs = Filter {
+            mask: "@Docs".into(),
+            ..Default::default()
+        };
+        let groups = [ExtensionGroup::new("Docs", "TXT")];
+        let texts = docs.matcher_with(&groups).unwrap();
+        let unknown = docs.matcher_with(&groups[..0]);
+        fs::remove_dir_all(&base).ok();
+        assert_eq!(images, [".d.jpg", "a.JPG", "b.png", "photos.jpg"]);
+        assert!(texts.matches(&base.join("c.txt")));
+        assert!(!texts.matches(&base.join("b.png")));
+        assert!(matches!(unknown, Err(DirectoryError::UnknownGroup(name)) if name == "Docs"));
+        assert_eq!(all, ["a.JPG", "b.png", "photos.jpg"]);
+        assert_eq!(files, [".d.jpg", "a.JPG", "b.png"]);
+        assert_eq!(folders, ["photos.jpg"]);
+        assert!(invalid.matcher().is_err());
+    }
+
+    #[cfg(feature = "report")]
+    #[test]
+    fn export_preview_as_csv() {
+        use crate::file::{Case, CaseOptions};
+        let base = temp_folder("export_preview");
+        fs::create_dir_all(base.join("sub")).unwrap();
+        for name in ["a.txt", "B.txt"] {
+            fs::write(base.join(name), name).unwrap();
+        }
+        let case = CaseOptions {
+            case: Case::Upper,
+            ..Default::default()
+        };
+        let mut out = vec![];
+        let mut dir = Directory::new(&base).unwrap();
+        let exported = dir.export_preview(&[Options::Case(case)], ReportFormat::Csv, &mut out);
+        let base = canonicalize(&base).unwrap();
+        fs::remove_dir_all(&base).ok();
+        exported.unwrap();
+        let lines = String::from_utf8(out).unwrap();
+        let lines = lines.lines().skip(1).collect::<Vec<_>>();
+        let (a, b) = (base.join("a.txt"), base.join("B.txt"));
+        assert_eq!(
+            lines,
+            [
+                format!("{},{},unchanged,false,,", b.display(), b.display()),
+                format!(
+                    "{},{},rename,false,,",
+                    a.display(),
+                    base.join("A.txt").display()
+                ),
+            ]
+        );
+    }
+}
@@ -0,0 +1,1034 @@
+use std::{
+    cmp::Ordering,
+    collections::BTreeMap,
+    fmt::{self, Debug, Display, Formatter},
+    fs, io,
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+
+pub mod add;
+pub mod blacklist;
+pub mod case;
+pub mod date;
+pub mod destination;
+pub mod extension;
+pub mod folder;
+pub mod lookup;
+pub mod name;
+pub mod normalize;
+pub mod number;
+pub mod pair;
+pub mod reg;
+pub mod release;
+pub mod remove;
+pub mod replace;
+pub mod sanitize;
+pub mod swap;
+
+use crate::{
+    directory::ExtensionGroup,
+    generate_path_as_string,
+    metadata::{self, Metadata},
+    PathString,
+};
+pub use add::AddOptions;
+pub use blacklist::{Blacklist, BlacklistMode, BlacklistOptions};
+pub use case::{Case, CaseOptions};
+use chrono::{DateTime, Local};
+pub use date::{DateFormat, DateMode, DateOptions, DatePrefix, DateSuffix, DateType};
+pub use destination::DestinationOptions;
+pub use extension::ExtensionOptions;
+pub use folder::{FolderMode, FolderOptions};
+pub use lookup::LookupOptions;
+pub use name::NameOptions;
+pub use normalize::NormalizeOptions;
+pub use number::{NumberFormat, NumberMode, NumberOptions};
+pub use pair::PairOptions;
+pub use reg::RegexOptions;
+pub use release::ReleaseOptions;
+pub use remove::RemoveOptions;
+pub use replace::ReplaceOptions;
+pub use sanitize::{CharPolicy, SanitizeOptions};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+pub use swap::{SwapMode, SwapOptions};
+use thiserror::Error;
+
+/// How long [File::preview] may spend on one file before skipping its remaining steps.
+pub const FILE_BUDGET: Duration = Duration::from_millis(250);
+
+pub trait Process {
+    fn process(&self, file: &mut File);
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct File {
+    stem: String,
+    valid_original: bool,
+    extension: Option<String>,
+    original: PathBuf,
+    metadata: Option<Metadata>,
+    tokens: Tokens,
+    errors: Vec<String>,
+    lookup: Option<LookupOptions>,
+    add: Option<AddOptions>,
+    blacklist: Option<BlacklistOptions>,
+    case: Option<CaseOptions>,
+    date: Option<DateOptions>,
+    ext: Option<ExtensionOptions>,
+    folder: Option<FolderOptions>,
+    name: Option<NameOptions>,
+    normalize: Option<NormalizeOptions>,
+    number: Option<NumberOptions>,
+    regex: Option<RegexOptions>,
+    release: Option<ReleaseOptions>,
+    remove: Option<RemoveOptions>,
+    replace: Option<ReplaceOptions>,
+    sanitize: Option<SanitizeOptions>,
+    destination: Option<DestinationOptions>,
+    /// The folder the Destination step moves the file to, as of the last [File::preview].
+    destination_folder: Option<PathBuf>,
+    /// The options in the order [File::with_pipeline] was given them, empty for [Step] order.
+    pipeline: Vec<Options>,
+    /// The extensions each step is limited to, see [File::with_scopes].
+    scopes: Scopes,
+    /// Whether changed names are trimmed as Windows would, see [File::with_windows_names].
+    windows_names: bool,
+    /// The blacklists [crate::batch::prepare] compiled for the last Blacklist options of the
+    /// pipeline, in order.
+    blacklists: Vec<Result<Blacklist, regex::Error>>,
+}
+
+impl File {
+    /// Create a new File object from a Path.
+    /// No checking is performed to validate that the Path exists or is a file.
+    /// To perform this check use [File::try_from<&Path>], [File::try_from<&PathBuf>], or [File::try_from<PathBuf>]
+    pub fn new(path: &Path) -> Result<Self, FileError> {
+        let extension = {
+            generate_path_as_string(path.extension()).map(|e| match e {
+                PathString::Valid(s) => s,
+                PathString::Invalid(s) => s,
+            })
+        };
+        match generate_path_as_string(path.file_stem()) {
+            Some(stem) => {
+                let (stem, valid_original) = match stem {
+                    PathString::Valid(s) => (s, true),
+                    PathString::Invalid(s) => (s, false),
+                };
+                Ok(Self {
+                    stem,
+                    valid_original,
+                    extension,
+                    original: path.to_owned(),
+                    windows_names: cfg!(windows),
+                    ..Default::default()
+                })
+            }
+            None => Err(FileError::BadStem),
+        }
+    }
+}
+
+impl TryFrom<&Path> for File {
+    type Error = FileError;
+
+    fn try_from(path: &Path) -> Result<Self, FileError> {
+        if !path.exists() {
+            return Err(FileError::NotExists);
+        }
+        let extension = {
+            generate_path_as_string(path.extension()).map(|e| match e {
+                PathString::Valid(s) => s,
+                PathString::Invalid(s) => s,
+            })
+        };
+        match generate_path_as_string(path.file_stem()) {
+            Some(stem) => {
+                let (stem, valid_original) = match stem {
+                    PathString::Valid(s) => (s, true),
+                    PathString::Invalid(s) => (s, false),
+                };
+                Ok(Self {
+                    stem,
+                    valid_original,
+                    extension,
+                    original: path.to_owned(),
+                    ..Default::default()
+                })
+            }
+            None => Err(FileError::BadStem),
+        }
+    }
+}
+
+impl TryFrom<PathBuf> for File {
+    type Error = FileError;
+
+    fn try_from(value: PathBuf) -> Result<Self, FileError> {
+        value.as_path().try_into()
+    }
+}
+
+impl TryFrom<&PathBuf> for File {
+    type Error = FileError;
+
+    fn try_from(value: &PathBuf) -> Result<Self, FileError> {
+        value.as_path().try_into()
+    }
+}
+impl Display for File {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match &self.extension {
+            None => write!(f, "{}", self.stem),
+            Some(ext) => write!(f, "{}.{}", self.stem, ext),
+        }
+    }
+}
+
+impl File {
+    /// Tool to rename a single file.
+    /// Takes the `&path` and various options (processed in order) to return a `PathBuf`
+    /// used to rename the file.
+    /// Options are processed in [Step] order, from RegEx (1) to Release Tags (14), or in
+    /// the order given to [File::with_pipeline].
+    /// Swap (15) is applied afterwards across a batch, see [SwapOptions::apply].
+    /// Lookup (16) tokens are read before the first step so every step can use them.
+    /// RAW+JPEG Pairs (17) are matched up last across a batch, see [PairOptions::apply].
+    /// Destination (18) only changes the folder the file goes to, not its name.
+    ///
+    /// Once a file has taken [FILE_BUDGET], its remaining steps are skipped and noted in
+    /// [File::errors].
+    ///
+    /// Last of all, for Windows names (see [File::with_windows_names]), dots and spaces are
+    /// trimmed from the end of a name the steps changed, since Windows silently drops them
+    /// on rename. A stem ending in `.` or ` ` is kept when there is an extension after it,
+    /// and an extension trimmed to nothing is removed.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use std::path::{Path, PathBuf};
+    /// # use mass_renamer_core::file::{NameOptions, Case, CaseOptions, File, Process, Options};
+    /// let file = Path::new("file.txt");
+    /// let name = NameOptions::Fixed("new_name".into());
+    /// let case = CaseOptions{case: Case::Upper, snake: false, exceptions: "n".into(), ..Default::default()};
+    /// let mut rename = File::new(file).unwrap().with_option(Options::Name(name)).with_option(Options::Case(case));
+    /// let new_name = rename.preview();
+    /// assert_eq!(new_name, PathBuf::from("nEW_nAME.txt"));
+    /// ```
+    pub fn preview(&mut self) -> PathBuf {
+        self.errors.clear();
+        self.destination_folder = None;
+        let lookup = self
+            .lookup
+            .as_ref()
+            .filter(|_| Step::Lookup.enabled() && self.in_scope(Step::Lookup));
+        if let Some(lookup) = lookup {
+            match lookup.tokens(&self.original) {
+                Some(tokens) => self.tokens.extend(tokens),
+                None => self.fail(Step::Lookup, "not found in the table"),
+            }
+        }
+        if let Some(metadata) = &self.metadata {
+            self.tokens.extend(metadata.tokens());
+        }
+        let opts = if self.pipeline.is_empty() {
+            Step::ALL
+                .into_iter()
+                .filter_map(|step| Some((step, self.processor(step)?)))
+                .collect::<Vec<_>>()
+        } else {
+            let blacklists = self
+                .pipeline
+                .iter()
+                .filter(|opt| opt.step() == Step::Blacklist)
+                .count();
+            let mut uncompiled = blacklists.saturating_sub(self.blacklists.len());
+            let mut compiled = self.blacklists.iter();
+            self.pipeline
+                .iter()
+                .filter_map(|opt| {
+                    let processor = match opt {
+                        Options::Blacklist(_) if uncompiled == 0 => compiled
+                            .next()
+                            .map(|blacklist| Box::new(blacklist.clone()) as Box<dyn Process>),
+                        Options::Blacklist(_) => {
+                            uncompiled -= 1;
+                            self.pipeline_processor(opt)
+                        }
+                        _ => self.pipeline_processor(opt),
+                    };
+                    Some((opt.step(), processor?))
+                })
+                .collect()
+        };
+        let before = (self.stem.clone(), self.extension.clone());
+        let start = Instant::now();
+        for (step, opt) in opts {
+            if !self.in_scope(step) {
+                continue;
+            }
+            // A huge name or a slow expression can't hold up the rest of the batch.
+            if start.elapsed() > FILE_BUDGET {
+                self.fail(step, format!("skipped, the name took over {FILE_BUDGET:?}"));
+                continue;
+            }
+            opt.process(self);
+        }
+        if self.windows_names && (&self.stem, &self.extension) != (&before.0, &before.1) {
+            self.trim_end();
+        }
+        self.new_path()
+    }
+
+    /// Trim the dots and spaces Windows would drop from the end of the name, unless
+    /// nothing would be left.
+    fn trim_end(&mut self) {
+        const TRAILING: [char; 2] = ['.', ' '];
+        if let Some(ext) = &self.extension {
+            let trimmed = ext.trim_end_matches(TRAILING);
+            if !trimmed.is_empty() {
+                self.extension = Some(trimmed.to_owned());
+                return;
+            }
+        }
+        let stem = self.stem.trim_end_matches(TRAILING);
+        if !stem.is_empty() {
+            self.extension = None;
+            self.stem = stem.to_owned();
+        }
+    }
+
+    /// The processor set for a step, if any. Swap, Lookup and Pair aren't applied per step.
+    fn processor(&self, step: Step) -> Option<Box<dyn Process>> {
+        fn boxed<T: Process + Clone + 'static>(opt: &Option<T>) -> Option<Box<dyn Process>> {
+            opt.clone().map(|opt| Box::new(opt) as Box<dyn Process>)
+        }
+        match step {
+            Step::Regex => boxed(&self.regex),
+            Step::Name => boxed(&self.name),
+            Step::Replace => boxed(&self.replace),
+            Step::Case => boxed(&self.case),
+            Step::Remove => boxed(&self.remove),
+            Step::Add => boxed(&self.add),
+            Step::Date => boxed(&self.date),
+            Step::Folder => boxed(&self.folder),
+            Step::Number => boxed(&self.number),
+            Step::Extension => boxed(&self.ext),
+            Step::Normalize => boxed(&self.normalize),
+            Step::Sanitize => boxed(&self.sanitize),
+            Step::Blacklist => boxed(&self.blacklist),
+            Step::Release => boxed(&self.release),
+            Step::Destination => boxed(&self.destination),
+            Step::Swap | Step::Lookup | Step::Pair => None,
+        }
+    }
+
+    /// The processor for an option of the pipeline. Numbering uses the file's own, so
+    /// each file keeps the number its batch gave it, see [NumberOptions::sequence].
+    fn pipeline_processor(&self, opt: &Options) -> Option<Box<dyn Process>> {
+        match opt {
+            Options::Number(_) => self.processor(Step::Number),
+            _ => File::default()
+                .with_option(opt.clone())
+                .processor(opt.step()),
+        }
+    }
+
+    /// The path the file will be renamed to, as of the last [File::preview].
+    pub fn new_path(&self) -> PathBuf {
+        let mut new_name = match (&self.destination_folder, self.original.parent()) {
+            (Some(folder), _) => folder.clone(),
+            (None, None) => PathBuf::from("/"),
+            (None, Some(p)) => PathBuf::from(p),
+        };
+        // Join the name by hand, `with_extension` would replace any dotted part of the stem.
+        match &self.extension {
+            None => new_name.push(&self.stem),
+            Some(e) => new_name.push(format!("{}.{}", self.stem, e)),
+        };
+        new_name
+    }
+
+    /// The extension the file will have, without the dot, as of the last [File::preview].
+    ///
+    /// ```
+    /// # use std::path::Path;
+    /// # use mass_renamer_core::file::{ExtensionOptions, File, Options};
+    /// let lower = Options::Extension(ExtensionOptions::Lower);
+    /// let mut file = File::new(Path::new("a.JPG")).unwrap().with_option(lower);
+    /// assert_eq!(file.extension(), Some("JPG"));
+    /// file.preview();
+    /// assert_eq!(file.extension(), Some("jpg"));
+    /// ```
+    pub fn extension(&self) -> Option<&str> {
+        self.extension.as_deref()
+    }
+
+    /// Why steps of the last [File::preview] couldn't be applied to the file, e.g. it
+    /// has no date to add. The steps are skipped, leaving the rest of the name as it is.
+    pub fn errors(&self) -> &[String] {
+        &self.errors
+    }
+
+    /// The number the Numbering step gives the file, formatted as in the new name, or
+    /// `None` if it isn't numbered. Each file of a batch has its own once
+    /// [crate::batch::prepare] has run.
+    pub fn counter(&self) -> Option<String> {
+        self.number
+            .as_ref()
+            .filter(|number| number.mode != NumberMode::None)
+            .map(NumberOptions::number_value)
+    }
+
+    /// Whether the file is copied to its new path rather than moved, see
+    /// [DestinationOptions].
+    pub fn copies(&self) -> bool {
+        self.destination.as_ref().is_some_and(|opt| opt.copy)
+    }
+
+    /// Use blacklists compiled for the batch for the last Blacklist options of the pipeline,
+    /// rather than compiling them again for the file.
+    pub(crate) fn set_blacklists(&mut self, blacklists: Vec<Result<Blacklist, regex::Error>>) {
+        self.blacklists = blacklists;
+    }
+
+    /// Note that a step couldn't be applied to the file, see [File::errors].
+    pub(crate) fn fail<E: Display>(&mut self, step: Step, reason: E) {
+        self.errors.push(format!("{step}: {reason}"));
+    }
+
+    /// The path the file is renamed from.
+    pub fn original(&self) -> &Path {
+        &self.original
+    }
+
+    /// Give the file a new name by hand, extension included, in place of the previewed one.
+    pub fn set_name(&mut self, name: &str) {
+        let name = Path::new(name);
+        self.stem = name
+            .file_stem()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .into();
+        self.extension = name
+            .extension()
+            .map(|ext| ext.to_string_lossy().into_owned());
+    }
+
+    /// Add text to the end of the new stem, after the options have been applied.
+    pub(crate) fn push_stem(&mut self, text: &str) {
+        self.stem.push_str(text);
+    }
+
+    /// Rename the file. Can not be undone.
+    pub fn rename(mut self) -> Result<(), FileError> {
+        let new_name = &self.preview();
+        fs::rename(&self.original, new_name)?;
+        Ok(())
+    }
+
+    /// Revert the previewed changes to a file.
+    pub fn revert(&mut self) {
+        let temp: &File = &self.original.clone().try_into().unwrap();
+        self.stem = temp.stem.clone();
+        self.extension = temp.extension.clone();
+    }
+
+    /// Add an option, replacing any other of its [Step]. With a pipeline, see
+    /// [File::with_pipeline], the option is applied after the rest of it.
+    pub fn with_option(mut self, option: Options) -> Self {
+        use Options::*;
+        if !self.pipeline.is_empty() {
+            self.pipeline.push(option.clone());
+        }
+        match option {
+            Regex(opt) => self.regex = Some(opt),
+            Name(opt) => self.name = Some(opt),
+            Replace(opt) => self.replace = Some(opt),
+            Case(opt) => self.case = Some(opt),
+            Remove(opt) => self.remove = Some(opt),
+            Add(opt) => self.add = Some(opt),
+            Date(opt) => self.date = Some(opt),
+            Folder(opt) => self.folder = Some(opt),
+            Number(opt) => self.number = Some(opt),
+            Extension(opt) => self.ext = Some(opt),
+            Normalize(opt) => self.normalize = Some(opt),
+            Sanitize(opt) => self.sanitize = Some(opt),
+            Blacklist(opt) => self.blacklist = Some(opt),
+            Release(opt) => self.release = Some(opt),
+            Destination(opt) => self.destination = Some(opt),
+            Lookup(opt) => self.lookup = Some(opt),
+            // Swapping needs the whole batch, see [SwapOptions::apply].
+            Swap(_) => (),
+            Pair(_) => (),
+        }
+        self
+    }
+
+    /// Apply the options in the order given, after any added before, rather than in
+    /// [Step] order, e.g. Case after Numbering. An option can be given more than once to
+    /// apply it at different stages. Steps that work across a batch, such as Numbering,
+    /// Swap and Lookup, use the last of their options.
+    ///
+    /// ```
+    /// # use std::path::{Path, PathBuf};
+    /// # use mass_renamer_core::file::{AddOptions, Case, CaseOptions, File, Options, ReplaceOptions};
+    /// let upper = Options::Case(CaseOptions { case: Case::Upper, ..Default::default() });
+    /// let add = Options::Add(AddOptions { suffix: Some("_v1".into()), ..Default::default() });
+    /// let replace = Options::Replace(ReplaceOptions { replace: "_".into(), with: " ".into(), case: true });
+    /// let mut file = File::new(Path::new("my_file.txt")).unwrap().with_pipeline(&[replace.clone(), upper, add, replace]);
+    /// assert_eq!(file.preview(), PathBuf::from("MY FILE v1.txt"));
+    /// ```
+    pub fn with_pipeline(mut self, options: &[Options]) -> Self {
+        let mut pipeline = match std::mem::take(&mut self.pipeline) {
+            pipeline if pipeline.is_empty() => self.options(),
+            pipeline => pipeline,
+        };
+        for opt in options {
+            self = self.with_option(opt.clone());
+        }
+        pipeline.extend_from_slice(options);
+        self.pipeline = pipeline;
+        self
+    }
+
+    /// The options added with [File::with_option], in [Step] order.
+    fn options(&self) -> Vec<Options> {
+        [
+            self.regex.clone().map(Options::Regex),
+            self.name.clone().map(Options::Name),
+            self.replace.clone().map(Options::Replace),
+            self.case.clone().map(Options::Case),
+            self.remove.clone().map(Options::Remove),
+            self.add.clone().map(Options::Add),
+            self.date.clone().map(Options::Date),
+            self.folder.clone().map(Options::Folder),
+            self.number.clone().map(Options::Number),
+            self.ext.clone().map(Options::Extension),
+            self.normalize.map(Options::Normalize),
+            self.sanitize.map(Options::Sanitize),
+            self.blacklist.clone().map(Options::Blacklist),
+            self.release.map(Options::Release),
+            self.destination.clone().map(Options::Destination),
+        ]
+        .into_iter()
+        .flatten()
+        .collect()
+    }
+
+    /// Limit steps to files with one of the extensions given for them, e.g. Replace to
+    /// `.jpg;.png` while the `.xmp` sidecars are only numbered. Extensions are compared
+    /// case-insensitively, see [ExtensionGroup]. Steps without extensions apply to every
+    /// file. Swap and RAW+JPEG Pairs work across the whole batch, so they can't be limited.
+    ///
+    /// ```
+    /// # use std::path::{Path, PathBuf};
+    /// # use mass_renamer_core::file::{AddOptions, File, Options, Scopes, Step};
+    /// let add = Options::Add(AddOptions { prefix: Some("IMG_".into()), ..Default::default() });
+    /// let scopes = Scopes::from([(Step::Add, ".jpg;.png".to_owned())]);
+    /// let photo = File::new(Path::new("a.JPG")).unwrap().with_option(add.clone());
+    /// let sidecar = File::new(Path::new("a.xmp")).unwrap().with_option(add);
+    /// assert_eq!(photo.with_scopes(scopes.clone()).preview(), PathBuf::from("IMG_a.JPG"));
+    /// assert_eq!(sidecar.with_scopes(scopes).preview(), PathBuf::from("a.xmp"));
+    /// ```
+    pub fn with_scopes(mut self, scopes: Scopes) -> Self {
+        self.scopes = scopes;
+        self
+    }
+
+    /// Make names Windows will keep as they are, by trimming the dots and spaces it drops
+    /// from the end of a changed name, see [File::preview]. On by default on Windows, and
+    /// worth setting for files shared with or synced to it.
+    pub fn with_windows_names(mut self, windows_names: bool) -> Self {
+        self.windows_names = windows_names;
+        self
+    }
+
+    /// Whether the step applies to the file, see [File::with_scopes].
+    pub(crate) fn in_scope(&self, step: Step) -> bool {
+        match self.scopes.get(&step) {
+            Some(extensions) if !extensions.trim().is_empty() => {
+                ExtensionGroup::new(step.name(), extensions).contains(&self.original)
+            }
+            _ => true,
+        }
+    }
+
+    /// Use metadata read ahead of time (see [crate::metadata::Scheduler]) instead of
+    /// reading it from the file system while previewing.
+    pub fn with_metadata(mut self, metadata: Metadata) -> Self {
+        self.metadata = Some(metadata);
+        self
+    }
+
+    /// Add values for `{name}` placeholders in the Name and Add text.
+    pub fn with_tokens(mut self, tokens: Tokens) -> Self {
+        self.tokens.extend(tokens);
+        self
+    }
+
+    /// Replace the `{name}` placeholders in `text` with the file's tokens.
+    pub fn expand(&self, text: &str) -> String {
+        expand_tokens(text, &self.tokens)
+    }
+
+    // Return the information on a file.
+    pub fn info(&self) -> (Filename, Extension, Size, DateModified, DateCreated) {
+        let mut size = None;
+        let mut modified = None;
+        let mut created = None;
+        if let Ok(data) = self.original.metadata() {
+            if self.original.is_file() {
+                size = Some(data.len())
+            };
+            if let Ok(dt) = data.modified() {
+                modified = Some(dt.into())
+            };
+            if let Ok(dt) = data.created() {
+                created = Some(dt.into())
+            };
+        };
+        (
+            &self.stem,
+            self.extension.as_deref(),
+            size,
+            modified,
+            created,
+        )
+    }
+
+    // Check if the original file was valid UTF-8
+    pub fn is_valid(&self) -> bool {
+        self.valid_original
+    }
+}
+
+pub type Filename<'a> = &'a str;
+pub type Extension<'a> = Option<&'a str>;
+pub type Size = Option<u64>;
+pub type DateCreated = Option<DateTime<Local>>;
+pub type DateModified = Option<DateTime<Local>>;
+
+#[derive(Debug, Error)]
+pub enum FileError {
+    #[error("File does not exist.")]
+    NotExists,
+    #[error("File does not have a stem.")]
+    BadStem,
+    #[error("{0} already exists.")]
+    Exists(PathBuf),
+    #[error("{0} has no earlier names to restore.")]
+    NoHistory(PathBuf),
+    /// The file was there when the batch was previewed, but was gone when it was renamed.
+    #[error("{0} no longer exists.")]
+    Missing(PathBuf),
+    /// The file, or its new name, is in a folder protected by [crate::guard::Guard].
+    #[error("{0} is in a protected folder.")]
+    Protected(PathBuf),
+    /// Failed on purpose to test partial failures, see [crate::batch::CHAOS_VAR].
+    #[error("{0} was failed on purpose.")]
+    Injected(PathBuf),
+    /// The batch was cancelled before the file was renamed, see [crate::batch::rename_watched].
+    #[error("{0} wasn't renamed as the batch was cancelled.")]
+    Cancelled(PathBuf),
+    /// The file was moved to a temporary name to break a cycle, see
+    /// [crate::batch::rename], then couldn't be given its new name nor its old one back.
+    #[error("{0} couldn't be renamed and was left at {1}.")]
+    Parked(PathBuf, PathBuf),
+    #[error(transparent)]
+    Io(#[from] io::Error),
+}
+
+/// A single processor to apply with [File::with_option].
+/// New processors may be added in minor releases.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[non_exhaustive]
+pub enum Options {
+    Regex(RegexOptions),
+    Name(NameOptions),
+    Replace(ReplaceOptions),
+    Case(CaseOptions),
+    Remove(RemoveOptions),
+    Add(AddOptions),
+    Date(DateOptions),
+    Folder(FolderOptions),
+    Number(NumberOptions),
+    Extension(ExtensionOptions),
+    Normalize(NormalizeOptions),
+    Sanitize(SanitizeOptions),
+    Blacklist(BlacklistOptions),
+    Release(ReleaseOptions),
+    /// Applied to a whole batch with [SwapOptions::apply], [File::with_option] ignores it.
+    Swap(SwapOptions),
+    Lookup(LookupOptions),
+    /// Applied to a whole batch with [PairOptions::apply], [File::with_option] ignores it.
+    Pair(PairOptions),
+    Destination(DestinationOptions),
+}
+
+/// The processing steps, in the order [File::preview] applies them.
+/// Each step is numbered from 1 in that order, as shown in the GUI and help.
+///
+/// Swap (15) and RAW+JPEG Pairs (17) are applied afterwards across a batch, and
+/// Lookup (16) tokens are read before the first step. Destination (18) comes last.
+///
+/// # Example
+///
+/// ```
+/// # use mass_renamer_core::file::{CaseOptions, Options, Step};
+/// let step = Options::Case(CaseOptions::default()).step();
+/// assert_eq!(step, Step::Case);
+/// assert_eq!(format!("step {} of {}", step.number(), Step::ALL.len()), "step 4 of 18");
+/// assert_eq!(step.to_string(), "Case (4)");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Step {
+    Regex,
+    Name,
+    Replace,
+    Case,
+    Remove,
+    Add,
+    Date,
+    Folder,
+    Number,
+    Extension,
+    Normalize,
+    Sanitize,
+    Blacklist,
+    Release,
+    Swap,
+    Lookup,
+    Pair,
+    Destination,
+}
+
+impl Step {
+    /// Every step, in processing order.
+    pub const ALL: [Step; 18] = [
+        Step::Regex,
+        Step::Name,
+        Step::Replace,
+        Step::Case,
+        Step::Remove,
+        Step::Add,
+        Step::Date,
+        Step::Folder,
+        Step::Number,
+        Step::Extension,
+        Step::Normalize,
+        Step::Sanitize,
+        Step::Blacklist,
+        Step::Release,
+        Step::Swap,
+        Step::Lookup,
+        Step::Pair,
+        Step::Destination,
+    ];
+
+    /// The step's number, counting from 1.
+    pub fn number(self) -> usize {
+        self as usize + 1
+    }
+
+    /// Whether the step is part of this build, see the crate features.
+    /// A disabled step's options are still accepted, they just have no effect.
+    pub const fn enabled(self) -> bool {
+        !matches!(self, Step::Lookup) || cfg!(feature = "lookup")
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Step::Regex => "RegEx",
+            Step::Name => "Name",
+            Step::Replace => "Replace",
+            Step::Case => "Case",
+            Step::Remove => "Remove",
+            Step::Add => "Add",
+            Step::Date => "Auto Date",
+            Step::Folder => "Append Folder Name",
+            Step::Number => "Numbering",
+            Step::Extension => "Extension",
+            Step::Normalize => "Unicode Normalization",
+            Step::Sanitize => "Sanitize",
+            Step::Blacklist => "Blacklist",
+            Step::Release => "Release Tags",
+            Step::Swap => "Swap",
+            Step::Lookup => "Lookup",
+            Step::Pair => "RAW+JPEG Pairs",
+            Step::Destination => "Destination",
+        }
+    }
+}
+
+impl Display for Step {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ({})", self.name(), self.number())
+    }
+}
+
+fn uses_metadata(text: &str) -> bool {
+    ["group_min_date", "group_max_date"]
+        .iter()
+        .chain(metadata::TOKENS.iter())
+        .any(|token| text.contains(&format!("{{{token}}}")))
+}
+
+/// The byte offset of the character at `idx` in `text`, or its length if `idx` is past
+/// the end, for editing names by character position without splitting a character.
+pub(crate) fn char_offset(text: &str, idx: usize) -> usize {
+    text.char_indices()
+        .nth(idx)
+        .map_or(text.len(), |(offset, _)| offset)
+}
+
+/// Values for `{name}` placeholders, keyed by name.
+pub type Tokens = BTreeMap<String, String>;
+
+/// The extensions each step is limited to, see [File::with_scopes].
+pub type Scopes = BTreeMap<Step, String>;
+
+/// Replace each `{name}` in `text` with its token. Unknown names are left as they are.
+///
+/// # Example
+///
+/// ```
+/// # use mass_renamer_core::file::{expand_tokens, Tokens};
+/// let tokens = Tokens::from([("album".to_string(), "Blue".to_string())]);
+/// assert_eq!(expand_tokens("{album} - {track}", &tokens), "Blue - {track}");
+/// ```
+pub fn expand_tokens(text: &str, tokens: &Tokens) -> String {
+    let mut expanded = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('{') {
+        expanded.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let token = rest
+            .find('}')
+            .and_then(|end| Some((tokens.get(&rest[1..end])?, end)));
+        match token {
+            Some((value, end)) => {
+                expanded.push_str(value);
+                rest = &rest[end + 1..];
+            }
+            None => {
+                expanded.push('{');
+                rest = &rest[1..];
+            }
+        }
+    }
+    expanded.push_str(rest);
+    expanded
+}
+
+/// Read a list of words from a text file with one word or phrase per line.
+/// Blank lines and lines starting with `#` are ignored.
+pub fn read_word_list<P: AsRef<Path>>(path: P) -> Result<Vec<String>, io::Error> {
+    Ok(fs::read_to_string(path)?
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(String::from)
+        .collect())
+}
+
+impl Options {
+    /// The step this option belongs to.
+    pub fn step(&self) -> Step {
+        match self {
+            Options::Regex(_) => Step::Regex,
+            Options::Name(_) => Step::Name,
+            Options::Replace(_) => Step::Replace,
+            Options::Case(_) => Step::Case,
+            Options::Remove(_) => Step::Remove,
+            Options::Add(_) => Step::Add,
+            Options::Date(_) => Step::Date,
+            Options::Folder(_) => Step::Folder,
+            Options::Number(_) => Step::Number,
+            Options::Extension(_) => Step::Extension,
+            Options::Normalize(_) => Step::Normalize,
+            Options::Sanitize(_) => Step::Sanitize,
+            Options::Blacklist(_) => Step::Blacklist,
+            Options::Release(_) => Step::Release,
+            Options::Swap(_) => Step::Swap,
+            Options::Lookup(_) => Step::Lookup,
+            Options::Pair(_) => Step::Pair,
+            Options::Destination(_) => Step::Destination,
+        }
+    }
+
+    /// Check whether the option reads the file's metadata, e.g. the created or modified date,
+    /// including through the `{group_min_date}` and `{group_max_date}` tokens and the
+    /// [metadata::TOKENS].
+    pub fn needs_metadata(&self) -> bool {
+        match self {
+            Options::Date(opt) => {
+                opt.date_mode != DateMode::None && opt.date_type != DateType::Current
+            }
+            // These tokens come from the files' metadata.
+            Options::Name(NameOptions::Fixed(text)) => uses_metadata(text),
+            Options::Add(opt) => [
+                opt.prefix.as_deref(),
+                opt.insert.as_ref().map(|(_, text)| text.as_str()),
+                opt.suffix.as_deref(),
+            ]
+            .into_iter()
+            .flatten()
+            .any(uses_metadata),
+            Options::Destination(opt) => {
+                opt.uses_date() && opt.date_type != DateType::Current || uses_metadata(&opt.folder)
+            }
+            _ => false,
+        }
+    }
+}
+
+impl Ord for File {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self.original.is_dir(), other.original.is_dir()) {
+            (true, true) => self.stem.cmp(&other.stem),
+            (true, false) => Ordering::Less,
+            (false, true) => Ordering::Greater,
+            (false, false) => match (&self.extension, &other.extension) {
+                (None, None) => self.stem.cmp(&other.stem),
+                (None, Some(ext)) => {
+                    let mut rhs = other.stem.clone();
+                    rhs.push_str(ext);
+                    self.stem.cmp(&rhs)
+                }
+                (Some(ext), None) => {
+                    let mut lhs = self.stem.clone();
+                    lhs.push_str(ext);
+                    lhs.cmp(&other.stem)
+                }
+                (Some(self_ext), Some(other_ext)) => {
+                    let mut lhs = self.stem.clone();
+                    lhs.push_str(self_ext);
+                    let mut rhs = other.stem.clone();
+                    rhs.push_str(other_ext);
+                    lhs.cmp(&rhs)
+                }
+            },
+        }
+    }
+}
+
+impl PartialEq for File {
+    fn eq(&self, other: &Self) -> bool {
+        self.original == other.original
+    }
+}
+
+impl Eq for File {}
+
+impl PartialOrd for File {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+#[cfg(test)]
+mod file_tests {
+    use super::*;
+
+    #[test]
+    fn test_regex() {
+        let file = Path::new("Testfile123.txt");
+        let expected = PathBuf::from("TestfileABC.txt");
+        let opt = RegexOptions {
+            exp: "123".into(),
+            rep: "ABC".into(),
+            extension: false,
+        };
+        let mut rename = File::new(file).unwrap().with_option(Options::Regex(opt));
+        let result = rename.preview();
+        assert_eq!(result, expected)
+    }
+
+    #[test]
+    fn test_name() {
+        let file = Path::new("file.txt");
+        let expected = PathBuf::from("new_name.txt");
+        let name = NameOptions::Fixed("new_name".into());
+        let mut rename = File::new(file).unwrap().with_option(Options::Name(name));
+        let new_name = rename.preview();
+        assert_eq!(new_name, expected)
+    }
+
+    #[test]
+    fn trailing_dots_and_spaces_trimmed() {
+        let folder = FolderOptions {
+            mode: FolderMode::Suffix,
+            sep: " .".into(),
+            levels: 1,
+        };
+        let mut rename = File::new(Path::new("dir/file"))
+            .unwrap()
+            .with_windows_names(true)
+            .with_option(Options::Folder(folder));
+        assert_eq!(rename.preview(), PathBuf::from("dir/filedir"));
+        let name = NameOptions::Fixed("new. ".into());
+        let mut rename = File::new(Path::new("file.txt"))
+            .unwrap()
+            .with_windows_names(true)
+            .with_option(Options::Name(name.clone()));
+        assert_eq!(rename.preview(), PathBuf::from("new. .txt"));
+        let mut rename = File::new(Path::new("file."))
+            .unwrap()
+            .with_windows_names(true)
+            .with_option(Options::Name(name.clone()));
+        assert_eq!(rename.preview(), PathBuf::from("new"));
+        // Only the names the steps change are trimmed.
+        let mut rename = File::new(Path::new("file. ."))
+            .unwrap()
+            .with_windows_names(true);
+        assert_eq!(rename.preview(), PathBuf::from("file. ."));
+        let mut rename = File::new(Path::new("file."))
+            .unwrap()
+            .with_windows_names(false)
+            .with_option(Options::Name(name));
+        assert_eq!(rename.preview(), PathBuf::from("new. ."));
+    }
+
+    #[test]
+    fn scoped_steps_skip_other_extensions() {
+        let number = NumberOptions {
+            mode: NumberMode::Suffix,
+            value: 1,
+            sep: "_".into(),
+            ..Default::default()
+        };
+        let replace = ReplaceOptions {
+            replace: "a".into(),
+            with: "b".into(),
+            case: true,
+        };
+        let scopes = Scopes::from([
+            (Step::Number, "jpg".to_owned()),
+            (Step::Replace, " ".to_owned()),
+        ]);
+        let mut files = ["a.jpg", "a.xmp", "c.jpg"].map(|name| {
+            File::new(Path::new(name))
+                .unwrap()
+                .with_scopes(scopes.clone())
+        });
+        let options = [Options::Replace(replace), Options::Number(number)];
+        crate::batch::preview(&mut files, &options);
+        let names = files.iter().map(File::new_path).collect::<Vec<_>>();
+        assert_eq!(names, ["b_1.jpg", "b.xmp", "c_2.jpg"].map(PathBuf::from));
+    }
+
+    #[test]
+    fn steps_in_processing_order() {
+        let numbers = Step::ALL.map(Step::number);
+        assert_eq!(numbers, std::array::from_fn(|idx| idx + 1));
+        assert!(Step::ALL.windows(2).all(|pair| pair[0] < pair[1]));
+        let release = Options::Release(ReleaseOptions::default());
+        assert_eq!(release.step().to_string(), "Release Tags (14)");
+    }
+}
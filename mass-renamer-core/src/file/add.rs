@@ -0,0 +1,202 @@
+use super::{char_offset, File, Process};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Add a fixed `Prefix` or`Suffix` to the filename,
+/// or `Insert` text at a specific character (0 indexed, negative to index from the end).
+///
+/// The text may use `{tokens}`, e.g. from [super::LookupOptions].
+///
+/// You may also choose to add a `Word Space`. This will insert a space before any
+/// capital letter (except the first character), unless there's a space already there.
+/// Runs of capitals are kept together as acronyms, so `HTTPServer` becomes `HTTP Server`.
+/// With `Digit Space` numbers are split from the letters around them too, e.g.
+/// `Server2Go` becomes `Server 2 Go`.
+#[derive(Default, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct AddOptions {
+    pub prefix: Option<String>,
+    pub insert: Option<(i32, String)>,
+    pub suffix: Option<String>,
+    pub word_space: bool,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub digit_space: bool,
+}
+
+impl Process for AddOptions {
+    fn process(&self, file: &mut File) {
+        let prefix = self.prefix.as_deref().map(|text| file.expand(text));
+        let insert = self
+            .insert
+            .as_ref()
+            .map(|(pos, text)| (*pos, file.expand(text)));
+        let suffix = self.suffix.as_deref().map(|text| file.expand(text));
+        let file = &mut file.stem;
+        if let Some(prefix) = &prefix {
+            file.insert_str(0, prefix);
+        }
+
+        if let Some((pos, insert)) = &insert {
+            // Positions count characters, negative ones from the end.
+            let len = file.chars().count() as i64;
+            let pos = i64::from(*pos);
+            let idx = if pos < 0 { len + pos } else { pos }.clamp(0, len);
+            file.insert_str(char_offset(file, idx as usize), insert);
+        }
+
+        if let Some(suffix) = &suffix {
+            file.push_str(suffix);
+        }
+
+        if self.word_space || self.digit_space {
+            *file = self.space_words(file);
+        }
+    }
+}
+
+impl AddOptions {
+    /// Put spaces between the words of a name, see [AddOptions].
+    fn space_words(&self, name: &str) -> String {
+        let chars = name.chars().collect::<Vec<_>>();
+        let mut new = String::with_capacity(name.len());
+        for (idx, &chr) in chars.iter().enumerate() {
+            let prev = idx.checked_sub(1).map(|prev| chars[prev]);
+            let next = chars.get(idx + 1).copied();
+            let space = match prev {
+                None => false,
+                Some(prev) if prev.is_whitespace() => false,
+                Some(prev)
+                    if self.digit_space
+                        && (prev.is_alphabetic() && chr.is_numeric()
+                            || prev.is_numeric() && chr.is_alphabetic()) =>
+                {
+                    true
+                }
+                // The last capital of an acronym starts the next word, as in `HTTPServer`.
+                Some(prev) if self.word_space && chr.is_uppercase() => {
+                    !prev.is_uppercase() || next.is_some_and(char::is_lowercase)
+                }
+                _ => false,
+            };
+            if space {
+                new.push(' ');
+            }
+            new.push(chr);
+        }
+        new
+    }
+}
+
+#[cfg(test)]
+mod add_tests {
+    use super::*;
+    use std::path::Path;
+
+    #[test]
+    fn add_all_options() {
+        let prefix = Some("prefix-".into());
+        let insert = Some((15, "-insert-".into()));
+        let suffix = Some("-suffix".into());
+        let word_space = true;
+        let file = Path::new("SomeTestFile");
+        let opt = AddOptions {
+            prefix,
+            insert,
+            suffix,
+            word_space,
+            digit_space: false,
+        };
+        let mut rename = File::new(file).unwrap();
+        opt.process(&mut rename);
+        assert_eq!(
+            rename.stem,
+            "prefix- Some Test-insert- File-suffix".to_owned()
+        )
+    }
+
+    #[test]
+    fn word_space_keeps_acronyms() {
+        let space = |name: &str, digit_space| {
+            let opt = AddOptions {
+                word_space: true,
+                digit_space,
+                ..Default::default()
+            };
+            let mut file = File::new(Path::new(name)).unwrap();
+            opt.process(&mut file);
+            file.stem
+        };
+        assert_eq!(space("HTTPServer2", false), "HTTP Server2");
+        assert_eq!(space("HTTPServer2", true), "HTTP Server 2");
+        assert_eq!(space("parseXMLFile", false), "parse XML File");
+        assert_eq!(space("My Trip2Rome", true), "My Trip 2 Rome");
+        assert_eq!(space("ÉtéÀParis", false), "Été À Paris");
+        assert_eq!(space("IMG", false), "IMG");
+    }
+
+    #[test]
+    fn test_negative_insert() {
+        let insert = Some((-1, "!".into()));
+        let file = Path::new("Some Test File");
+        let opt = AddOptions {
+            prefix: None,
+            insert,
+            suffix: None,
+            word_space: false,
+            digit_space: false,
+        };
+        let mut rename = File::new(file).unwrap();
+        opt.process(&mut rename);
+        assert_eq!(rename.stem, "Some Test Fil!e".to_owned());
+    }
+
+    #[test]
+    fn test_insert_too_far_positive() {
+        let insert = Some((100, "!".into()));
+        let file = Path::new("Some Test File");
+        let opt = AddOptions {
+            prefix: None,
+            insert,
+            suffix: None,
+            word_space: false,
+            digit_space: false,
+        };
+        let mut rename = File::new(file).unwrap();
+        opt.process(&mut rename);
+        assert_eq!(rename.stem, "Some Test File!".to_owned());
+    }
+
+    #[test]
+    fn test_insert_too_far_negative() {
+        let insert = Some((-100, "!".into()));
+        let file = Path::new("Some Test File");
+        let opt = AddOptions {
+            prefix: None,
+            insert,
+            suffix: None,
+            word_space: false,
+            digit_space: false,
+        };
+        let mut rename = File::new(file).unwrap();
+        opt.process(&mut rename);
+        assert_eq!(rename.stem, "!Some Test File".to_owned());
+    }
+
+    #[test]
+    fn insert_counts_characters() {
+        let opt = AddOptions {
+            insert: Some((-1, "!".into())),
+            ..Default::default()
+        };
+        let mut rename = File::new(Path::new("Ça va 😀")).unwrap();
+        opt.process(&mut rename);
+        assert_eq!(rename.stem, "Ça va !😀");
+        let opt = AddOptions {
+            insert: Some((2, "-".into())),
+            ..Default::default()
+        };
+        let mut rename = File::new(Path::new("東京都")).unwrap();
+        opt.process(&mut rename);
+        assert_eq!(rename.stem, "東京-都");
+    }
+}
@@ -0,0 +1,201 @@
+use super::{read_word_list, File, Process, Step};
+use regex::{Regex, RegexBuilder, RegexSet, RegexSetBuilder};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::{io, path::Path, sync::Arc};
+
+/// Check new names against a list of unwanted words or phrases,
+/// e.g. tracker or release-group tags on downloaded files.
+/// - `words` - The blacklisted words and phrases. Use [BlacklistOptions::read_words] to load
+///   them from a text file with one entry per line.
+/// - `mode` - Only flag names containing the words, or remove the words from the name.
+/// - `case` - true for case sensitive, false for case-insensitive.
+///
+/// Removing a word does not tidy the spaces around it, use the `Trim` and `D/S` remove
+/// options for that.
+///
+/// The options compile the words each time they are used, use [BlacklistOptions::compile]
+/// to compile them once for a batch. [crate::batch::prepare] does so.
+#[derive(Default, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct BlacklistOptions {
+    pub words: Vec<String>,
+    pub mode: BlacklistMode,
+    pub case: bool,
+}
+
+/// Select from
+/// - `BlacklistMode::Flag` to report names containing blacklisted words (default)
+/// - `BlacklistMode::Remove` to remove blacklisted words from the names
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum BlacklistMode {
+    #[default]
+    Flag,
+    Remove,
+}
+
+/// A blacklist compiled by [BlacklistOptions::compile]. Clones share the compiled words.
+#[derive(Debug, Clone)]
+pub struct Blacklist {
+    mode: BlacklistMode,
+    compiled: Arc<Compiled>,
+}
+
+/// The compiled words.
+#[derive(Debug)]
+struct Compiled {
+    words: Vec<String>,
+    /// Any of the words, longest first, for removing them. `None` for no words.
+    any: Option<Regex>,
+    /// Each of the words, for telling which a name has.
+    each: RegexSet,
+}
+
+impl Process for BlacklistOptions {
+    fn process(&self, file: &mut File) {
+        self.compile().process(file)
+    }
+}
+
+impl Process for Blacklist {
+    fn process(&self, file: &mut File) {
+        if self.mode == BlacklistMode::Remove {
+            if let Some(any) = &self.compiled.any {
+                file.stem = any.replace_all(&file.stem, "").to_string();
+            }
+        }
+    }
+}
+
+/// A blacklist that couldn't be compiled fails the step for each file it is used on.
+impl Process for Result<Blacklist, regex::Error> {
+    fn process(&self, file: &mut File) {
+        match self {
+            Ok(blacklist) => blacklist.process(file),
+            Err(err) => file.fail(Step::Blacklist, err),
+        }
+    }
+}
+
+impl BlacklistOptions {
+    /// Read a blacklist from a text file. Each line is a word or phrase,
+    /// blank lines and lines starting with `#` are ignored.
+    pub fn read_words<P: AsRef<Path>>(path: P) -> Result<Vec<String>, io::Error> {
+        read_word_list(path)
+    }
+
+    /// Compile the words, e.g. once for a whole batch.
+    /// Fails if the list is too big for the regex size limit.
+    pub fn compile(&self) -> Result<Blacklist, regex::Error> {
+        let mut escaped = self
+            .words
+            .iter()
+            .map(|word| regex::escape(word))
+            .collect::<Vec<_>>();
+        let each = RegexSetBuilder::new(&escaped)
+            .case_insensitive(!self.case)
+            .build()?;
+        // Longer words first, so `-group` isn't cut short by `-g`.
+        escaped.sort_by_key(|word| std::cmp::Reverse(word.len()));
+        let any = match escaped.is_empty() {
+            true => None,
+            false => Some(
+                RegexBuilder::new(&escaped.join("|"))
+                    .case_insensitive(!self.case)
+                    .build()?,
+            ),
+        };
+        let compiled = Compiled {
+            words: self.words.clone(),
+            any,
+            each,
+        };
+        Ok(Blacklist {
+            mode: self.mode,
+            compiled: Arc::new(compiled),
+        })
+    }
+}
+
+impl Blacklist {
+    /// Return the blacklisted words found in `name`, as written in the blacklist.
+    pub fn find(&self, name: &str) -> Vec<&str> {
+        self.compiled
+            .each
+            .matches(name)
+            .into_iter()
+            .map(|idx| self.compiled.words[idx].as_str())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod blacklist_tests {
+    use super::*;
+    use crate::tester::run_test;
+    use std::{fs, io::Write, path::Path};
+
+    #[test]
+    fn flag_does_not_change_name() {
+        let mut file = File::new(Path::new("Movie [RARBG].mkv")).unwrap();
+        let opt = BlacklistOptions {
+            words: vec!["[rarbg]".into(), "YIFY".into()],
+            mode: BlacklistMode::Flag,
+            case: false,
+        };
+        opt.process(&mut file);
+        assert_eq!(file.stem, "Movie [RARBG]");
+        assert_eq!(opt.compile().unwrap().find(&file.stem), vec!["[rarbg]"]);
+    }
+
+    #[test]
+    fn remove_case_insensitive() {
+        let mut file = File::new(Path::new("Show.S01E01.eztv.x264-GROUP.mkv")).unwrap();
+        let opt = BlacklistOptions {
+            words: vec![".EZTV".into(), "-group".into()],
+            mode: BlacklistMode::Remove,
+            case: false,
+        };
+        opt.process(&mut file);
+        assert_eq!(file.stem, "Show.S01E01.x264");
+    }
+
+    #[test]
+    fn remove_case_sensitive() {
+        let mut file = File::new(Path::new("Tag tag TAG.txt")).unwrap();
+        let opt = BlacklistOptions {
+            words: vec!["tag".into()],
+            mode: BlacklistMode::Remove,
+            case: true,
+        };
+        opt.process(&mut file);
+        assert_eq!(file.stem, "Tag  TAG");
+    }
+
+    #[test]
+    fn too_big_to_compile_fails() {
+        let mut file = File::new(Path::new("a.txt")).unwrap();
+        let opt = BlacklistOptions {
+            words: vec!["word".repeat(1 << 19)],
+            mode: BlacklistMode::Remove,
+            case: false,
+        };
+        opt.process(&mut file);
+        assert_eq!(file.stem, "a");
+        assert_eq!(
+            file.errors(),
+            ["Blacklist (13): Compiled regex exceeds size limit of 10485760 bytes."]
+        );
+    }
+
+    #[test]
+    fn read_words_from_file() {
+        run_test(&vec!["blacklist.txt"], || {
+            let mut list = fs::File::create("blacklist.txt").unwrap();
+            writeln!(list, "# release groups\nYIFY\n\n  [ettv]  \n").unwrap();
+            let words = BlacklistOptions::read_words("blacklist.txt").unwrap();
+            assert_eq!(words, vec![String::from("YIFY"), String::from("[ettv]")]);
+        })
+    }
+}
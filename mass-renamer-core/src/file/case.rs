@@ -0,0 +1,362 @@
+use std::collections::HashMap;
+
+use super::{File, Process};
+use inflector::Inflector;
+use regex::{Captures, Regex};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Known words with unusual capitalization, used by [Case::Dictionary].
+/// Extended or overridden by [CaseOptions::dictionary].
+const DICTIONARY: [&str; 4] = [
+    // Names
+    "McDonald McCartney McGregor MacArthur MacDonald O'Brien O'Connor O'Neill D'Angelo \
+     DiCaprio DeNiro LeBron FitzGerald",
+    // Name particles (Dutch, German, French, Spanish, Italian, Arabic)
+    "van der den von de du la le del della di da bin ibn",
+    // Brands
+    "iPhone iPad iPod iMac macOS iOS eBay YouTube PowerPoint PlayStation GitHub LinkedIn \
+     WhatsApp PayPal JavaScript TikTok",
+    // Abbreviations
+    "USA UK EU NASA FBI BBC DVD CD TV PDF HD DJ II III IV VI VII VIII IX XI XII",
+];
+
+/// Change the case of the file.
+/// - `Keep` - Do change the capitalization (default).
+/// - `Lower` - change all selected files to lowercase.
+/// - `Upper` - CHANGE ALL SELECTED FILES TO UPPERCASE.
+/// - `Title` - Change All Selected Files To Title Case.
+/// - `Sentence` - Change all selected files to sentence case.
+/// - `Snake` - Flag_to_change_all_selected_files_to_snake_case.
+///
+/// - `Dictionary` - Fix ALL CAPS or all lower words using a list of known spellings,
+///   e.g. mcdonald → McDonald, IPHONE → iPhone. Other words in a single case are
+///   capitalized, mixed case words are left alone. Add your own spellings in `dictionary`.
+///
+/// Exceptions: You can also enter a list of "exceptions", separated by semicolons.
+/// So for example if you entered PDF;doc then any occurrence of pdf (or PDF, Pdf,
+/// etc) would be converted to upper-case, and every occurrence of DOC (or DoC)
+/// would become doc.
+#[derive(Default, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct CaseOptions {
+    pub case: Case,
+    pub snake: bool,
+    pub exceptions: String,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub dictionary: Vec<String>,
+}
+
+/// Select from
+/// `Case::Keep` to not change case (default),
+/// `Case::Lower` to convert to lowercase,
+/// `Case::Upper` to convert to uppercase,
+/// `Case::Title` to convert to titlecase,
+/// `Case::Sentence` to convert to sentence case, or
+/// `Case::Dictionary` to use the known spelling of each word.
+#[derive(Default, Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Case {
+    #[default]
+    Keep,
+    Lower,
+    Upper,
+    Title,
+    Sentence,
+    Dictionary,
+}
+
+impl Case {
+    pub fn iterator() -> impl Iterator<Item = Case> {
+        [
+            Self::Keep,
+            Self::Lower,
+            Self::Upper,
+            Self::Title,
+            Self::Sentence,
+            Self::Dictionary,
+        ]
+        .iter()
+        .copied()
+    }
+}
+
+impl Process for CaseOptions {
+    fn process(&self, file: &mut File) {
+        match self.case {
+            Case::Keep => (),
+            Case::Lower => {
+                file.stem = file.stem.to_lowercase();
+            }
+            Case::Upper => {
+                file.stem = file.stem.to_uppercase();
+            }
+            Case::Title => {
+                file.stem = file.stem.to_title_case();
+            }
+            Case::Sentence => {
+                file.stem = file.stem.to_sentence_case();
+            }
+            Case::Dictionary => {
+                file.stem = self.recase(&file.stem);
+            }
+        };
+        if !&self.exceptions.is_empty() {
+            for exception in self.exceptions.split(';') {
+                let mod_exception = match self.case {
+                    Case::Keep => exception.to_owned(),
+                    Case::Lower => exception.to_lowercase(),
+                    Case::Upper => exception.to_uppercase(),
+                    Case::Title => exception.to_title_case(),
+                    Case::Sentence => exception.to_sentence_case(),
+                    Case::Dictionary => self.recase(exception),
+                };
+                file.stem = file.stem.replace(&mod_exception, exception);
+            }
+        }
+        if self.snake {
+            file.stem = file.stem.replace(' ', "_")
+        };
+    }
+}
+
+impl CaseOptions {
+    /// Replace each word with its dictionary spelling, capitalizing unknown words
+    /// that are entirely upper or lower case.
+    fn recase(&self, text: &str) -> String {
+        let mut known = DICTIONARY
+            .iter()
+            .flat_map(|words| words.split_whitespace())
+            .map(|word| (word.to_lowercase(), word.to_string()))
+            .collect::<HashMap<_, _>>();
+        known.extend(
+            self.dictionary
+                .iter()
+                .map(|word| (word.to_lowercase(), word.clone())),
+        );
+        let Ok(words) = Regex::new(r"[\p{L}\p{N}]+(?:'[\p{L}\p{N}]+)*") else {
+            return text.to_owned();
+        };
+        let fix = |word: &str| match known.get(&word.to_lowercase()) {
+            Some(spelling) => spelling.clone(),
+            None if word == word.to_lowercase() || word == word.to_uppercase() => {
+                let mut chars = word.chars();
+                match chars.next() {
+                    Some(first) => first
+                        .to_uppercase()
+                        .chain(chars.flat_map(char::to_lowercase))
+                        .collect(),
+                    None => String::new(),
+                }
+            }
+            None => word.to_owned(),
+        };
+        words
+            .replace_all(text, |caps: &Captures| {
+                let word = &caps[0];
+                if known.contains_key(&word.to_lowercase()) {
+                    return fix(word);
+                }
+                // Unknown contractions and possessives, e.g. DON'T → Don't.
+                let mut parts = word.split('\'');
+                let mut fixed = parts.next().map(fix).unwrap_or_default();
+                for part in parts {
+                    fixed.push('\'');
+                    match known.get(&part.to_lowercase()) {
+                        Some(spelling) => fixed.push_str(spelling),
+                        None if part == part.to_uppercase() => fixed.push_str(&part.to_lowercase()),
+                        None => fixed.push_str(part),
+                    }
+                }
+                fixed
+            })
+            .to_string()
+    }
+}
+
+#[cfg(test)]
+mod case_tests {
+    use super::*;
+    use std::path::Path;
+    #[test]
+    fn test_keep_case() {
+        let mut file = File::new(Path::new("test file")).unwrap();
+        let opt = CaseOptions {
+            case: Case::Keep,
+            snake: false,
+            exceptions: String::new(),
+            ..Default::default()
+        };
+        opt.process(&mut file);
+        assert_eq!(file.stem, String::from("test file"));
+    }
+
+    #[test]
+    fn test_keep_case_snake() {
+        let mut file = File::new(Path::new("test file")).unwrap();
+        let opt = CaseOptions {
+            case: Case::Keep,
+            snake: true,
+            exceptions: String::new(),
+            ..Default::default()
+        };
+        opt.process(&mut file);
+        assert_eq!(file.stem, String::from("test_file"));
+    }
+
+    #[test]
+    fn test_lower_case() {
+        let mut file = File::new(Path::new("TEST FILE")).unwrap();
+        let opt = CaseOptions {
+            case: Case::Lower,
+            snake: false,
+            exceptions: String::new(),
+            ..Default::default()
+        };
+        opt.process(&mut file);
+        assert_eq!(file.stem, String::from("test file"));
+    }
+
+    #[test]
+    fn test_lower_case_snake() {
+        let mut file = File::new(Path::new("TEST FILE")).unwrap();
+        let opt = CaseOptions {
+            case: Case::Lower,
+            snake: true,
+            exceptions: String::new(),
+            ..Default::default()
+        };
+        opt.process(&mut file);
+        assert_eq!(file.stem, String::from("test_file"));
+    }
+
+    #[test]
+    fn test_upper_case() {
+        let mut file = File::new(Path::new("test file")).unwrap();
+        let opt = CaseOptions {
+            case: Case::Upper,
+            snake: false,
+            exceptions: String::new(),
+            ..Default::default()
+        };
+        opt.process(&mut file);
+        assert_eq!(file.stem, String::from("TEST FILE"));
+    }
+
+    #[test]
+    fn test_upper_case_snake() {
+        let mut file = File::new(Path::new("test file")).unwrap();
+        let opt = CaseOptions {
+            case: Case::Upper,
+            snake: true,
+            exceptions: String::new(),
+            ..Default::default()
+        };
+        opt.process(&mut file);
+        assert_eq!(file.stem, String::from("TEST_FILE"));
+    }
+
+    #[test]
+    fn test_title_case() {
+        let mut file = File::new(Path::new("test file")).unwrap();
+        let opt = CaseOptions {
+            case: Case::Title,
+            snake: false,
+            exceptions: String::new(),
+            ..Default::default()
+        };
+        opt.process(&mut file);
+        assert_eq!(file.stem, String::from("Test File"));
+    }
+
+    #[test]
+    fn test_title_case_snake() {
+        let mut file = File::new(Path::new("test file")).unwrap();
+        let opt = CaseOptions {
+            case: Case::Title,
+            snake: true,
+            exceptions: String::new(),
+            ..Default::default()
+        };
+        opt.process(&mut file);
+        assert_eq!(file.stem, String::from("Test_File"));
+    }
+
+    #[test]
+    fn test_sentence_case() {
+        let mut file = File::new(Path::new("test file")).unwrap();
+        let opt = CaseOptions {
+            case: Case::Sentence,
+            snake: false,
+            exceptions: String::new(),
+            ..Default::default()
+        };
+        opt.process(&mut file);
+        assert_eq!(file.stem, String::from("Test file"));
+    }
+
+    #[test]
+    fn test_sentence_case_snake() {
+        let mut file = File::new(Path::new("test file")).unwrap();
+        let opt = CaseOptions {
+            case: Case::Sentence,
+            snake: true,
+            exceptions: String::new(),
+            ..Default::default()
+        };
+        opt.process(&mut file);
+        assert_eq!(file.stem, String::from("Test_file"));
+    }
+
+    #[test]
+    fn test_exceptions_with_upper() {
+        let mut files = (
+            File::new(Path::new("test file.doc.bak")).unwrap(),
+            File::new(Path::new("test file.pdf.bak")).unwrap(),
+        );
+        let opt = CaseOptions {
+            case: Case::Upper,
+            snake: false,
+            exceptions: "doc;PDF".into(),
+            ..Default::default()
+        };
+        opt.process(&mut files.0);
+        opt.process(&mut files.1);
+        let expected = (String::from("TEST FILE.doc"), String::from("TEST FILE.PDF"));
+        assert_eq!((files.0.stem, files.1.stem), expected);
+    }
+
+    #[test]
+    fn dictionary_case() {
+        let mut file = File::new(Path::new("RONALD MCDONALD'S IPHONE photos.jpg")).unwrap();
+        let opt = CaseOptions {
+            case: Case::Dictionary,
+            ..Default::default()
+        };
+        opt.process(&mut file);
+        assert_eq!(file.stem, "Ronald McDonald's iPhone Photos");
+    }
+
+    #[test]
+    fn dictionary_case_particles_and_mixed_case() {
+        let mut file = File::new(Path::new("vincent VAN gogh - LaTeX notes.txt")).unwrap();
+        let opt = CaseOptions {
+            case: Case::Dictionary,
+            ..Default::default()
+        };
+        opt.process(&mut file);
+        assert_eq!(file.stem, "Vincent van Gogh - LaTeX Notes");
+    }
+
+    #[test]
+    fn dictionary_case_user_words() {
+        let mut file = File::new(Path::new("de la cruz ALBUM.mp3")).unwrap();
+        let opt = CaseOptions {
+            case: Case::Dictionary,
+            dictionary: vec!["De".into(), "ALBUM".into()],
+            ..Default::default()
+        };
+        opt.process(&mut file);
+        assert_eq!(file.stem, "De la Cruz ALBUM");
+    }
+}
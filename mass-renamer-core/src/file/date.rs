@@ -0,0 +1,654 @@
+use super::{File, Process, Step};
+use chrono::{DateTime, Datelike, FixedOffset, Local, Utc};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    error::Error,
+    fmt::Write,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+/// Use the prefix or suffix `Mode` to modify the filename with a date format.
+/// The `Date` that the file was created, modified, or the current date can be added in
+/// the format (`FMT`) selected. A `Sep`erator can be specified for the character(s)
+/// between the filename and the date as well as a format for setting the character(s)
+/// between date `Seg`ments. Select the `YYYY` box to display years as 4 digit instead
+/// of the default 2 (except for custom dates).
+///
+/// You also have the option to specify your own custom date formats using
+/// [chrono::format::strftime](https://docs.rs/chrono/0.4.31/chrono/format/strftime/index.html) specifiers,
+/// plus `{quarter}` for the quarter of the year.
+///
+/// Month and weekday names (`%B`, `%b`, `%A`, `%a`) are in the `locale` given, e.g. `fr_FR`,
+/// see [LOCALES]. Left empty, or without the `locales` feature, they are in English.
+///
+/// Dates are shown in the `zone` selected, see [DateZone].
+#[derive(Default, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct DateOptions {
+    pub date_mode: DateMode,
+    pub date_type: DateType,
+    pub fmt: DateFormat,
+    pub custom_fmt: String,
+    pub sep: String,
+    pub seg: String,
+    pub full_year: bool,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub locale: String,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub zone: DateZone,
+}
+
+/// Locales offered for month and weekday names. Any locale known to `chrono` works.
+pub const LOCALES: [&str; 16] = [
+    "de_DE", "en_GB", "en_US", "es_ES", "fr_FR", "it_IT", "ja_JP", "ko_KR", "nl_NL", "pl_PL",
+    "pt_BR", "pt_PT", "ru_RU", "sv_SE", "tr_TR", "zh_CN",
+];
+
+impl Process for DateOptions {
+    fn process(&self, file: &mut File) {
+        if self.date_mode == DateMode::None {
+            return;
+        }
+        let date = match self.get_date(file) {
+            Ok(datetime) => self.render(&datetime),
+            Err(err) => return file.fail(Step::Date, err),
+        };
+        match self.date_mode {
+            DateMode::Prefix => file.stem.insert_str(0, &format!("{}{}", date, self.sep)),
+            DateMode::Suffix => {
+                write!(file.stem, "{}{}", self.sep, date)
+                    .expect("Unexpected error appending to string.");
+            }
+            DateMode::None => {}
+        }
+    }
+}
+
+impl DateOptions {
+    /// The earliest and latest dates that would be added to the files, formatted as in
+    /// the new names. `None` if no file has a date.
+    pub fn bounds<'a, I: IntoIterator<Item = &'a File>>(
+        &self,
+        files: I,
+    ) -> Option<(String, String)> {
+        let dates = files
+            .into_iter()
+            .filter_map(|file| self.get_date(file).ok())
+            .collect::<Vec<_>>();
+        let first = self.render(dates.iter().min()?);
+        let last = self.render(dates.iter().max()?);
+        Some((first, last))
+    }
+
+    /// Give each file `{group_min_date}` and `{group_max_date}` tokens, the earliest and
+    /// latest dates of the files in the same folder, so a whole album can share one date.
+    /// The dates are formatted as in the new names. Folders without any dates get no tokens.
+    pub fn group_tokens<'a, I: IntoIterator<Item = &'a mut File>>(&self, files: I) {
+        let files = files.into_iter().collect::<Vec<_>>();
+        let mut groups: HashMap<PathBuf, (DateTime<Local>, DateTime<Local>)> = HashMap::new();
+        for file in &files {
+            if let Ok(date) = self.get_date(file) {
+                groups
+                    .entry(folder(file))
+                    .and_modify(|(first, last)| {
+                        *first = date.min(*first);
+                        *last = date.max(*last);
+                    })
+                    .or_insert((date, date));
+            }
+        }
+        for file in files {
+            if let Some((first, last)) = groups.get(&folder(file)) {
+                file.tokens
+                    .insert("group_min_date".into(), self.render(first));
+                file.tokens
+                    .insert("group_max_date".into(), self.render(last));
+            }
+        }
+    }
+
+    /// The date formatted for the new name. `{quarter}` becomes the quarter of the
+    /// year, 1 to 4, which `strftime` has no specifier for.
+    fn render(&self, date: &DateTime<Local>) -> String {
+        let date = self.zone.convert(date);
+        let quarter = (date.month0() / 3 + 1).to_string();
+        let format = self.format_string().replace(QUARTER, &quarter);
+        #[cfg(feature = "locales")]
+        if let Ok(locale) = chrono::Locale::try_from(self.locale.as_str()) {
+            return date.format_localized(&format, locale).to_string();
+        }
+        date.format(&format).to_string()
+    }
+
+    fn format_string(&self) -> String {
+        match &self.fmt {
+            DateFormat::Std((prefix, suffix)) => {
+                let mut fmt = prefix.get_format(&self.seg, self.full_year);
+                if let Some(suf) = suffix {
+                    fmt.push_str(&self.seg);
+                    fmt.push_str(&suf.get_format(&self.seg));
+                }
+                fmt
+            }
+            DateFormat::Custom => self.custom_fmt.clone(),
+        }
+    }
+
+    pub(super) fn get_date(&self, file: &File) -> Result<DateTime<Local>, Box<dyn Error>> {
+        if let Some(metadata) = &file.metadata {
+            let datetime = match self.date_type {
+                DateType::Created => metadata.created,
+                DateType::Modified => metadata.modified,
+                DateType::Current => Some(Local::now()),
+            };
+            return datetime.ok_or_else(|| "no date available".into());
+        }
+        let metadata = file.original.metadata()?;
+        let dt = match self.date_type {
+            DateType::Created => metadata.created()?,
+            DateType::Modified => metadata.modified()?,
+            DateType::Current => SystemTime::now(),
+        };
+        let datetime: DateTime<Local> = dt.into();
+        Ok(datetime)
+    }
+}
+
+fn folder(file: &File) -> PathBuf {
+    file.original
+        .parent()
+        .map(Path::to_path_buf)
+        .unwrap_or_default()
+}
+
+/// Replaced with the quarter of the year in date formats, see [DateOptions].
+const QUARTER: &str = "{quarter}";
+
+/// Select from
+/// - `DateZone::Local` for this computer's time zone (default)
+/// - `DateZone::Utc` for UTC
+/// - `DateZone::Fixed(minutes)` for a fixed offset east of UTC, e.g. 330 for UTC+05:30
+///
+/// Files synced between computers in different time zones only get the same names
+/// with UTC or a fixed offset.
+#[derive(Default, PartialEq, Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum DateZone {
+    #[default]
+    Local,
+    Utc,
+    Fixed(i32),
+}
+
+impl DateZone {
+    /// The largest offset allowed, in minutes either side of UTC.
+    pub const MAX_OFFSET: i32 = 14 * 60;
+
+    fn convert(&self, date: &DateTime<Local>) -> DateTime<FixedOffset> {
+        match self {
+            Self::Local => date.fixed_offset(),
+            Self::Utc => date.with_timezone(&Utc).fixed_offset(),
+            Self::Fixed(minutes) => {
+                let minutes = minutes.clamp(&-Self::MAX_OFFSET, &Self::MAX_OFFSET);
+                FixedOffset::east_opt(minutes * 60)
+                    .map_or_else(|| date.fixed_offset(), |zone| date.with_timezone(&zone))
+            }
+        }
+    }
+}
+
+/// Select from
+/// `DateMode::Prefix`,
+/// `DateMode::Suffix`.
+#[derive(Default, PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum DateMode {
+    Prefix,
+    Suffix,
+    #[default]
+    None,
+}
+
+/// Select from
+/// - `DateType::Created` for the file creation date
+/// - `Datetype::Modified` for the date last modified
+/// - `DateType::Current` for today's date
+///
+/// Note, if an OS does not support `Created` or `Modified` this option will
+/// result in no change to the file name.
+#[derive(Default, PartialEq, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum DateType {
+    #[default]
+    Created,
+    Modified,
+    Current,
+}
+
+/// Select from
+/// - `DateFormat::Std(DatePrefix, Option<DateSuffix>)` to use the standard options
+/// - `DateFormat::Custom` to use a custom `strftime` format
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum DateFormat {
+    Std((DatePrefix, Option<DateSuffix>)),
+    Custom,
+}
+
+impl Default for DateFormat {
+    fn default() -> Self {
+        Self::Std((DatePrefix::Dmy, None))
+    }
+}
+
+impl DateFormat {
+    pub fn format(&self) -> &str {
+        match self {
+            Self::Std((DatePrefix::Dmy, None)) => "DMY",
+            Self::Std((DatePrefix::Mdy, None)) => "MDY",
+            Self::Std((DatePrefix::Ymd, None)) => "YMD",
+            Self::Std((DatePrefix::Dmy, Some(DateSuffix::Hm))) => "DMY HM",
+            Self::Std((DatePrefix::Mdy, Some(DateSuffix::Hm))) => "MDY HM",
+            Self::Std((DatePrefix::Ymd, Some(DateSuffix::Hm))) => "YMD HM",
+            Self::Std((DatePrefix::Dmy, Some(DateSuffix::Hms))) => "DMY HMS",
+            Self::Std((DatePrefix::Mdy, Some(DateSuffix::Hms))) => "MDY HMS",
+            Self::Std((DatePrefix::Ymd, Some(DateSuffix::Hms))) => "YMD HMS",
+            Self::Std((DatePrefix::Week, _)) => "Year Week",
+            Self::Std((DatePrefix::Quarter, _)) => "Year Quarter",
+            Self::Std((DatePrefix::DayOfYear, _)) => "Year Day",
+            Self::Custom => "Custom",
+        }
+    }
+
+    pub fn iter() -> impl Iterator<Item = DateFormat> {
+        [
+            Self::Std((DatePrefix::Dmy, None)),
+            Self::Std((DatePrefix::Mdy, None)),
+            Self::Std((DatePrefix::Ymd, None)),
+            Self::Std((DatePrefix::Dmy, Some(DateSuffix::Hm))),
+            Self::Std((DatePrefix::Mdy, Some(DateSuffix::Hm))),
+            Self::Std((DatePrefix::Ymd, Some(DateSuffix::Hm))),
+            Self::Std((DatePrefix::Dmy, Some(DateSuffix::Hms))),
+            Self::Std((DatePrefix::Mdy, Some(DateSuffix::Hms))),
+            Self::Std((DatePrefix::Ymd, Some(DateSuffix::Hms))),
+            Self::Std((DatePrefix::Week, None)),
+            Self::Std((DatePrefix::Quarter, None)),
+            Self::Std((DatePrefix::DayOfYear, None)),
+            Self::Custom,
+        ]
+        .iter()
+        .copied()
+    }
+}
+
+/// Select from
+/// - `DatePrefix::DMY` for Day Month Year
+/// - `DatePrefix::MDY` for Month Year Day
+/// - `DatePrefix::YMD` for Year Month Day
+/// - `DatePrefix::Week` for the ISO week-numbering Year and Week, e.g. `2024-W01`
+/// - `DatePrefix::Quarter` for Year Quarter, e.g. `2024-Q1`
+/// - `DatePrefix::DayOfYear` for Year Day of the year, e.g. `2024-366`
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum DatePrefix {
+    #[default]
+    Dmy,
+    Mdy,
+    Ymd,
+    Week,
+    Quarter,
+    DayOfYear,
+}
+
+impl DatePrefix {
+    fn get_format(&self, sep: &str, full_year: bool) -> String {
+        let y = if full_year { "%Y" } else { "%y" };
+        match self {
+            Self::Dmy => format!("%d{sep}%m{sep}{y}"),
+            Self::Mdy => format!("%m{sep}%d{sep}{y}"),
+            Self::Ymd => format!("{y}{sep}%m{sep}%d"),
+            // The ISO week year, e.g. 1 January 2021 is in week 53 of 2020.
+            Self::Week if full_year => format!("%G{sep}W%V"),
+            Self::Week => format!("%g{sep}W%V"),
+            Self::Quarter => format!("{y}{sep}Q{QUARTER}"),
+            Self::DayOfYear => format!("{y}{sep}%j"),
+        }
+    }
+}
+
+/// Select from
+/// - `DateSuffix::HM` for Hour Minute
+/// - `DateSuffix::HMS` for Hour Minute Second
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum DateSuffix {
+    Hm,
+    Hms,
+}
+
+impl DateSuffix {
+    fn get_format(&self, sep: &str) -> String {
+        match self {
+            Self::Hm => format!("%H{sep}%M{sep}"),
+            Self::Hms => format!("%H{sep}%M{sep}%S"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod date_tests {
+    use super::*;
+    use crate::{metadata::Metadata, tester::run_test};
+    use chrono::TimeZone;
+    use std::path::Path;
+
+    #[test]
+    fn prefix_date_modified_hyphen_separator_full_year() {
+        run_test(&vec!["test file.txt"], || {
+            let mut file = File::new(Path::new("test file.txt")).unwrap();
+            let date_mode = DateMode::Prefix;
+            let date_type = DateType::Modified;
+            let fmt = DateFormat::Std((DatePrefix::Dmy, None));
+            let custom_fmt = String::new();
+            let sep = "-".into();
+            let seg = "_".into();
+            let full_year = true;
+            let opt = DateOptions {
+                date_mode,
+                date_type,
+                fmt,
+                custom_fmt,
+                sep,
+                seg,
+                full_year,
+                ..Default::default()
+            };
+            let date = format!("{}", chrono::Local::now().format("%d_%m_%Y"));
+            let expected = format!("{date}-test file");
+            opt.process(&mut file);
+            assert_eq!(file.stem, expected);
+        })
+    }
+
+    #[test]
+    fn suffix_date_created_no_separator() {
+        crate::tester::run_test(&vec!["test file.txt"], || {
+            let date = format!("{}", chrono::Local::now().format("%d_%m_%y_%H_%M_%S"));
+            let mut file = File::new(Path::new("test file.txt")).unwrap();
+            let date_mode = DateMode::Suffix;
+            let date_type = DateType::Created;
+            let fmt = DateFormat::Std((DatePrefix::Dmy, Some(DateSuffix::Hms)));
+            let custom_fmt = String::new();
+            let sep = "".into();
+            let seg = "_".into();
+            let full_year = false;
+            let opt = DateOptions {
+                date_mode,
+                date_type,
+                fmt,
+                custom_fmt,
+                sep,
+                seg,
+                full_year,
+                ..Default::default()
+            };
+            let expected = format!("test file{date}");
+            opt.process(&mut file);
+            assert_eq!(file.stem, expected);
+        })
+    }
+
+    #[test]
+    fn prefix_date_current_custom_format() {
+        crate::tester::run_test(&vec!["test file.txt"], || {
+            let mut file = File::new(Path::new("test file.txt")).unwrap();
+            let date_mode = DateMode::Prefix;
+            let date_type = DateType::Current;
+            let fmt = DateFormat::Custom;
+            let custom_fmt = String::from("%v++");
+            let sep = "~".into();
+            let seg = "_".into();
+            let full_year = true;
+            let opt = DateOptions {
+                date_mode,
+                date_type,
+                fmt,
+                custom_fmt,
+                sep,
+                seg,
+                full_year,
+                ..Default::default()
+            };
+            let date = format!("{}", chrono::Local::now().format("%v"));
+            let expected = format!("{date}++~test file");
+            opt.process(&mut file);
+            assert_eq!(file.stem, expected);
+        })
+    }
+
+    #[test]
+    fn suffix_date_modified_from_metadata() {
+        let modified = chrono::Local.with_ymd_and_hms(2021, 3, 4, 5, 6, 7).unwrap();
+        let metadata = Metadata {
+            modified: Some(modified),
+            ..Default::default()
+        };
+        let mut file = File::new(Path::new("not a file.txt"))
+            .unwrap()
+            .with_metadata(metadata);
+        let opt = DateOptions {
+            date_mode: DateMode::Suffix,
+            date_type: DateType::Modified,
+            fmt: DateFormat::Std((DatePrefix::Ymd, None)),
+            sep: " ".into(),
+            seg: "-".into(),
+            full_year: true,
+            ..Default::default()
+        };
+        opt.process(&mut file);
+        assert_eq!(file.stem, "not a file 2021-03-04");
+    }
+
+    #[test]
+    fn week_quarter_and_day_of_year() {
+        let modified = chrono::Local.with_ymd_and_hms(2021, 1, 1, 0, 0, 0).unwrap();
+        let metadata = Metadata {
+            modified: Some(modified),
+            ..Default::default()
+        };
+        let result = [DatePrefix::Week, DatePrefix::Quarter, DatePrefix::DayOfYear].map(|prefix| {
+            let mut file = File::new(Path::new("not a file.txt"))
+                .unwrap()
+                .with_metadata(metadata.clone());
+            let opt = DateOptions {
+                date_mode: DateMode::Prefix,
+                date_type: DateType::Modified,
+                fmt: DateFormat::Std((prefix, None)),
+                sep: " ".into(),
+                seg: "-".into(),
+                full_year: true,
+                ..Default::default()
+            };
+            opt.process(&mut file);
+            file.stem
+        });
+        assert_eq!(
+            result,
+            [
+                "2020-W53 not a file",
+                "2021-Q1 not a file",
+                "2021-001 not a file"
+            ]
+        );
+    }
+
+    #[cfg(feature = "locales")]
+    #[test]
+    fn localized_month_names() {
+        let modified = chrono::Local.with_ymd_and_hms(2021, 3, 4, 5, 6, 7).unwrap();
+        let metadata = Metadata {
+            modified: Some(modified),
+            ..Default::default()
+        };
+        let result = ["", "fr_FR", "de_DE"].map(|locale| {
+            let mut file = File::new(Path::new("not a file.txt"))
+                .unwrap()
+                .with_metadata(metadata.clone());
+            let opt = DateOptions {
+                date_mode: DateMode::Prefix,
+                date_type: DateType::Modified,
+                fmt: DateFormat::Custom,
+                custom_fmt: "%A %d %B".into(),
+                sep: " ".into(),
+                locale: locale.into(),
+                ..Default::default()
+            };
+            opt.process(&mut file);
+            file.stem
+        });
+        assert_eq!(
+            result,
+            [
+                "Thursday 04 March not a file",
+                "jeudi 04 mars not a file",
+                "Donnerstag 04 März not a file"
+            ]
+        );
+    }
+
+    #[test]
+    fn utc_and_fixed_time_zones() {
+        let modified = chrono::Utc
+            .with_ymd_and_hms(2021, 3, 4, 23, 30, 0)
+            .unwrap()
+            .with_timezone(&Local);
+        let metadata = Metadata {
+            modified: Some(modified),
+            ..Default::default()
+        };
+        let result = [DateZone::Utc, DateZone::Fixed(60), DateZone::Fixed(-330)].map(|zone| {
+            let mut file = File::new(Path::new("not a file.txt"))
+                .unwrap()
+                .with_metadata(metadata.clone());
+            let opt = DateOptions {
+                date_mode: DateMode::Prefix,
+                date_type: DateType::Modified,
+                fmt: DateFormat::Custom,
+                custom_fmt: "%Y-%m-%d %H%M".into(),
+                sep: " ".into(),
+                zone,
+                ..Default::default()
+            };
+            opt.process(&mut file);
+            file.stem
+        });
+        assert_eq!(
+            result,
+            [
+                "2021-03-04 2330 not a file",
+                "2021-03-05 0030 not a file",
+                "2021-03-04 1800 not a file"
+            ]
+        );
+    }
+
+    #[test]
+    fn missing_metadata_date_is_skipped() {
+        let mut file = File::new(Path::new("not a file.txt"))
+            .unwrap()
+            .with_metadata(Metadata::default());
+        let opt = DateOptions {
+            date_mode: DateMode::Prefix,
+            date_type: DateType::Created,
+            ..Default::default()
+        };
+        opt.process(&mut file);
+        assert_eq!(file.stem, "not a file");
+    }
+
+    #[test]
+    fn bounds_across_files() {
+        let files = [(2024, 11, 30), (2023, 1, 2), (2023, 6, 1)].map(|(y, m, d)| {
+            let modified = chrono::Local.with_ymd_and_hms(y, m, d, 0, 0, 0).unwrap();
+            File::new(Path::new("not a file.txt"))
+                .unwrap()
+                .with_metadata(Metadata {
+                    modified: Some(modified),
+                    ..Default::default()
+                })
+        });
+        let opt = DateOptions {
+            date_mode: DateMode::Prefix,
+            date_type: DateType::Modified,
+            fmt: DateFormat::Std((DatePrefix::Ymd, None)),
+            seg: "-".into(),
+            full_year: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            opt.bounds(&files),
+            Some(("2023-01-02".into(), "2024-11-30".into()))
+        );
+        assert_eq!(opt.bounds(&files[..0]), None);
+    }
+
+    #[test]
+    fn group_tokens_per_folder() {
+        let dated = |name: &str, day: u32| {
+            let modified = chrono::Local
+                .with_ymd_and_hms(2023, 5, day, 0, 0, 0)
+                .unwrap();
+            File::new(Path::new(name)).unwrap().with_metadata(Metadata {
+                modified: Some(modified),
+                ..Default::default()
+            })
+        };
+        let mut files = [
+            dated("album/b.jpg", 9),
+            dated("album/a.jpg", 3),
+            dated("other/c.jpg", 20),
+            File::new(Path::new("album/undated.jpg"))
+                .unwrap()
+                .with_metadata(Metadata::default()),
+        ];
+        let opt = DateOptions {
+            date_type: DateType::Modified,
+            fmt: DateFormat::Std((DatePrefix::Ymd, None)),
+            seg: "-".into(),
+            full_year: true,
+            ..Default::default()
+        };
+        opt.group_tokens(files.iter_mut());
+        let tokens = files
+            .iter()
+            .map(|file| file.expand("{group_min_date}/{group_max_date}"))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            tokens,
+            vec![
+                "2023-05-03/2023-05-09",
+                "2023-05-03/2023-05-09",
+                "2023-05-20/2023-05-20",
+                "2023-05-03/2023-05-09",
+            ]
+        );
+    }
+
+    #[test]
+    fn missing_date_recorded_on_file() {
+        let mut file = File::new(Path::new("undated.jpg"))
+            .unwrap()
+            .with_metadata(Metadata::default());
+        let opt = DateOptions {
+            date_mode: DateMode::Prefix,
+            date_type: DateType::Modified,
+            ..Default::default()
+        };
+        opt.process(&mut file);
+        assert_eq!(file.stem, "undated");
+        assert_eq!(file.errors(), ["Auto Date (7): no date available"]);
+    }
+}
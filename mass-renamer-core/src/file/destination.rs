@@ -0,0 +1,149 @@
+use super::{DateOptions, DateType, File, Process, Step, Tokens};
+use chrono::Datelike;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// The date tokens a `folder` can use, filled from the `date_type` of the file.
+const DATE_TOKENS: [&str; 3] = ["year", "month", "day"];
+
+/// Move the renamed files to another `folder`, so a batch sorts files into subfolders as
+/// it renames them, e.g. `{year}/{month}` for photos. Missing folders are created.
+/// - `folder` - Relative to the file's own folder, or absolute. Besides the file's tokens,
+///   e.g. `{artist}`, it can use `{year}`, `{month}` and `{day}` of the `date_type`.
+///   Slashes and `..` in a token's value are replaced with `_`, so `AC/DC` is one folder
+///   and no value leads out of it. Left empty, the files stay where they are.
+/// - `copy` - Copy the files instead, leaving the originals where they are.
+///
+/// ```
+/// # use std::path::{Path, PathBuf};
+/// # use mass_renamer_core::file::{DestinationOptions, File, Options, Tokens};
+/// let tokens = Tokens::from([("artist".to_string(), "Nina".to_string())]);
+/// let sort = DestinationOptions { folder: "sorted/{artist}".into(), ..Default::default() };
+/// let mut file = File::new(Path::new("music/song.mp3"))
+///     .unwrap()
+///     .with_tokens(tokens)
+///     .with_option(Options::Destination(sort));
+/// assert_eq!(file.preview(), PathBuf::from("music/sorted/Nina/song.mp3"));
+/// ```
+#[derive(Default, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct DestinationOptions {
+    pub folder: String,
+    pub date_type: DateType,
+    pub copy: bool,
+}
+
+impl Process for DestinationOptions {
+    fn process(&self, file: &mut File) {
+        if self.folder.trim().is_empty() {
+            return;
+        }
+        let mut tokens = file
+            .tokens
+            .iter()
+            .map(|(token, value)| (token.clone(), folder_name(value)))
+            .collect::<Tokens>();
+        if self.uses_date() {
+            let date = DateOptions {
+                date_type: self.date_type.clone(),
+                ..Default::default()
+            };
+            match date.get_date(file) {
+                Ok(date) => {
+                    tokens.insert("year".into(), format!("{:04}", date.year()));
+                    tokens.insert("month".into(), format!("{:02}", date.month()));
+                    tokens.insert("day".into(), format!("{:02}", date.day()));
+                }
+                Err(err) => return file.fail(Step::Destination, err),
+            }
+        }
+        let folder = PathBuf::from(super::expand_tokens(self.folder.trim(), &tokens));
+        let parent = file.original.parent().unwrap_or(Path::new("/"));
+        file.destination_folder = Some(parent.join(folder));
+    }
+}
+
+impl DestinationOptions {
+    /// Whether the folder has any of the date tokens.
+    pub fn uses_date(&self) -> bool {
+        DATE_TOKENS
+            .iter()
+            .any(|token| self.folder.contains(&format!("{{{token}}}")))
+    }
+}
+
+/// A token's value as a single folder name, with the separators and `..` that would nest
+/// it or lead out of the folder replaced.
+fn folder_name(value: &str) -> String {
+    value.replace(['/', '\\'], "_").replace("..", "__")
+}
+
+#[cfg(test)]
+mod destination_tests {
+    use super::*;
+    use crate::file::Options;
+    use crate::tester::temp_folder;
+
+    #[test]
+    fn sorted_by_date() {
+        let dir = temp_folder("destination");
+        let path = dir.join("photo.jpg");
+        std::fs::write(&path, "").unwrap();
+        let sort = DestinationOptions {
+            folder: "{year}/{month}-{day}".into(),
+            date_type: DateType::Current,
+            copy: false,
+        };
+        let mut file = File::new(&path)
+            .unwrap()
+            .with_option(Options::Destination(sort));
+        let new = file.preview();
+        std::fs::remove_dir_all(&dir).ok();
+        let today = chrono::Local::now();
+        let expected = dir
+            .join(format!("{:04}", today.year()))
+            .join(format!("{:02}-{:02}", today.month(), today.day()))
+            .join("photo.jpg");
+        assert_eq!(new, expected);
+    }
+
+    #[test]
+    fn token_values_stay_in_the_folder() {
+        let sort = DestinationOptions {
+            folder: "sorted/{artist}/{album}".into(),
+            ..Default::default()
+        };
+        let tokens = Tokens::from([
+            ("artist".to_string(), "AC/DC".to_string()),
+            ("album".to_string(), r"..\..".to_string()),
+        ]);
+        let mut file = File::new(Path::new("music/song.mp3"))
+            .unwrap()
+            .with_tokens(tokens)
+            .with_option(Options::Destination(sort));
+        assert_eq!(
+            file.preview(),
+            PathBuf::from("music/sorted/AC_DC/_____/song.mp3")
+        );
+    }
+
+    #[test]
+    fn absolute_and_empty_folders() {
+        let absolute = std::env::temp_dir().join("sorted");
+        let opt = |folder: &str| {
+            Options::Destination(DestinationOptions {
+                folder: folder.into(),
+                ..Default::default()
+            })
+        };
+        let mut file = File::new(Path::new("dir/file.txt"))
+            .unwrap()
+            .with_option(opt(&absolute.to_string_lossy()));
+        assert_eq!(file.preview(), absolute.join("file.txt"));
+        let mut file = File::new(Path::new("dir/file.txt"))
+            .unwrap()
+            .with_option(opt(" "));
+        assert_eq!(file.preview(), PathBuf::from("dir/file.txt"));
+    }
+}
@@ -0,0 +1,193 @@
+use super::{File, Process};
+use inflector::Inflector;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::{fmt::Write, slice::Iter};
+
+/// Select from
+/// - `ExtensionOptions::Keep` to not change case (default)
+/// - `ExtensionOptions::Lower` to convert to lowercase
+/// - `ExtensionOptions::Upper` to convert to uppercase
+/// - `ExtensionOptions::Title` to convert to titlecase
+/// - `ExtensionOptions::New(&'a str)` to convert to a new extension
+/// - `ExtensionOptions::Extra(&'a str)` to add a new extension
+/// - `ExtensionOptions::Remove` to remove the extension
+/// - `ExtensionOptions::Map(Vec<(String, String)>)` to change some extensions into
+///   others, e.g. `jpeg` to `jpg` and `tif` to `tiff`, matched whatever their case. The
+///   first pair matching is used, extensions without one are left alone.
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum ExtensionOptions {
+    #[default]
+    Keep,
+    Lower,
+    Upper,
+    Title,
+    New(String),
+    Extra(String),
+    Remove,
+    Map(Vec<(String, String)>),
+}
+
+impl Process for ExtensionOptions {
+    fn process(&self, file: &mut File) {
+        match (self, &mut file.extension) {
+            (ExtensionOptions::Lower, Some(ext)) => {
+                file.extension = Some(ext.to_lowercase());
+            }
+            (ExtensionOptions::Upper, Some(ext)) => {
+                file.extension = Some(ext.to_uppercase());
+            }
+            (ExtensionOptions::Title, Some(ext)) => {
+                file.extension = Some(ext.to_title_case());
+            }
+            (ExtensionOptions::New(s), _) => {
+                file.extension = Some(s.to_string());
+            }
+            (ExtensionOptions::Extra(s), ext) => {
+                match ext {
+                    Some(ext) => write!(ext, ".{s}").expect("Unexpected error appending string."),
+                    None => file.extension = Some(s.to_string()),
+                };
+            }
+            (ExtensionOptions::Remove, _) => {
+                file.extension = None;
+            }
+            (ExtensionOptions::Map(pairs), Some(ext)) => {
+                let mapped = pairs.iter().find(|(from, _)| {
+                    from.trim_start_matches('.').to_lowercase() == ext.to_lowercase()
+                });
+                if let Some((_, to)) = mapped {
+                    file.extension = Some(to.trim_start_matches('.').to_owned());
+                }
+            }
+            _ => (),
+        };
+    }
+}
+
+impl ExtensionOptions {
+    pub fn iter() -> Iter<'static, ExtensionOptions> {
+        static OPTIONS: [ExtensionOptions; 8] = [
+            ExtensionOptions::Keep,
+            ExtensionOptions::Lower,
+            ExtensionOptions::Upper,
+            ExtensionOptions::Title,
+            ExtensionOptions::New(String::new()),
+            ExtensionOptions::Extra(String::new()),
+            ExtensionOptions::Remove,
+            ExtensionOptions::Map(Vec::new()),
+        ];
+        OPTIONS.iter()
+    }
+
+    /// The option's name, without the extension it is given.
+    ///
+    /// ```
+    /// # use mass_renamer_core::file::ExtensionOptions;
+    /// assert_eq!(ExtensionOptions::New("jpg".into()).name(), "New");
+    /// ```
+    pub fn name(&self) -> &'static str {
+        match self {
+            ExtensionOptions::Keep => "Keep",
+            ExtensionOptions::Lower => "Lower",
+            ExtensionOptions::Upper => "Upper",
+            ExtensionOptions::Title => "Title",
+            ExtensionOptions::New(_) => "New",
+            ExtensionOptions::Extra(_) => "Extra",
+            ExtensionOptions::Remove => "Remove",
+            ExtensionOptions::Map(_) => "Map",
+        }
+    }
+
+    /// Whether the option is given an extension, as `New` and `Extra` are.
+    pub fn takes_value(&self) -> bool {
+        matches!(self, ExtensionOptions::New(_) | ExtensionOptions::Extra(_))
+    }
+}
+
+#[cfg(test)]
+mod extension_tests {
+    use super::*;
+    use std::path::Path;
+    #[test]
+    fn test_keep_case() {
+        let mut file = File::new(Path::new("test file.txt")).unwrap();
+        let opt = ExtensionOptions::Keep;
+        opt.process(&mut file);
+        assert_eq!(file.extension, Some(String::from("txt")));
+    }
+
+    #[test]
+    fn test_lower_case() {
+        let mut file = File::new(Path::new("test file.TXT")).unwrap();
+        let opt = ExtensionOptions::Lower;
+        opt.process(&mut file);
+        assert_eq!(file.extension, Some(String::from("txt")));
+    }
+
+    #[test]
+    fn test_upper_case() {
+        let mut file = File::new(Path::new("test file.txt")).unwrap();
+        let opt = ExtensionOptions::Upper;
+        opt.process(&mut file);
+        assert_eq!(file.extension, Some(String::from("TXT")));
+    }
+
+    #[test]
+    fn test_title_case() {
+        let mut file = File::new(Path::new("test file.txt")).unwrap();
+        let opt = ExtensionOptions::Title;
+        opt.process(&mut file);
+        assert_eq!(file.extension, Some(String::from("Txt")));
+    }
+
+    #[test]
+    fn test_new_case() {
+        let mut file = File::new(Path::new("test file.txt")).unwrap();
+        let opt = ExtensionOptions::New("csv".into());
+        opt.process(&mut file);
+        assert_eq!(file.extension, Some(String::from("csv")));
+    }
+
+    #[test]
+    fn test_extra_case_with_existing() {
+        let mut file = File::new(Path::new("test file.txt")).unwrap();
+        let opt = ExtensionOptions::Extra("bak".into());
+        opt.process(&mut file);
+        assert_eq!(file.extension, Some(String::from("txt.bak")));
+    }
+
+    #[test]
+    fn test_extra_case_without_existing() {
+        let mut file = File::new(Path::new("test file")).unwrap();
+        let opt = ExtensionOptions::Extra("bak".into());
+        opt.process(&mut file);
+        assert_eq!(file.extension, Some(String::from("bak")));
+    }
+
+    #[test]
+    fn mapped_whatever_the_case() {
+        let opt = ExtensionOptions::Map(vec![
+            ("jpeg".into(), "jpg".into()),
+            (".TIF".into(), "tiff".into()),
+        ]);
+        let mapped = ["a.JPEG", "b.tif", "c.png", "d"].map(|name| {
+            let mut file = File::new(Path::new(name)).unwrap();
+            opt.process(&mut file);
+            file.extension
+        });
+        assert_eq!(
+            mapped,
+            [Some("jpg"), Some("tiff"), Some("png"), None].map(|ext| ext.map(String::from))
+        );
+    }
+
+    #[test]
+    fn test_remove() {
+        let mut file = File::new(Path::new("test file")).unwrap();
+        let opt = ExtensionOptions::Remove;
+        opt.process(&mut file);
+        assert_eq!(file.extension, None);
+    }
+}
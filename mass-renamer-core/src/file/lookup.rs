@@ -0,0 +1,131 @@
+use super::reg::compile;
+use super::Tokens;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, path::Path};
+
+/// Take extra `{tokens}` for each file from a table, e.g. a spreadsheet exported as CSV.
+/// The first column of the table holds the key each file is looked up by, every other
+/// column becomes a token named by its header, e.g. `{customer_name}`.
+/// The tokens can be used in the Name and Add text.
+///
+/// A file's key is its original name with extension, or the text matched by `key` when set.
+/// If `key` has a capture group the first group is used instead, so `^(\d+)_` looks up
+/// `1042_scan.pdf` by `1042`.
+#[derive(Default, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct LookupOptions {
+    pub key: String,
+    pub columns: Vec<String>,
+    pub rows: BTreeMap<String, Vec<String>>,
+}
+
+impl LookupOptions {
+    /// Read a table from a CSV file, or a TSV file when it has a `.tsv` extension.
+    /// The first row names the columns. Rows repeating an earlier key are ignored.
+    #[cfg(feature = "lookup")]
+    pub fn read_csv<P: AsRef<Path>>(path: P) -> Result<Self, csv::Error> {
+        let path = path.as_ref();
+        let delimiter = match path.extension().and_then(|ext| ext.to_str()) {
+            Some(ext) if ext.eq_ignore_ascii_case("tsv") => b'\t',
+            _ => b',',
+        };
+        let mut reader = csv::ReaderBuilder::new()
+            .delimiter(delimiter)
+            .flexible(true)
+            .trim(csv::Trim::All)
+            .from_path(path)?;
+        let columns = reader.headers()?.iter().skip(1).map(String::from).collect();
+        let mut rows = BTreeMap::new();
+        for record in reader.records() {
+            let record = record?;
+            let mut fields = record.iter().map(String::from);
+            if let Some(key) = fields.next() {
+                rows.entry(key).or_insert_with(|| fields.collect());
+            }
+        }
+        Ok(Self {
+            key: String::new(),
+            columns,
+            rows,
+        })
+    }
+
+    /// The tokens for a file, or `None` if it isn't in the table.
+    /// Missing cells are empty tokens.
+    pub fn tokens(&self, original: &Path) -> Option<Tokens> {
+        let name = original.file_name()?.to_string_lossy();
+        let key = if self.key.is_empty() {
+            name.as_ref()
+        } else {
+            let captures = compile(&self.key).ok()?.captures(&name)?;
+            captures.get(1).or_else(|| captures.get(0))?.as_str()
+        };
+        let row = self.rows.get(key)?;
+        Some(
+            self.columns
+                .iter()
+                .enumerate()
+                .map(|(idx, column)| (column.clone(), row.get(idx).cloned().unwrap_or_default()))
+                .collect(),
+        )
+    }
+}
+
+#[cfg(all(test, feature = "lookup"))]
+mod lookup_tests {
+    use super::*;
+    use crate::file::{AddOptions, File, NameOptions, Options};
+    use crate::tester::temp_folder;
+    use std::{fs, path::PathBuf};
+
+    #[test]
+    fn read_csv_and_rename() {
+        let dir = temp_folder("lookup");
+        let path = dir.join("customers.csv");
+        fs::write(
+            &path,
+            "id,customer_name,city\n1042,\"Smith, Jane\",Leeds\n7,Acme\n1042,Duplicate,Nowhere\n",
+        )
+        .unwrap();
+        let lookup = LookupOptions {
+            key: r"^(\d+)_".into(),
+            ..LookupOptions::read_csv(&path).unwrap()
+        };
+        fs::remove_dir_all(&dir).ok();
+        assert_eq!(lookup.columns, vec!["customer_name", "city"]);
+        assert_eq!(lookup.rows.len(), 2);
+        let rename = |name: &str| {
+            File::new(Path::new(name))
+                .unwrap()
+                .with_option(Options::Lookup(lookup.clone()))
+                .with_option(Options::Name(NameOptions::Fixed("{customer_name}".into())))
+                .with_option(Options::Add(AddOptions {
+                    suffix: Some(" ({city}) {unknown}".into()),
+                    ..Default::default()
+                }))
+                .preview()
+        };
+        assert_eq!(
+            rename("1042_scan.pdf"),
+            PathBuf::from("Smith, Jane (Leeds) {unknown}.pdf")
+        );
+        assert_eq!(rename("7_a.pdf"), PathBuf::from("Acme () {unknown}.pdf"));
+        assert_eq!(
+            rename("99_b.pdf"),
+            PathBuf::from("{customer_name} ({city}) {unknown}.pdf")
+        );
+    }
+
+    #[test]
+    fn key_by_file_name() {
+        let lookup = LookupOptions {
+            columns: vec!["title".into()],
+            rows: BTreeMap::from([("a.txt".into(), vec!["Alpha".into()])]),
+            ..Default::default()
+        };
+        let tokens = lookup.tokens(Path::new("dir/a.txt")).unwrap();
+        assert_eq!(tokens.get("title").map(String::as_str), Some("Alpha"));
+        assert!(lookup.tokens(Path::new("b.txt")).is_none());
+    }
+}
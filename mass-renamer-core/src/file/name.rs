@@ -0,0 +1,129 @@
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::{
+    fmt::{self, Display, Formatter},
+    slice::Iter,
+};
+
+use super::{File, Process};
+
+/// Select from.
+/// - `NameOptions::Keep` - Do not change the original file name (default).
+/// - `NameOptions::Remove` - Completely erase the file from the selected items. This allows it to be rebuilt using components higher than (2).
+/// - `NameOptions::Fixed` - Specify a new file in the box for all selected items. Only really useful if you're also using the Numbering section. The name may use `{tokens}`, e.g. from [super::LookupOptions].
+/// - `NameOptions::Reverse` - Reverse the name, e.g. 12345.txt becomes 54321.txt.
+#[derive(Default, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum NameOptions {
+    #[default]
+    Keep,
+    Remove,
+    Fixed(String),
+    Reverse,
+}
+
+impl Process for NameOptions {
+    fn process(&self, file: &mut File) {
+        match self {
+            NameOptions::Keep => (),
+            NameOptions::Remove => file.stem = "".to_owned(),
+            NameOptions::Fixed(x) => file.stem = file.expand(x),
+            NameOptions::Reverse => file.stem = file.stem.chars().rev().collect::<String>(),
+        };
+    }
+}
+
+impl Display for NameOptions {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            NameOptions::Keep => "Keep",
+            NameOptions::Remove => "Remove",
+            NameOptions::Fixed(_) => "Fixed",
+            NameOptions::Reverse => "Reverse",
+        })
+    }
+}
+
+impl NameOptions {
+    pub fn iter() -> Iter<'static, Self> {
+        static OPTIONS: [NameOptions; 4] = [
+            NameOptions::Keep,
+            NameOptions::Remove,
+            NameOptions::Fixed(String::new()),
+            NameOptions::Reverse,
+        ];
+        OPTIONS.iter()
+    }
+}
+
+#[cfg(test)]
+mod name_tests {
+    use super::*;
+    use chrono::TimeZone;
+    use std::path::Path;
+    #[test]
+    fn keep_name() {
+        let mut file = File::new(Path::new("file")).unwrap();
+        let opt = NameOptions::Keep;
+        opt.process(&mut file);
+        assert_eq!(&file.stem, "file");
+    }
+    #[test]
+    fn remove_name() {
+        let mut file = File::new(Path::new("file")).unwrap();
+        let opt = NameOptions::Remove;
+        opt.process(&mut file);
+        assert_eq!(&file.stem, "");
+    }
+    #[test]
+    fn fixed_name() {
+        let mut file = File::new(Path::new("file")).unwrap();
+        let new_name = "renamed_file";
+        let opt = NameOptions::Fixed(String::from(new_name));
+        opt.process(&mut file);
+        assert_eq!(file.stem, new_name);
+    }
+    #[test]
+    fn reverse_name() {
+        let mut file = File::new(Path::new("file")).unwrap();
+        let opt = NameOptions::Reverse;
+        opt.process(&mut file);
+        assert_eq!(&file.stem, "elif");
+    }
+    #[test]
+    fn orientation_token() {
+        let metadata = crate::metadata::Metadata {
+            dimensions: Some((4000, 3000)),
+            ..Default::default()
+        };
+        let mut file = File::new(Path::new("photo.jpg"))
+            .unwrap()
+            .with_metadata(metadata)
+            .with_option(crate::file::Options::Name(NameOptions::Fixed(
+                "{orientation}".into(),
+            )));
+        assert_eq!(file.preview(), Path::new("landscape.jpg"));
+    }
+    #[test]
+    fn exif_tokens() {
+        let captured = chrono::Local
+            .with_ymd_and_hms(2023, 12, 24, 18, 15, 0)
+            .unwrap();
+        let metadata = crate::metadata::Metadata {
+            captured: Some(captured),
+            camera: Some("X100V".into()),
+            iso: Some(400),
+            ..Default::default()
+        };
+        let mut file = File::new(Path::new("DSCF0001.jpg"))
+            .unwrap()
+            .with_metadata(metadata)
+            .with_option(crate::file::Options::Name(NameOptions::Fixed(
+                "{exif:date} {camera} ISO{iso} {width}".into(),
+            )));
+        assert_eq!(
+            file.preview(),
+            Path::new("20231224_181500 X100V ISO400 {width}.jpg")
+        );
+    }
+}
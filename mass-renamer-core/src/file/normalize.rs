@@ -0,0 +1,77 @@
+use super::{File, Process};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use unicode_normalization::UnicodeNormalization;
+
+/// Select from
+/// - `NormalizeOptions::Keep` to leave the Unicode form unchanged (default)
+/// - `NormalizeOptions::Nfc` to compose characters (Windows and Linux convention)
+/// - `NormalizeOptions::Nfd` to decompose characters (macOS convention)
+///
+/// Visually identical names like "café" can be stored as different bytes. Normalizing
+/// the new names keeps files consistent when they move between macOS and Linux.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum NormalizeOptions {
+    #[default]
+    Keep,
+    Nfc,
+    Nfd,
+}
+
+impl Process for NormalizeOptions {
+    fn process(&self, file: &mut File) {
+        file.stem = self.normalize(&file.stem);
+        file.extension = file.extension.as_deref().map(|ext| self.normalize(ext));
+    }
+}
+
+impl NormalizeOptions {
+    fn normalize(&self, text: &str) -> String {
+        match self {
+            NormalizeOptions::Keep => text.to_owned(),
+            NormalizeOptions::Nfc => text.nfc().collect(),
+            NormalizeOptions::Nfd => text.nfd().collect(),
+        }
+    }
+
+    pub fn iter() -> impl Iterator<Item = NormalizeOptions> {
+        [Self::Keep, Self::Nfc, Self::Nfd].iter().copied()
+    }
+}
+
+#[cfg(test)]
+mod normalize_tests {
+    use super::*;
+    use std::path::Path;
+
+    #[test]
+    fn keep_form() {
+        let mut file = File::new(Path::new("cafe\u{301}.txt")).unwrap();
+        let opt = NormalizeOptions::Keep;
+        opt.process(&mut file);
+        assert_eq!(file.stem, "cafe\u{301}");
+    }
+
+    #[test]
+    fn compose_nfc() {
+        let mut file = File::new(Path::new("cafe\u{301}.txt")).unwrap();
+        let opt = NormalizeOptions::Nfc;
+        opt.process(&mut file);
+        assert_eq!(file.stem, "caf\u{e9}");
+    }
+
+    #[test]
+    fn decompose_nfd_with_extension() {
+        let mut file = File::new(Path::new("caf\u{e9}.r\u{e9}s")).unwrap();
+        let opt = NormalizeOptions::Nfd;
+        opt.process(&mut file);
+        assert_eq!(
+            (file.stem, file.extension),
+            (
+                String::from("cafe\u{301}"),
+                Some(String::from("re\u{301}s"))
+            )
+        );
+    }
+}
@@ -0,0 +1,504 @@
+use super::{char_offset, File, Process, Step};
+use regex::Regex;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    fmt::Write,
+    path::{Path, PathBuf},
+};
+
+/// Add sequential numbers to the file.
+/// - `Mode` - Choose between prefix, suffix, both, or insert at a location (0 indexed).
+/// - `Value` - Specify a value number for the numbering, given to the first file of a batch.
+/// - `Step` - The number to be added to the previous.
+/// - `Reset at` - Start again from `Value` once the number would go past this.
+/// - `Reset per folder` - Start again from `Value` whenever the folder changes.
+/// - `Pad` - The minimum number of digits occupied by the numeric element.
+/// - `Char` - The character to use for padding. By default, numeric bases will be padded with leading zeros; the a-z and A-Z options will be padded with "a" or "A" as appropriate.
+/// - `Sep`. - A character or characters that you wish to be inserted between the old filename and the number. If you enter the special character ":" (colon) in the Sep. box then this will be replaced with the auto-number. So a separator value of ABC:DEF: would result in ABC1DEF1, ABC2ABC2 etc.
+/// - `Format` - You can choose to append the auto-number in any various bases (binary, decimal, hex (upper and lower), octal), ASCII letters A-Z, or Roman numerals (upper and lower). Roman numerals have no zero, so 0 is written `N` and the panels pad them with spaces rather than zeros.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct NumberOptions {
+    pub mode: NumberMode,
+    pub value: u32,
+    #[cfg_attr(feature = "serde", serde(default = "one"))]
+    pub step: u32,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub reset_at: Option<u32>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub reset_folder: bool,
+    pub pad: usize,
+    pub char: char,
+    pub sep: String,
+    pub format: NumberFormat,
+}
+
+impl Default for NumberOptions {
+    fn default() -> Self {
+        Self {
+            mode: NumberMode::default(),
+            value: 0,
+            step: 1,
+            reset_at: None,
+            reset_folder: false,
+            pad: 0,
+            char: char::default(),
+            sep: String::new(),
+            format: NumberFormat::default(),
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+fn one() -> u32 {
+    1
+}
+
+impl Process for NumberOptions {
+    fn process(&self, file: &mut File) {
+        let val = self.number_value();
+        let sep = self.sep.replace(':', &val);
+        match self.mode {
+            NumberMode::None => (),
+            NumberMode::Prefix => file.stem.insert_str(0, &format!("{val}{sep}")),
+            NumberMode::Suffix => {
+                write!(file.stem, "{sep}{val}").expect("Unexpected error appending string.")
+            }
+            NumberMode::Insert(idx) => {
+                let at = char_offset(&file.stem, idx);
+                file.stem.insert_str(at, &format!("{sep}{val}{sep}"))
+            }
+        };
+    }
+}
+
+impl NumberOptions {
+    /// The first and last numbers given to `count` files in one folder, formatted as in
+    /// the new names. `None` if there are no files.
+    pub fn bounds(&self, count: usize) -> Option<(String, String)> {
+        let last = Self {
+            value: self.nth(count.checked_sub(1)?),
+            ..self.clone()
+        };
+        Some((self.number_value(), last.number_value()))
+    }
+
+    /// The number given to the file `n` places after the first in one folder, e.g. the
+    /// number to start the next batch from when `n` files were numbered.
+    ///
+    /// ```
+    /// # use mass_renamer_core::file::NumberOptions;
+    /// let number = NumberOptions { value: 1, step: 2, reset_at: Some(5), ..Default::default() };
+    /// assert_eq!([0, 1, 2, 3].map(|n| number.nth(n)), [1, 3, 5, 1]);
+    /// ```
+    pub fn nth(&self, n: usize) -> u32 {
+        let mut steps = u32::try_from(n).unwrap_or(u32::MAX);
+        if let Some(reset) = self.reset_at.filter(|_| self.step > 0) {
+            // The numbers from `value` up to `reset` repeat.
+            let period = reset.saturating_sub(self.value) / self.step + 1;
+            steps %= period;
+        }
+        self.value.saturating_add(steps.saturating_mul(self.step))
+    }
+
+    /// Give each numbered file of a batch its own number, in batch order: the first gets
+    /// `value` and each after it `step` more, starting over from `value` past `reset_at`
+    /// or, with `reset_folder`, at the first file of each folder.
+    ///
+    /// ```
+    /// # use std::path::{Path, PathBuf};
+    /// # use mass_renamer_core::file::{File, NumberMode, NumberOptions, Options};
+    /// let number = NumberOptions { mode: NumberMode::Prefix, value: 1, step: 2, reset_at: Some(4), ..Default::default() };
+    /// let mut files = ["a", "b", "c"]
+    ///     .map(|name| File::new(Path::new(name)).unwrap().with_option(Options::Number(number.clone())));
+    /// number.sequence(files.iter_mut());
+    /// let names = files.iter_mut().map(|file| file.preview()).collect::<Vec<_>>();
+    /// assert_eq!(names, ["1a", "3b", "1c"].map(PathBuf::from));
+    /// ```
+    pub fn sequence<'a, I: IntoIterator<Item = &'a mut File>>(&self, files: I) {
+        let mut value = self.value;
+        let mut folder = None;
+        for file in files {
+            if !file.in_scope(Step::Number) {
+                continue;
+            }
+            let Some(number) = &mut file.number else {
+                continue;
+            };
+            let parent = file.original.parent().map(Path::to_path_buf);
+            if self.reset_folder && folder.as_ref().is_some_and(|folder| *folder != parent) {
+                value = self.value;
+            }
+            if self.reset_at.is_some_and(|reset| value > reset) {
+                value = self.value;
+            }
+            number.value = value;
+            folder = Some(parent);
+            value = value.saturating_add(self.step);
+        }
+    }
+
+    /// Give each file `{dirnum}` and `{filenum}` tokens for numbering a tree of files as
+    /// `1.1`, `1.2`, `2.1`: each folder is numbered in the order its first file comes in
+    /// the batch and each file within its folder. Both start at 1 and are padded and
+    /// formatted as the numbers from this panel.
+    pub fn nested_tokens<'a, I: IntoIterator<Item = &'a mut File>>(&self, files: I) {
+        let mut folders: HashMap<PathBuf, (u32, u32)> = HashMap::new();
+        for file in files {
+            let folder = file.original.parent().map(Path::to_path_buf);
+            let next = u32::try_from(folders.len() + 1).unwrap_or(u32::MAX);
+            let (dir, count) = folders
+                .entry(folder.unwrap_or_default())
+                .or_insert((next, 0));
+            *count += 1;
+            for (token, value) in [("dirnum", *dir), ("filenum", *count)] {
+                let number = Self {
+                    value,
+                    ..self.clone()
+                };
+                file.tokens.insert(token.into(), number.number_value());
+            }
+        }
+    }
+
+    pub(crate) fn number_value(&self) -> String {
+        let replace = match &self.format {
+            NumberFormat::Decimal => format!("{}", self.value),
+            NumberFormat::Binary => format!("{:b}", self.value),
+            NumberFormat::Octal => format!("{:o}", self.value),
+            NumberFormat::HexUpper => format!("{:X}", self.value),
+            NumberFormat::HexLower => format!("{:x}", self.value),
+            NumberFormat::RomanUpper => roman(self.value),
+            NumberFormat::RomanLower => roman(self.value).to_lowercase(),
+            f => {
+                let offset = match f {
+                    NumberFormat::AsciiLower => 96_u8,
+                    _ => 64_u8,
+                };
+                let mut res: Vec<char> = Vec::new();
+                let mut val = self.value;
+                while val > 0 {
+                    res.push(char::from((val % 26) as u8 + offset));
+                    val /= 26;
+                }
+                res.reverse();
+                res.into_iter().collect::<String>()
+            }
+        };
+        if self.pad > replace.len() {
+            let mut val = std::iter::repeat(self.char)
+                .take(self.pad - replace.len())
+                .collect::<String>();
+            val.push_str(&replace);
+            val
+        } else {
+            replace
+        }
+    }
+}
+
+/// Write `value` in Roman numerals, e.g. `XIV` for 14. 0 is `N`, for nulla, and each
+/// thousand from 4000 up adds another `M`.
+fn roman(value: u32) -> String {
+    if value == 0 {
+        return "N".into();
+    }
+    const NUMERALS: [(u32, &str); 13] = [
+        (1000, "M"),
+        (900, "CM"),
+        (500, "D"),
+        (400, "CD"),
+        (100, "C"),
+        (90, "XC"),
+        (50, "L"),
+        (40, "XL"),
+        (10, "X"),
+        (9, "IX"),
+        (5, "V"),
+        (4, "IV"),
+        (1, "I"),
+    ];
+    let mut rest = value;
+    let mut numeral = String::new();
+    for (worth, letters) in NUMERALS {
+        while rest >= worth {
+            numeral.push_str(letters);
+            rest -= worth;
+        }
+    }
+    numeral
+}
+
+/// The padded width most used by the numbers already in `names`, e.g. 4 for
+/// `IMG_0001.jpg` and `IMG_0012.jpg`, to match when numbering more files.
+/// Only the last number in each name counts, and only numbers with a leading zero show
+/// padding, so names like `2023 report.pdf` are ignored. Ties go to the wider padding.
+///
+/// # Example
+///
+/// ```
+/// # use mass_renamer_core::file::number::detect_pad;
+/// assert_eq!(detect_pad(&["IMG_0001.jpg", "IMG_0257.jpg", "IMG_1000.jpg"]), Some(4));
+/// assert_eq!(detect_pad(&["2023 report.pdf"]), None);
+/// ```
+pub fn detect_pad<P: AsRef<Path>>(names: &[P]) -> Option<usize> {
+    let digits = Regex::new(r"\d+").expect("Digits expression is valid.");
+    let mut widths: HashMap<usize, usize> = HashMap::new();
+    for name in names {
+        let Some(stem) = name.as_ref().file_stem() else {
+            continue;
+        };
+        let stem = stem.to_string_lossy();
+        if let Some(number) = digits.find_iter(&stem).last() {
+            if number.len() > 1 && number.as_str().starts_with('0') {
+                *widths.entry(number.len()).or_default() += 1;
+            }
+        }
+    }
+    widths
+        .into_iter()
+        .max_by_key(|(width, count)| (*count, *width))
+        .map(|(width, _)| width)
+}
+
+/// Select from
+/// `NumberMode::None` to leave the name alone (default),
+/// `NumberMode::Prefix`,
+/// `NumberMode::Suffix`, or
+/// `NumberMode::Insert(usize)` before the character at that position.
+#[derive(Default, PartialEq, Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum NumberMode {
+    #[default]
+    None,
+    Prefix,
+    Suffix,
+    Insert(usize),
+}
+
+/// Select from
+/// `NumberFormat:Binary`,
+/// `NumberFormat:Decimal`,
+/// `NumberFormat:HexUpper`,
+/// `NumberFormat:HexLower`,
+/// `NumberFormat:Octal`,
+/// `NumberFormat:AsciiUpper`,
+/// `NumberFormat:AsciiLower`,
+/// `NumberFormat:RomanUpper`, or
+/// `NumberFormat:RomanLower`
+#[derive(Default, PartialEq, Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum NumberFormat {
+    Binary,
+    #[default]
+    Decimal,
+    HexUpper,
+    HexLower,
+    Octal,
+    AsciiUpper,
+    AsciiLower,
+    RomanUpper,
+    RomanLower,
+}
+
+#[cfg(test)]
+mod numbering_test {
+    use super::*;
+    use crate::file::Options;
+    use std::path::{Path, PathBuf};
+
+    fn vec_compare(va: &[String], vb: &[String]) -> bool {
+        (va.len() == vb.len()) &&  // zip stops at the shortest
+     va.iter()
+       .zip(vb)
+       .all(|(a,b)| a == b)
+    }
+
+    #[test]
+    fn prefix_decimal_with_padding() {
+        let mut files = (0..10)
+            .map(|_| File::new(Path::new("TestFile.txt")).unwrap())
+            .collect::<Vec<File>>();
+        let pad = 2;
+        let char = '0';
+        let sep = "--";
+        for (value, file) in files.iter_mut().enumerate() {
+            let format = NumberFormat::Decimal;
+            let mode = NumberMode::Prefix;
+            let opt = NumberOptions {
+                mode,
+                value: (&value + 1) as u32,
+                pad,
+                char,
+                sep: String::from(sep),
+                format,
+                ..Default::default()
+            };
+            opt.process(file);
+        }
+        let expected = (1..=10)
+            .map(|i| format!("{i:02}--TestFile"))
+            .collect::<Vec<String>>();
+        let result = files
+            .iter()
+            .map(|f| f.stem.clone())
+            .collect::<Vec<String>>();
+        assert!(vec_compare(&result, &expected));
+    }
+
+    #[test]
+    fn nested_tokens_per_folder() {
+        let mut files = ["b/x.txt", "b/y.txt", "a/z.txt", "b/w.txt"]
+            .map(|name| File::new(Path::new(name)).unwrap());
+        let opt = NumberOptions {
+            pad: 2,
+            char: '0',
+            ..Default::default()
+        };
+        opt.nested_tokens(files.iter_mut());
+        let result = files
+            .iter()
+            .map(|file| file.expand("{dirnum}.{filenum}"))
+            .collect::<Vec<_>>();
+        assert_eq!(result, ["01.01", "01.02", "02.01", "01.03"]);
+    }
+
+    #[test]
+    fn sequence_resets_per_folder() {
+        let opt = NumberOptions {
+            mode: NumberMode::Suffix,
+            value: 1,
+            reset_folder: true,
+            sep: "_".into(),
+            ..Default::default()
+        };
+        let mut files = ["a/x.txt", "a/y.txt", "b/z.txt", "b/w.txt"].map(|name| {
+            File::new(Path::new(name))
+                .unwrap()
+                .with_option(Options::Number(opt.clone()))
+        });
+        opt.sequence(files.iter_mut());
+        let counters = files.iter().map(File::counter).collect::<Vec<_>>();
+        assert_eq!(counters, ["1", "2", "1", "2"].map(|n| Some(n.to_string())));
+        let result = files
+            .iter_mut()
+            .map(|file| file.preview())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            result,
+            ["a/x_1.txt", "a/y_2.txt", "b/z_1.txt", "b/w_2.txt"].map(PathBuf::from)
+        );
+    }
+
+    #[test]
+    fn suffix_binary_no_padding() {
+        let mut file = File::new(Path::new("TestFile.txt")).unwrap();
+        let format = NumberFormat::Binary;
+        let value = 5;
+        let pad = 0;
+        let char = '0';
+        let sep = ".".into();
+        let mode = NumberMode::Suffix;
+        let opt = NumberOptions {
+            mode,
+            value,
+            pad,
+            char,
+            sep,
+            format,
+            ..Default::default()
+        };
+        opt.process(&mut file);
+        assert_eq!(file.stem, "TestFile.101");
+    }
+
+    #[test]
+    fn insert_asciiupper() {
+        let mut file = File::new(Path::new("TestFile.txt")).unwrap();
+        let format = NumberFormat::AsciiUpper;
+        let value = 50;
+        let pad = 0;
+        let char = '0';
+        let sep = "_".into();
+        let mode = NumberMode::Insert(4);
+        let opt = NumberOptions {
+            mode,
+            value,
+            pad,
+            char,
+            sep,
+            format,
+            ..Default::default()
+        };
+        opt.process(&mut file);
+        assert_eq!(file.stem, "Test_AX_File");
+    }
+
+    #[test]
+    fn roman_numerals_padded() {
+        let numeral = |value, format, pad| {
+            NumberOptions {
+                value,
+                format,
+                pad,
+                char: ' ',
+                ..Default::default()
+            }
+            .number_value()
+        };
+        assert_eq!(numeral(1994, NumberFormat::RomanUpper, 0), "MCMXCIV");
+        assert_eq!(numeral(4, NumberFormat::RomanLower, 0), "iv");
+        assert_eq!(numeral(0, NumberFormat::RomanUpper, 0), "N");
+        assert_eq!(numeral(4000, NumberFormat::RomanUpper, 0), "MMMM");
+        assert_eq!(numeral(2, NumberFormat::RomanUpper, 4), "  II");
+        assert_eq!(numeral(8, NumberFormat::RomanUpper, 4), "VIII");
+    }
+
+    #[test]
+    fn insert_non_ascii() {
+        let opt = NumberOptions {
+            mode: NumberMode::Insert(2),
+            value: 7,
+            sep: " ".into(),
+            ..Default::default()
+        };
+        let mut file = File::new(Path::new("été.txt")).unwrap();
+        opt.process(&mut file);
+        assert_eq!(file.stem, "ét 7 é");
+        let mut file = File::new(Path::new("😀.txt")).unwrap();
+        opt.process(&mut file);
+        assert_eq!(file.stem, "😀 7 ");
+    }
+
+    #[test]
+    fn bounds_with_padding() {
+        let opt = NumberOptions {
+            value: 1,
+            pad: 3,
+            char: '0',
+            ..Default::default()
+        };
+        let stepped = NumberOptions {
+            step: 5,
+            ..opt.clone()
+        };
+        let reset = NumberOptions {
+            reset_at: Some(10),
+            ..opt.clone()
+        };
+        assert_eq!(opt.bounds(257), Some(("001".into(), "257".into())));
+        assert_eq!(stepped.bounds(3), Some(("001".into(), "011".into())));
+        assert_eq!(reset.bounds(12), Some(("001".into(), "002".into())));
+        assert_eq!(opt.bounds(0), None);
+    }
+
+    #[test]
+    fn detect_prevailing_pad() {
+        let names = ["IMG_0001.jpg", "IMG_0002.jpg", "track 01.mp3", "2023.txt"];
+        assert_eq!(detect_pad(&names), Some(4));
+        assert_eq!(detect_pad(&["01 a.mp3", "0001 b.mp3"]), Some(4));
+        assert_eq!(detect_pad(&["1.txt", "10.txt", "no number"]), None);
+    }
+}
@@ -0,0 +1,158 @@
+use super::File;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, HashSet},
+    ffi::OsString,
+    path::{Path, PathBuf},
+};
+
+/// Extensions of camera RAW files.
+pub const RAW_EXTENSIONS: [&str; 12] = [
+    "arw", "cr2", "cr3", "crw", "dng", "nef", "nrw", "orf", "pef", "raf", "rw2", "srw",
+];
+/// Extensions of JPEG files.
+pub const JPEG_EXTENSIONS: [&str; 2] = ["jpg", "jpeg"];
+
+/// Keep the RAW and JPEG files of a shot with the same name, e.g. `IMG_0001.CR3` and
+/// `IMG_0001.JPG`. Files in the same folder with the same stem are a pair, and when `keep`
+/// is set the JPEG is given the RAW file's new name, with its own extension.
+///
+/// Pairing needs the whole batch, so it is applied with [PairOptions::apply] after
+/// previewing each file rather than through [File::with_option].
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct PairOptions {
+    pub keep: bool,
+}
+
+impl PairOptions {
+    /// Give the JPEGs of previewed files (see [File::preview]) the new stem of their RAW.
+    /// Use [File::new_path] for the resulting paths.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use std::path::{Path, PathBuf};
+    /// # use mass_renamer_core::file::{File, NameOptions, Options, PairOptions};
+    /// let [raw, jpeg] = ["a.CR3", "a.JPG"].map(|name| File::new(Path::new(name)).unwrap());
+    /// let mut files = [raw.with_option(Options::Name(NameOptions::Fixed("beach".into()))), jpeg];
+    /// for file in files.iter_mut() {
+    ///     file.preview();
+    /// }
+    /// PairOptions { keep: true }.apply(files.iter_mut());
+    /// assert_eq!(files[1].new_path(), PathBuf::from("beach.JPG"));
+    /// ```
+    pub fn apply<'a, I: IntoIterator<Item = &'a mut File>>(&self, files: I) {
+        if !self.keep {
+            return;
+        }
+        let mut shots: BTreeMap<(PathBuf, OsString), Vec<&mut File>> = BTreeMap::new();
+        for file in files {
+            if let Some(key) = shot(&file.original) {
+                shots.entry(key).or_default().push(file);
+            }
+        }
+        for files in shots.values_mut() {
+            let Some(stem) = files
+                .iter()
+                .find(|file| is_raw(&file.original))
+                .map(|file| file.stem.clone())
+            else {
+                continue;
+            };
+            for file in files.iter_mut().filter(|file| is_jpeg(&file.original)) {
+                file.stem.clone_from(&stem);
+            }
+        }
+    }
+}
+
+pub fn is_raw(path: &Path) -> bool {
+    has_extension(path, &RAW_EXTENSIONS)
+}
+
+pub fn is_jpeg(path: &Path) -> bool {
+    has_extension(path, &JPEG_EXTENSIONS)
+}
+
+fn has_extension(path: &Path, extensions: &[&str]) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| {
+            extensions
+                .iter()
+                .any(|known| ext.eq_ignore_ascii_case(known))
+        })
+}
+
+/// The folder and stem identifying a RAW or JPEG file's shot.
+fn shot(path: &Path) -> Option<(PathBuf, OsString)> {
+    if !is_raw(path) && !is_jpeg(path) {
+        return None;
+    }
+    Some((path.parent()?.to_path_buf(), path.file_stem()?.to_owned()))
+}
+
+/// The files in `selected` whose RAW or JPEG partner is among `all` but not selected,
+/// so renaming them would split the pair.
+pub fn split<P: AsRef<Path>, Q: AsRef<Path>>(selected: &[P], all: &[Q]) -> Vec<PathBuf> {
+    let chosen = selected
+        .iter()
+        .map(|path| path.as_ref())
+        .collect::<HashSet<_>>();
+    let left_out = all
+        .iter()
+        .map(|path| path.as_ref())
+        .filter(|path| !chosen.contains(path))
+        .filter_map(|path| Some((shot(path)?, is_raw(path))))
+        .collect::<HashSet<_>>();
+    selected
+        .iter()
+        .map(|path| path.as_ref())
+        .filter(|path| shot(path).is_some_and(|shot| left_out.contains(&(shot, !is_raw(path)))))
+        .map(Path::to_path_buf)
+        .collect()
+}
+
+#[cfg(test)]
+mod pair_tests {
+    use super::*;
+
+    fn files(names: &[&str]) -> Vec<File> {
+        names
+            .iter()
+            .map(|name| File::new(Path::new(name)).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn jpeg_follows_raw() {
+        let mut files = files(&["d/a.CR3", "d/a.jpg", "d/a.JPEG", "e/a.jpg", "d/b.jpg"]);
+        for file in files.iter_mut() {
+            file.preview();
+            file.stem = format!("{}_new", file.stem);
+        }
+        files[0].stem = "raw".into();
+        PairOptions { keep: true }.apply(files.iter_mut());
+        let names = files.iter().map(File::new_path).collect::<Vec<_>>();
+        assert_eq!(
+            names,
+            [
+                "d/raw.CR3",
+                "d/raw.jpg",
+                "d/raw.JPEG",
+                "e/a_new.jpg",
+                "d/b_new.jpg"
+            ]
+            .map(PathBuf::from)
+        );
+    }
+
+    #[test]
+    fn split_by_selection() {
+        let all = ["a.CR2", "a.jpg", "b.NEF", "b.JPG", "c.jpg", "c.txt"];
+        let selected = ["a.CR2", "a.jpg", "b.JPG", "c.jpg"];
+        assert_eq!(split(&selected, &all), [PathBuf::from("b.JPG")]);
+    }
+}
@@ -0,0 +1,326 @@
+use super::{File, Process, Step};
+use regex::{Captures, Regex, RegexBuilder, Replacer};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// The most memory a typed regular expression may compile to, in bytes. Larger ones,
+/// e.g. `\w{10000}`, fail to compile rather than slowing every preview down.
+pub const SIZE_LIMIT: usize = 1 << 20;
+
+/// Compile a regular expression typed by the user, within the [SIZE_LIMIT].
+///
+/// ```
+/// # use mass_renamer_core::file::reg::compile;
+/// assert!(compile(r"^IMG_(\d+)").is_ok());
+/// assert!(compile(r"\w{10000}").is_err());
+/// ```
+pub fn compile(exp: &str) -> Result<Regex, regex::Error> {
+    RegexBuilder::new(exp)
+        .size_limit(SIZE_LIMIT)
+        .dfa_size_limit(SIZE_LIMIT)
+        .build()
+}
+
+/// Use a regular expression `exp` to find the offending text and `rep` it with new.
+/// `rep` can refer to what the groups caught and change its case, see [Replacement].
+///
+/// Using the `extension` boolean to declare whether to search the file extension too.
+#[derive(Default, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct RegexOptions {
+    pub exp: String,
+    pub rep: String,
+    pub extension: bool,
+}
+
+impl Process for RegexOptions {
+    fn process(&self, file: &mut File) {
+        let exp = match compile(&self.exp) {
+            Ok(exp) => exp,
+            Err(err) => return file.fail(Step::Regex, err),
+        };
+        let rep = Replacement::parse(&self.rep);
+        match (self.extension, &file.extension) {
+            (false, _) | (_, None) => file.stem = exp.replace_all(&file.stem, &rep).to_string(),
+            (true, Some(ext)) => {
+                let mut f = file.stem.clone();
+                f.push('.');
+                f.push_str(ext);
+                let res = exp.replace_all(&f, &rep).to_string();
+                match res.rsplit_once('.') {
+                    None => file.stem = res,
+                    Some((s, e)) => {
+                        file.stem = s.to_owned();
+                        file.extension = Some(e.to_owned());
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// The text matches are replaced with, as other renamers write it.
+/// - `$1`, `${1}`, `\1` - What group 1 caught, `$0` the whole match.
+/// - `$name`, `${name}` - What the group `(?<name>...)` caught.
+/// - `\U` and `\L` - Upper or lower case what follows, until `\E`.
+/// - `\u` and `\l` - Upper or lower case the next letter only.
+/// - `$$` and `\\` - A `$` or `\` of their own.
+///
+/// Groups that didn't take part in the match are empty. Any other `\` is kept as it is.
+///
+/// ```
+/// # use mass_renamer_core::file::reg::{compile, Replacement};
+/// let exp = compile(r"(\w+)_(\w+)").unwrap();
+/// let rep = Replacement::parse(r"\U$2\E-\u\L\1");
+/// assert_eq!(exp.replace_all("hello_world", &rep), "WORLD-Hello");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Replacement {
+    pieces: Vec<Piece>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Piece {
+    Text(String),
+    Index(usize),
+    Name(String),
+    /// Change the case of what follows, `None` to stop.
+    Case(Option<Case>),
+    /// Change the case of the next letter.
+    Next(Case),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Case {
+    Upper,
+    Lower,
+}
+
+impl Case {
+    fn apply(self, c: char, dst: &mut String) {
+        match self {
+            Case::Upper => dst.extend(c.to_uppercase()),
+            Case::Lower => dst.extend(c.to_lowercase()),
+        }
+    }
+}
+
+impl Replacement {
+    pub fn parse(rep: &str) -> Self {
+        let mut pieces = vec![];
+        let mut text = String::new();
+        let mut chars = rep.chars().peekable();
+        while let Some(c) = chars.next() {
+            let piece = match (c, chars.peek().copied()) {
+                ('$', Some('$')) | ('\\', Some('\\')) => {
+                    chars.next();
+                    text.push(c);
+                    continue;
+                }
+                ('$', Some('{')) => {
+                    let mut rest = chars.clone();
+                    rest.next();
+                    let mut name = String::new();
+                    let mut closed = false;
+                    for c in rest.by_ref() {
+                        if c == '}' {
+                            closed = true;
+                            break;
+                        }
+                        name.push(c);
+                    }
+                    if !closed {
+                        text.push(c);
+                        continue;
+                    }
+                    chars = rest;
+                    group(name)
+                }
+                ('$', Some(next)) if next == '_' || next.is_alphanumeric() => {
+                    let mut name = String::new();
+                    let digits = next.is_ascii_digit();
+                    while let Some(next) = chars.peek().copied() {
+                        let part = match digits {
+                            true => next.is_ascii_digit(),
+                            false => next == '_' || next.is_alphanumeric(),
+                        };
+                        if !part {
+                            break;
+                        }
+                        name.push(next);
+                        chars.next();
+                    }
+                    group(name)
+                }
+                ('\\', Some(next)) if next.is_ascii_digit() => {
+                    let mut index = String::new();
+                    while let Some(next) = chars.peek().copied().filter(char::is_ascii_digit) {
+                        index.push(next);
+                        chars.next();
+                    }
+                    group(index)
+                }
+                ('\\', Some(next @ ('U' | 'L' | 'E' | 'u' | 'l'))) => {
+                    chars.next();
+                    match next {
+                        'U' => Piece::Case(Some(Case::Upper)),
+                        'L' => Piece::Case(Some(Case::Lower)),
+                        'E' => Piece::Case(None),
+                        'u' => Piece::Next(Case::Upper),
+                        _ => Piece::Next(Case::Lower),
+                    }
+                }
+                _ => {
+                    text.push(c);
+                    continue;
+                }
+            };
+            if !text.is_empty() {
+                pieces.push(Piece::Text(std::mem::take(&mut text)));
+            }
+            pieces.push(piece);
+        }
+        if !text.is_empty() {
+            pieces.push(Piece::Text(text));
+        }
+        Self { pieces }
+    }
+
+    /// Add the replacement for one match to `dst`.
+    pub fn expand(&self, caps: &Captures, dst: &mut String) {
+        let mut case = None;
+        let mut next = None;
+        for piece in &self.pieces {
+            let text = match piece {
+                Piece::Text(text) => text.as_str(),
+                Piece::Index(idx) => caps.get(*idx).map_or("", |m| m.as_str()),
+                Piece::Name(name) => caps.name(name).map_or("", |m| m.as_str()),
+                Piece::Case(new) => {
+                    case = *new;
+                    continue;
+                }
+                Piece::Next(new) => {
+                    next = Some(*new);
+                    continue;
+                }
+            };
+            for c in text.chars() {
+                match next.take().or(case) {
+                    Some(case) => case.apply(c, dst),
+                    None => dst.push(c),
+                }
+            }
+        }
+    }
+}
+
+/// A group referred to by its number or its name.
+fn group(name: String) -> Piece {
+    match name.parse() {
+        Ok(idx) => Piece::Index(idx),
+        Err(_) => Piece::Name(name),
+    }
+}
+
+impl Replacer for &Replacement {
+    fn replace_append(&mut self, caps: &Captures<'_>, dst: &mut String) {
+        self.expand(caps, dst);
+    }
+}
+
+#[cfg(test)]
+mod regex_tests {
+    use super::*;
+    use std::path::Path;
+    #[test]
+    fn regex_test_with_extension() {
+        let exp = "0123.txt".into();
+        let file = Path::new("./file0123.txt");
+        let rep = "ABCD.csv".into();
+        let opt = RegexOptions {
+            exp,
+            rep,
+            extension: true,
+        };
+        let mut rename = File::new(file).unwrap();
+        opt.process(&mut rename);
+        assert_eq!(
+            (rename.stem, rename.extension),
+            (String::from("fileABCD"), Some(String::from("csv")))
+        );
+    }
+    #[test]
+    fn regex_test_no_extension() {
+        let exp = "0123".into();
+        let rep = "ABCD".into();
+        let file = Path::new("./file0123.txt");
+        let opt = RegexOptions {
+            exp,
+            rep,
+            extension: false,
+        };
+        let mut rename = File::new(file).unwrap();
+        opt.process(&mut rename);
+        assert_eq!(
+            (rename.stem, rename.extension),
+            (String::from("fileABCD"), Some(String::from("txt")))
+        );
+    }
+    #[test]
+    fn regex_test_no_extension_no_match() {
+        let exp = "0123".into();
+        let rep = "ABCD".into();
+        let file = Path::new("./file123.txt");
+        let opt = RegexOptions {
+            exp,
+            rep,
+            extension: false,
+        };
+        let mut rename = File::new(file).unwrap();
+        opt.process(&mut rename);
+        assert_eq!(
+            (rename.stem, rename.extension),
+            (String::from("file123"), Some(String::from("txt")))
+        );
+    }
+
+    #[test]
+    fn capture_case_transforms() {
+        let rename = |exp: &str, rep: &str, name: &str| {
+            let opt = RegexOptions {
+                exp: exp.into(),
+                rep: rep.into(),
+                extension: false,
+            };
+            let mut file = File::new(Path::new(name)).unwrap();
+            opt.process(&mut file);
+            file.stem
+        };
+        let exp = r"(?<artist>\w+) - (\w+)";
+        assert_eq!(rename(exp, r"\2 - \1", "mIxEd - CaSe.mp3"), "CaSe - mIxEd");
+        assert_eq!(
+            rename(exp, r"\U$2\E - \L$1", "mIxEd - CaSe.mp3"),
+            "CASE - mixed"
+        );
+        assert_eq!(rename(exp, r"\u\L${artist}", "mIxEd - CaSe.mp3"), "Mixed");
+        assert_eq!(rename(exp, r"\l$2$$\\", "mIxEd - CaSe.mp3"), "caSe$\\");
+        // \u goes on to the next letter when a group caught nothing.
+        assert_eq!(rename(r"(x?)(ab)", r"\u$1$2", "ab.txt"), "Ab");
+        assert_eq!(rename(r"(ab)", r"$1c ${1 \n", "ab.txt"), "abc ${1 \\n");
+        assert_eq!(rename("é", r"\U$0", "café.txt"), "cafÉ");
+    }
+
+    #[test]
+    fn oversized_expression_recorded_on_file() {
+        let mut file = File::new(Path::new("file.txt")).unwrap();
+        let opt = RegexOptions {
+            exp: r"\w{10000}".into(),
+            rep: "x".into(),
+            extension: false,
+        };
+        opt.process(&mut file);
+        assert_eq!(file.stem, "file");
+        assert_eq!(file.errors().len(), 1);
+        assert!(file.errors()[0].contains("size limit"));
+    }
+}
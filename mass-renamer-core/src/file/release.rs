@@ -0,0 +1,170 @@
+use super::{File, Process};
+use regex::Regex;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+const RESOLUTION: &str = r"(?i)\b(?:(?:480|576|720|1080|1440|2160|4320)[pi]|4K|8K|UHD)\b";
+const SOURCE: &str = r"(?i)\b(?:WEB[-. ]?DL|WEB[-. ]?Rip|Blu[-. ]?Ray|BDRip|BRRip|HDTV|PDTV|DVDRip|HDRip|REMUX|AMZN|DSNP|HMAX|ATVP)\b";
+const CODEC: &str =
+    r"(?i)\b(?:[xh][-. ]?26[45]|HEVC|AVC|XviD|DivX|AV1|VP9|10[-. ]?bit|HDR(?:10)?|DoVi)\b";
+const AUDIO: &str = r"(?i)\b(?:(?:AAC|DDP?|E-?AC-?3)(?:[-. ]?[257]\.[01])?|AC3|DTS(?:-HD)?(?:[-. ]?MA)?|TrueHD|Atmos|FLAC|MP3|Opus)\b";
+const TAGS: &str =
+    r"(?i)\b(?:PROPER|REPACK|INTERNAL|LIMITED|UNRATED|EXTENDED|REMASTERED|MULTi|SUBBED|DUBBED)\b";
+const BRACKET_GROUP: &str = r"^\[[^\[\]]*\]\s*|\s*\[[^\[\]]*\]$";
+const SCENE_GROUP: &str = r"-[[:alnum:]]+$";
+const EMPTY_BRACKETS: &str = r"\[[\s._-]*\]|\([\s._-]*\)";
+const SEPARATORS: [(&str, &str); 4] = [
+    (r" {2,}", " "),
+    (r"\.{2,}", "."),
+    ("_{2,}", "_"),
+    ("-{2,}", "-"),
+];
+const OUTER_SEPARATORS: &str = r"^[\s._-]+|[\s._-]+$";
+
+/// Strip common release clutter from downloaded media names.
+/// - `resolution` - 480p, 720p, 1080p, 2160p, 4K, UHD etc.
+/// - `source` - WEB-DL, WEBRip, BluRay, HDTV, REMUX and streaming service tags (AMZN, DSNP etc).
+/// - `codec` - x264, H.265, HEVC, XviD, AV1, 10bit, HDR etc.
+/// - `audio` - AAC, DD5.1, DDP5.1, DTS-HD MA, TrueHD, Atmos, FLAC etc.
+/// - `tags` - PROPER, REPACK, INTERNAL, LIMITED, EXTENDED etc.
+/// - `group` - Release group names in leading or trailing square brackets (`[GROUP] Show`,
+///   `Show [GROUP]`) and, for dot separated scene names without spaces, a trailing `-GROUP`.
+/// - `dots` - Replace `.` and `_` separators with spaces.
+///
+/// Once something has been removed, leftover empty brackets, repeated separators and
+/// separators at the start or end of the name are tidied up.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ReleaseOptions {
+    pub resolution: bool,
+    pub source: bool,
+    pub codec: bool,
+    pub audio: bool,
+    pub tags: bool,
+    pub group: bool,
+    pub dots: bool,
+}
+
+impl Process for ReleaseOptions {
+    fn process(&self, file: &mut File) {
+        let original = file.stem.clone();
+        let stem = &mut file.stem;
+        if self.group {
+            remove(stem, BRACKET_GROUP);
+            // Only scene names are dot separated, e.g. not `X-Men` or `Trip-001`.
+            if !stem.contains(' ') && stem.contains('.') {
+                remove(stem, SCENE_GROUP);
+            }
+        }
+        for (enabled, exp) in [
+            (self.resolution, RESOLUTION),
+            (self.source, SOURCE),
+            (self.codec, CODEC),
+            (self.audio, AUDIO),
+            (self.tags, TAGS),
+        ] {
+            if enabled {
+                remove(stem, exp);
+            }
+        }
+        if *stem != original {
+            remove(stem, EMPTY_BRACKETS);
+            for (exp, sep) in SEPARATORS {
+                if let Ok(exp) = Regex::new(exp) {
+                    *stem = exp.replace_all(stem, sep).to_string();
+                }
+            }
+            remove(stem, OUTER_SEPARATORS);
+        }
+        if self.dots {
+            *stem = stem.replace(['.', '_'], " ");
+        }
+    }
+}
+
+fn remove(text: &mut String, exp: &str) {
+    if let Ok(exp) = Regex::new(exp) {
+        *text = exp.replace_all(text, "").to_string();
+    }
+}
+
+#[cfg(test)]
+mod release_tests {
+    use super::*;
+    use std::path::Path;
+
+    fn all() -> ReleaseOptions {
+        ReleaseOptions {
+            resolution: true,
+            source: true,
+            codec: true,
+            audio: true,
+            tags: true,
+            group: true,
+            dots: false,
+        }
+    }
+
+    #[test]
+    fn scene_name() {
+        let mut file = File::new(Path::new(
+            "Some.Movie.2020.PROPER.1080p.BluRay.x264.DTS-HD.MA-GRP.mkv",
+        ))
+        .unwrap();
+        let opt = all();
+        opt.process(&mut file);
+        assert_eq!(file.stem, "Some.Movie.2020");
+    }
+
+    #[test]
+    fn scene_name_to_spaces() {
+        let mut file =
+            File::new(Path::new("Show.S01E02.720p.WEB-DL.DDP5.1.H.264-GRP.mkv")).unwrap();
+        let opt = ReleaseOptions {
+            dots: true,
+            ..all()
+        };
+        opt.process(&mut file);
+        assert_eq!(file.stem, "Show S01E02");
+    }
+
+    #[test]
+    fn bracketed_groups_and_tags() {
+        let mut file = File::new(Path::new(
+            "[SubGroup] Show - 01 [1080p] [HEVC 10bit] [ABCD1234].mkv",
+        ))
+        .unwrap();
+        let opt = all();
+        opt.process(&mut file);
+        assert_eq!(file.stem, "Show - 01");
+    }
+
+    #[test]
+    fn individual_toggles() {
+        let mut file = File::new(Path::new("Movie (2019) 2160p x265 AAC.mkv")).unwrap();
+        let opt = ReleaseOptions {
+            resolution: true,
+            ..Default::default()
+        };
+        opt.process(&mut file);
+        assert_eq!(file.stem, "Movie (2019) x265 AAC");
+    }
+
+    #[test]
+    fn hyphenated_names_keep_their_ending() {
+        let opt = all();
+        for name in ["X-Men.mkv", "Spider-Man.mkv", "Trip-001.jpg"] {
+            let mut file = File::new(Path::new(name)).unwrap();
+            opt.process(&mut file);
+            assert_eq!(file.new_path(), Path::new(name));
+        }
+    }
+
+    #[test]
+    fn untouched_name_not_tidied() {
+        let mut file = File::new(Path::new("My  holiday..photos.jpg")).unwrap();
+        let opt = all();
+        opt.process(&mut file);
+        assert_eq!(file.stem, "My  holiday..photos");
+    }
+}
@@ -0,0 +1,129 @@
+use super::{File, Process};
+use regex::{NoExpand, RegexBuilder};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Options for basic renaming rules.
+/// - `replace` - text to be replaced
+/// - `with` - new text. Note: the text is always replaced with the text as written, including any specific text case.
+/// - `case` - true for case sensitive, false for case-insensitive
+#[derive(Default, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ReplaceOptions {
+    pub replace: String,
+    pub with: String,
+    pub case: bool,
+}
+
+impl Process for ReplaceOptions {
+    fn process(&self, file: &mut File) {
+        let file = &mut file.stem;
+        if self.case {
+            *file = file.replace(&self.replace, &self.with);
+        } else {
+            // Lowercasing can change the length of a name, so the match is found in it as it is.
+            let replace = RegexBuilder::new(&regex::escape(&self.replace))
+                .case_insensitive(true)
+                .build()
+                .expect("Escaped text is a valid expression.");
+            *file = replace
+                .replace_all(file.as_str(), NoExpand(&self.with))
+                .into_owned();
+        }
+    }
+}
+
+#[cfg(test)]
+mod match_tests {
+    use super::*;
+    use std::path::Path;
+    #[test]
+    fn no_matching_text_case_sensitive() {
+        let replace = "ABC".into();
+        let with = "123".into();
+        let mut file = File::new(Path::new("fileabc")).unwrap();
+        let case = true;
+        let opt = ReplaceOptions {
+            replace,
+            with,
+            case,
+        };
+        opt.process(&mut file);
+        assert_eq!(file.stem, String::from("fileabc"))
+    }
+    #[test]
+    fn no_matching_text_case_insensitive() {
+        let replace = "qrs".into();
+        let with = "123".into();
+        let mut file = File::new(Path::new("fileabc")).unwrap();
+        let case = false;
+        let opt = ReplaceOptions {
+            replace,
+            with,
+            case,
+        };
+        opt.process(&mut file);
+        assert_eq!(file.stem, String::from("fileabc"))
+    }
+    #[test]
+    fn matched_case_sensitive() {
+        let replace = "abc".into();
+        let with = "123".into();
+        let mut file = File::new(Path::new("fileabc")).unwrap();
+        let case = true;
+        let opt = ReplaceOptions {
+            replace,
+            with,
+            case,
+        };
+        opt.process(&mut file);
+        assert_eq!(file.stem, String::from("file123"))
+    }
+    #[test]
+    fn matched_case_insensitive() {
+        let replace = "ABC".into();
+        let with = "123".into();
+        let mut file = File::new(Path::new("fileabc")).unwrap();
+        let case = false;
+        let opt = ReplaceOptions {
+            replace,
+            with,
+            case,
+        };
+        opt.process(&mut file);
+        assert_eq!(file.stem, String::from("file123"))
+    }
+
+    #[test]
+    fn case_insensitive_non_ascii() {
+        // "İ" lowercases to two characters, which moved the match in the lowercased name.
+        let mut file = File::new(Path::new("İstanbul ÉTÉ")).unwrap();
+        let opt = ReplaceOptions {
+            replace: "été".into(),
+            with: "summer".into(),
+            case: false,
+        };
+        opt.process(&mut file);
+        assert_eq!(file.stem, "İstanbul summer");
+    }
+
+    #[test]
+    fn case_insensitive_every_match() {
+        let mut file = File::new(Path::new("Σοφία ΣΟΦΊΑ σοφία")).unwrap();
+        let opt = ReplaceOptions {
+            replace: "σοφία".into(),
+            with: "😀".into(),
+            case: false,
+        };
+        opt.process(&mut file);
+        assert_eq!(file.stem, "😀 😀 😀");
+        let mut file = File::new(Path::new("a-b(c).txt")).unwrap();
+        let opt = ReplaceOptions {
+            replace: "B(C".into(),
+            with: "$1".into(),
+            case: false,
+        };
+        opt.process(&mut file);
+        assert_eq!(file.stem, "a-$1)");
+    }
+}
@@ -0,0 +1,348 @@
+use super::{File, Process};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::ops::Range;
+
+const ZERO_WIDTH_JOINER: char = '\u{200D}';
+/// The characters Windows doesn't allow in names, besides control characters.
+pub const WINDOWS_INVALID: [char; 9] = ['<', '>', ':', '"', '/', '\\', '|', '?', '*'];
+/// The names Windows keeps for devices.
+const WINDOWS_RESERVED: [&str; 22] = [
+    "CON", "PRN", "AUX", "NUL", "COM1", "COM2", "COM3", "COM4", "COM5", "COM6", "COM7", "COM8",
+    "COM9", "LPT1", "LPT2", "LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8", "LPT9",
+];
+
+/// Clean up invisible or script-breaking characters that often sneak into downloaded filenames.
+/// Each group of characters has its own `CharPolicy`.
+/// - `emoji` - Pictographs, flags and their modifiers. A full emoji sequence (e.g. 👍🏽 or 👨‍👩‍👧)
+///   counts as a single character.
+/// - `zero_width` - Zero width spaces, joiners, word joiners, byte order marks and soft hyphens.
+/// - `control` - Control characters such as tabs, new lines and escape codes.
+/// - `bidi` - Bidirectional text marks and overrides (e.g. U+202E) that can disguise extensions.
+///
+/// Set `web_safe` to then turn the name into a slug for web assets: letters are
+/// transliterated to ASCII (`Café Ø` becomes `Cafe O`), lowercased, and anything other than
+/// `a-z`, `0-9`, `.`, `_` and `-` becomes a single `-`, e.g. `Menu (Final).PDF` becomes
+/// `menu-final.pdf`. Without the `transliterate` feature `web_safe` has no effect.
+///
+/// Set `windows` to make names Windows can use, for files shared with or synced to it:
+/// the characters in [WINDOWS_INVALID] and control characters become `_`, and names Windows
+/// keeps for devices are followed by `_`, e.g. `con.txt` becomes `con_.txt`.
+///
+/// The extension is sanitized as well as the name.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SanitizeOptions {
+    pub emoji: CharPolicy,
+    pub zero_width: CharPolicy,
+    pub control: CharPolicy,
+    pub bidi: CharPolicy,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub web_safe: bool,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub windows: bool,
+}
+
+/// Select from
+/// - `CharPolicy::Keep` to leave the characters alone (default)
+/// - `CharPolicy::Strip` to remove the characters
+/// - `CharPolicy::Replace` to replace each character with `_`
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum CharPolicy {
+    #[default]
+    Keep,
+    Strip,
+    Replace,
+}
+
+impl CharPolicy {
+    pub fn iter() -> impl Iterator<Item = CharPolicy> {
+        [Self::Keep, Self::Strip, Self::Replace].iter().copied()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CharClass {
+    Emoji,
+    ZeroWidth,
+    Control,
+    Bidi,
+    Other,
+}
+
+impl Process for SanitizeOptions {
+    fn process(&self, file: &mut File) {
+        file.stem = self.sanitize(&file.stem);
+        file.extension = file.extension.as_deref().map(|ext| self.sanitize(ext));
+        if self.web_safe && cfg!(feature = "transliterate") {
+            file.stem = slugify(&file.stem);
+            file.extension = file.extension.as_deref().map(slugify);
+        }
+        if self.windows {
+            file.stem = windows_safe(&file.stem);
+            file.extension = file.extension.as_deref().map(windows_safe);
+            if reserved_on_windows(&file.stem) {
+                let device = file.stem.find('.').unwrap_or(file.stem.len());
+                file.stem.insert(device, '_');
+            }
+        }
+    }
+}
+
+/// Whether Windows keeps the name for a device, whatever its case or extension, e.g.
+/// `CON`, `nul.txt` or `Lpt1.tar.gz`.
+///
+/// ```
+/// # use mass_renamer_core::file::sanitize::reserved_on_windows;
+/// assert!(reserved_on_windows("nul.txt"));
+/// assert!(!reserved_on_windows("console.txt"));
+/// ```
+pub fn reserved_on_windows(name: &str) -> bool {
+    let device = name.split('.').next().unwrap_or_default().trim_end();
+    WINDOWS_RESERVED
+        .iter()
+        .any(|reserved| reserved.eq_ignore_ascii_case(device))
+}
+
+/// Replace the characters Windows doesn't allow with `_`.
+fn windows_safe(text: &str) -> String {
+    text.chars()
+        .map(
+            |chr| match WINDOWS_INVALID.contains(&chr) || chr.is_control() {
+                true => '_',
+                false => chr,
+            },
+        )
+        .collect()
+}
+
+impl SanitizeOptions {
+    fn sanitize(&self, text: &str) -> String {
+        let mut new = String::with_capacity(text.len());
+        for (class, range) in classify(text) {
+            let policy = match class {
+                CharClass::Emoji => self.emoji,
+                CharClass::ZeroWidth => self.zero_width,
+                CharClass::Control => self.control,
+                CharClass::Bidi => self.bidi,
+                CharClass::Other => CharPolicy::Keep,
+            };
+            match policy {
+                CharPolicy::Keep => new.push_str(&text[range]),
+                CharPolicy::Strip => (),
+                CharPolicy::Replace => new.push('_'),
+            }
+        }
+        new
+    }
+}
+
+/// Transliterate to lowercase ASCII and collapse everything outside `[a-z0-9._-]`
+/// into single dashes, without leading or trailing dashes.
+fn slugify(text: &str) -> String {
+    #[cfg(feature = "transliterate")]
+    let text = deunicode::deunicode(text);
+    let mut slug = String::with_capacity(text.len());
+    for chr in text.to_lowercase().chars() {
+        match chr {
+            'a'..='z' | '0'..='9' | '.' | '_' => slug.push(chr),
+            _ if !slug.ends_with('-') => slug.push('-'),
+            _ => (),
+        }
+    }
+    slug.trim_matches('-').to_owned()
+}
+
+/// Split the text into runs of a single class. Emoji sequences (modifiers, joiners,
+/// flag pairs) are kept together, every other character is its own run.
+fn classify(text: &str) -> Vec<(CharClass, Range<usize>)> {
+    let mut units: Vec<(CharClass, Range<usize>)> = vec![];
+    for (idx, chr) in text.char_indices() {
+        let end = idx + chr.len_utf8();
+        if let Some((CharClass::Emoji, range)) = units.last_mut() {
+            let unit = &text[range.clone()];
+            let last = unit.chars().last().unwrap_or_default();
+            let regional = unit.chars().filter(|c| is_regional_indicator(*c)).count();
+            if is_emoji_modifier(chr)
+                || chr == ZERO_WIDTH_JOINER
+                || (last == ZERO_WIDTH_JOINER && is_emoji(chr))
+                || (is_regional_indicator(chr) && is_regional_indicator(last) && regional % 2 == 1)
+            {
+                range.end = end;
+                continue;
+            }
+        }
+        let class = match chr {
+            c if is_emoji(c) || is_emoji_modifier(c) => CharClass::Emoji,
+            '\u{200B}'..='\u{200D}' | '\u{2060}' | '\u{FEFF}' | '\u{180E}' | '\u{AD}' => {
+                CharClass::ZeroWidth
+            }
+            '\u{200E}'
+            | '\u{200F}'
+            | '\u{61C}'
+            | '\u{202A}'..='\u{202E}'
+            | '\u{2066}'..='\u{2069}' => CharClass::Bidi,
+            c if c.is_control() => CharClass::Control,
+            _ => CharClass::Other,
+        };
+        units.push((class, idx..end));
+    }
+    units
+}
+
+fn is_emoji(chr: char) -> bool {
+    matches!(
+        chr,
+        '\u{1F000}'..='\u{1FAFF}'
+            | '\u{2600}'..='\u{27BF}'
+            | '\u{231A}'
+            | '\u{231B}'
+            | '\u{23E9}'..='\u{23FA}'
+            | '\u{2B1B}'
+            | '\u{2B1C}'
+            | '\u{2B50}'
+            | '\u{2B55}'
+    )
+}
+
+/// Characters that only change the look of the preceding emoji
+/// (variation selectors, skin tones, keycaps and tag sequences).
+fn is_emoji_modifier(chr: char) -> bool {
+    matches!(
+        chr,
+        '\u{FE0E}'
+            | '\u{FE0F}'
+            | '\u{1F3FB}'..='\u{1F3FF}'
+            | '\u{20E3}'
+            | '\u{E0020}'..='\u{E007F}'
+    )
+}
+
+fn is_regional_indicator(chr: char) -> bool {
+    matches!(chr, '\u{1F1E6}'..='\u{1F1FF}')
+}
+
+#[cfg(test)]
+mod sanitize_tests {
+    use super::*;
+    use std::path::Path;
+
+    fn all(policy: CharPolicy) -> SanitizeOptions {
+        SanitizeOptions {
+            emoji: policy,
+            zero_width: policy,
+            control: policy,
+            bidi: policy,
+            web_safe: false,
+            windows: false,
+        }
+    }
+
+    #[test]
+    fn keep_everything() {
+        let mut file = File::new(Path::new("party🎉\u{200B}file.txt")).unwrap();
+        let opt = all(CharPolicy::Keep);
+        opt.process(&mut file);
+        assert_eq!(file.stem, "party🎉\u{200B}file");
+    }
+
+    #[test]
+    fn strip_everything() {
+        let mut file = File::new(Path::new("👍🏽party\u{200B}\u{1B}[0m\u{202E}file 🇳🇱.txt")).unwrap();
+        let opt = all(CharPolicy::Strip);
+        opt.process(&mut file);
+        assert_eq!(file.stem, "party[0mfile ");
+    }
+
+    #[test]
+    fn replace_emoji_sequence_once() {
+        let mut file = File::new(Path::new("family 👨\u{200D}👩\u{200D}👧 photo.jpg")).unwrap();
+        let opt = SanitizeOptions {
+            emoji: CharPolicy::Replace,
+            ..Default::default()
+        };
+        opt.process(&mut file);
+        assert_eq!(file.stem, "family _ photo");
+    }
+
+    #[test]
+    fn replace_skin_tone_with_its_emoji() {
+        let mut file = File::new(Path::new("a👍🏽b.txt")).unwrap();
+        let opt = SanitizeOptions {
+            emoji: CharPolicy::Replace,
+            ..Default::default()
+        };
+        opt.process(&mut file);
+        assert_eq!(file.stem, "a_b");
+    }
+
+    #[test]
+    fn mixed_policies() {
+        let mut file = File::new(Path::new("a\tb\u{200F}c😀.t\u{200B}xt")).unwrap();
+        let opt = SanitizeOptions {
+            emoji: CharPolicy::Keep,
+            zero_width: CharPolicy::Strip,
+            control: CharPolicy::Replace,
+            bidi: CharPolicy::Strip,
+            web_safe: false,
+            windows: false,
+        };
+        opt.process(&mut file);
+        assert_eq!(
+            (file.stem, file.extension),
+            (String::from("a_bc😀"), Some(String::from("txt")))
+        );
+    }
+
+    #[test]
+    fn windows_safe_names() {
+        let opt = SanitizeOptions {
+            windows: true,
+            ..Default::default()
+        };
+        let sanitized =
+            ["what? <draft>: 1|2.txt", "Con.tar.gz", "aux", "CONSOLE.log"].map(|name| {
+                let mut file = File::new(Path::new(name)).unwrap();
+                opt.process(&mut file);
+                file.to_string()
+            });
+        assert_eq!(
+            sanitized,
+            [
+                "what_ _draft__ 1_2.txt",
+                "Con_.tar.gz",
+                "aux_",
+                "CONSOLE.log"
+            ]
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "transliterate")]
+    fn web_safe_slug() {
+        let mut file = File::new(Path::new("  Café Ø -- Menu (Final)\u{200B}.PDF")).unwrap();
+        let opt = SanitizeOptions {
+            zero_width: CharPolicy::Strip,
+            web_safe: true,
+            ..Default::default()
+        };
+        opt.process(&mut file);
+        assert_eq!(
+            (file.stem, file.extension),
+            (String::from("cafe-o-menu-final"), Some(String::from("pdf")))
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "transliterate")]
+    fn web_safe_transliterates_other_scripts() {
+        let mut file = File::new(Path::new("Straße_Ελληνικά.txt")).unwrap();
+        let opt = SanitizeOptions {
+            web_safe: true,
+            ..Default::default()
+        };
+        opt.process(&mut file);
+        assert_eq!(file.stem, "strasse_ellenika");
+    }
+}
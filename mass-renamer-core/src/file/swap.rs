@@ -0,0 +1,178 @@
+use super::reg::compile;
+use super::File;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Exchange new names between files, e.g. to swap two files' names.
+/// Files whose original name matches `exp` are grouped by the text of its capture groups,
+/// so `(.*)_(front|back)` groups `a_front.jpg` with `a_back.jpg` and `b_front.jpg` with
+/// `b_back.jpg`. Without capture groups every matching file is in the same group.
+/// Within a group, in the order the files are given, `Mode` decides who takes which name.
+///
+/// Swapping needs the whole batch, so it is applied with [SwapOptions::apply] after
+/// previewing each file rather than through [File::with_option].
+#[derive(Default, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SwapOptions {
+    pub exp: String,
+    pub mode: SwapMode,
+}
+
+/// Select from
+/// - `SwapMode::None` to leave the names alone (default)
+/// - `SwapMode::Swap` to swap the names of each pair of files, 1 with 2, 3 with 4, etc.
+/// - `SwapMode::Rotate` to give each file the name of the next, and the last file the
+///   name of the first (a→b, b→c, c→a)
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum SwapMode {
+    #[default]
+    None,
+    Swap,
+    Rotate,
+}
+
+impl SwapOptions {
+    /// Exchange the new names of previewed files (see [File::preview]).
+    /// Use [File::new_path] for the resulting paths. An invalid `exp` changes nothing.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use std::path::{Path, PathBuf};
+    /// # use mass_renamer_core::file::{File, SwapMode, SwapOptions};
+    /// let mut files = ["a.txt", "b.txt", "c.txt"].map(|name| File::new(Path::new(name)).unwrap());
+    /// let swap = SwapOptions { exp: ".*".into(), mode: SwapMode::Rotate };
+    /// swap.apply(files.iter_mut());
+    /// let names = files.iter().map(File::new_path).collect::<Vec<_>>();
+    /// assert_eq!(names, ["b.txt", "c.txt", "a.txt"].map(PathBuf::from));
+    /// ```
+    pub fn apply<'a, I: IntoIterator<Item = &'a mut File>>(&self, files: I) {
+        if self.mode == SwapMode::None {
+            return;
+        }
+        let Ok(exp) = compile(&self.exp) else {
+            return;
+        };
+        let mut groups: BTreeMap<Vec<String>, Vec<&mut File>> = BTreeMap::new();
+        for file in files {
+            let name = file
+                .original
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_default();
+            if let Some(captures) = exp.captures(&name) {
+                let key = captures
+                    .iter()
+                    .skip(1)
+                    .map(|group| group.map_or("", |group| group.as_str()).to_owned())
+                    .collect();
+                groups.entry(key).or_default().push(file);
+            }
+        }
+        for group in groups.values_mut() {
+            let names = group
+                .iter()
+                .map(|file| (file.stem.clone(), file.extension.clone()))
+                .collect::<Vec<_>>();
+            for (idx, file) in group.iter_mut().enumerate() {
+                let from = match self.mode {
+                    SwapMode::Swap if idx % 2 == 0 => idx + 1,
+                    SwapMode::Swap => idx - 1,
+                    _ => (idx + 1) % names.len(),
+                };
+                if let Some((stem, extension)) = names.get(from) {
+                    file.stem = stem.clone();
+                    file.extension = extension.clone();
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod swap_tests {
+    use super::*;
+    use std::path::{Path, PathBuf};
+
+    fn files(names: &[&str]) -> Vec<File> {
+        names
+            .iter()
+            .map(|name| File::new(Path::new(name)).unwrap())
+            .collect()
+    }
+
+    fn new_names(files: &[File]) -> Vec<PathBuf> {
+        files.iter().map(File::new_path).collect()
+    }
+
+    #[test]
+    fn swap_pairs_leaves_odd_file() {
+        let mut files = files(&["1.txt", "2.txt", "3.txt"]);
+        let opt = SwapOptions {
+            exp: String::new(),
+            mode: SwapMode::Swap,
+        };
+        opt.apply(files.iter_mut());
+        assert_eq!(
+            new_names(&files),
+            ["2.txt", "1.txt", "3.txt"].map(PathBuf::from)
+        );
+    }
+
+    #[test]
+    fn swap_within_capture_groups() {
+        let mut files = files(&[
+            "a_front.jpg",
+            "b_front.jpg",
+            "a_back.png",
+            "b_back.jpg",
+            "other.jpg",
+        ]);
+        let opt = SwapOptions {
+            exp: r"(.*)_(?:front|back)".into(),
+            mode: SwapMode::Swap,
+        };
+        opt.apply(files.iter_mut());
+        assert_eq!(
+            new_names(&files),
+            [
+                "a_back.png",
+                "b_back.jpg",
+                "a_front.jpg",
+                "b_front.jpg",
+                "other.jpg"
+            ]
+            .map(PathBuf::from)
+        );
+    }
+
+    #[test]
+    fn rotate_previewed_names() {
+        let mut files = files(&["dir/x.txt", "dir/y.txt", "dir/z.txt"]);
+        let opt = SwapOptions {
+            exp: r"\.txt$".into(),
+            mode: SwapMode::Rotate,
+        };
+        for file in files.iter_mut() {
+            file.preview();
+        }
+        opt.apply(files.iter_mut());
+        assert_eq!(
+            new_names(&files),
+            ["dir/y.txt", "dir/z.txt", "dir/x.txt"].map(PathBuf::from)
+        );
+    }
+
+    #[test]
+    fn invalid_expression_changes_nothing() {
+        let mut files = files(&["1.txt", "2.txt"]);
+        let opt = SwapOptions {
+            exp: "(".into(),
+            mode: SwapMode::Rotate,
+        };
+        opt.apply(files.iter_mut());
+        assert_eq!(new_names(&files), ["1.txt", "2.txt"].map(PathBuf::from));
+    }
+}
@@ -0,0 +1,121 @@
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+/// Name of the folder [generate] creates its files in.
+pub const SANDBOX: &str = "mass-renamer-sandbox";
+
+/// Awkward names for trying out rules: Unicode in both normalization forms, long names,
+/// dotfiles, stems shared between extensions and unusual extensions.
+/// Only characters allowed on Windows, macOS and Linux are used.
+pub const NAMES: [&str; 32] = [
+    "café.txt",
+    "cafe\u{301}.txt",
+    "日本語のファイル.txt",
+    "Ελληνικά αρχεία.txt",
+    "עברית.txt",
+    "emoji 😀🎉.png",
+    "Straße MASSE.txt",
+    ".hidden",
+    ".config.json",
+    "..double dot.txt",
+    "photo.jpg",
+    "photo.JPG",
+    "photo.png",
+    "Photo.jpeg",
+    "IMG_0001.JPG",
+    "IMG_0002.JPG",
+    "IMG_0010.JPG",
+    "archive.tar.gz",
+    "no extension",
+    "file..txt",
+    "UPPER CASE.TXT",
+    "my_holiday_01.jpg",
+    "  leading and trailing spaces  .txt",
+    "double  spaces  here.txt",
+    "report 04.05.2023.pdf",
+    "Movie.2020.1080p.BluRay.x264-GROUP.mkv",
+    "The Show S01E02 720p WEB-DL AAC.mp4",
+    "percent%20encoded%20name.html",
+    "symbols ~!@#$%^&()_+=[]{},;'.txt",
+    "12345.txt",
+    "a.b.c.d.e",
+    "nested/deep/inside.txt",
+];
+
+/// Length of the generated long name, leaving room for the folder on Windows.
+const LONG_NAME: usize = 180;
+
+/// Fill a [SANDBOX] folder inside `dir` with empty files from [NAMES], plus a very long
+/// name, and return their paths. Files already there are left alone.
+pub fn generate<P: AsRef<Path>>(dir: P) -> Result<Vec<PathBuf>, io::Error> {
+    let sandbox = dir.as_ref().join(SANDBOX);
+    let long = format!("{}.txt", "long name ".repeat(LONG_NAME / 10));
+    let mut paths = vec![];
+    for name in NAMES.iter().copied().chain([long.as_str()]) {
+        let path = sandbox.join(name);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        if !path.exists() {
+            fs::File::create(&path)?;
+        }
+        paths.push(path);
+    }
+    Ok(paths)
+}
+
+#[cfg(test)]
+mod fixtures_tests {
+    use super::*;
+    use crate::file::{
+        AddOptions, Case, CaseOptions, File, NameOptions, Options, RemoveOptions, ReplaceOptions,
+    };
+    use crate::tester::temp_folder;
+
+    #[test]
+    fn rules_handle_every_fixture() {
+        let dir = temp_folder("fixtures_test");
+        let paths = generate(&dir).unwrap();
+        let options = [
+            Options::Name(NameOptions::Reverse),
+            Options::Replace(ReplaceOptions {
+                replace: "_".into(),
+                with: " ".into(),
+                case: false,
+            }),
+            Options::Case(CaseOptions {
+                case: Case::Title,
+                ..Default::default()
+            }),
+            Options::Remove(RemoveOptions {
+                first_n: 1,
+                trim: true,
+                double_space: true,
+                ..Default::default()
+            }),
+            Options::Add(AddOptions {
+                prefix: Some("é-".into()),
+                ..Default::default()
+            }),
+        ];
+        let renamed = paths
+            .iter()
+            .map(|path| {
+                let mut file = File::new(path).unwrap();
+                for opt in &options {
+                    file = file.with_option(opt.clone());
+                }
+                file.preview()
+            })
+            .collect::<Vec<_>>();
+        let exists = paths.iter().all(|path| path.exists());
+        fs::remove_dir_all(&dir).ok();
+        assert!(exists);
+        assert_eq!(renamed.len(), NAMES.len() + 1);
+        assert!(renamed
+            .iter()
+            .all(|path| path.starts_with(dir.join(SANDBOX))));
+    }
+}
@@ -0,0 +1,95 @@
+//! Folders too important to rename in, such as `/` or `C:\Windows`. [crate::batch::rename]
+//! refuses to rename a protected folder, or anything directly in one, failing with
+//! [FileError::Protected] instead, unless the batch is renamed with [Guard::none].
+//!
+//! [FileError::Protected]: crate::file::FileError::Protected
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// The protected folders. The default protects the system folders and the home folder.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Guard {
+    pub paths: Vec<PathBuf>,
+}
+
+#[cfg(windows)]
+const SYSTEM: [&str; 5] = [
+    r"C:\",
+    r"C:\Windows",
+    r"C:\Program Files",
+    r"C:\Program Files (x86)",
+    r"C:\Users",
+];
+#[cfg(not(windows))]
+const SYSTEM: [&str; 17] = [
+    "/",
+    "/Applications",
+    "/Library",
+    "/System",
+    "/Users",
+    "/bin",
+    "/boot",
+    "/dev",
+    "/etc",
+    "/home",
+    "/lib",
+    "/opt",
+    "/proc",
+    "/sbin",
+    "/sys",
+    "/usr",
+    "/var",
+];
+
+impl Default for Guard {
+    fn default() -> Self {
+        let mut paths = SYSTEM.map(PathBuf::from).to_vec();
+        paths.extend(home::home_dir());
+        Self { paths }
+    }
+}
+
+impl Guard {
+    /// Protect nothing, to rename in protected folders on purpose.
+    pub fn none() -> Self {
+        Self { paths: vec![] }
+    }
+
+    /// Whether renaming the file, or renaming a file to it, touches a protected folder.
+    ///
+    /// ```
+    /// # use std::path::Path;
+    /// # use mass_renamer_core::guard::Guard;
+    /// let guard = Guard { paths: vec![std::env::temp_dir()] };
+    /// assert!(guard.protects(&std::env::temp_dir().join("file.txt")));
+    /// assert!(!guard.protects(&std::env::temp_dir().join("folder").join("file.txt")));
+    /// assert!(!Guard::none().protects(Path::new("/")));
+    /// ```
+    pub fn protects(&self, path: &Path) -> bool {
+        if self.paths.is_empty() {
+            return false;
+        }
+        let path = canonical(path);
+        let folder = path.parent().map(canonical);
+        self.paths
+            .iter()
+            .map(|protected| canonical(protected))
+            .any(|protected| protected == path || Some(&protected) == folder.as_ref())
+    }
+}
+
+/// The path with links resolved, as far as it exists.
+fn canonical(path: &Path) -> PathBuf {
+    match (fs::canonicalize(path), path.parent(), path.file_name()) {
+        (Ok(path), _, _) => path,
+        (Err(_), Some(parent), Some(name)) => canonical(parent).join(name),
+        _ => path.to_path_buf(),
+    }
+}
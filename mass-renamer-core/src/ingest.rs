@@ -0,0 +1,308 @@
+//! Copy photos off a memory card in one go: every file under the card is copied to the
+//! destination under its new name, a SHA-256 checksum manifest is written beside the
+//! copies and the copies are checked against it. The card itself is never changed.
+//!
+//! Each file gets a `{taken}` token, when the photo was taken (EXIF `DateTimeOriginal`)
+//! or else when the file was last modified, so the Name and Add text can use it, as well
+//! as the `{orientation}` of images.
+
+use std::{
+    fs,
+    io::{self, BufRead, Write},
+    path::{Path, PathBuf},
+};
+
+use sha2::{Digest, Sha256};
+use thiserror::Error;
+
+use crate::{
+    batch::{self, Throttle},
+    directory,
+    file::{File, Options, Tokens},
+    metadata::{Camera, CaptureTime, Dimensions, Extractor, FileTimes, Metadata},
+};
+
+/// The manifest written to the destination, in the format read by `sha256sum -c`.
+pub const MANIFEST: &str = "manifest.sha256";
+/// How the `{taken}` token is formatted, e.g. `20231224_181500`.
+pub const TAKEN_FORMAT: &str = "%Y%m%d_%H%M%S";
+
+#[derive(Debug, Error)]
+pub enum IngestError {
+    #[error(transparent)]
+    Io(#[from] io::Error),
+    #[error("{0} already exists.")]
+    Exists(PathBuf),
+    #[error("The copy of {0} doesn't match the original.")]
+    Mismatch(PathBuf),
+    #[error("More than one file would be copied to {0}, nothing was copied.")]
+    Duplicate(PathBuf),
+}
+
+/// A file copied by [copy], with the checksum of its contents.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Copied {
+    pub original: PathBuf,
+    pub copy: PathBuf,
+    pub checksum: String,
+}
+
+/// The outcome of an [ingest].
+/// - `copied` - The files copied, and checked, successfully.
+/// - `failed` - The files that couldn't be copied, with the reason.
+/// - `unverified` - Files in the manifest that are missing or no longer match it.
+#[derive(Debug, Default)]
+pub struct Report {
+    pub copied: Vec<Copied>,
+    pub failed: Vec<(PathBuf, IngestError)>,
+    pub unverified: Vec<PathBuf>,
+}
+
+/// Copy everything under `source` to `destination`, renamed with the options, then write
+/// the checksum manifest and verify the copies against it.
+pub fn ingest<P: AsRef<Path>, Q: AsRef<Path>>(
+    source: P,
+    destination: Q,
+    options: &[Options],
+    throttle: Throttle,
+) -> Result<Report, IngestError> {
+    let destination = destination.as_ref();
+    fs::create_dir_all(destination)?;
+    let copies = plan(source, destination, options)?;
+    let mut report = Report::default();
+    for ((original, _), result) in copies.iter().zip(copy(&copies, throttle)) {
+        match result {
+            Ok(copied) => report.copied.push(copied),
+            Err(err) => report.failed.push((original.clone(), err)),
+        }
+    }
+    let manifest = write_manifest(destination, &report.copied)?;
+    report.unverified = verify(manifest)?;
+    Ok(report)
+}
+
+/// Where each file under `source` is copied to, as `(original, copy)` pairs. The files
+/// are previewed together, as a [batch], and all copied into `destination` itself.
+/// Fails if the new names give files of different folders of the card the same name, as
+/// only one of them could be copied.
+pub fn plan<P: AsRef<Path>, Q: AsRef<Path>>(
+    source: P,
+    destination: Q,
+    options: &[Options],
+) -> Result<Vec<(PathBuf, PathBuf)>, IngestError> {
+    let (mut originals, mut files) = (vec![], vec![]);
+    for path in walk(source.as_ref())? {
+        let Ok(file) = File::new(&path) else {
+            continue;
+        };
+        let mut metadata = Metadata::default();
+        FileTimes.extract(&path, &mut metadata).ok();
+        CaptureTime.extract(&path, &mut metadata).ok();
+        Dimensions.extract(&path, &mut metadata).ok();
+        Camera.extract(&path, &mut metadata).ok();
+        let mut tokens = Tokens::new();
+        if let Some(taken) = metadata.captured.or(metadata.modified) {
+            tokens.insert("taken".into(), taken.format(TAKEN_FORMAT).to_string());
+        }
+        files.push(file.with_metadata(metadata).with_tokens(tokens));
+        originals.push(path);
+    }
+    batch::preview(&mut files, options);
+    let copies = originals
+        .into_iter()
+        .zip(&files)
+        .map(|(original, file)| {
+            let name = file.new_path();
+            let copy = destination
+                .as_ref()
+                .join(name.file_name().unwrap_or_default());
+            (original, copy)
+        })
+        .collect::<Vec<_>>();
+    // Files already in the destination are left to [copy], which fails just those.
+    let duplicate = batch::collisions(&copies)
+        .into_iter()
+        .find(|collision| collision.other.is_some());
+    match duplicate {
+        Some(collision) => Err(IngestError::Duplicate(collision.new)),
+        None => Ok(copies),
+    }
+}
+
+/// Every file under `dir`, in name order. Links aren't followed, see [directory::walk].
+fn walk(dir: &Path) -> Result<Vec<PathBuf>, io::Error> {
+    let mut files = directory::walk(dir, false)?
+        .into_iter()
+        .flat_map(|folder| folder.files)
+        .collect::<Vec<_>>();
+    files.sort();
+    Ok(files)
+}
+
+/// Copy the files, given as `(original, copy)` pairs, keeping to the [Throttle].
+/// Existing files are never overwritten. Each copy is checked against its original and
+/// removed again if it doesn't match, or if copying or checking it fails partway.
+pub fn copy<P: AsRef<Path>, Q: AsRef<Path>>(
+    copies: &[(P, Q)],
+    throttle: Throttle,
+) -> Vec<Result<Copied, IngestError>> {
+    let mut pace = throttle.pace();
+    copies
+        .iter()
+        .map(|(from, to)| {
+            let (from, to) = (from.as_ref(), to.as_ref());
+            if to.symlink_metadata().is_ok() {
+                return Err(IngestError::Exists(to.to_path_buf()));
+            }
+            pace.wait();
+            let checksums = fs::copy(from, to).and_then(|_| Ok((checksum(from)?, checksum(to)?)));
+            let (checksum, copied) = match checksums {
+                Ok(checksums) => checksums,
+                Err(err) => {
+                    fs::remove_file(to).ok();
+                    return Err(err.into());
+                }
+            };
+            if checksum != copied {
+                fs::remove_file(to).ok();
+                return Err(IngestError::Mismatch(from.to_path_buf()));
+            }
+            Ok(Copied {
+                original: from.to_path_buf(),
+                copy: to.to_path_buf(),
+                checksum,
+            })
+        })
+        .collect()
+}
+
+/// The SHA-256 of the file's contents, in lower case hex.
+pub fn checksum<P: AsRef<Path>>(path: P) -> Result<String, io::Error> {
+    let mut hasher = Sha256::new();
+    io::copy(&mut fs::File::open(path)?, &mut hasher)?;
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+/// Add the copies to the [MANIFEST] in `destination`, returning its path.
+/// Earlier ingests into the same folder are kept.
+pub fn write_manifest<P: AsRef<Path>>(
+    destination: P,
+    copied: &[Copied],
+) -> Result<PathBuf, io::Error> {
+    let path = destination.as_ref().join(MANIFEST);
+    let mut manifest = fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)?;
+    for file in copied {
+        let name = file.copy.file_name().unwrap_or_default().to_string_lossy();
+        writeln!(manifest, "{}  {name}", file.checksum)?;
+    }
+    Ok(path)
+}
+
+/// Check the files listed in a manifest, returning those missing or no longer matching.
+pub fn verify<P: AsRef<Path>>(manifest: P) -> Result<Vec<PathBuf>, io::Error> {
+    let manifest = manifest.as_ref();
+    let folder = manifest.parent().unwrap_or(Path::new(""));
+    let mut unverified = vec![];
+    for line in io::BufReader::new(fs::File::open(manifest)?).lines() {
+        let line = line?;
+        let Some((expected, name)) = line.split_once("  ") else {
+            continue;
+        };
+        let path = folder.join(name);
+        if checksum(&path).ok().as_deref() != Some(expected) {
+            unverified.push(path);
+        }
+    }
+    Ok(unverified)
+}
+
+#[cfg(test)]
+mod ingest_tests {
+    use super::*;
+    use crate::file::{NameOptions, NumberMode, NumberOptions, ReplaceOptions};
+    use crate::tester::temp_folder;
+
+    fn card(name: &str) -> (PathBuf, PathBuf) {
+        let root = temp_folder(name);
+        let source = root.join("DCIM").join("100CANON");
+        fs::create_dir_all(&source).unwrap();
+        for file in ["IMG_0001.JPG", "IMG_0002.JPG"] {
+            fs::write(source.join(file), file).unwrap();
+        }
+        (root.join("DCIM"), root.join("photos"))
+    }
+
+    #[test]
+    fn copies_renames_and_verifies() {
+        let (source, destination) = card("ingest_card");
+        let replace = ReplaceOptions {
+            replace: "IMG".into(),
+            with: "trip".into(),
+            case: true,
+        };
+        let report = ingest(
+            &source,
+            &destination,
+            &[Options::Replace(replace)],
+            Throttle::default(),
+        )
+        .unwrap();
+        let manifest = fs::read_to_string(destination.join(MANIFEST)).unwrap();
+        let originals = walk(&source).unwrap().len();
+        fs::write(destination.join("trip_0002.JPG"), "changed").unwrap();
+        let changed = verify(destination.join(MANIFEST)).unwrap();
+        fs::remove_dir_all(source.parent().unwrap()).ok();
+        assert!(report.failed.is_empty());
+        assert!(report.unverified.is_empty());
+        assert_eq!(originals, 2);
+        assert_eq!(
+            manifest,
+            format!(
+                "{}  trip_0001.JPG\n{}  trip_0002.JPG\n",
+                report.copied[0].checksum, report.copied[1].checksum
+            )
+        );
+        assert_eq!(changed, [destination.join("trip_0002.JPG")]);
+    }
+
+    #[test]
+    fn taken_token_and_no_overwrites() {
+        let (source, destination) = card("ingest_taken");
+        fs::create_dir_all(&destination).unwrap();
+        // The photos are taken the same second, numbering tells them apart.
+        let name = NameOptions::Fixed("{taken}".into());
+        let number = NumberOptions {
+            mode: NumberMode::Suffix,
+            sep: "_".into(),
+            ..Default::default()
+        };
+        let options = [Options::Name(name), Options::Number(number)];
+        let copies = plan(&source, &destination, &options).unwrap();
+        let collide = [(&copies[0].0, &copies[0].1), (&copies[1].0, &copies[0].1)];
+        let results = copy(&collide, Throttle::default());
+        fs::remove_dir_all(source.parent().unwrap()).ok();
+        let name = copies[0].1.file_stem().unwrap().to_string_lossy();
+        assert_eq!(name.len(), "20231224_181500_0".len());
+        assert!(results[0].is_ok());
+        assert!(matches!(results[1], Err(IngestError::Exists(_))));
+    }
+
+    #[test]
+    fn same_names_in_two_folders_refused() {
+        let (source, destination) = card("ingest_folders");
+        let second = source.join("101CANON");
+        fs::create_dir_all(&second).unwrap();
+        fs::write(second.join("IMG_0001.JPG"), "second").unwrap();
+        let result = ingest(&source, &destination, &[], Throttle::default());
+        let copied = fs::read_dir(&destination).unwrap().count();
+        fs::remove_dir_all(source.parent().unwrap()).ok();
+        let Err(IngestError::Duplicate(name)) = result else {
+            panic!("expected a duplicate, got {result:?}");
+        };
+        assert_eq!(name, destination.join("IMG_0001.JPG"));
+        assert_eq!(copied, 0);
+    }
+}
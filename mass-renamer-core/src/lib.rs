@@ -0,0 +1,174 @@
+//! The processing core of Mass Renamer, without any GUI dependencies.
+//!
+//! Build a [File] from a path, add processors with [File::with_option] and call
+//! [File::preview] to get the new name, or [File::rename] to apply it.
+//! The [analysis] module checks a batch of new names before renaming.
+//!
+//! ```
+//! # use std::path::{Path, PathBuf};
+//! # use mass_renamer_core::file::{File, Options, ReplaceOptions};
+//! let replace = ReplaceOptions { replace: "_".into(), with: " ".into(), case: true };
+//! let mut file = File::new(Path::new("my_holiday.jpg"))
+//!     .unwrap()
+//!     .with_option(Options::Replace(replace));
+//! assert_eq!(file.preview(), PathBuf::from("my holiday.jpg"));
+//! ```
+//!
+//! # Stability
+//!
+//! This crate follows semantic versioning. Everything public is covered, except that
+//! [file::Options] is `#[non_exhaustive]` so new processors can be added in minor releases.
+
+use std::{
+    collections::HashSet,
+    ffi::OsStr,
+    fs::canonicalize,
+    path::{Path, PathBuf},
+};
+
+pub mod analysis;
+#[cfg(feature = "zip")]
+pub mod archive;
+pub mod batch;
+pub mod directory;
+pub mod file;
+pub mod fixtures;
+pub mod guard;
+#[cfg(feature = "ingest")]
+pub mod ingest;
+#[cfg(feature = "mapping")]
+pub mod mapping;
+#[cfg(feature = "mapping")]
+pub mod merge;
+pub mod metadata;
+pub mod mirror;
+pub mod original;
+pub mod persist;
+pub mod playlist;
+pub mod policy;
+pub mod priority;
+pub mod references;
+#[cfg(feature = "report")]
+pub mod report;
+pub mod rotate;
+pub mod script;
+pub mod snapshot;
+pub mod sort;
+#[cfg(feature = "watch")]
+pub mod watch;
+
+pub use directory::Directory;
+pub use file::File;
+
+/// The files picked for renaming, kept by canonical path so sorting, filtering or
+/// re-reading a folder never loses them.
+#[derive(Debug, Default, Clone)]
+pub struct Selected {
+    selected: HashSet<PathBuf>,
+}
+
+impl Selected {
+    pub fn clear(&mut self) {
+        self.selected.clear()
+    }
+
+    pub fn add(&mut self, file: PathBuf) {
+        self.selected.insert(canonical(&file));
+    }
+
+    pub fn remove(&mut self, file: &Path) {
+        self.selected.remove(&canonical(file));
+    }
+
+    /// Add or remove the file.
+    pub fn set(&mut self, file: &Path, selected: bool) {
+        if selected {
+            self.add(file.to_path_buf())
+        } else {
+            self.remove(file)
+        }
+    }
+
+    pub fn contains(&self, file: &Path) -> bool {
+        self.selected.contains(&canonical(file))
+    }
+
+    pub fn len(&self) -> usize {
+        self.selected.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.selected.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Path> {
+        self.selected.iter().map(PathBuf::as_path)
+    }
+}
+
+/// The canonical form of a path, or the path itself if it can't be resolved,
+/// e.g. because the file was removed.
+fn canonical(path: &Path) -> PathBuf {
+    canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
+}
+
+#[derive(Debug)]
+enum PathString {
+    Valid(String),
+    Invalid(String),
+}
+/// Convert a Path to a mutable string
+fn generate_path_as_string(part: Option<&OsStr>) -> Option<PathString> {
+    part.map(|path| match path.to_str() {
+        Some(s) => PathString::Valid(s.into()),
+        None => PathString::Invalid(path.to_string_lossy().into_owned()),
+    })
+}
+
+#[cfg(test)]
+pub(crate) mod tester {
+    use std::{env, fs, panic, path::PathBuf, process};
+
+    /// A fresh, empty folder for the test called `name`, unique to this run so tests
+    /// running at the same time, or left over from another run, don't get in the way.
+    pub(crate) fn temp_folder(name: &str) -> PathBuf {
+        let dir = env::temp_dir().join(format!("mass_renamer_{name}_{}", process::id()));
+        fs::remove_dir_all(&dir).ok();
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[allow(unused_must_use)]
+    pub(crate) fn run_test<T>(files: &Vec<&str>, test: T)
+    where
+        T: FnOnce() + panic::UnwindSafe,
+    {
+        for file in files {
+            fs::File::create(file);
+        }
+        let result = panic::catch_unwind(test);
+        for file in files {
+            fs::remove_file(file);
+        }
+        assert!(result.is_ok())
+    }
+}
+
+#[cfg(test)]
+mod selected_tests {
+    use super::*;
+    use crate::tester::run_test;
+
+    #[test]
+    fn keyed_by_canonical_path() {
+        run_test(&vec!["selected_a.txt"], || {
+            let mut selected = Selected::default();
+            selected.add(PathBuf::from("selected_a.txt"));
+            let absolute = std::env::current_dir().unwrap().join("selected_a.txt");
+            assert!(selected.contains(&absolute));
+            assert!(selected.contains(Path::new("./selected_a.txt")));
+            selected.set(&absolute, false);
+            assert!(selected.is_empty());
+        })
+    }
+}
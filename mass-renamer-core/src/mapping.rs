@@ -0,0 +1,168 @@
+//! Rename from a table of `old_name,new_name` pairs, e.g. a spreadsheet of curated names
+//! exported as CSV, instead of the rules. Each row names a file of one folder and the
+//! name it is given, extension included.
+
+use std::{
+    collections::HashSet,
+    fmt::Display,
+    fs,
+    path::{Component, Path, PathBuf},
+};
+
+use crate::batch::{self, Collision};
+
+/// Why a row of a mapping can't be used.
+/// - `Missing` - There is no file of that name in the folder.
+/// - `Repeated` - An earlier row already renames the file.
+/// - `Invalid` - The new name is empty or has a folder in it.
+/// - `Collision` - The new name is taken, see [batch::collisions].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MappingError {
+    Missing(String),
+    Repeated(String),
+    Invalid(String),
+    Collision(Collision),
+}
+
+impl Display for MappingError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MappingError::Missing(name) => write!(f, "{name}: no such file"),
+            MappingError::Repeated(name) => write!(f, "{name}: renamed by an earlier row"),
+            MappingError::Invalid(name) => write!(f, "{name}: not a file name"),
+            MappingError::Collision(collision) => write!(f, "{collision}"),
+        }
+    }
+}
+
+/// What a mapping does to a folder.
+/// - `renames` - The `(from, to)` paths of the rows that can be used.
+/// - `errors` - The rows that can't, left out of `renames`.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct MappingPlan {
+    pub renames: Vec<(PathBuf, PathBuf)>,
+    pub errors: Vec<MappingError>,
+}
+
+/// Read the `(old_name, new_name)` pairs from a CSV file, or a TSV file when it has a
+/// `.tsv` extension. There is no header row and columns after the second are ignored.
+pub fn read<P: AsRef<Path>>(path: P) -> Result<Vec<(String, String)>, csv::Error> {
+    let mut reader = table(path.as_ref(), false)?;
+    let mut pairs = vec![];
+    for record in reader.records() {
+        let record = record?;
+        if let (Some(old), Some(new)) = (record.get(0), record.get(1)) {
+            pairs.push((old.to_string(), new.to_string()));
+        }
+    }
+    Ok(pairs)
+}
+
+/// A reader of a CSV file, or a TSV file when it has a `.tsv` extension.
+pub(crate) fn table(path: &Path, has_headers: bool) -> Result<csv::Reader<fs::File>, csv::Error> {
+    let delimiter = match path.extension().and_then(|ext| ext.to_str()) {
+        Some(ext) if ext.eq_ignore_ascii_case("tsv") => b'\t',
+        _ => b',',
+    };
+    csv::ReaderBuilder::new()
+        .delimiter(delimiter)
+        .has_headers(has_headers)
+        .flexible(true)
+        .trim(csv::Trim::All)
+        .from_path(path)
+}
+
+/// Check the pairs against the files in `folder`. Rows naming a missing file, repeating
+/// one, or giving a file a name another file has or is given are left out, so the
+/// renames can be passed to [batch::rename] as they are.
+///
+/// ```
+/// # use mass_renamer_core::mapping::{plan, MappingError};
+/// let dir = std::env::temp_dir().join(format!("mapping_doc_{}", std::process::id()));
+/// std::fs::create_dir_all(&dir).unwrap();
+/// std::fs::write(dir.join("IMG_1.jpg"), "").unwrap();
+/// let pairs = [("IMG_1.jpg", "beach.jpg"), ("IMG_2.jpg", "sunset.jpg")]
+///     .map(|(old, new)| (old.to_string(), new.to_string()));
+/// let plan = plan(&dir, &pairs);
+/// std::fs::remove_dir_all(&dir).ok();
+/// assert_eq!(plan.renames, [(dir.join("IMG_1.jpg"), dir.join("beach.jpg"))]);
+/// assert_eq!(plan.errors, [MappingError::Missing("IMG_2.jpg".into())]);
+/// ```
+pub fn plan(folder: &Path, pairs: &[(String, String)]) -> MappingPlan {
+    let mut plan = MappingPlan::default();
+    let mut seen = HashSet::new();
+    for (old, new) in pairs {
+        let from = folder.join(old);
+        if !is_name(old) || !from.is_file() {
+            plan.errors.push(MappingError::Missing(old.clone()));
+        } else if !seen.insert(old) {
+            plan.errors.push(MappingError::Repeated(old.clone()));
+        } else if !is_name(new) {
+            plan.errors.push(MappingError::Invalid(new.clone()));
+        } else {
+            plan.renames.push((from, folder.join(new)));
+        }
+    }
+    let collisions = batch::collisions(&plan.renames);
+    let collided = collisions
+        .iter()
+        .map(|collision| collision.original.clone())
+        .collect::<HashSet<_>>();
+    plan.renames.retain(|(from, _)| !collided.contains(from));
+    plan.errors
+        .extend(collisions.into_iter().map(MappingError::Collision));
+    plan
+}
+
+/// Whether the text is the name of a file directly in a folder.
+fn is_name(text: &str) -> bool {
+    let mut parts = Path::new(text).components();
+    matches!(
+        (parts.next(), parts.next()),
+        (Some(Component::Normal(_)), None)
+    )
+}
+
+#[cfg(test)]
+mod mapping_tests {
+    use super::*;
+    use crate::tester::temp_folder;
+
+    #[test]
+    fn read_and_check() {
+        let dir = temp_folder("mapping");
+        for name in ["a.txt", "b.txt", "c.txt", "keep.txt"] {
+            fs::write(dir.join(name), name).unwrap();
+        }
+        let table = dir.join("names.tsv");
+        fs::write(
+            &table,
+            "a.txt\tb.txt\nb.txt\ta.txt\nc.txt\tkeep.txt\na.txt\tz.txt\nx.txt\ty.txt\nb.txt\n",
+        )
+        .unwrap();
+        let pairs = read(&table);
+        let folder = plan(&dir, &[("c.txt".into(), "../c.txt".into())]);
+        let plan = pairs.as_ref().map(|pairs| plan(&dir, pairs));
+        fs::remove_dir_all(&dir).ok();
+        let plan = plan.unwrap();
+        assert_eq!(pairs.unwrap().len(), 5);
+        assert_eq!(
+            plan.renames,
+            [("a.txt", "b.txt"), ("b.txt", "a.txt")]
+                .map(|(from, to)| (dir.join(from), dir.join(to)))
+        );
+        assert_eq!(
+            plan.errors,
+            [
+                MappingError::Repeated("a.txt".into()),
+                MappingError::Missing("x.txt".into()),
+                MappingError::Collision(Collision {
+                    original: dir.join("c.txt"),
+                    new: dir.join("keep.txt"),
+                    other: None,
+                }),
+            ]
+        );
+        assert_eq!(folder.errors, [MappingError::Invalid("../c.txt".into())]);
+    }
+}
@@ -0,0 +1,98 @@
+//! Give files names made row by row from a template and a table, as a mail merge does.
+//! The template holds `{column}` placeholders for the table's columns and `{seq}` for the
+//! row number, so `2024_Q1_Region-{region}_{seq}` with a `region` column names the first
+//! file `2024_Q1_Region-North_1`, the second from the next row, and so on.
+
+use std::{fs, io, path::Path};
+
+use thiserror::Error;
+
+use crate::{
+    file::{expand_tokens, Tokens},
+    mapping,
+};
+
+#[derive(Debug, Error)]
+pub enum MergeError {
+    #[error(transparent)]
+    Io(#[from] io::Error),
+    #[error(transparent)]
+    Csv(#[from] csv::Error),
+}
+
+/// A template and the rows it is filled in from.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Merge {
+    pub template: String,
+    pub columns: Vec<String>,
+    pub rows: Vec<Vec<String>>,
+}
+
+impl Merge {
+    /// Read the template from a text file, its first line that isn't blank, and the rows
+    /// from a CSV file, or a TSV file when it has a `.tsv` extension, whose first row
+    /// names the columns.
+    pub fn read<P: AsRef<Path>, Q: AsRef<Path>>(template: P, table: Q) -> Result<Self, MergeError> {
+        let template = fs::read_to_string(template)?
+            .lines()
+            .map(str::trim)
+            .find(|line| !line.is_empty())
+            .unwrap_or_default()
+            .to_string();
+        let mut reader = mapping::table(table.as_ref(), true)?;
+        let columns = reader.headers()?.iter().map(String::from).collect();
+        let rows = reader
+            .records()
+            .map(|record| Ok(record?.iter().map(String::from).collect()))
+            .collect::<Result<_, csv::Error>>()?;
+        Ok(Self {
+            template,
+            columns,
+            rows,
+        })
+    }
+
+    /// The name made from each row, in table order. Missing cells are left empty.
+    ///
+    /// ```
+    /// # use mass_renamer_core::merge::Merge;
+    /// let merge = Merge {
+    ///     template: "2024_Q1_Region-{region}_{seq}".into(),
+    ///     columns: vec!["region".into()],
+    ///     rows: vec![vec!["North".into()], vec!["South".into()]],
+    /// };
+    /// assert_eq!(merge.names(), ["2024_Q1_Region-North_1", "2024_Q1_Region-South_2"]);
+    /// ```
+    pub fn names(&self) -> Vec<String> {
+        self.rows
+            .iter()
+            .enumerate()
+            .map(|(idx, row)| {
+                let mut tokens = self
+                    .columns
+                    .iter()
+                    .enumerate()
+                    .map(|(col, name)| (name.clone(), row.get(col).cloned().unwrap_or_default()))
+                    .collect::<Tokens>();
+                tokens.insert("seq".into(), (idx + 1).to_string());
+                expand_tokens(&self.template, &tokens)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod merge_tests {
+    use super::*;
+    use crate::tester::temp_folder;
+
+    #[test]
+    fn read_template_and_table() {
+        let dir = temp_folder("merge");
+        fs::write(dir.join("template.txt"), "\n  {client} - {job} ({seq})\n").unwrap();
+        fs::write(dir.join("jobs.tsv"), "client\tjob\nAcme\tLogo\nGlobex\n").unwrap();
+        let merge = Merge::read(dir.join("template.txt"), dir.join("jobs.tsv"));
+        fs::remove_dir_all(&dir).ok();
+        assert_eq!(merge.unwrap().names(), ["Acme - Logo (1)", "Globex -  (2)"]);
+    }
+}
@@ -0,0 +1,639 @@
+use std::{
+    collections::HashMap,
+    fs, io,
+    panic::{self, AssertUnwindSafe},
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        mpsc::{self, Receiver, RecvTimeoutError, Sender},
+        Arc, Mutex,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use chrono::{DateTime, Local};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::file::Tokens;
+
+#[cfg(feature = "serde")]
+pub mod cache;
+
+/// Default number of files read at the same time.
+pub const DEFAULT_WORKERS: usize = 4;
+/// Default time allowed for reading a single file's metadata.
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(10);
+/// Most files still being read after timing out. Past it, files are read on the worker.
+const MAX_STRAGGLERS: usize = 4;
+
+/// Facts about a file used by the metadata based processors (e.g. Auto Date).
+/// Each [Extractor] fills in the parts it knows about, anything left as `None`
+/// is skipped by the processors.
+#[derive(Debug, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Metadata {
+    pub size: Option<u64>,
+    pub modified: Option<DateTime<Local>>,
+    pub created: Option<DateTime<Local>>,
+    /// When the content was captured, see [CaptureTime].
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub captured: Option<DateTime<Local>>,
+    /// The width and height of an image as shown, see [Dimensions].
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub dimensions: Option<(u32, u32)>,
+    /// The model of camera a photo was taken with, see [Camera].
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub camera: Option<String>,
+    /// The ISO speed a photo was taken at, see [Camera].
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub iso: Option<u32>,
+}
+
+/// The `{tokens}` filled in from the metadata, see [Metadata::tokens].
+pub const TOKENS: [&str; 6] = [
+    "exif:date",
+    "camera",
+    "iso",
+    "width",
+    "height",
+    "orientation",
+];
+
+/// How the `{exif:date}` token is formatted, e.g. `20231224_181500`.
+pub const DATE_TOKEN_FORMAT: &str = "%Y%m%d_%H%M%S";
+
+impl Metadata {
+    /// `portrait`, `landscape` or `square`, from the image's dimensions.
+    pub fn orientation(&self) -> Option<&'static str> {
+        let (width, height) = self.dimensions?;
+        Some(match width.cmp(&height) {
+            std::cmp::Ordering::Less => "portrait",
+            std::cmp::Ordering::Equal => "square",
+            std::cmp::Ordering::Greater => "landscape",
+        })
+    }
+
+    /// The [TOKENS] known for the file, for the Name and Add text. Unknown ones are
+    /// left out, so they stay in the new name as typed.
+    ///
+    /// ```
+    /// # use mass_renamer_core::metadata::Metadata;
+    /// let metadata = Metadata { camera: Some("X100V".into()), dimensions: Some((6000, 4000)), ..Default::default() };
+    /// let tokens = metadata.tokens();
+    /// assert_eq!(tokens["camera"], "X100V");
+    /// assert_eq!(tokens["width"], "6000");
+    /// assert_eq!(tokens["orientation"], "landscape");
+    /// assert!(!tokens.contains_key("iso"));
+    /// ```
+    pub fn tokens(&self) -> Tokens {
+        let mut tokens = Tokens::new();
+        let mut add = |name: &str, value: Option<String>| {
+            if let Some(value) = value {
+                tokens.insert(name.into(), value);
+            }
+        };
+        add(
+            "exif:date",
+            self.captured
+                .map(|date| date.format(DATE_TOKEN_FORMAT).to_string()),
+        );
+        add("camera", self.camera.clone());
+        add("iso", self.iso.map(|iso| iso.to_string()));
+        add("width", self.dimensions.map(|(width, _)| width.to_string()));
+        add(
+            "height",
+            self.dimensions.map(|(_, height)| height.to_string()),
+        );
+        add("orientation", self.orientation().map(String::from));
+        tokens
+    }
+}
+
+/// Reads some metadata for a file, e.g. from the file system, EXIF tags or audio tags.
+pub trait Extractor: Send + Sync {
+    fn extract(&self, path: &Path, metadata: &mut Metadata) -> Result<(), MetadataError>;
+}
+
+/// The size and times reported by the file system.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct FileTimes;
+
+impl Extractor for FileTimes {
+    fn extract(&self, path: &Path, metadata: &mut Metadata) -> Result<(), MetadataError> {
+        let data = fs::metadata(path)?;
+        if data.is_file() {
+            metadata.size = Some(data.len());
+        }
+        metadata.modified = data.modified().ok().map(DateTime::from);
+        metadata.created = data.created().ok().map(DateTime::from);
+        Ok(())
+    }
+}
+
+/// When a photo was taken (EXIF `DateTimeOriginal`) or a song recorded (ID3 recording
+/// date or year), which survives copying files unlike the file system dates.
+/// Files without either are left alone rather than failing, as are all files without
+/// the `exif` and `id3` features.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct CaptureTime;
+
+impl Extractor for CaptureTime {
+    fn extract(&self, path: &Path, metadata: &mut Metadata) -> Result<(), MetadataError> {
+        let is_mp3 = path
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("mp3"));
+        metadata.captured = if is_mp3 { recorded(path) } else { taken(path) };
+        Ok(())
+    }
+}
+
+/// The size of an image, read from a PNG header or from the EXIF tags of a photo.
+/// Photos the camera marked as rotated a quarter turn have their width and height
+/// swapped, so they are as shown. Other files, and photos without the `exif` feature,
+/// are left alone.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Dimensions;
+
+/// The first bytes of every PNG file.
+const PNG_SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+
+impl Extractor for Dimensions {
+    fn extract(&self, path: &Path, metadata: &mut Metadata) -> Result<(), MetadataError> {
+        // Folders can't be opened everywhere, and have no size anyway.
+        let Ok(mut file) = fs::File::open(path) else {
+            return Ok(());
+        };
+        let mut header = [0; 24];
+        let read = io::Read::read_exact(&mut file, &mut header);
+        metadata.dimensions =
+            if read.is_ok() && header.starts_with(PNG_SIGNATURE) && &header[12..16] == b"IHDR" {
+                let number =
+                    |at: usize| u32::from_be_bytes([0, 1, 2, 3].map(|idx| header[at + idx]));
+                Some((number(16), number(20)))
+            } else {
+                photo_size(path)
+            };
+        Ok(())
+    }
+}
+
+/// The camera model and ISO speed from the EXIF tags of a photo. Other files, and
+/// photos without the `exif` feature, are left alone.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Camera;
+
+impl Extractor for Camera {
+    fn extract(&self, path: &Path, metadata: &mut Metadata) -> Result<(), MetadataError> {
+        (metadata.camera, metadata.iso) = camera(path);
+        Ok(())
+    }
+}
+
+#[cfg(feature = "exif")]
+fn camera(path: &Path) -> (Option<String>, Option<u32>) {
+    let Some(exif) = fs::File::open(path).ok().and_then(|file| {
+        exif::Reader::new()
+            .read_from_container(&mut io::BufReader::new(file))
+            .ok()
+    }) else {
+        return (None, None);
+    };
+    let field = |tag| exif.get_field(tag, exif::In::PRIMARY);
+    let model = field(exif::Tag::Model).and_then(|field| match &field.value {
+        // Folders can't be named in a file name, so their separators go.
+        exif::Value::Ascii(text) => Some(
+            String::from_utf8_lossy(text.first()?)
+                .trim_matches(['\0', ' '])
+                .replace(['/', '\\'], "-"),
+        ),
+        _ => None,
+    });
+    let iso = field(exif::Tag::PhotographicSensitivity).and_then(|field| field.value.get_uint(0));
+    (model.filter(|model| !model.is_empty()), iso)
+}
+
+#[cfg(not(feature = "exif"))]
+fn camera(_path: &Path) -> (Option<String>, Option<u32>) {
+    (None, None)
+}
+
+#[cfg(feature = "exif")]
+fn photo_size(path: &Path) -> Option<(u32, u32)> {
+    let mut reader = io::BufReader::new(fs::File::open(path).ok()?);
+    let exif = exif::Reader::new().read_from_container(&mut reader).ok()?;
+    let number = |tag| {
+        exif.get_field(tag, exif::In::PRIMARY)
+            .and_then(|field| field.value.get_uint(0))
+    };
+    let width = number(exif::Tag::PixelXDimension)?;
+    let height = number(exif::Tag::PixelYDimension)?;
+    // Orientations 5 to 8 are rotated a quarter turn.
+    match number(exif::Tag::Orientation) {
+        Some(5..=8) => Some((height, width)),
+        _ => Some((width, height)),
+    }
+}
+
+#[cfg(not(feature = "exif"))]
+fn photo_size(_path: &Path) -> Option<(u32, u32)> {
+    None
+}
+
+/// EXIF dates have no time zone, they are the camera's local time.
+#[cfg(feature = "exif")]
+fn taken(path: &Path) -> Option<DateTime<Local>> {
+    let mut reader = io::BufReader::new(fs::File::open(path).ok()?);
+    let exif = exif::Reader::new().read_from_container(&mut reader).ok()?;
+    let field = exif.get_field(exif::Tag::DateTimeOriginal, exif::In::PRIMARY)?;
+    let exif::Value::Ascii(text) = &field.value else {
+        return None;
+    };
+    let date = exif::DateTime::from_ascii(text.first()?).ok()?;
+    local(
+        date.year.into(),
+        date.month,
+        date.day,
+        (date.hour, date.minute, date.second),
+    )
+}
+
+#[cfg(not(feature = "exif"))]
+fn taken(_path: &Path) -> Option<DateTime<Local>> {
+    None
+}
+
+#[cfg(feature = "id3")]
+fn recorded(path: &Path) -> Option<DateTime<Local>> {
+    use id3::TagLike;
+    let tag = id3::Tag::read_from_path(path).ok()?;
+    match tag.date_recorded() {
+        Some(date) => local(
+            date.year,
+            date.month.unwrap_or(1),
+            date.day.unwrap_or(1),
+            (
+                date.hour.unwrap_or(0),
+                date.minute.unwrap_or(0),
+                date.second.unwrap_or(0),
+            ),
+        ),
+        None => local(tag.year()?, 1, 1, (0, 0, 0)),
+    }
+}
+
+#[cfg(not(feature = "id3"))]
+fn recorded(_path: &Path) -> Option<DateTime<Local>> {
+    None
+}
+
+#[cfg(any(feature = "exif", feature = "id3"))]
+fn local(year: i32, month: u8, day: u8, (hour, min, sec): (u8, u8, u8)) -> Option<DateTime<Local>> {
+    chrono::NaiveDate::from_ymd_opt(year, month.into(), day.into())?
+        .and_hms_opt(hour.into(), min.into(), sec.into())?
+        .and_local_timezone(Local)
+        .earliest()
+}
+
+#[derive(Debug, Error)]
+pub enum MetadataError {
+    #[error(transparent)]
+    Io(#[from] io::Error),
+    #[error("Timed out after {0:?}")]
+    Timeout(Duration),
+    #[error("Extraction failed unexpectedly")]
+    Panicked,
+}
+
+/// The progress of a file's metadata.
+/// - `Pending` - Queued or being read.
+/// - `Ready` - Read successfully.
+/// - `Failed` - Reading failed or timed out, with the reason.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Extraction {
+    Pending,
+    Ready(Metadata),
+    Failed(String),
+}
+
+type Outcome = (PathBuf, Result<Metadata, MetadataError>);
+
+/// Reads metadata in the background, shared by every metadata based processor.
+/// At most `workers` files are read at once and each file gets `timeout` to finish.
+/// Request files with [Scheduler::request], then [Scheduler::poll] to collect the
+/// results as they arrive.
+pub struct Scheduler {
+    jobs: Sender<PathBuf>,
+    results: Receiver<Outcome>,
+    states: HashMap<PathBuf, Extraction>,
+}
+
+impl Default for Scheduler {
+    fn default() -> Self {
+        Self::new(
+            vec![
+                Arc::new(FileTimes),
+                Arc::new(CaptureTime),
+                Arc::new(Dimensions),
+                Arc::new(Camera),
+            ],
+            DEFAULT_WORKERS,
+            DEFAULT_TIMEOUT,
+        )
+    }
+}
+
+impl Scheduler {
+    pub fn new(extractors: Vec<Arc<dyn Extractor>>, workers: usize, timeout: Duration) -> Self {
+        let (jobs, queue) = mpsc::channel::<PathBuf>();
+        let (sender, results) = mpsc::channel();
+        let queue = Arc::new(Mutex::new(queue));
+        let extractors: Arc<[Arc<dyn Extractor>]> = extractors.into();
+        let running = Arc::new(AtomicUsize::new(0));
+        let limit = workers.max(1) + MAX_STRAGGLERS;
+        for _ in 0..workers.max(1) {
+            let queue = Arc::clone(&queue);
+            let sender: Sender<Outcome> = sender.clone();
+            let extractors = Arc::clone(&extractors);
+            let running = Arc::clone(&running);
+            // The queue closes when the scheduler is dropped.
+            thread::spawn(move || {
+                while let Some(path) = queue.lock().ok().and_then(|queue| queue.recv().ok()) {
+                    let result = extract(&path, &extractors, timeout, (&running, limit));
+                    if sender.send((path, result)).is_err() {
+                        break;
+                    }
+                }
+            });
+        }
+        Self {
+            jobs,
+            results,
+            states: HashMap::new(),
+        }
+    }
+
+    /// Queue a file, unless it has already been requested.
+    pub fn request(&mut self, path: &Path) {
+        if !self.states.contains_key(path) {
+            self.states.insert(path.to_path_buf(), Extraction::Pending);
+            if self.jobs.send(path.to_path_buf()).is_err() {
+                self.states.insert(
+                    path.to_path_buf(),
+                    Extraction::Failed(MetadataError::Panicked.to_string()),
+                );
+            }
+        }
+    }
+
+    /// Use metadata from elsewhere (e.g. a cache) instead of reading the file.
+    pub fn insert(&mut self, path: &Path, metadata: Metadata) {
+        self.states
+            .insert(path.to_path_buf(), Extraction::Ready(metadata));
+    }
+
+    /// Collect the results that have arrived, returning the files they are for.
+    pub fn poll(&mut self) -> Vec<PathBuf> {
+        let mut arrived = vec![];
+        while let Ok((path, result)) = self.results.try_recv() {
+            // Results for forgotten files are dropped.
+            if let Some(state) = self.states.get_mut(&path) {
+                *state = match result {
+                    Ok(metadata) => Extraction::Ready(metadata),
+                    Err(err) => Extraction::Failed(err.to_string()),
+                };
+                arrived.push(path);
+            }
+        }
+        arrived
+    }
+
+    pub fn get(&self, path: &Path) -> Option<&Extraction> {
+        self.states.get(path)
+    }
+
+    /// Number of requested files still waiting for their metadata.
+    pub fn pending(&self) -> usize {
+        self.states
+            .values()
+            .filter(|state| **state == Extraction::Pending)
+            .count()
+    }
+
+    /// Forget every result, e.g. when the folder changes.
+    pub fn clear(&mut self) {
+        self.states.clear();
+    }
+}
+
+/// Run the extractors on their own thread so a stuck file only holds up its worker
+/// until the timeout. The threads are counted in `running`, stuck ones included, and once
+/// `limit` are running the file is read on the worker instead, its result dropped if it
+/// comes too late, so stuck files can't pile up threads.
+fn extract(
+    path: &Path,
+    extractors: &Arc<[Arc<dyn Extractor>]>,
+    timeout: Duration,
+    (running, limit): (&Arc<AtomicUsize>, usize),
+) -> Result<Metadata, MetadataError> {
+    if running.fetch_add(1, Ordering::SeqCst) >= limit {
+        running.fetch_sub(1, Ordering::SeqCst);
+        let start = Instant::now();
+        let result = panic::catch_unwind(AssertUnwindSafe(|| read(path, extractors)))
+            .unwrap_or(Err(MetadataError::Panicked));
+        return match start.elapsed() > timeout {
+            true => Err(MetadataError::Timeout(timeout)),
+            false => result,
+        };
+    }
+    let (sender, receiver) = mpsc::channel();
+    let path = path.to_path_buf();
+    let extractors = Arc::clone(extractors);
+    let running = Running(Arc::clone(running));
+    thread::spawn(move || {
+        let _running = running;
+        sender.send(read(&path, &extractors)).ok();
+    });
+    match receiver.recv_timeout(timeout) {
+        Ok(result) => result,
+        Err(RecvTimeoutError::Timeout) => Err(MetadataError::Timeout(timeout)),
+        Err(RecvTimeoutError::Disconnected) => Err(MetadataError::Panicked),
+    }
+}
+
+fn read(path: &Path, extractors: &[Arc<dyn Extractor>]) -> Result<Metadata, MetadataError> {
+    let mut metadata = Metadata::default();
+    extractors
+        .iter()
+        .try_for_each(|extractor| extractor.extract(path, &mut metadata))
+        .map(|_| metadata)
+}
+
+/// Counts an extraction thread as running until it ends, panics included.
+struct Running(Arc<AtomicUsize>);
+
+impl Drop for Running {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+#[cfg(test)]
+mod metadata_tests {
+    use super::*;
+    use crate::tester::run_test;
+
+    fn wait(scheduler: &mut Scheduler) {
+        let start = Instant::now();
+        while scheduler.pending() > 0 && start.elapsed() < Duration::from_secs(5) {
+            scheduler.poll();
+            thread::sleep(Duration::from_millis(5));
+        }
+    }
+
+    struct Slow(Duration);
+
+    impl Extractor for Slow {
+        fn extract(&self, path: &Path, _metadata: &mut Metadata) -> Result<(), MetadataError> {
+            if path.ends_with("slow") {
+                thread::sleep(self.0);
+            }
+            Ok(())
+        }
+    }
+
+    struct Counter {
+        running: AtomicUsize,
+        most: AtomicUsize,
+    }
+
+    impl Extractor for Counter {
+        fn extract(&self, _path: &Path, _metadata: &mut Metadata) -> Result<(), MetadataError> {
+            let running = self.running.fetch_add(1, Ordering::SeqCst) + 1;
+            self.most.fetch_max(running, Ordering::SeqCst);
+            thread::sleep(Duration::from_millis(20));
+            self.running.fetch_sub(1, Ordering::SeqCst);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn file_times() {
+        run_test(&vec!["metadata_times.txt"], || {
+            let mut scheduler = Scheduler::default();
+            let path = Path::new("metadata_times.txt");
+            scheduler.request(path);
+            assert_eq!(scheduler.get(path), Some(&Extraction::Pending));
+            wait(&mut scheduler);
+            let Some(Extraction::Ready(metadata)) = scheduler.get(path) else {
+                panic!("{:?}", scheduler.get(path));
+            };
+            assert_eq!(metadata.size, Some(0));
+            assert!(metadata.modified.is_some());
+        })
+    }
+
+    #[test]
+    #[cfg(feature = "id3")]
+    fn capture_time_from_id3() {
+        run_test(&vec!["metadata_song.mp3"], || {
+            use id3::TagLike;
+            let path = Path::new("metadata_song.mp3");
+            let mut tag = id3::Tag::new();
+            tag.set_year(1999);
+            tag.write_to_path(path, id3::Version::Id3v24).unwrap();
+            let mut metadata = Metadata::default();
+            CaptureTime.extract(path, &mut metadata).unwrap();
+            assert_eq!(metadata.captured, local(1999, 1, 1, (0, 0, 0)));
+            // Files without tags have no capture time.
+            CaptureTime
+                .extract(Path::new("Cargo.toml"), &mut metadata)
+                .unwrap();
+            assert_eq!(metadata.captured, None);
+        })
+    }
+
+    #[test]
+    fn missing_file_fails() {
+        let mut scheduler = Scheduler::default();
+        let path = Path::new("metadata_missing.txt");
+        scheduler.request(path);
+        wait(&mut scheduler);
+        assert!(matches!(scheduler.get(path), Some(Extraction::Failed(_))));
+    }
+
+    #[test]
+    fn timeout_only_fails_slow_file() {
+        let extractor = Arc::new(Slow(Duration::from_millis(500)));
+        let mut scheduler = Scheduler::new(vec![extractor], 1, Duration::from_millis(50));
+        scheduler.request(Path::new("slow"));
+        scheduler.request(Path::new("fast"));
+        wait(&mut scheduler);
+        assert_eq!(
+            scheduler.get(Path::new("slow")),
+            Some(&Extraction::Failed(
+                MetadataError::Timeout(Duration::from_millis(50)).to_string()
+            ))
+        );
+        assert_eq!(
+            scheduler.get(Path::new("fast")),
+            Some(&Extraction::Ready(Metadata::default()))
+        );
+    }
+
+    #[test]
+    fn stuck_files_dont_pile_up_threads() {
+        let counter = Arc::new(Counter {
+            running: AtomicUsize::new(0),
+            most: AtomicUsize::new(0),
+        });
+        let mut scheduler = Scheduler::new(vec![counter.clone()], 1, Duration::from_millis(1));
+        for idx in 0..12 {
+            scheduler.request(&PathBuf::from(idx.to_string()));
+        }
+        wait(&mut scheduler);
+        assert_eq!(scheduler.pending(), 0);
+        // The worker's own thread, the stragglers, and the file read on the worker.
+        assert!(counter.most.load(Ordering::SeqCst) <= 1 + MAX_STRAGGLERS + 1);
+    }
+
+    #[test]
+    fn png_dimensions() {
+        run_test(&vec!["metadata_size.png", "metadata_size.txt"], || {
+            let mut header = PNG_SIGNATURE.to_vec();
+            header.extend([0, 0, 0, 13]);
+            header.extend(b"IHDR");
+            header.extend(600_u32.to_be_bytes());
+            header.extend(800_u32.to_be_bytes());
+            fs::write("metadata_size.png", header).unwrap();
+            let mut png = Metadata::default();
+            Dimensions
+                .extract(Path::new("metadata_size.png"), &mut png)
+                .unwrap();
+            let mut text = Metadata::default();
+            Dimensions
+                .extract(Path::new("metadata_size.txt"), &mut text)
+                .unwrap();
+            assert_eq!(png.dimensions, Some((600, 800)));
+            assert_eq!(png.orientation(), Some("portrait"));
+            assert_eq!(text.orientation(), None);
+        })
+    }
+
+    #[test]
+    fn bounded_parallelism() {
+        let counter = Arc::new(Counter {
+            running: AtomicUsize::new(0),
+            most: AtomicUsize::new(0),
+        });
+        let mut scheduler = Scheduler::new(vec![counter.clone()], 3, DEFAULT_TIMEOUT);
+        for idx in 0..12 {
+            scheduler.request(&PathBuf::from(idx.to_string()));
+        }
+        wait(&mut scheduler);
+        assert_eq!(scheduler.pending(), 0);
+        assert!(counter.most.load(Ordering::SeqCst) <= 3);
+    }
+}
@@ -0,0 +1,248 @@
+use std::{
+    collections::HashMap,
+    fs, io,
+    path::{Path, PathBuf},
+    time::UNIX_EPOCH,
+};
+
+use serde::{Deserialize, Serialize};
+
+use super::Metadata;
+use crate::persist;
+
+/// Default number of files kept in the cache.
+pub const DEFAULT_MAX_ENTRIES: usize = 100_000;
+
+/// Metadata kept on disk between sessions so re-opening a large folder doesn't read
+/// every file again. Entries are keyed by path and are only used while the file's
+/// size and modified time still match, otherwise they are dropped.
+/// Once the cache holds more than `max_entries` files the least recently used are removed,
+/// a tenth of them at a time so a full cache isn't sorted on every insert.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MetadataCache {
+    max_entries: usize,
+    entries: HashMap<PathBuf, Entry>,
+    #[serde(skip)]
+    clock: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Entry {
+    stamp: Stamp,
+    metadata: Metadata,
+    used: u64,
+}
+
+/// What a cached entry is checked against to tell if the file changed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+struct Stamp {
+    size: u64,
+    secs: u64,
+    nanos: u32,
+}
+
+impl Stamp {
+    fn read(path: &Path) -> Option<Self> {
+        let data = fs::metadata(path).ok()?;
+        let modified = data.modified().ok()?.duration_since(UNIX_EPOCH).ok()?;
+        Some(Self {
+            size: data.len(),
+            secs: modified.as_secs(),
+            nanos: modified.subsec_nanos(),
+        })
+    }
+}
+
+impl Default for MetadataCache {
+    fn default() -> Self {
+        Self::new(DEFAULT_MAX_ENTRIES)
+    }
+}
+
+impl MetadataCache {
+    pub fn new(max_entries: usize) -> Self {
+        Self {
+            max_entries,
+            entries: HashMap::new(),
+            clock: 0,
+        }
+    }
+
+    /// Read a cache saved with [MetadataCache::save]. A missing file gives an empty cache.
+    /// A damaged file, e.g. from a crash while saving, falls back to the previous save.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, io::Error> {
+        let Some(mut cache) = persist::read(path, |text| serde_json::from_str::<Self>(text))?
+        else {
+            return Ok(Self::default());
+        };
+        cache.clock = cache
+            .entries
+            .values()
+            .map(|entry| entry.used)
+            .max()
+            .unwrap_or(0);
+        Ok(cache)
+    }
+
+    /// Save the cache, see [persist::write].
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), io::Error> {
+        persist::write(path, serde_json::to_string(self)?)
+    }
+
+    /// The cached metadata, if the file hasn't changed since it was stored.
+    pub fn get(&mut self, path: &Path) -> Option<Metadata> {
+        let stamp = Stamp::read(path);
+        match self.entries.get_mut(path) {
+            Some(entry) if Some(entry.stamp) == stamp => {
+                self.clock += 1;
+                entry.used = self.clock;
+                Some(entry.metadata.clone())
+            }
+            Some(_) => {
+                self.entries.remove(path);
+                None
+            }
+            None => None,
+        }
+    }
+
+    /// Store a file's metadata, stamped with its current size and modified time.
+    pub fn insert(&mut self, path: &Path, metadata: Metadata) {
+        let Some(stamp) = Stamp::read(path) else {
+            return;
+        };
+        self.clock += 1;
+        self.entries.insert(
+            path.to_path_buf(),
+            Entry {
+                stamp,
+                metadata,
+                used: self.clock,
+            },
+        );
+        self.shrink();
+    }
+
+    pub fn max_entries(&self) -> usize {
+        self.max_entries
+    }
+
+    pub fn set_max_entries(&mut self, max_entries: usize) {
+        self.max_entries = max_entries;
+        self.shrink();
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    /// Drop the least recently used entries once the cache is over its size, down to 90%
+    /// of it, so the next inserts have room.
+    fn shrink(&mut self) {
+        if self.entries.len() <= self.max_entries {
+            return;
+        }
+        let keep = self.max_entries - self.max_entries / 10;
+        let mut used = self
+            .entries
+            .values()
+            .map(|entry| entry.used)
+            .collect::<Vec<_>>();
+        let last = used.len() - keep - 1;
+        let (_, cutoff, _) = used.select_nth_unstable(last);
+        let cutoff = *cutoff;
+        self.entries.retain(|_, entry| entry.used > cutoff);
+    }
+}
+
+#[cfg(test)]
+mod cache_tests {
+    use super::*;
+    use crate::tester::run_test;
+    use std::io::Write;
+
+    fn sized(size: u64) -> Metadata {
+        Metadata {
+            size: Some(size),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn hit_until_file_changes() {
+        run_test(&vec!["cache_changes.txt"], || {
+            let path = Path::new("cache_changes.txt");
+            let mut cache = MetadataCache::default();
+            cache.insert(path, sized(1));
+            assert_eq!(cache.get(path), Some(sized(1)));
+            let mut file = fs::OpenOptions::new().append(true).open(path).unwrap();
+            writeln!(file, "changed").unwrap();
+            assert_eq!(cache.get(path), None);
+            assert!(cache.is_empty());
+        })
+    }
+
+    #[test]
+    fn evicts_least_recently_used() {
+        let files = vec!["cache_lru_a.txt", "cache_lru_b.txt", "cache_lru_c.txt"];
+        run_test(&files, || {
+            let [a, b, c] =
+                ["cache_lru_a.txt", "cache_lru_b.txt", "cache_lru_c.txt"].map(Path::new);
+            let mut cache = MetadataCache::new(2);
+            cache.insert(a, sized(1));
+            cache.insert(b, sized(2));
+            cache.get(a);
+            cache.insert(c, sized(3));
+            assert_eq!(cache.len(), 2);
+            assert_eq!(cache.get(b), None);
+            assert_eq!(cache.get(a), Some(sized(1)));
+            cache.set_max_entries(1);
+            assert_eq!(cache.len(), 1);
+            assert_eq!(cache.get(a), Some(sized(1)));
+        })
+    }
+
+    #[test]
+    fn evicts_a_tenth_at_a_time() {
+        let files = (0..21)
+            .map(|idx| format!("cache_batch_{idx}.txt"))
+            .collect::<Vec<_>>();
+        let names = files.iter().map(String::as_str).collect::<Vec<_>>();
+        run_test(&names, || {
+            let mut cache = MetadataCache::new(20);
+            for (idx, file) in files.iter().enumerate() {
+                cache.insert(Path::new(file), sized(idx as u64));
+            }
+            assert_eq!(cache.len(), 18);
+            assert_eq!(cache.get(Path::new(&files[2])), None);
+            assert_eq!(cache.get(Path::new(&files[3])), Some(sized(3)));
+        })
+    }
+
+    #[test]
+    fn save_and_load() {
+        run_test(
+            &vec!["cache_saved.txt", "cache_test.json", "cache_test.json.bak"],
+            || {
+                let path = Path::new("cache_saved.txt");
+                let mut cache = MetadataCache::new(10);
+                cache.insert(path, sized(7));
+                cache.save("cache_test.json").unwrap();
+                let mut loaded = MetadataCache::load("cache_test.json").unwrap();
+                assert_eq!(loaded.max_entries(), 10);
+                assert_eq!(loaded.get(path), Some(sized(7)));
+                assert!(MetadataCache::load("cache_missing.json")
+                    .unwrap()
+                    .is_empty());
+            },
+        )
+    }
+}
@@ -0,0 +1,152 @@
+//! Apply the renames of one folder tree to a mirror of it, e.g. a tree of RAW files
+//! alongside a tree of their JPEG exports. Files are matched by stem within the same
+//! relative folder, so `photos/2023/IMG_1.jpg` renamed to `photos/2023/beach.jpg` renames
+//! `raw/2023/IMG_1.cr2` and `raw/2023/IMG_1.xmp` to `beach.cr2` and `beach.xmp`.
+
+use std::{
+    collections::{HashMap, HashSet},
+    ffi::OsString,
+    io,
+    path::{Path, PathBuf},
+};
+
+use crate::directory;
+
+/// The files of a mirror tree, read once with [Mirror::read].
+#[derive(Debug, Default, Clone)]
+pub struct Mirror {
+    source: PathBuf,
+    root: PathBuf,
+    /// The files in each folder of the mirror, keyed by the folder relative to `root`.
+    folders: HashMap<PathBuf, Vec<PathBuf>>,
+}
+
+/// What a batch does to the mirror.
+/// - `renames` - The mirror files to rename, as `(from, to)` pairs.
+/// - `unmatched` - Source files with no mirror file and mirror files with no source file,
+///   in the folders the batch touches.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct MirrorPlan {
+    pub renames: Vec<(PathBuf, PathBuf)>,
+    pub unmatched: Vec<PathBuf>,
+}
+
+impl Mirror {
+    /// Read every file under `root`, the mirror of the tree under `source`.
+    pub fn read<P: AsRef<Path>, Q: AsRef<Path>>(source: P, root: Q) -> Result<Self, io::Error> {
+        let root = root.as_ref().to_path_buf();
+        // Don't follow links, they could lead out of the tree or loop.
+        let folders = directory::walk(&root, false)?
+            .into_iter()
+            .map(|folder| {
+                let relative = folder.path.strip_prefix(&root).unwrap_or(Path::new(""));
+                (relative.to_path_buf(), folder.files)
+            })
+            .collect();
+        Ok(Self {
+            source: source.as_ref().to_path_buf(),
+            root,
+            folders,
+        })
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Match a batch of `(from, to)` renames in the source tree against the mirror.
+    /// Renames outside the source tree are ignored. Pass the source and mirror renames to
+    /// [crate::batch::rename] together to keep both trees consistent.
+    pub fn plan<P: AsRef<Path>, Q: AsRef<Path>>(&self, renames: &[(P, Q)]) -> MirrorPlan {
+        let mut plan = MirrorPlan::default();
+        let mut stems: HashMap<&Path, HashSet<OsString>> = HashMap::new();
+        for (from, to) in renames {
+            let (from, to) = (from.as_ref(), to.as_ref());
+            let (Some(folder), Some(to_folder)) = (self.relative(from), self.relative(to)) else {
+                continue;
+            };
+            let Some((folder, files)) = self.folders.get_key_value(folder) else {
+                plan.unmatched.push(from.to_path_buf());
+                continue;
+            };
+            let stem = from.file_stem().unwrap_or_default();
+            stems.entry(folder).or_default().insert(stem.to_owned());
+            let matches = files
+                .iter()
+                .filter(|file| file.file_stem() == Some(stem))
+                .collect::<Vec<_>>();
+            if matches.is_empty() {
+                plan.unmatched.push(from.to_path_buf());
+            }
+            let new_stem = to.file_stem().unwrap_or_default();
+            for file in matches {
+                let mut name = new_stem.to_owned();
+                if let Some(ext) = file.extension() {
+                    name.push(".");
+                    name.push(ext);
+                }
+                let new = self.root.join(to_folder).join(name);
+                if *file != new {
+                    plan.renames.push((file.clone(), new));
+                }
+            }
+        }
+        for (folder, stems) in stems {
+            plan.unmatched.extend(
+                self.folders[folder]
+                    .iter()
+                    .filter(|file| !stems.contains(file.file_stem().unwrap_or_default()))
+                    .cloned(),
+            );
+        }
+        plan
+    }
+
+    /// The folder of a source file relative to the source root.
+    fn relative<'a>(&self, path: &'a Path) -> Option<&'a Path> {
+        path.parent()?.strip_prefix(&self.source).ok()
+    }
+}
+
+#[cfg(test)]
+mod mirror_tests {
+    use super::*;
+    use crate::tester::temp_folder;
+    use std::fs;
+
+    #[test]
+    fn rename_matching_stems() {
+        let dir = temp_folder("mirror");
+        let (source, raw) = (dir.join("jpg"), dir.join("raw"));
+        for file in [
+            "jpg/a.jpg",
+            "jpg/sub/b.jpg",
+            "jpg/sub/same.jpg",
+            "raw/a.cr2",
+            "raw/a.xmp",
+            "raw/sub/c.cr2",
+            "raw/sub/same.cr2",
+        ] {
+            let path = dir.join(file);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, "").unwrap();
+        }
+        let mirror = Mirror::read(&source, &raw);
+        fs::remove_dir_all(&dir).ok();
+        let renames = [
+            ("a.jpg", "x.jpg"),
+            ("sub/b.jpg", "sub/y.jpg"),
+            ("sub/same.jpg", "sub/same.jpg"),
+        ]
+        .map(|(from, to)| (source.join(from), source.join(to)));
+        let plan = mirror.unwrap().plan(&renames);
+        assert_eq!(
+            plan.renames,
+            [("a.cr2", "x.cr2"), ("a.xmp", "x.xmp")]
+                .map(|(from, to)| (raw.join(from), raw.join(to)))
+        );
+        let mut unmatched = plan.unmatched;
+        unmatched.sort();
+        assert_eq!(unmatched, [source.join("sub/b.jpg"), raw.join("sub/c.cr2")]);
+    }
+}
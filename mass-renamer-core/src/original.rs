@@ -0,0 +1,210 @@
+//! Keep a file's earlier names on the file itself, in an extended attribute (or an NTFS
+//! alternate data stream on Windows), so a rename can be undone without any other record.
+//!
+//! Each rename adds the name the file had to its history, numbered from the oldest.
+//! [restore] takes a file back one rename at a time.
+
+use std::{
+    io,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    batch::{self, Throttle},
+    file::FileError,
+};
+
+/// Name of the extended attribute, or alternate data stream, holding the history.
+#[cfg(unix)]
+pub const ATTRIBUTE: &str = "user.mass-renamer.original";
+/// Name of the extended attribute, or alternate data stream, holding the history.
+#[cfg(not(unix))]
+pub const ATTRIBUTE: &str = "mass-renamer.original";
+
+/// Names can't contain a NUL on any platform, so it separates the entries.
+const SEPARATOR: char = '\0';
+
+/// The names the file had before each rename, oldest first.
+/// Files without a history, or on file systems without attributes, give an empty list.
+pub fn history<P: AsRef<Path>>(path: P) -> Result<Vec<String>, io::Error> {
+    Ok(match read(path.as_ref())? {
+        Some(text) => text.split(SEPARATOR).map(String::from).collect(),
+        None => vec![],
+    })
+}
+
+/// Add `original`, the file's name before it was renamed to `path`, to its history.
+pub fn stash<P: AsRef<Path>>(path: P, original: &str) -> Result<(), io::Error> {
+    let path = path.as_ref();
+    let mut names = history(path)?;
+    names.push(original.to_string());
+    write(path, &names)
+}
+
+/// Rename each pair with [batch::rename] and [stash] the old names on the renamed files.
+/// Failing to stash a name is reported in that pair's result, the rename still stands.
+pub fn rename<P: AsRef<Path>, Q: AsRef<Path>>(renames: &[(P, Q)]) -> Vec<Result<(), FileError>> {
+    rename_throttled(renames, Throttle::default())
+}
+
+/// [rename], keeping to the [Throttle].
+pub fn rename_throttled<P: AsRef<Path>, Q: AsRef<Path>>(
+    renames: &[(P, Q)],
+    throttle: Throttle,
+) -> Vec<Result<(), FileError>> {
+    batch::rename_throttled(renames, throttle)
+        .into_iter()
+        .zip(renames)
+        .map(|(result, (from, to))| {
+            result?;
+            let (from, to) = (from.as_ref(), to.as_ref());
+            if from != to {
+                let name = from.file_name().unwrap_or_default().to_string_lossy();
+                stash(to, &name)?;
+            }
+            Ok(())
+        })
+        .collect()
+}
+
+/// Rename the file back to the last name in its history and return the restored path.
+/// An existing file is never overwritten.
+pub fn restore<P: AsRef<Path>>(path: P) -> Result<PathBuf, FileError> {
+    let path = path.as_ref();
+    let mut names = history(path)?;
+    let Some(name) = names.pop() else {
+        return Err(FileError::NoHistory(path.to_path_buf()));
+    };
+    let restored = path.with_file_name(name);
+    batch::rename(&[(path, &restored)])
+        .pop()
+        .unwrap_or(Ok(()))?;
+    write(&restored, &names)?;
+    Ok(restored)
+}
+
+fn write(path: &Path, names: &[String]) -> Result<(), io::Error> {
+    if names.is_empty() {
+        remove(path)
+    } else {
+        let sep = SEPARATOR.to_string();
+        set(path, names.join(&sep).as_bytes())
+    }
+}
+
+fn read(path: &Path) -> Result<Option<String>, io::Error> {
+    Ok(get(path)?.map(|bytes| String::from_utf8_lossy(&bytes).into_owned()))
+}
+
+#[cfg(unix)]
+fn get(path: &Path) -> Result<Option<Vec<u8>>, io::Error> {
+    if !path.exists() {
+        return Err(io::ErrorKind::NotFound.into());
+    }
+    match xattr::get(path, ATTRIBUTE) {
+        Err(err) if err.kind() == io::ErrorKind::Unsupported => Ok(None),
+        result => result,
+    }
+}
+
+#[cfg(unix)]
+fn set(path: &Path, value: &[u8]) -> Result<(), io::Error> {
+    xattr::set(path, ATTRIBUTE, value)
+}
+
+#[cfg(unix)]
+fn remove(path: &Path) -> Result<(), io::Error> {
+    match xattr::remove(path, ATTRIBUTE) {
+        Err(err) if xattr_missing(&err) => Ok(()),
+        result => result,
+    }
+}
+
+#[cfg(unix)]
+fn xattr_missing(err: &io::Error) -> bool {
+    // ENODATA on Linux, ENOATTR elsewhere.
+    matches!(err.raw_os_error(), Some(61) | Some(93)) || err.kind() == io::ErrorKind::Unsupported
+}
+
+/// The alternate data stream of a file, `photo.jpg:mass-renamer.original`.
+#[cfg(windows)]
+fn stream(path: &Path) -> PathBuf {
+    let mut stream = path.as_os_str().to_owned();
+    stream.push(":");
+    stream.push(ATTRIBUTE);
+    PathBuf::from(stream)
+}
+
+#[cfg(windows)]
+fn get(path: &Path) -> Result<Option<Vec<u8>>, io::Error> {
+    if !path.exists() {
+        return Err(io::ErrorKind::NotFound.into());
+    }
+    match std::fs::read(stream(path)) {
+        Ok(bytes) => Ok(Some(bytes)),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(None),
+        Err(err) => Err(err),
+    }
+}
+
+#[cfg(windows)]
+fn set(path: &Path, value: &[u8]) -> Result<(), io::Error> {
+    std::fs::write(stream(path), value)
+}
+
+#[cfg(windows)]
+fn remove(path: &Path) -> Result<(), io::Error> {
+    match std::fs::remove_file(stream(path)) {
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(()),
+        result => result,
+    }
+}
+
+#[cfg(not(any(unix, windows)))]
+fn get(_path: &Path) -> Result<Option<Vec<u8>>, io::Error> {
+    Ok(None)
+}
+
+#[cfg(not(any(unix, windows)))]
+fn set(_path: &Path, _value: &[u8]) -> Result<(), io::Error> {
+    Err(io::ErrorKind::Unsupported.into())
+}
+
+#[cfg(not(any(unix, windows)))]
+fn remove(_path: &Path) -> Result<(), io::Error> {
+    Ok(())
+}
+
+#[cfg(test)]
+mod original_tests {
+    use super::*;
+    use crate::tester::temp_folder;
+    use std::fs;
+
+    #[test]
+    fn rename_twice_and_restore() {
+        let dir = temp_folder("original");
+        let [a, b, c] = ["a.txt", "b.txt", "c.txt"].map(|name| dir.join(name));
+        fs::write(&a, "A").unwrap();
+        let first = rename(&[(&a, &b)]).remove(0);
+        if matches!(&first, Err(FileError::Io(err)) if err.kind() == io::ErrorKind::Unsupported) {
+            // The file system doesn't support attributes.
+            fs::remove_dir_all(&dir).ok();
+            return;
+        }
+        let second = rename(&[(&b, &c)]).remove(0);
+        let stashed = history(&c).unwrap();
+        let restored = restore(&c).unwrap();
+        let left = history(&b).unwrap();
+        let original = restore(&b).unwrap();
+        let none = restore(&a);
+        let text = fs::read_to_string(&a).unwrap();
+        fs::remove_dir_all(&dir).ok();
+        assert!(first.is_ok() && second.is_ok());
+        assert_eq!(stashed, ["a.txt", "b.txt"]);
+        assert_eq!((restored, original), (b, a));
+        assert_eq!(left, ["a.txt"]);
+        assert!(matches!(none, Err(FileError::NoHistory(_))));
+        assert_eq!(text, "A");
+    }
+}
@@ -0,0 +1,125 @@
+//! Crash-safe saving for settings, presets, caches and the like.
+//!
+//! [write] never leaves a half written file in place: the new contents go to a temporary
+//! file that replaces the old one in a single rename, and the previous version is kept as
+//! a backup. [read] checks the file with a parser and falls back to the backup if it is
+//! missing or damaged.
+
+use std::{
+    ffi::OsString,
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+/// The backup kept next to a saved file, `settings.json` → `settings.json.bak`.
+pub fn backup_path(path: &Path) -> PathBuf {
+    with_suffix(path, ".bak")
+}
+
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.file_name().map(OsString::from).unwrap_or_default();
+    name.push(suffix);
+    path.with_file_name(name)
+}
+
+/// Replace the file at `path` with `contents`, creating any missing folders.
+/// The previous file, if any, is kept as its [backup_path].
+pub fn write<P: AsRef<Path>, C: AsRef<[u8]>>(path: P, contents: C) -> Result<(), io::Error> {
+    let path = path.as_ref();
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let temp = with_suffix(path, ".tmp");
+    let mut file = fs::File::create(&temp)?;
+    io::Write::write_all(&mut file, contents.as_ref())?;
+    file.sync_all()?;
+    drop(file);
+    if path.exists() {
+        fs::rename(path, backup_path(path))?;
+    }
+    fs::rename(&temp, path)
+}
+
+/// Read a file saved with [write], using `parse` to check its contents.
+/// If the file is missing or doesn't parse, e.g. after a crash while saving, the backup
+/// is used and restored in its place. Returns `None` when neither exists.
+pub fn read<P, T, E, F>(path: P, parse: F) -> Result<Option<T>, io::Error>
+where
+    P: AsRef<Path>,
+    F: Fn(&str) -> Result<T, E>,
+    E: std::error::Error + Send + Sync + 'static,
+{
+    let path = path.as_ref();
+    let backup = backup_path(path);
+    let damaged = match read_text(path)? {
+        Some(text) => match parse(&text) {
+            Ok(value) => return Ok(Some(value)),
+            Err(err) => Some(err),
+        },
+        None => None,
+    };
+    match read_text(&backup)?.map(|text| (parse(&text), text)) {
+        Some((Ok(value), text)) => {
+            let temp = with_suffix(path, ".tmp");
+            fs::write(&temp, text)?;
+            fs::rename(&temp, path)?;
+            Ok(Some(value))
+        }
+        _ => match damaged {
+            Some(err) => Err(io::Error::new(io::ErrorKind::InvalidData, err)),
+            None => Ok(None),
+        },
+    }
+}
+
+fn read_text(path: &Path) -> Result<Option<String>, io::Error> {
+    match fs::read_to_string(path) {
+        Ok(text) => Ok(Some(text)),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(None),
+        // A partly written file may not be valid UTF-8.
+        Err(err) if err.kind() == io::ErrorKind::InvalidData => Ok(Some(String::new())),
+        Err(err) => Err(err),
+    }
+}
+
+#[cfg(test)]
+mod persist_tests {
+    use super::*;
+    use crate::tester::temp_folder;
+    use std::num::ParseIntError;
+
+    fn parse(text: &str) -> Result<u32, ParseIntError> {
+        text.parse()
+    }
+
+    #[test]
+    fn keeps_backup_and_restores_it() {
+        let dir = temp_folder("persist_restore");
+        let path = dir.join("saved.txt");
+        write(&path, "1").unwrap();
+        write(&path, "2").unwrap();
+        let saved = read(&path, parse).unwrap();
+        let backup = fs::read_to_string(backup_path(&path)).unwrap();
+        // A crash mid-save would leave a damaged file.
+        fs::write(&path, "2x").unwrap();
+        let restored = read(&path, parse).unwrap();
+        let contents = fs::read_to_string(&path).unwrap();
+        fs::remove_dir_all(&dir).ok();
+        assert_eq!(saved, Some(2));
+        assert_eq!(backup, "1");
+        assert_eq!(restored, Some(1));
+        assert_eq!(contents, "1");
+    }
+
+    #[test]
+    fn missing_and_damaged() {
+        let dir = temp_folder("persist_damaged");
+        let path = dir.join("saved.txt");
+        let missing = read(&path, parse).unwrap();
+        write(&path, "x").unwrap();
+        let damaged = read(&path, parse);
+        fs::remove_dir_all(&dir).ok();
+        assert_eq!(missing, None);
+        assert_eq!(damaged.unwrap_err().kind(), io::ErrorKind::InvalidData);
+    }
+}
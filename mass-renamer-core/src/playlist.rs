@@ -0,0 +1,119 @@
+//! Read and write selections as lists of paths, one per line, such as a `.txt` list made
+//! by another tool or an `.m3u` playlist. Lines starting with `#`, e.g. `#EXTINF`, are
+//! kept but not read as paths, and relative paths are relative to the list's folder.
+
+use std::{
+    collections::HashMap,
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use crate::{canonical, persist};
+
+/// The paths in a list, in list order.
+pub fn read<P: AsRef<Path>>(list: P) -> Result<Vec<PathBuf>, io::Error> {
+    let list = list.as_ref();
+    let base = list.parent().unwrap_or(Path::new(""));
+    Ok(fs::read_to_string(list)?
+        .lines()
+        .filter_map(entry)
+        .map(|line| base.join(line))
+        .collect())
+}
+
+/// Write the paths as a list, with an `#EXTM3U` header for `.m3u` and `.m3u8` files.
+pub fn write<P: AsRef<Path>, Q: AsRef<Path>>(list: P, paths: &[Q]) -> Result<(), io::Error> {
+    let list = list.as_ref();
+    let mut text = String::new();
+    if is_m3u(list) {
+        text.push_str("#EXTM3U\n");
+    }
+    for path in paths {
+        text.push_str(&path.as_ref().to_string_lossy());
+        text.push('\n');
+    }
+    persist::write(list, text)
+}
+
+/// Give the renamed files their new paths in the list, leaving every other line as it is.
+/// Relative paths stay relative. Returns how many lines changed.
+///
+/// ```
+/// # use std::fs;
+/// # use mass_renamer_core::playlist;
+/// let dir = std::env::temp_dir().join(format!("playlist_doc_{}", std::process::id()));
+/// fs::create_dir_all(&dir).unwrap();
+/// fs::write(dir.join("a.mp3"), "").unwrap();
+/// fs::write(dir.join("mix.m3u"), "#EXTM3U\n#EXTINF:61,A\na.mp3\n").unwrap();
+/// let updated = playlist::update(dir.join("mix.m3u"), &[(dir.join("a.mp3"), dir.join("01 A.mp3"))]);
+/// let text = fs::read_to_string(dir.join("mix.m3u")).unwrap();
+/// fs::remove_dir_all(&dir).ok();
+/// assert_eq!(updated.unwrap(), 1);
+/// assert_eq!(text, "#EXTM3U\n#EXTINF:61,A\n01 A.mp3\n");
+/// ```
+pub fn update<P: AsRef<Path>, Q: AsRef<Path>, R: AsRef<Path>>(
+    list: P,
+    renames: &[(Q, R)],
+) -> Result<usize, io::Error> {
+    let list = list.as_ref();
+    let base = list.parent().unwrap_or(Path::new(""));
+    let renamed = renames
+        .iter()
+        .map(|(from, to)| (canonical(from.as_ref()), to.as_ref()))
+        .collect::<HashMap<_, _>>();
+    let text = fs::read_to_string(list)?;
+    let mut changed = 0;
+    let mut updated = String::with_capacity(text.len());
+    for line in text.lines() {
+        let new = entry(line).and_then(|path| {
+            let new = renamed.get(&canonical(&base.join(path)))?;
+            // Keep the list's way of writing the path.
+            Some(match Path::new(path).is_relative() {
+                true => new.strip_prefix(base).unwrap_or(new),
+                false => new,
+            })
+        });
+        match new {
+            Some(new) => {
+                changed += 1;
+                updated.push_str(&new.to_string_lossy());
+            }
+            None => updated.push_str(line),
+        }
+        updated.push('\n');
+    }
+    if changed > 0 {
+        persist::write(list, updated)?;
+    }
+    Ok(changed)
+}
+
+/// The path on a line of a list, if it has one.
+fn entry(line: &str) -> Option<&str> {
+    let line = line.trim();
+    (!line.is_empty() && !line.starts_with('#')).then_some(line)
+}
+
+fn is_m3u(list: &Path) -> bool {
+    list.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("m3u") || ext.eq_ignore_ascii_case("m3u8"))
+}
+
+#[cfg(test)]
+mod playlist_tests {
+    use super::*;
+    use crate::tester::temp_folder;
+
+    #[test]
+    fn read_skips_comments() {
+        let dir = temp_folder("playlist");
+        let list = dir.join("songs.m3u");
+        write(&list, &[dir.join("a.mp3"), PathBuf::from("b.mp3")]).unwrap();
+        let text = fs::read_to_string(&list);
+        let paths = read(&list);
+        fs::remove_dir_all(&dir).ok();
+        assert!(text.unwrap().starts_with("#EXTM3U\n"));
+        assert_eq!(paths.unwrap(), [dir.join("a.mp3"), dir.join("b.mp3")]);
+    }
+}
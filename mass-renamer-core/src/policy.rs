@@ -0,0 +1,267 @@
+//! Naming policies a team shares, so every new name follows the same conventions before
+//! anything is renamed.
+//!
+//! A policy file has one `key = value` rule per line. Blank lines and lines starting with
+//! `#` are ignored, and every rule is optional.
+//! - `max_length` - The most characters a name may have, extension included.
+//! - `charset` - The characters a name may use, as the inside of a regular expression
+//!   character class, e.g. `A-Za-z0-9_.-`.
+//! - `prefix` - A regular expression the start of the name must match, e.g.
+//!   `[A-Z]{3}-\d{4}_`.
+//! - `forbidden` - Words a name may not contain, separated by commas. They are matched as
+//!   whole words whatever their case, e.g. `final` forbids `Report_FINAL.pdf` but not
+//!   `Finalists.pdf`.
+//!
+//! ```
+//! # use mass_renamer_core::policy::NamingPolicy;
+//! let policy: NamingPolicy = "
+//!     ## Acme project files
+//!     max_length = 40
+//!     charset = A-Za-z0-9_.-
+//!     prefix = ACME-\\d{4}_
+//!     forbidden = final, copy
+//! "
+//! .parse()
+//! .unwrap();
+//! let batch = [("/d/a.txt", "/d/ACME-0001_plan.txt"), ("/d/b.txt", "/d/plan_final.txt")];
+//! let violations = policy.check(&batch);
+//! assert_eq!(violations.len(), 2);
+//! let prefix = "/d/b.txt: plan_final.txt doesn't start with the prefix";
+//! assert_eq!(violations[0].to_string(), prefix);
+//! ```
+
+use std::{
+    fmt::{self, Display},
+    fs, io,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use regex::Regex;
+use thiserror::Error;
+
+use crate::file::reg;
+
+#[derive(Debug, Error)]
+pub enum PolicyError {
+    #[error("Line {0}: expected a rule such as \"max_length = 64\", found \"{1}\".")]
+    Line(usize, String),
+    #[error("Line {0}: unknown rule \"{1}\".")]
+    Rule(usize, String),
+    #[error("Line {0}: \"{1}\" isn't a length.")]
+    Length(usize, String),
+    #[error("Line {0}: {1}")]
+    Regex(usize, regex::Error),
+    #[error(transparent)]
+    Io(#[from] io::Error),
+}
+
+/// The rules every new name must follow, see the [module docs](self).
+#[derive(Debug, Clone, Default)]
+pub struct NamingPolicy {
+    max_length: Option<usize>,
+    charset: Option<(String, Regex)>,
+    prefix: Option<Regex>,
+    forbidden: Vec<(String, Regex)>,
+}
+
+/// A new name that breaks a rule of the [NamingPolicy]. `original` is the path of the
+/// file being renamed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Violation {
+    pub original: PathBuf,
+    pub name: String,
+    pub rule: Rule,
+}
+
+/// The rule a [Violation] breaks.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Rule {
+    /// The name has the first number of characters, over the second.
+    MaxLength(usize, usize),
+    /// The name uses a character outside the charset.
+    Charset(char),
+    Prefix,
+    Forbidden(String),
+}
+
+impl Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (original, name) = (self.original.display(), &self.name);
+        match &self.rule {
+            Rule::MaxLength(length, limit) => write!(
+                f,
+                "{original}: {name} is {length} characters, over the {limit} allowed"
+            ),
+            Rule::Charset(c) => write!(f, "{original}: {name} uses {c:?}, not allowed"),
+            Rule::Prefix => write!(f, "{original}: {name} doesn't start with the prefix"),
+            Rule::Forbidden(word) => write!(f, "{original}: {name} contains \"{word}\""),
+        }
+    }
+}
+
+impl FromStr for NamingPolicy {
+    type Err = PolicyError;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let mut policy = NamingPolicy::default();
+        for (idx, line) in text.lines().enumerate() {
+            let number = idx + 1;
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let Some((key, value)) = line.split_once('=') else {
+                return Err(PolicyError::Line(number, line.to_owned()));
+            };
+            let (key, value) = (key.trim(), value.trim());
+            let compile =
+                |exp: &str| reg::compile(exp).map_err(|err| PolicyError::Regex(number, err));
+            match key {
+                "max_length" => {
+                    let length = value
+                        .parse()
+                        .map_err(|_| PolicyError::Length(number, value.to_owned()))?;
+                    policy.max_length = Some(length);
+                }
+                "charset" => {
+                    policy.charset = Some((value.to_owned(), compile(&format!("[^{value}]"))?))
+                }
+                "prefix" => policy.prefix = Some(compile(&format!("^(?:{value})"))?),
+                "forbidden" => {
+                    for word in value
+                        .split(',')
+                        .map(str::trim)
+                        .filter(|word| !word.is_empty())
+                    {
+                        let word_exp =
+                            format!(r"(?i)(?:^|[^\pL\pN]){}(?:[^\pL\pN]|$)", regex::escape(word));
+                        let exp = compile(&word_exp)?;
+                        policy.forbidden.push((word.to_owned(), exp));
+                    }
+                }
+                _ => return Err(PolicyError::Rule(number, key.to_owned())),
+            }
+        }
+        Ok(policy)
+    }
+}
+
+impl NamingPolicy {
+    /// Read a policy file, see the [module docs](self).
+    pub fn read<P: AsRef<Path>>(path: P) -> Result<Self, PolicyError> {
+        fs::read_to_string(path)?.parse()
+    }
+
+    /// Check the new names of a batch, given as `(original, new)` paths, returning every
+    /// rule each breaks.
+    pub fn check<P: AsRef<Path>, Q: AsRef<Path>>(&self, batch: &[(P, Q)]) -> Vec<Violation> {
+        let mut violations = vec![];
+        for (original, new) in batch {
+            let name = new
+                .as_ref()
+                .file_name()
+                .unwrap_or_default()
+                .to_string_lossy()
+                .into_owned();
+            let mut broken = vec![];
+            let length = name.chars().count();
+            if let Some(limit) = self.max_length.filter(|limit| length > *limit) {
+                broken.push(Rule::MaxLength(length, limit));
+            }
+            if let Some((_, outside)) = &self.charset {
+                if let Some(found) = outside.find(&name) {
+                    let c = found.as_str().chars().next().unwrap_or_default();
+                    broken.push(Rule::Charset(c));
+                }
+            }
+            if self
+                .prefix
+                .as_ref()
+                .is_some_and(|prefix| !prefix.is_match(&name))
+            {
+                broken.push(Rule::Prefix);
+            }
+            for (word, exp) in &self.forbidden {
+                if exp.is_match(&name) {
+                    broken.push(Rule::Forbidden(word.clone()));
+                }
+            }
+            violations.extend(broken.into_iter().map(|rule| Violation {
+                original: original.as_ref().to_path_buf(),
+                name: name.clone(),
+                rule,
+            }));
+        }
+        violations
+    }
+
+    /// The rules, one per line as in a policy file.
+    pub fn describe(&self) -> Vec<String> {
+        let mut rules = vec![];
+        if let Some(limit) = self.max_length {
+            rules.push(format!("max_length = {limit}"));
+        }
+        if let Some((charset, _)) = &self.charset {
+            rules.push(format!("charset = {charset}"));
+        }
+        if let Some(prefix) = &self.prefix {
+            let prefix = prefix.as_str();
+            rules.push(format!("prefix = {}", &prefix[4..prefix.len() - 1]));
+        }
+        if !self.forbidden.is_empty() {
+            let words = self.forbidden.iter().map(|(word, _)| word.as_str());
+            rules.push(format!(
+                "forbidden = {}",
+                words.collect::<Vec<_>>().join(", ")
+            ));
+        }
+        rules
+    }
+}
+
+#[cfg(test)]
+mod policy_tests {
+    use super::*;
+
+    #[test]
+    fn every_rule_checked() {
+        let policy: NamingPolicy = "max_length = 12\ncharset = a-z.\nforbidden = draft"
+            .parse()
+            .unwrap();
+        let batch = [
+            ("a", "ok.txt"),
+            ("b", "much_too_long.txt"),
+            ("c", "Draft.txt"),
+            ("d", "drafts.txt"),
+        ];
+        let rules = policy
+            .check(&batch)
+            .into_iter()
+            .map(|violation| (violation.original, violation.rule))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            rules,
+            [
+                (PathBuf::from("b"), Rule::MaxLength(17, 12)),
+                (PathBuf::from("b"), Rule::Charset('_')),
+                (PathBuf::from("c"), Rule::Charset('D')),
+                (PathBuf::from("c"), Rule::Forbidden("draft".into())),
+            ]
+        );
+    }
+
+    #[test]
+    fn bad_files_rejected() {
+        let err = |text: &str| text.parse::<NamingPolicy>().unwrap_err().to_string();
+        assert_eq!(
+            err("# comment\n\nmax_length 12"),
+            "Line 3: expected a rule such as \"max_length = 64\", found \"max_length 12\"."
+        );
+        assert_eq!(err("max_len = 12"), "Line 1: unknown rule \"max_len\".");
+        assert_eq!(err("max_length = -1"), "Line 1: \"-1\" isn't a length.");
+        assert!(err("prefix = (").starts_with("Line 1: "));
+        let policy: NamingPolicy = "prefix = [A-Z]+_\nforbidden = a, b".parse().unwrap();
+        assert_eq!(policy.describe(), ["prefix = [A-Z]+_", "forbidden = a, b"]);
+    }
+}
@@ -0,0 +1,99 @@
+//! Run a thread in the background, so a huge batch doesn't slow down the programs being
+//! used in the meantime. The thread can be given its priority back part way.
+//!
+//! - Windows - The thread's background mode, which lowers its CPU and disk priority.
+//! - macOS - The thread's background policy, which lowers its CPU and disk priority.
+//! - Linux - The thread's disk priority is lowered to idle. Its CPU priority is left
+//!   alone, as it couldn't be raised back without privileges.
+//!
+//! Other platforms fail with [io::ErrorKind::Unsupported].
+
+use std::io;
+
+/// Lower the priority of the current thread, or give it back with `false`.
+///
+/// ```
+/// # use mass_renamer_core::priority::set_background;
+/// # #[cfg(any(windows, target_os = "linux", target_os = "macos"))]
+/// # {
+/// set_background(true).unwrap();
+/// set_background(false).unwrap();
+/// # }
+/// ```
+pub fn set_background(background: bool) -> io::Result<()> {
+    imp::set_background(background)
+}
+
+#[cfg(target_os = "linux")]
+mod imp {
+    use std::io;
+
+    const IOPRIO_WHO_PROCESS: libc::c_int = 1;
+    const IOPRIO_CLASS_SHIFT: libc::c_int = 13;
+    const IOPRIO_CLASS_BE: libc::c_int = 2;
+    const IOPRIO_CLASS_IDLE: libc::c_int = 3;
+    /// The best effort level threads start with.
+    const IOPRIO_NORMAL: libc::c_int = 4;
+
+    pub fn set_background(background: bool) -> io::Result<()> {
+        let priority = match background {
+            true => IOPRIO_CLASS_IDLE << IOPRIO_CLASS_SHIFT,
+            false => IOPRIO_CLASS_BE << IOPRIO_CLASS_SHIFT | IOPRIO_NORMAL,
+        };
+        // 0 is the calling thread.
+        let result =
+            unsafe { libc::syscall(libc::SYS_ioprio_set, IOPRIO_WHO_PROCESS, 0, priority) };
+        match result {
+            -1 => Err(io::Error::last_os_error()),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod imp {
+    use std::io;
+
+    pub fn set_background(background: bool) -> io::Result<()> {
+        let priority = match background {
+            true => libc::PRIO_DARWIN_BG,
+            false => 0,
+        };
+        // 0 is the calling thread.
+        let result = unsafe { libc::setpriority(libc::PRIO_DARWIN_THREAD, 0, priority) };
+        match result {
+            -1 => Err(io::Error::last_os_error()),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(windows)]
+mod imp {
+    use std::io;
+
+    use windows_sys::Win32::System::Threading::{
+        GetCurrentThread, SetThreadPriority, THREAD_MODE_BACKGROUND_BEGIN,
+        THREAD_MODE_BACKGROUND_END,
+    };
+
+    pub fn set_background(background: bool) -> io::Result<()> {
+        let mode = match background {
+            true => THREAD_MODE_BACKGROUND_BEGIN,
+            false => THREAD_MODE_BACKGROUND_END,
+        };
+        match unsafe { SetThreadPriority(GetCurrentThread(), mode) } {
+            0 => Err(io::Error::last_os_error()),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(not(any(windows, target_os = "linux", target_os = "macos")))]
+mod imp {
+    use std::io;
+
+    pub fn set_background(_background: bool) -> io::Result<()> {
+        Err(io::ErrorKind::Unsupported.into())
+    }
+}
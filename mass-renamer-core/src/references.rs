@@ -0,0 +1,366 @@
+//! Rewrite the relative links to renamed files in text files, e.g. the images and pages
+//! a documentation site links to, so the links keep working after a rename.
+//!
+//! A link is a relative path, optionally starting with `./`, at the start of a line or
+//! after a space, `(`, a quote, `<` or `=`, and ending the line or followed by a space,
+//! `)`, a quote, `>`, `#` or `?`. This covers Markdown links and HTML attributes, as
+//! well as plain lists of paths. Paths with spaces are matched as written or with the
+//! spaces encoded as `%20`.
+
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Display,
+    fs, io, iter,
+    path::{Component, Path, PathBuf},
+};
+
+use crate::{directory, persist};
+
+/// A link changed, or to change, in a text file.
+/// - `line` - The line of the link, counted from 1.
+/// - `old`, `new` - The link before and after, as written in the file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Reference {
+    pub document: PathBuf,
+    pub line: usize,
+    pub old: String,
+    pub new: String,
+}
+
+impl Display for Reference {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}:{}: {} -> {}",
+            self.document.display(),
+            self.line,
+            self.old,
+            self.new
+        )
+    }
+}
+
+/// The links found or changed in a set of documents, see [find] and [update].
+/// - `failed` - The documents that couldn't be read or written, and why. The others are
+///   still searched or updated. Documents that aren't UTF-8 text, e.g. a Latin-1 page,
+///   are skipped.
+#[derive(Debug, Default)]
+pub struct Links {
+    pub references: Vec<Reference>,
+    pub failed: Vec<(PathBuf, io::Error)>,
+}
+
+/// The files under `root`, `root` included, with one of the extensions, e.g.
+/// `["md", "html"]`. Extensions are compared case-insensitively and links aren't followed.
+pub fn documents<P: AsRef<Path>, S: AsRef<str>>(
+    root: P,
+    extensions: &[S],
+) -> Result<Vec<PathBuf>, io::Error> {
+    let mut documents = directory::walk(root, false)?
+        .into_iter()
+        .flat_map(|folder| folder.files)
+        .filter(|file| {
+            file.extension()
+                .and_then(|ext| ext.to_str())
+                .is_some_and(|ext| {
+                    extensions
+                        .iter()
+                        .any(|wanted| wanted.as_ref().eq_ignore_ascii_case(ext))
+                })
+        })
+        .collect::<Vec<_>>();
+    documents.sort();
+    Ok(documents)
+}
+
+/// The links to renamed files in the documents, without changing anything.
+pub fn find<P: AsRef<Path>>(documents: &[P], renames: &[(PathBuf, PathBuf)]) -> Links {
+    let renamed = Renamed::new(renames);
+    let mut found = Links::default();
+    for document in documents {
+        let document = document.as_ref();
+        match rewrite(document, &renamed) {
+            Ok((_, references)) => found.references.extend(references),
+            Err(err) => found.failed.push((document.to_path_buf(), err)),
+        }
+    }
+    found
+}
+
+/// Point the links to renamed files in the documents at their new names.
+/// Returns every link changed. Documents without any are left untouched.
+///
+/// ```
+/// # use std::fs;
+/// # use mass_renamer_core::references;
+/// let dir = std::env::temp_dir().join(format!("references_doc_{}", std::process::id()));
+/// fs::create_dir_all(dir.join("img")).unwrap();
+/// fs::write(dir.join("index.md"), "![Logo](./img/logo v1.png)\n").unwrap();
+/// let renames = [(dir.join("img/logo v1.png"), dir.join("img/logo.png"))];
+/// let changed = references::update(&[dir.join("index.md")], &renames).references;
+/// let text = fs::read_to_string(dir.join("index.md")).unwrap();
+/// fs::remove_dir_all(&dir).ok();
+/// let line = format!("{}:1: img/logo v1.png -> img/logo.png", dir.join("index.md").display());
+/// assert_eq!(changed[0].to_string(), line);
+/// assert_eq!(text, "![Logo](./img/logo.png)\n");
+/// ```
+pub fn update<P: AsRef<Path>>(documents: &[P], renames: &[(PathBuf, PathBuf)]) -> Links {
+    let renamed = Renamed::new(renames);
+    let mut changed = Links::default();
+    for document in documents {
+        let document = document.as_ref();
+        let updated = rewrite(document, &renamed).and_then(|(text, found)| {
+            if !found.is_empty() {
+                persist::write(document, text)?;
+            }
+            Ok(found)
+        });
+        match updated {
+            Ok(found) => changed.references.extend(found),
+            Err(err) => changed.failed.push((document.to_path_buf(), err)),
+        }
+    }
+    changed
+}
+
+/// The renamed files of a batch, looked up by the paths links resolve to.
+/// - `names` - The file names, so most words are ruled out without building a path.
+/// - `longest` - The length of the longest old path, which bounds the length of a link.
+struct Renamed<'a> {
+    paths: HashMap<PathBuf, &'a Path>,
+    names: HashSet<String>,
+    longest: usize,
+}
+
+impl<'a> Renamed<'a> {
+    fn new(renames: &'a [(PathBuf, PathBuf)]) -> Self {
+        let mut renamed = Renamed {
+            paths: HashMap::new(),
+            names: HashSet::new(),
+            longest: 0,
+        };
+        for (from, to) in renames {
+            if let Some(name) = from.file_name() {
+                renamed.names.insert(name.to_string_lossy().to_string());
+                renamed.longest = renamed.longest.max(from.as_os_str().len());
+                renamed.paths.insert(resolve(from), to);
+            }
+        }
+        renamed
+    }
+
+    /// Where `link`, found in a document in `folder`, now points, written as a link.
+    fn new_link(&self, folder: &Path, link: &str) -> Option<String> {
+        let decoded = link.replace("%20", " ");
+        let name = decoded.rsplit('/').next()?;
+        if !self.names.contains(name) {
+            return None;
+        }
+        let to = self.paths.get(&resolve(&folder.join(&decoded)))?;
+        let new = relative(folder, to)?;
+        match link.contains("%20") {
+            true => Some(new.replace(' ', "%20")),
+            false => Some(new),
+        }
+    }
+}
+
+/// The text of the document with its links to renamed files changed, and the links.
+fn rewrite(document: &Path, renamed: &Renamed) -> Result<(String, Vec<Reference>), io::Error> {
+    let folder = document.parent().unwrap_or(Path::new(""));
+    let text = match fs::read_to_string(document) {
+        // Not UTF-8 text, so there are no links to rewrite.
+        Err(err) if err.kind() == io::ErrorKind::InvalidData => return Ok((String::new(), vec![])),
+        text => text?,
+    };
+    if renamed.paths.is_empty() {
+        return Ok((text, vec![]));
+    }
+    // A link climbs at most out of every folder above the document, with its spaces
+    // written as `%20`.
+    let longest = 3 * (3 * folder.components().count() + renamed.longest);
+    let mut found = vec![];
+    let mut rewritten = String::with_capacity(text.len());
+    for (idx, line) in text.split_inclusive('\n').enumerate() {
+        let mut last = 0;
+        let after = line
+            .char_indices()
+            .filter(|(_, c)| c.is_whitespace() || "(\"'<=".contains(*c))
+            .map(|(at, c)| at + c.len_utf8());
+        for start in iter::once(0).chain(after) {
+            if start < last {
+                continue;
+            }
+            let start = match line[start..].starts_with("./") {
+                true => start + 2,
+                false => start,
+            };
+            // Longer links first, so `a.png.bak` isn't taken for `a.png`.
+            let ends = line[start..]
+                .char_indices()
+                .skip(1)
+                .map(|(at, _)| start + at)
+                .chain([line.len()])
+                .take_while(|end| end - start <= longest)
+                .filter(|end| {
+                    line[*end..]
+                        .chars()
+                        .next()
+                        .is_none_or(|c| c.is_whitespace() || ")\"'>#?".contains(c))
+                })
+                .collect::<Vec<_>>();
+            let link = ends.into_iter().rev().find_map(|end| {
+                let old = &line[start..end];
+                renamed.new_link(folder, old).map(|new| (end, old, new))
+            });
+            let Some((end, old, new)) = link else {
+                continue;
+            };
+            rewritten.push_str(&line[last..start]);
+            rewritten.push_str(&new);
+            last = end;
+            found.push(Reference {
+                document: document.to_path_buf(),
+                line: idx + 1,
+                old: old.to_string(),
+                new,
+            });
+        }
+        rewritten.push_str(&line[last..]);
+    }
+    Ok((rewritten, found))
+}
+
+/// `path` with the `.` and `..` parts worked out, without looking at the disk.
+fn resolve(path: &Path) -> PathBuf {
+    let mut resolved = PathBuf::new();
+    for part in path.components() {
+        match part {
+            Component::CurDir => {}
+            Component::ParentDir
+                if matches!(
+                    resolved.components().next_back(),
+                    Some(Component::Normal(_))
+                ) =>
+            {
+                resolved.pop();
+            }
+            part => resolved.push(part),
+        }
+    }
+    resolved
+}
+
+/// `path` relative to `folder`, with `/` between the parts as links have.
+/// `None` if there is no relative path, e.g. they are on different drives.
+fn relative(folder: &Path, path: &Path) -> Option<String> {
+    let folder = folder.components().collect::<Vec<_>>();
+    let path = path.components().collect::<Vec<_>>();
+    let common = folder.iter().zip(&path).take_while(|(a, b)| a == b).count();
+    let rooted = |part: &Component| matches!(part, Component::Prefix(_) | Component::RootDir);
+    if common == 0 && folder.iter().chain(&path).any(rooted) || common == path.len() {
+        return None;
+    }
+    let parts = folder[common..]
+        .iter()
+        .map(|_| "..".into())
+        .chain(
+            path[common..]
+                .iter()
+                .map(|part| part.as_os_str().to_string_lossy()),
+        )
+        .collect::<Vec<_>>();
+    Some(parts.join("/"))
+}
+
+#[cfg(test)]
+mod references_tests {
+    use super::*;
+    use crate::tester::temp_folder;
+
+    #[test]
+    fn links_in_markdown_and_html() {
+        let dir = temp_folder("references");
+        fs::create_dir_all(dir.join("docs")).unwrap();
+        fs::create_dir_all(dir.join("assets")).unwrap();
+        let page = dir.join("docs/page.html");
+        fs::write(
+            &page,
+            "<img src=\"../assets/a.png\"><a href='../assets/a.png#top'>a</a>\n\
+             ../assets/a.png.bak ../assets/b.png ../other/a.png\n",
+        )
+        .unwrap();
+        fs::write(dir.join("docs/notes.txt"), "../assets/a.png\n").unwrap();
+        let renames = [
+            (dir.join("assets/a.png"), dir.join("assets/b.png")),
+            (dir.join("assets/b.png"), dir.join("assets/c.png")),
+        ];
+        let documents = documents(&dir, &["HTML", "md"]);
+        let found = documents
+            .as_ref()
+            .map(|documents| update(documents, &renames).references);
+        let text = fs::read_to_string(&page);
+        fs::remove_dir_all(&dir).ok();
+        assert_eq!(documents.as_deref().unwrap(), std::slice::from_ref(&page));
+        let lines = found
+            .unwrap()
+            .into_iter()
+            .map(|found| (found.line, found.new))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            lines,
+            [
+                (1, "../assets/b.png".into()),
+                (1, "../assets/b.png".into()),
+                (2, "../assets/c.png".into())
+            ]
+        );
+        assert_eq!(
+            text.unwrap(),
+            "<img src=\"../assets/b.png\"><a href='../assets/b.png#top'>a</a>\n\
+             ../assets/a.png.bak ../assets/c.png ../other/a.png\n"
+        );
+    }
+
+    #[test]
+    fn unreadable_documents_dont_stop_the_rest() {
+        let dir = temp_folder("references_unreadable");
+        // "café a.png" in Latin-1, which isn't UTF-8.
+        fs::write(dir.join("latin1.html"), b"caf\xe9 a.png\n").unwrap();
+        fs::write(dir.join("page.md"), "a.png\n").unwrap();
+        let documents = [
+            dir.join("latin1.html"),
+            dir.join("gone.md"),
+            dir.join("page.md"),
+        ];
+        let renames = [(dir.join("a.png"), dir.join("b.png"))];
+        let updated = update(&documents, &renames);
+        let text = fs::read_to_string(dir.join("page.md"));
+        fs::remove_dir_all(&dir).ok();
+        assert_eq!(updated.references.len(), 1);
+        let failed = updated
+            .failed
+            .iter()
+            .map(|(document, _)| document.clone())
+            .collect::<Vec<_>>();
+        assert_eq!(failed, [dir.join("gone.md")]);
+        assert_eq!(text.unwrap(), "b.png\n");
+    }
+
+    #[test]
+    fn thousands_of_renames_at_once() {
+        let dir = temp_folder("references_many");
+        fs::write(dir.join("page.md"), "[Last](photos/IMG_19999.jpg)\n").unwrap();
+        let renames = (0..20_000)
+            .map(|n| {
+                let from = dir.join(format!("photos/IMG_{n}.jpg"));
+                (from, dir.join(format!("photos/Trip {n}.jpg")))
+            })
+            .collect::<Vec<_>>();
+        let updated = update(&[dir.join("page.md")], &renames);
+        let text = fs::read_to_string(dir.join("page.md"));
+        fs::remove_dir_all(&dir).ok();
+        assert_eq!(updated.references.len(), 1);
+        assert_eq!(text.unwrap(), "[Last](photos/Trip 19999.jpg)\n");
+    }
+}
@@ -0,0 +1,153 @@
+//! Write what a batch would do as a CSV or JSON report, to circulate the new names for
+//! review before anything is renamed.
+
+use std::{collections::HashMap, io::Write, path::PathBuf};
+
+use serde::Serialize;
+use thiserror::Error;
+
+use crate::{batch, File};
+
+#[derive(Debug, Error)]
+pub enum ReportError {
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error(transparent)]
+    Csv(#[from] csv::Error),
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum ReportFormat {
+    #[default]
+    Csv,
+    Json,
+}
+
+impl ReportFormat {
+    /// The usual extension of a report file.
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ReportFormat::Csv => "csv",
+            ReportFormat::Json => "json",
+        }
+    }
+}
+
+/// What renaming would do to a file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Status {
+    Unchanged,
+    Rename,
+    /// The new name is taken, see [batch::collisions].
+    Conflict,
+}
+
+/// A line of the report.
+/// - `conflict` - Whether the new name is taken, by `conflict_with` or, if that is
+///   empty, by a file outside the batch.
+/// - `errors` - The steps that couldn't be applied, see [File::errors], separated by `; `.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ReportRow {
+    pub original: PathBuf,
+    pub new: PathBuf,
+    pub status: Status,
+    pub conflict: bool,
+    pub conflict_with: Option<PathBuf>,
+    pub errors: String,
+}
+
+/// A row per file of a previewed batch, in batch order.
+pub fn rows<'a, I: IntoIterator<Item = &'a File>>(files: I) -> Vec<ReportRow> {
+    let files = files.into_iter().collect::<Vec<_>>();
+    let renames = files
+        .iter()
+        .map(|file| (file.original().to_path_buf(), file.new_path()))
+        .collect::<Vec<_>>();
+    let collisions = batch::collisions(&renames)
+        .into_iter()
+        .map(|collision| (collision.original, collision.other))
+        .collect::<HashMap<_, _>>();
+    files
+        .into_iter()
+        .zip(renames)
+        .map(|(file, (original, new))| {
+            let conflict = collisions.get(&original);
+            let status = match conflict {
+                Some(_) => Status::Conflict,
+                None if original == new => Status::Unchanged,
+                None => Status::Rename,
+            };
+            ReportRow {
+                conflict: conflict.is_some(),
+                conflict_with: conflict.cloned().flatten(),
+                errors: file.errors().join("; "),
+                original,
+                new,
+                status,
+            }
+        })
+        .collect()
+}
+
+/// Write the rows as CSV, with a header line, or as a JSON array.
+///
+/// # Example
+///
+/// ```
+/// # use std::path::Path;
+/// # use mass_renamer_core::{File, report::{rows, write, ReportFormat}};
+/// let mut file = File::new(Path::new("/d/a.txt")).unwrap();
+/// file.set_name("b.txt");
+/// let mut out = vec![];
+/// write(&rows([&file]), ReportFormat::Csv, &mut out).unwrap();
+/// assert_eq!(
+///     String::from_utf8(out).unwrap(),
+///     "original,new,status,conflict,conflict_with,errors\n/d/a.txt,/d/b.txt,rename,false,,\n"
+/// );
+/// ```
+pub fn write<W: Write>(
+    rows: &[ReportRow],
+    format: ReportFormat,
+    out: W,
+) -> Result<(), ReportError> {
+    match format {
+        ReportFormat::Csv => {
+            let mut writer = csv::Writer::from_writer(out);
+            for row in rows {
+                writer.serialize(row)?;
+            }
+            writer.flush()?;
+        }
+        ReportFormat::Json => serde_json::to_writer_pretty(out, rows)?,
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod report_tests {
+    use super::*;
+    use std::path::Path;
+
+    #[test]
+    fn conflicts_flagged() {
+        let mut files =
+            ["/d/a.txt", "/d/b.txt", "/d/c.txt"].map(|path| File::new(Path::new(path)).unwrap());
+        files[0].set_name("x");
+        files[1].set_name("x");
+        let rows = rows(&files);
+        let status = rows.iter().map(|row| row.status).collect::<Vec<_>>();
+        assert_eq!(
+            status,
+            [Status::Conflict, Status::Conflict, Status::Unchanged]
+        );
+        assert_eq!(rows[0].conflict_with, Some(PathBuf::from("/d/b.txt")));
+        let mut out = vec![];
+        write(&rows[2..], ReportFormat::Json, &mut out).unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&out).unwrap();
+        assert_eq!(json[0]["status"], "unchanged");
+        assert_eq!(json[0]["conflict_with"], serde_json::Value::Null);
+    }
+}
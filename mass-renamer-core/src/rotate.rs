@@ -0,0 +1,138 @@
+//! Swap extensions between files of the same name, e.g. `.yml` and `.yaml` across a tree,
+//! or rotate them through a longer list. Every file moves to a name another file of the
+//! plan leaves, so [crate::batch::rename] can make it without a clash.
+
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+
+/// Give each file with one of the `extensions` the next one, the last going back to the
+/// first. Two extensions swap. Compared without case, and without the dot.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rotation {
+    pub extensions: Vec<String>,
+    /// Only rename files whose name has every extension next to it, e.g. both
+    /// `config.yml` and `config.yaml`. Otherwise a lone `.yml` file becomes `.yaml`.
+    pub complete: bool,
+}
+
+impl Rotation {
+    /// Read the extensions from text separated by spaces or commas, e.g. `yml yaml`.
+    pub fn new(extensions: &str, complete: bool) -> Self {
+        let extensions = extensions
+            .split([' ', ','])
+            .map(|ext| ext.trim().trim_start_matches('.'))
+            .filter(|ext| !ext.is_empty())
+            .map(String::from)
+            .collect();
+        Self {
+            extensions,
+            complete,
+        }
+    }
+
+    /// Which of the extensions `path` has.
+    fn position(&self, path: &Path) -> Option<usize> {
+        let ext = path.extension()?.to_string_lossy();
+        self.extensions
+            .iter()
+            .position(|other| other.eq_ignore_ascii_case(&ext))
+    }
+
+    /// The `(from, to)` paths of the `files` to rename, in the order given.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use std::path::PathBuf;
+    /// # use mass_renamer_core::rotate::Rotation;
+    /// let files = ["/d/a.yml", "/d/a.yaml", "/d/b.yml"].map(PathBuf::from);
+    /// let plan = Rotation::new("yml yaml", true).plan(&files);
+    /// assert_eq!(plan[0], (files[0].clone(), PathBuf::from("/d/a.yaml")));
+    /// assert_eq!(plan[1], (files[1].clone(), PathBuf::from("/d/a.yml")));
+    /// assert_eq!(plan.len(), 2);
+    /// ```
+    pub fn plan<P: AsRef<Path>>(&self, files: &[P]) -> Vec<(PathBuf, PathBuf)> {
+        // Nothing rotates through fewer than two extensions, or the same one twice.
+        let mut distinct = self
+            .extensions
+            .iter()
+            .map(|ext| ext.to_lowercase())
+            .collect::<Vec<_>>();
+        distinct.sort();
+        distinct.dedup();
+        if distinct.len() < 2 || distinct.len() != self.extensions.len() {
+            return vec![];
+        }
+        let mut stems: HashMap<PathBuf, Vec<(usize, &Path)>> = HashMap::new();
+        for file in files {
+            let file = file.as_ref();
+            if let Some(idx) = self.position(file) {
+                stems
+                    .entry(file.with_extension(""))
+                    .or_default()
+                    .push((idx, file));
+            }
+        }
+        files
+            .iter()
+            .map(AsRef::as_ref)
+            .filter_map(|file| {
+                let idx = self.position(file)?;
+                let siblings = &stems[&file.with_extension("")];
+                if self.complete && siblings.len() < self.extensions.len() {
+                    return None;
+                }
+                let next = (idx + 1) % self.extensions.len();
+                // Take the name of the file leaving it as it's spelled, whatever its case.
+                let to = match siblings.iter().find(|(other, _)| *other == next) {
+                    Some((_, sibling)) => sibling.to_path_buf(),
+                    None => file.with_extension(&self.extensions[next]),
+                };
+                Some((file.to_path_buf(), to))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod rotate_tests {
+    use super::*;
+    use crate::batch;
+    use crate::tester::temp_folder;
+    use std::fs;
+
+    #[test]
+    fn rotate_through_three() {
+        let dir = temp_folder("rotate");
+        for (name, text) in [
+            ("a.jpg", "1"),
+            ("a.jpeg", "2"),
+            ("a.jpe", "3"),
+            ("b.jpg", "4"),
+        ] {
+            fs::write(dir.join(name), text).unwrap();
+        }
+        let files = ["a.jpg", "a.jpeg", "a.jpe", "b.jpg"].map(|name| dir.join(name));
+        let every = Rotation::new("jpg, .JPEG, jpe", false).plan(&files);
+        let complete = Rotation::new("jpg jpeg jpe", true).plan(&files);
+        let results = batch::rename(&complete);
+        let texts = ["a.jpeg", "a.jpe", "a.jpg", "b.jpg"]
+            .map(|name| fs::read_to_string(dir.join(name)).unwrap_or_default());
+        fs::remove_dir_all(&dir).ok();
+        assert_eq!(every.len(), 4);
+        assert_eq!(every[0].1, dir.join("a.jpeg"));
+        assert_eq!(every[3].1, dir.join("b.JPEG"));
+        assert_eq!(complete.len(), 3);
+        assert!(results.iter().all(Result::is_ok));
+        assert_eq!(texts, ["1", "2", "3", "4"]);
+    }
+
+    #[test]
+    fn repeated_extensions() {
+        let files = ["a.yml", "a.yaml"];
+        assert!(Rotation::new("yml YML", false).plan(&files).is_empty());
+        assert!(Rotation::new("yml", false).plan(&files).is_empty());
+    }
+}
@@ -0,0 +1,237 @@
+//! Write a batch of renames as a shell script, to review or to run where Mass Renamer
+//! isn't installed. The script makes the same moves as [crate::batch::rename]: files
+//! taking each other's names wait their turn and cycles go through a temporary name.
+
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Write,
+    path::{Path, PathBuf},
+};
+
+/// The shell a script is written for.
+/// - `Posix` - `sh`, `bash` and `zsh`, with `mv` and `cp`.
+/// - `PowerShell` - PowerShell on Windows or elsewhere, with `[System.IO.File]::Move` and
+///   `[System.IO.File]::Copy`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Shell {
+    #[default]
+    Posix,
+    PowerShell,
+}
+
+impl Shell {
+    /// The usual extension of a script file.
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Shell::Posix => "sh",
+            Shell::PowerShell => "ps1",
+        }
+    }
+
+    /// Quote a path so the shell takes it as it is, spaces, quotes and `$` included.
+    /// Paths that aren't valid UTF-8 are written lossily.
+    pub fn quote(&self, path: &Path) -> String {
+        let path = path.to_string_lossy();
+        match self {
+            Shell::Posix => format!("'{}'", path.replace('\'', r"'\''")),
+            // PowerShell also takes the typographic single quotes as quotes.
+            Shell::PowerShell => {
+                let mut quoted = String::from("'");
+                for c in path.chars() {
+                    if matches!(c, '\'' | '‘' | '’' | '‚' | '‛') {
+                        quoted.push(c);
+                    }
+                    quoted.push(c);
+                }
+                quoted.push('\'');
+                quoted
+            }
+        }
+    }
+
+    fn header(&self) -> &'static str {
+        match self {
+            Shell::Posix => "#!/bin/sh\n# Written by Mass Renamer.\nset -e\n",
+            Shell::PowerShell => "# Written by Mass Renamer.\n$ErrorActionPreference = 'Stop'\n",
+        }
+    }
+
+    fn rename(&self, from: &Path, to: &Path) -> String {
+        match self {
+            Shell::Posix => format!("mv -- {} {}", self.quote(from), self.quote(to)),
+            Shell::PowerShell => format!(
+                "[System.IO.File]::Move({}, {})",
+                self.quote(from),
+                self.quote(to)
+            ),
+        }
+    }
+
+    fn copy(&self, from: &Path, to: &Path) -> String {
+        match self {
+            Shell::Posix => format!("cp -- {} {}", self.quote(from), self.quote(to)),
+            Shell::PowerShell => format!(
+                "[System.IO.File]::Copy({}, {})",
+                self.quote(from),
+                self.quote(to)
+            ),
+        }
+    }
+}
+
+/// A script making a batch of `(from, to)` renames, one move per line. The script stops
+/// at the first move that fails.
+///
+/// ```
+/// # use mass_renamer_core::script::{script, Shell};
+/// let renames = [("/d/it's.txt", "/d/it is.txt")];
+/// assert!(script(&renames, Shell::Posix).ends_with("mv -- '/d/it'\\''s.txt' '/d/it is.txt'\n"));
+/// assert!(script(&renames, Shell::PowerShell)
+///     .ends_with("[System.IO.File]::Move('/d/it''s.txt', '/d/it is.txt')\n"));
+/// ```
+pub fn script<P: AsRef<Path>, Q: AsRef<Path>>(renames: &[(P, Q)], shell: Shell) -> String {
+    let mut script = String::from(shell.header());
+    for (from, to) in moves(renames) {
+        let line = shell.rename(&from, &to);
+        writeln!(script, "{line}").expect("Unexpected error writing string.");
+    }
+    script
+}
+
+/// A script making a batch of `(from, to)` copies, one per line, leaving the originals
+/// where they are as [crate::batch::copy_watched] does. Copies never take each other's
+/// place, so they are made in order. The script stops at the first copy that fails.
+///
+/// ```
+/// # use mass_renamer_core::script::{copy_script, Shell};
+/// let copies = [("/d/a.txt", "/e/a.txt")];
+/// assert!(copy_script(&copies, Shell::Posix).ends_with("cp -- '/d/a.txt' '/e/a.txt'\n"));
+/// assert!(copy_script(&copies, Shell::PowerShell)
+///     .ends_with("[System.IO.File]::Copy('/d/a.txt', '/e/a.txt')\n"));
+/// ```
+pub fn copy_script<P: AsRef<Path>, Q: AsRef<Path>>(copies: &[(P, Q)], shell: Shell) -> String {
+    let mut script = String::from(shell.header());
+    for (from, to) in copies {
+        let (from, to) = (from.as_ref(), to.as_ref());
+        if from == to {
+            continue;
+        }
+        let line = shell.copy(from, to);
+        writeln!(script, "{line}").expect("Unexpected error writing string.");
+    }
+    script
+}
+
+/// The moves making a batch of renames, in an order that never moves a file onto
+/// another of the batch. As [crate::batch::rename_guarded], without touching the disk.
+fn moves<P: AsRef<Path>, Q: AsRef<Path>>(renames: &[(P, Q)]) -> Vec<(PathBuf, PathBuf)> {
+    let mut moves = vec![];
+    let mut current = renames
+        .iter()
+        .map(|(from, _)| from.as_ref().to_path_buf())
+        .collect::<Vec<_>>();
+    let target = |idx: usize| renames[idx].1.as_ref();
+    let mut used = renames
+        .iter()
+        .flat_map(|(from, to)| [from.as_ref().to_path_buf(), to.as_ref().to_path_buf()])
+        .collect::<HashSet<_>>();
+    let mut occupied = HashMap::new();
+    let mut waiting: HashMap<PathBuf, Vec<usize>> = HashMap::new();
+    let mut ready = vec![];
+    for (idx, from) in current.iter().enumerate() {
+        if from != target(idx) {
+            occupied.insert(from.clone(), idx);
+        }
+    }
+    for idx in (0..renames.len()).rev() {
+        if current[idx] == target(idx) {
+            continue;
+        }
+        if occupied.contains_key(target(idx)) {
+            waiting
+                .entry(target(idx).to_path_buf())
+                .or_default()
+                .push(idx);
+        } else {
+            ready.push(idx);
+        }
+    }
+    loop {
+        while let Some(idx) = ready.pop() {
+            occupied.remove(&current[idx]);
+            moves.push((current[idx].clone(), target(idx).to_path_buf()));
+            if let Some(next) = waiting.remove(&current[idx]) {
+                ready.extend(next);
+            }
+        }
+        // Everyone left is waiting on someone else, so they form cycles. The first file
+        // of the batch breaks its cycle, so the same batch always gives the same script.
+        let Some(idx) = waiting.values().flatten().min().copied() else {
+            break;
+        };
+        let from = current[idx].clone();
+        let name = from
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let temp = (0..)
+            .map(|n| from.with_file_name(format!(".{name}.mass-renamer-{n}")))
+            .find(|temp| !used.contains(temp))
+            .expect("Ran out of temporary names.");
+        used.insert(temp.clone());
+        occupied.remove(&from);
+        occupied.insert(temp.clone(), idx);
+        moves.push((from.clone(), temp.clone()));
+        current[idx] = temp;
+        if let Some(next) = waiting.remove(&from) {
+            ready.extend(next);
+        }
+    }
+    moves
+}
+
+#[cfg(test)]
+mod script_tests {
+    use super::*;
+
+    #[test]
+    fn chain_and_swap() {
+        let renames = [
+            ("d/a", "d/b"),
+            ("d/b", "d/c"),
+            ("d/x", "d/y"),
+            ("d/y", "d/x"),
+            ("d/same", "d/same"),
+        ];
+        let moves = moves(&renames)
+            .into_iter()
+            .map(|(from, to)| format!("{} {}", from.display(), to.display()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            moves,
+            [
+                "d/b d/c",
+                "d/a d/b",
+                "d/x d/.x.mass-renamer-0",
+                "d/y d/x",
+                "d/.x.mass-renamer-0 d/y",
+            ]
+        );
+    }
+
+    #[test]
+    fn copies_keep_their_order() {
+        let copies = [("d/a", "d/b"), ("d/b", "d/c"), ("d/same", "d/same")];
+        let script = copy_script(&copies, Shell::Posix);
+        let lines = script.lines().skip(3).collect::<Vec<_>>();
+        assert_eq!(lines, ["cp -- 'd/a' 'd/b'", "cp -- 'd/b' 'd/c'"]);
+        assert!(!script.contains("mv "));
+    }
+
+    #[test]
+    fn quoting() {
+        let path = Path::new("$HOME `a` ‘b’.txt");
+        assert_eq!(Shell::Posix.quote(path), "'$HOME `a` ‘b’.txt'");
+        assert_eq!(Shell::PowerShell.quote(path), "'$HOME `a` ‘‘b’’.txt'");
+    }
+}
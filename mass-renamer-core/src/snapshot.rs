@@ -0,0 +1,232 @@
+//! What a folder held before and after a batch, to check that the batch did what it was
+//! expected to and nothing else changed on disk while it ran.
+
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    fmt::Display,
+    fs, io,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+/// The entries directly in a folder at one moment, with their size and when they were
+/// last modified.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Snapshot {
+    folder: PathBuf,
+    entries: BTreeMap<PathBuf, Entry>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Entry {
+    size: u64,
+    modified: Option<SystemTime>,
+    /// Tells entries apart whatever their name, where the platform has it.
+    id: Option<u64>,
+}
+
+/// A change on disk the batch wasn't expected to make.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Change {
+    Added(PathBuf),
+    Removed(PathBuf),
+    /// The size or modified time changed, e.g. by a program writing the file.
+    Modified(PathBuf),
+}
+
+impl Display for Change {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Change::Added(path) => write!(f, "{} was added", path.display()),
+            Change::Removed(path) => write!(f, "{} was removed", path.display()),
+            Change::Modified(path) => write!(f, "{} was modified", path.display()),
+        }
+    }
+}
+
+/// How a folder changed compared to the renames expected of a batch.
+/// - `renamed` - The `(from, to)` paths expected and found.
+/// - `missed` - The `(from, to)` paths expected but not found, e.g. failed renames.
+/// - `unexpected` - Every other change.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Comparison {
+    pub renamed: Vec<(PathBuf, PathBuf)>,
+    pub missed: Vec<(PathBuf, PathBuf)>,
+    pub unexpected: Vec<Change>,
+}
+
+impl Comparison {
+    /// Whether the folder changed exactly as expected.
+    pub fn as_expected(&self) -> bool {
+        self.missed.is_empty() && self.unexpected.is_empty()
+    }
+}
+
+impl Snapshot {
+    pub fn take<P: AsRef<Path>>(folder: P) -> io::Result<Self> {
+        let folder = folder.as_ref().to_path_buf();
+        let mut entries = BTreeMap::new();
+        for entry in fs::read_dir(&folder)? {
+            let entry = entry?;
+            // Gone since the folder was read.
+            let Ok(meta) = entry.path().symlink_metadata() else {
+                continue;
+            };
+            let entry_info = Entry {
+                size: if meta.is_dir() { 0 } else { meta.len() },
+                modified: meta.modified().ok(),
+                id: file_id(&meta),
+            };
+            entries.insert(entry.path(), entry_info);
+        }
+        Ok(Self { folder, entries })
+    }
+
+    pub fn folder(&self) -> &Path {
+        &self.folder
+    }
+
+    /// Compare with a snapshot of the same folder taken after renaming the `(from, to)`
+    /// paths. Only the side of a rename in this folder is checked, renames between other
+    /// folders are left out.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use std::fs;
+    /// # use mass_renamer_core::snapshot::{Change, Snapshot};
+    /// let dir = std::env::temp_dir().join(format!("snapshot_doc_{}", std::process::id()));
+    /// fs::create_dir_all(&dir).unwrap();
+    /// fs::write(dir.join("a.txt"), "").unwrap();
+    /// let before = Snapshot::take(&dir).unwrap();
+    /// fs::rename(dir.join("a.txt"), dir.join("b.txt")).unwrap();
+    /// fs::write(dir.join("c.txt"), "").unwrap();
+    /// let after = Snapshot::take(&dir).unwrap();
+    /// fs::remove_dir_all(&dir).ok();
+    /// let expected = [(dir.join("a.txt"), dir.join("b.txt"))];
+    /// let comparison = before.compare(&after, &expected);
+    /// assert_eq!(comparison.renamed.len(), 1);
+    /// assert_eq!(comparison.unexpected, [Change::Added(dir.join("c.txt"))]);
+    /// ```
+    pub fn compare(&self, after: &Snapshot, renames: &[(PathBuf, PathBuf)]) -> Comparison {
+        let here = |path: &Path| path.parent() == Some(self.folder.as_path());
+        let mut comparison = Comparison::default();
+        // The paths the expected renames account for.
+        let mut explained = HashSet::new();
+        // The entry each path held before, once renamed.
+        let mut moved = HashMap::new();
+        let renames = renames
+            .iter()
+            .filter(|(from, to)| from != to)
+            .collect::<Vec<_>>();
+        let targets = renames.iter().map(|(_, to)| to).collect::<HashSet<_>>();
+        for (from, to) in renames {
+            let (from_here, to_here) = (here(from), here(to));
+            if !from_here && !to_here {
+                continue;
+            }
+            // Something is still at `from` when files swap names.
+            let left = !from_here
+                || self.entries.contains_key(from)
+                    && (!after.entries.contains_key(from)
+                        || targets.contains(from)
+                        || renames_to(from, to));
+            let arrived = match (after.entries.get(to), self.entries.get(from)) {
+                _ if !to_here => true,
+                (Some(now), Some(was)) if now.id.is_some() => now.id == was.id,
+                (now, _) => now.is_some(),
+            };
+            if !(left && arrived) {
+                comparison.missed.push((from.clone(), to.clone()));
+                continue;
+            }
+            comparison.renamed.push((from.clone(), to.clone()));
+            if from_here {
+                explained.insert(from);
+            }
+            if to_here {
+                explained.insert(to);
+            }
+            if let (true, Some(entry)) = (to_here, self.entries.get(from)) {
+                moved.insert(to, *entry);
+            }
+        }
+        for (path, entry) in &after.entries {
+            let before = moved.get(path).or_else(|| match explained.contains(path) {
+                // Taken over by a file renamed to it.
+                true => None,
+                false => self.entries.get(path),
+            });
+            match before {
+                None if !explained.contains(path) => {
+                    comparison.unexpected.push(Change::Added(path.clone()))
+                }
+                Some(before) if before != entry => {
+                    comparison.unexpected.push(Change::Modified(path.clone()))
+                }
+                _ => (),
+            }
+        }
+        for path in self.entries.keys() {
+            if !after.entries.contains_key(path) && !explained.contains(path) {
+                comparison.unexpected.push(Change::Removed(path.clone()));
+            }
+        }
+        comparison
+    }
+}
+
+/// Whether the rename only changes the case, so the same entry is found at both paths on
+/// case-insensitive file systems.
+fn renames_to(from: &Path, to: &Path) -> bool {
+    from.to_string_lossy().to_lowercase() == to.to_string_lossy().to_lowercase()
+}
+
+#[cfg(unix)]
+fn file_id(meta: &fs::Metadata) -> Option<u64> {
+    use std::os::unix::fs::MetadataExt;
+    Some(meta.ino())
+}
+
+#[cfg(not(unix))]
+fn file_id(_meta: &fs::Metadata) -> Option<u64> {
+    None
+}
+
+#[cfg(test)]
+mod snapshot_tests {
+    use super::*;
+    use crate::tester::temp_folder;
+
+    #[test]
+    fn unexpected_changes() {
+        let dir = temp_folder("snapshot");
+        for name in ["a", "b", "kept", "written", "deleted"] {
+            fs::write(dir.join(name), "").unwrap();
+        }
+        let before = Snapshot::take(&dir).unwrap();
+        // a and b swap names, c fails, and other programs are busy meanwhile.
+        fs::rename(dir.join("a"), dir.join("temp")).unwrap();
+        fs::rename(dir.join("b"), dir.join("a")).unwrap();
+        fs::rename(dir.join("temp"), dir.join("b")).unwrap();
+        fs::write(dir.join("written"), "more").unwrap();
+        fs::remove_file(dir.join("deleted")).unwrap();
+        fs::write(dir.join("added"), "").unwrap();
+        let after = Snapshot::take(&dir).unwrap();
+        fs::remove_dir_all(&dir).ok();
+        let renames = [("a", "b"), ("b", "a"), ("c", "d"), ("kept", "kept")]
+            .map(|(from, to)| (dir.join(from), dir.join(to)));
+        let comparison = before.compare(&after, &renames);
+        assert_eq!(comparison.renamed, renames[..2]);
+        assert_eq!(comparison.missed, renames[2..3]);
+        assert_eq!(
+            comparison.unexpected,
+            [
+                Change::Added(dir.join("added")),
+                Change::Modified(dir.join("written")),
+                Change::Removed(dir.join("deleted")),
+            ]
+        );
+        assert!(!comparison.as_expected());
+    }
+}
@@ -0,0 +1,226 @@
+//! Custom sort orders written as an expression, e.g. `extension, then number desc`, so
+//! the order files are numbered in can be saved and used again on another machine.
+//!
+//! An expression is a list of columns separated by commas, each optionally followed by
+//! `asc` or `desc`. Later columns break ties in earlier ones, and the name breaks any
+//! that are left. A leading `then` is allowed for readability.
+//!
+//! ```
+//! # use mass_renamer_core::sort::{SortColumn, SortExpression};
+//! let expression: SortExpression = "extension, then number desc".parse().unwrap();
+//! assert!(expression.uses(SortColumn::Number));
+//! assert_eq!(expression.to_string(), "extension, number desc");
+//! ```
+
+use std::{cmp::Ordering, fmt, path::Path, str::FromStr};
+
+use chrono::{DateTime, Local};
+use thiserror::Error;
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum SortError {
+    #[error("Unknown sort column \"{0}\".")]
+    Column(String),
+    #[error("Expected asc or desc after {0}, found \"{1}\".")]
+    Direction(String, String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortColumn {
+    Name,
+    Stem,
+    Extension,
+    Size,
+    Modified,
+    Created,
+    /// When the photo was taken or the song recorded.
+    Captured,
+    /// The number the stem ends with, e.g. 12 in `IMG_12`.
+    Number,
+}
+
+impl SortColumn {
+    const ALL: [(SortColumn, &'static str); 8] = [
+        (SortColumn::Name, "name"),
+        (SortColumn::Stem, "stem"),
+        (SortColumn::Extension, "extension"),
+        (SortColumn::Size, "size"),
+        (SortColumn::Modified, "modified"),
+        (SortColumn::Created, "created"),
+        (SortColumn::Captured, "captured"),
+        (SortColumn::Number, "number"),
+    ];
+}
+
+impl fmt::Display for SortColumn {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (_, word) = SortColumn::ALL
+            .iter()
+            .find(|(column, _)| column == self)
+            .expect("every column has a word");
+        write!(f, "{word}")
+    }
+}
+
+impl FromStr for SortColumn {
+    type Err = SortError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let word = match s.to_lowercase().as_str() {
+            "ext" | "type" => String::from("extension"),
+            word => word.to_owned(),
+        };
+        SortColumn::ALL
+            .iter()
+            .find(|(_, name)| *name == word)
+            .map(|(column, _)| *column)
+            .ok_or_else(|| SortError::Column(s.to_owned()))
+    }
+}
+
+/// One column of a [SortExpression].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SortKey {
+    pub column: SortColumn,
+    pub descending: bool,
+}
+
+/// What a file is sorted on.
+#[derive(Debug, Clone, Copy)]
+pub struct SortFields<'a> {
+    pub path: &'a Path,
+    pub size: Option<u64>,
+    pub modified: Option<DateTime<Local>>,
+    pub created: Option<DateTime<Local>>,
+    pub captured: Option<DateTime<Local>>,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct SortExpression {
+    pub keys: Vec<SortKey>,
+}
+
+impl SortExpression {
+    /// Whether the expression sorts on the column.
+    pub fn uses(&self, column: SortColumn) -> bool {
+        self.keys.iter().any(|key| key.column == column)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    /// Compare two files column by column, then by name.
+    pub fn compare(&self, lhs: &SortFields, rhs: &SortFields) -> Ordering {
+        self.keys
+            .iter()
+            .map(|key| {
+                let order = compare_column(key.column, lhs, rhs);
+                if key.descending {
+                    order.reverse()
+                } else {
+                    order
+                }
+            })
+            .find(|order| order.is_ne())
+            .unwrap_or_else(|| lhs.path.cmp(rhs.path))
+    }
+}
+
+fn compare_column(column: SortColumn, lhs: &SortFields, rhs: &SortFields) -> Ordering {
+    match column {
+        SortColumn::Name => lhs.path.file_name().cmp(&rhs.path.file_name()),
+        SortColumn::Stem => lhs.path.file_stem().cmp(&rhs.path.file_stem()),
+        SortColumn::Extension => lhs.path.extension().cmp(&rhs.path.extension()),
+        SortColumn::Size => lhs.size.cmp(&rhs.size),
+        SortColumn::Modified => lhs.modified.cmp(&rhs.modified),
+        SortColumn::Created => lhs.created.cmp(&rhs.created),
+        SortColumn::Captured => lhs.captured.cmp(&rhs.captured),
+        SortColumn::Number => number(lhs.path).cmp(&number(rhs.path)),
+    }
+}
+
+/// The number the file's stem ends with, if it does.
+fn number(path: &Path) -> Option<u128> {
+    let stem = path.file_stem()?.to_string_lossy();
+    let digits = stem.len() - stem.trim_end_matches(|c: char| c.is_ascii_digit()).len();
+    stem[stem.len() - digits..].parse().ok()
+}
+
+impl FromStr for SortExpression {
+    type Err = SortError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut keys = vec![];
+        for part in s.split(',').map(str::trim).filter(|part| !part.is_empty()) {
+            let mut words = part.split_whitespace();
+            let mut column = words.next().unwrap_or_default();
+            if column.eq_ignore_ascii_case("then") {
+                column = words.next().unwrap_or_default();
+            }
+            let column = column.parse::<SortColumn>()?;
+            let descending = match words.next().map(str::to_lowercase).as_deref() {
+                None | Some("asc") => false,
+                Some("desc") => true,
+                Some(other) => {
+                    return Err(SortError::Direction(column.to_string(), other.to_owned()))
+                }
+            };
+            keys.push(SortKey { column, descending });
+        }
+        Ok(Self { keys })
+    }
+}
+
+impl fmt::Display for SortExpression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let keys = self
+            .keys
+            .iter()
+            .map(|key| {
+                let direction = if key.descending { " desc" } else { "" };
+                format!("{}{direction}", key.column)
+            })
+            .collect::<Vec<_>>();
+        write!(f, "{}", keys.join(", "))
+    }
+}
+
+#[cfg(test)]
+mod sort_tests {
+    use super::*;
+
+    fn fields(path: &Path) -> SortFields<'_> {
+        SortFields {
+            path,
+            size: None,
+            modified: None,
+            created: None,
+            captured: None,
+        }
+    }
+
+    #[test]
+    fn extension_then_number() {
+        let expression: SortExpression = "ext, then number".parse().unwrap();
+        let mut paths = ["b_10.jpg", "a_2.png", "b_9.jpg", "c.jpg"].map(Path::new);
+        paths.sort_by(|lhs, rhs| expression.compare(&fields(lhs), &fields(rhs)));
+        assert_eq!(
+            paths,
+            ["c.jpg", "b_9.jpg", "b_10.jpg", "a_2.png"].map(Path::new)
+        );
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert_eq!(
+            "size, colour".parse::<SortExpression>(),
+            Err(SortError::Column("colour".into()))
+        );
+        assert_eq!(
+            "size upwards".parse::<SortExpression>(),
+            Err(SortError::Direction("size".into(), "upwards".into()))
+        );
+        assert!("".parse::<SortExpression>().unwrap().is_empty());
+    }
+}
@@ -0,0 +1,71 @@
+//! Notice when other programs add, remove or rename entries in a folder, so its listing
+//! can be read again rather than renaming against entries that are gone.
+
+use std::{
+    path::{Path, PathBuf},
+    sync::mpsc::{channel, Receiver},
+};
+
+use notify::{event::ModifyKind, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+
+/// Watches the entries directly in one folder until dropped.
+pub struct FolderWatch {
+    folder: PathBuf,
+    events: Receiver<notify::Result<notify::Event>>,
+    // Stops watching when dropped.
+    _watcher: RecommendedWatcher,
+}
+
+impl FolderWatch {
+    pub fn new<P: AsRef<Path>>(folder: P) -> Result<Self, notify::Error> {
+        let (sender, events) = channel();
+        let mut watcher = notify::recommended_watcher(move |event| {
+            // The receiver is only gone once the watch is dropped.
+            sender.send(event).ok();
+        })?;
+        watcher.watch(folder.as_ref(), RecursiveMode::NonRecursive)?;
+        Ok(Self {
+            folder: folder.as_ref().to_path_buf(),
+            events,
+            _watcher: watcher,
+        })
+    }
+
+    pub fn folder(&self) -> &Path {
+        &self.folder
+    }
+
+    /// Whether entries were added, removed or renamed since the last call. Changes to
+    /// what is in the files, and reading them, don't count. Never blocks.
+    ///
+    /// ```
+    /// # use mass_renamer_core::watch::FolderWatch;
+    /// # use std::{fs, thread, time::Duration};
+    /// let dir = std::env::temp_dir().join(format!("watch_doc_{}", std::process::id()));
+    /// fs::create_dir_all(&dir).unwrap();
+    /// let watch = FolderWatch::new(&dir).unwrap();
+    /// fs::write(dir.join("new.txt"), "").unwrap();
+    /// thread::sleep(Duration::from_millis(500));
+    /// let changed = watch.changed();
+    /// fs::remove_dir_all(&dir).ok();
+    /// assert!(changed);
+    /// ```
+    pub fn changed(&self) -> bool {
+        let mut changed = false;
+        for event in self.events.try_iter() {
+            changed |= match event {
+                Ok(event) => matches!(
+                    event.kind,
+                    EventKind::Create(_)
+                        | EventKind::Remove(_)
+                        | EventKind::Modify(ModifyKind::Name(_))
+                        | EventKind::Any
+                        | EventKind::Other
+                ),
+                // Events may have been missed, so the listing can't be trusted.
+                Err(_) => true,
+            };
+        }
+        changed
+    }
+}
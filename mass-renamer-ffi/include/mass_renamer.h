@@ -0,0 +1,53 @@
+/*
+ * C ABI for the Mass Renamer pipeline.
+ *
+ * A pipeline is a JSON array of options, e.g.
+ *   [{"Replace": {"replace": "_", "with": " ", "case": true}}, {"Extension": "Lower"}]
+ * and paths are a JSON array of strings. All strings are UTF-8 and NUL terminated.
+ */
+#ifndef MASS_RENAMER_H
+#define MASS_RENAMER_H
+
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* Preview the new names. Returns a JSON array of names, or NULL on error.
+ * Free the result with mr_string_free. */
+char *mr_preview(const char *pipeline, const char *paths);
+
+/* Rename the paths. Returns a JSON array of {"from", "to", "error"} objects,
+ * or NULL if the arguments could not be read. Free the result with mr_string_free. */
+char *mr_execute(const char *pipeline, const char *paths);
+
+/* As mr_execute, also keeping each file's old name on the file (in an extended
+ * attribute, or an NTFS stream on Windows) for mr_restore. */
+char *mr_execute_keeping_originals(const char *pipeline, const char *paths);
+
+/* Rename the paths back to their names before mr_execute_keeping_originals.
+ * Returns the same JSON as mr_execute. Free the result with mr_string_free. */
+char *mr_restore(const char *paths);
+
+/* As mr_execute, also renaming the files with the same stems in the mirror of the
+ * source folder. Returns a JSON object {"outcomes": [...], "unmatched": [...]},
+ * or NULL on error. Free the result with mr_string_free. */
+char *mr_execute_mirrored(const char *pipeline, const char *paths, const char *source,
+                          const char *mirror);
+
+/* Limit every following rename call to per_second renames a second, e.g. to avoid
+ * saturating a NAS. 0, the default, removes the limit. */
+void mr_set_throttle(uint32_t per_second);
+
+/* The last error on this thread, or NULL. Owned by the library, do not free. */
+const char *mr_last_error(void);
+
+/* Free a string returned by the library. */
+void mr_string_free(char *text);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* MASS_RENAMER_H */
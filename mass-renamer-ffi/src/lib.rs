@@ -0,0 +1,480 @@
+//! C ABI, and optional Python bindings, for the Mass Renamer pipeline.
+//!
+//! A pipeline is a JSON array of [Options], applied in the order given, e.g.
+//! `[{"Replace": {"replace": "_", "with": " ", "case": true}}, {"Extension": "Lower"}]`.
+//! Files can swap or rotate names with a `Swap` entry, see [mass_renamer_core::file::SwapOptions].
+//! Renames can keep the old names on the files to [restore] them later, or be repeated
+//! in a mirror folder tree with [execute_mirrored], and be slowed down with [set_throttle].
+//! Paths are passed as a JSON array of strings.
+//!
+//! The C functions are declared in `include/mass_renamer.h`. Build with the `python`
+//! feature for a Python extension module named `mass_renamer_ffi`.
+
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    ffi::{c_char, CStr, CString, NulError},
+    path::{Path, PathBuf},
+    ptr,
+    str::Utf8Error,
+    sync::atomic::{AtomicU32, Ordering},
+};
+
+use mass_renamer_core::{
+    batch::{self, Throttle},
+    file::{File, FileError, Options},
+    mirror::Mirror,
+    original,
+};
+use serde::Serialize;
+use thiserror::Error;
+
+#[cfg(feature = "python")]
+mod python;
+
+/// Renames per second for every execute, 0 for no limit.
+static THROTTLE: AtomicU32 = AtomicU32::new(0);
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+#[derive(Debug, Error)]
+pub enum FfiError {
+    #[error("{0} is a null pointer")]
+    Null(&'static str),
+    #[error(transparent)]
+    Utf8(#[from] Utf8Error),
+    #[error(transparent)]
+    Nul(#[from] NulError),
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+    #[error(transparent)]
+    File(#[from] FileError),
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+}
+
+/// The result of renaming a single path. `error` is set if the rename failed.
+#[derive(Debug, Serialize)]
+pub struct Outcome {
+    pub from: PathBuf,
+    pub to: PathBuf,
+    pub error: Option<String>,
+}
+
+/// Read a pipeline from its JSON form.
+pub fn parse_pipeline(json: &str) -> Result<Vec<Options>, FfiError> {
+    Ok(serde_json::from_str(json)?)
+}
+
+/// Apply the pipeline to each path and return the new names. Nothing is renamed.
+pub fn preview<P: AsRef<Path>>(
+    pipeline: &[Options],
+    paths: &[P],
+) -> Result<Vec<PathBuf>, FfiError> {
+    let mut files = paths
+        .iter()
+        .map(|path| File::new(path.as_ref()))
+        .collect::<Result<Vec<_>, FileError>>()?;
+    batch::preview(&mut files, pipeline);
+    Ok(files.iter().map(File::new_path).collect())
+}
+
+/// Rename each path with the pipeline. A failure is recorded in that path's [Outcome]
+/// and does not stop the remaining renames. Files may take each other's names.
+pub fn execute<P: AsRef<Path>>(pipeline: &[Options], paths: &[P]) -> Vec<Outcome> {
+    run(pipeline, paths, false, None).0
+}
+
+/// [execute], also keeping each file's old name on the file so it can be put back with
+/// [restore], see [mass_renamer_core::original].
+pub fn execute_keeping_originals<P: AsRef<Path>>(
+    pipeline: &[Options],
+    paths: &[P],
+) -> Vec<Outcome> {
+    run(pipeline, paths, true, None).0
+}
+
+/// The result of [execute_mirrored].
+#[derive(Debug, Serialize)]
+pub struct MirrorOutcome {
+    /// An [Outcome] per path, followed by one per renamed mirror file.
+    pub outcomes: Vec<Outcome>,
+    /// Paths with no matching mirror file, and mirror files with no matching path.
+    pub unmatched: Vec<PathBuf>,
+}
+
+/// [execute], also giving the files with the same stems in the `mirror` of the `source`
+/// tree the same new names, see [mass_renamer_core::mirror].
+pub fn execute_mirrored<P: AsRef<Path>>(
+    pipeline: &[Options],
+    paths: &[P],
+    source: &Path,
+    mirror: &Path,
+) -> Result<MirrorOutcome, FfiError> {
+    let mirror = Mirror::read(source, mirror)?;
+    let (outcomes, unmatched) = run(pipeline, paths, false, Some(&mirror));
+    Ok(MirrorOutcome {
+        outcomes,
+        unmatched,
+    })
+}
+
+/// Limit every following execute to `per_second` renames a second, e.g. for a NAS.
+/// 0 (the default) removes the limit.
+pub fn set_throttle(per_second: u32) {
+    THROTTLE.store(per_second, Ordering::Relaxed);
+}
+
+fn throttle() -> Throttle {
+    Throttle {
+        per_second: THROTTLE.load(Ordering::Relaxed),
+    }
+}
+
+/// Rename each path back to the name it had before its last rename by [execute_keeping_originals].
+pub fn restore<P: AsRef<Path>>(paths: &[P]) -> Vec<Outcome> {
+    let mut pace = throttle().pace();
+    paths
+        .iter()
+        .map(|path| {
+            let from = path.as_ref().to_path_buf();
+            pace.wait();
+            match original::restore(&from) {
+                Ok(to) => Outcome {
+                    from,
+                    to,
+                    error: None,
+                },
+                Err(err) => Outcome {
+                    to: from.clone(),
+                    from,
+                    error: Some(err.to_string()),
+                },
+            }
+        })
+        .collect()
+}
+
+fn run<P: AsRef<Path>>(
+    pipeline: &[Options],
+    paths: &[P],
+    keep_originals: bool,
+    mirror: Option<&Mirror>,
+) -> (Vec<Outcome>, Vec<PathBuf>) {
+    let (mut froms, mut files, mut outcomes) = (vec![], vec![], vec![]);
+    for path in paths {
+        let from = path.as_ref().to_path_buf();
+        match File::try_from(from.as_path()) {
+            Ok(file) => {
+                froms.push(from);
+                files.push(file);
+            }
+            Err(err) => outcomes.push(Outcome {
+                to: from.clone(),
+                from,
+                error: Some(err.to_string()),
+            }),
+        }
+    }
+    batch::preview(&mut files, pipeline);
+    let mut renames = froms
+        .into_iter()
+        .zip(files.iter().map(File::new_path))
+        .collect::<Vec<_>>();
+    let plan = mirror
+        .map(|mirror| mirror.plan(&renames))
+        .unwrap_or_default();
+    renames.extend(plan.renames);
+    let results = if keep_originals {
+        original::rename_throttled(&renames, throttle())
+    } else {
+        batch::rename_throttled(&renames, throttle())
+    };
+    for ((from, to), result) in renames.iter().zip(results) {
+        outcomes.push(match result {
+            Ok(()) => Outcome {
+                from: from.clone(),
+                to: to.clone(),
+                error: None,
+            },
+            Err(err) => Outcome {
+                from: from.clone(),
+                to: from.clone(),
+                error: Some(err.to_string()),
+            },
+        });
+    }
+    // Back in the order the paths were given, with the mirror files last.
+    let mut positions = HashMap::new();
+    for (idx, path) in paths.iter().enumerate() {
+        positions.entry(path.as_ref()).or_insert(idx);
+    }
+    outcomes.sort_by_key(|outcome| {
+        let position = positions.get(outcome.from.as_path()).copied();
+        (position.is_none(), position)
+    });
+    (outcomes, plan.unmatched)
+}
+
+/// Preview the new names for a JSON array of paths.
+/// Returns a JSON array of the new names, or null on error (see [mr_last_error]).
+/// Free the returned string with [mr_string_free].
+///
+/// # Safety
+///
+/// `pipeline` and `paths` must be null or point to NUL terminated strings.
+#[no_mangle]
+pub unsafe extern "C" fn mr_preview(pipeline: *const c_char, paths: *const c_char) -> *mut c_char {
+    respond(|| {
+        let pipeline = parse_pipeline(read(pipeline, "pipeline")?)?;
+        let paths: Vec<PathBuf> = serde_json::from_str(read(paths, "paths")?)?;
+        Ok(serde_json::to_string(&preview(&pipeline, &paths)?)?)
+    })
+}
+
+/// Rename a JSON array of paths.
+/// Returns a JSON array of `{"from", "to", "error"}` objects, or null if the arguments
+/// could not be read (see [mr_last_error]). Free the returned string with [mr_string_free].
+///
+/// # Safety
+///
+/// `pipeline` and `paths` must be null or point to NUL terminated strings.
+#[no_mangle]
+pub unsafe extern "C" fn mr_execute(pipeline: *const c_char, paths: *const c_char) -> *mut c_char {
+    respond(|| {
+        let pipeline = parse_pipeline(read(pipeline, "pipeline")?)?;
+        let paths: Vec<PathBuf> = serde_json::from_str(read(paths, "paths")?)?;
+        Ok(serde_json::to_string(&execute(&pipeline, &paths))?)
+    })
+}
+
+/// [mr_execute], also keeping each file's old name on the file for [mr_restore].
+///
+/// # Safety
+///
+/// `pipeline` and `paths` must be null or point to NUL terminated strings.
+#[no_mangle]
+pub unsafe extern "C" fn mr_execute_keeping_originals(
+    pipeline: *const c_char,
+    paths: *const c_char,
+) -> *mut c_char {
+    respond(|| {
+        let pipeline = parse_pipeline(read(pipeline, "pipeline")?)?;
+        let paths: Vec<PathBuf> = serde_json::from_str(read(paths, "paths")?)?;
+        Ok(serde_json::to_string(&execute_keeping_originals(
+            &pipeline, &paths,
+        ))?)
+    })
+}
+
+/// Rename a JSON array of paths back to their names before [mr_execute_keeping_originals].
+/// Returns the same `{"from", "to", "error"}` objects as [mr_execute].
+///
+/// # Safety
+///
+/// `paths` must be null or point to a NUL terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn mr_restore(paths: *const c_char) -> *mut c_char {
+    respond(|| {
+        let paths: Vec<PathBuf> = serde_json::from_str(read(paths, "paths")?)?;
+        Ok(serde_json::to_string(&restore(&paths))?)
+    })
+}
+
+/// [mr_execute], also renaming the files with the same stems in the `mirror` of the
+/// `source` folder. Returns a JSON object with the `outcomes` and the `unmatched` paths,
+/// or null on error (see [mr_last_error]). Free the returned string with [mr_string_free].
+///
+/// # Safety
+///
+/// All arguments must be null or point to NUL terminated strings.
+#[no_mangle]
+pub unsafe extern "C" fn mr_execute_mirrored(
+    pipeline: *const c_char,
+    paths: *const c_char,
+    source: *const c_char,
+    mirror: *const c_char,
+) -> *mut c_char {
+    respond(|| {
+        let pipeline = parse_pipeline(read(pipeline, "pipeline")?)?;
+        let paths: Vec<PathBuf> = serde_json::from_str(read(paths, "paths")?)?;
+        let (source, mirror) = (read(source, "source")?, read(mirror, "mirror")?);
+        Ok(serde_json::to_string(&execute_mirrored(
+            &pipeline,
+            &paths,
+            Path::new(source),
+            Path::new(mirror),
+        )?)?)
+    })
+}
+
+/// Limit every following rename call to `per_second` renames a second, 0 for no limit.
+#[no_mangle]
+pub extern "C" fn mr_set_throttle(per_second: u32) {
+    set_throttle(per_second);
+}
+
+/// The message of the last error on this thread, or null if there was none.
+/// The string is owned by the library and valid until the next call on this thread.
+#[no_mangle]
+pub extern "C" fn mr_last_error() -> *const c_char {
+    LAST_ERROR.with(|last| {
+        last.borrow()
+            .as_ref()
+            .map_or(ptr::null(), |err| err.as_ptr())
+    })
+}
+
+/// Free a string returned by the library.
+///
+/// # Safety
+///
+/// `text` must be null or a string returned by the library that has not already been freed.
+#[no_mangle]
+pub unsafe extern "C" fn mr_string_free(text: *mut c_char) {
+    if !text.is_null() {
+        drop(CString::from_raw(text));
+    }
+}
+
+unsafe fn read<'a>(text: *const c_char, name: &'static str) -> Result<&'a str, FfiError> {
+    if text.is_null() {
+        return Err(FfiError::Null(name));
+    }
+    Ok(CStr::from_ptr(text).to_str()?)
+}
+
+fn respond<F: FnOnce() -> Result<String, FfiError>>(call: F) -> *mut c_char {
+    let result = call().and_then(|json| Ok(CString::new(json)?));
+    LAST_ERROR.with(|last| match result {
+        Ok(json) => {
+            *last.borrow_mut() = None;
+            json.into_raw()
+        }
+        Err(err) => {
+            *last.borrow_mut() = CString::new(err.to_string()).ok();
+            ptr::null_mut()
+        }
+    })
+}
+
+#[cfg(test)]
+mod ffi_tests {
+    use super::*;
+    use std::fs;
+
+    const PIPELINE: &str = r#"[
+        {"Replace": {"replace": "_", "with": " ", "case": true}},
+        {"Extension": "Lower"}
+    ]"#;
+
+    #[test]
+    fn preview_from_json() {
+        let pipeline = parse_pipeline(PIPELINE).unwrap();
+        let names = preview(&pipeline, &["photos/my_cat.JPG", "a_b_c.TXT"]).unwrap();
+        assert_eq!(
+            names,
+            vec![
+                PathBuf::from("photos/my cat.jpg"),
+                PathBuf::from("a b c.txt")
+            ]
+        );
+    }
+
+    #[test]
+    fn execute_renames_and_reports_missing() {
+        fs::File::create("ffi_execute_test.TXT").unwrap();
+        let pipeline = parse_pipeline(PIPELINE).unwrap();
+        let outcomes = execute(&pipeline, &["ffi_execute_test.TXT", "ffi_missing.TXT"]);
+        let renamed = Path::new("ffi execute test.txt");
+        let exists = renamed.exists();
+        fs::remove_file(renamed).ok();
+        assert!(exists);
+        assert_eq!(outcomes[0].to, renamed);
+        assert!(outcomes[0].error.is_none());
+        assert_eq!(outcomes[1].to, PathBuf::from("ffi_missing.TXT"));
+        assert!(outcomes[1].error.is_some());
+    }
+
+    #[test]
+    fn execute_swaps_names() {
+        fs::write("ffi_swap_a.txt", "a").unwrap();
+        fs::write("ffi_swap_b.txt", "b").unwrap();
+        let pipeline =
+            parse_pipeline(r#"[{"Swap": {"exp": "^ffi_swap_", "mode": "Swap"}}]"#).unwrap();
+        let outcomes = execute(&pipeline, &["ffi_swap_a.txt", "ffi_swap_b.txt"]);
+        let contents = ["ffi_swap_a.txt", "ffi_swap_b.txt"].map(fs::read_to_string);
+        fs::remove_file("ffi_swap_a.txt").ok();
+        fs::remove_file("ffi_swap_b.txt").ok();
+        assert!(outcomes.iter().all(|outcome| outcome.error.is_none()));
+        assert_eq!(outcomes[0].to, PathBuf::from("ffi_swap_b.txt"));
+        assert_eq!(contents.map(Result::unwrap), ["b", "a"]);
+    }
+
+    #[test]
+    fn restore_kept_original() {
+        fs::File::create("ffi_keep_test.TXT").unwrap();
+        let pipeline = parse_pipeline(PIPELINE).unwrap();
+        let renamed = execute_keeping_originals(&pipeline, &["ffi_keep_test.TXT"]);
+        let restored = restore(&["ffi keep test.txt"]);
+        let exists = Path::new("ffi_keep_test.TXT").exists();
+        fs::remove_file("ffi_keep_test.TXT").ok();
+        fs::remove_file("ffi keep test.txt").ok();
+        assert!(renamed[0].error.is_none());
+        assert!(restored[0].error.is_none());
+        assert_eq!(restored[0].to, PathBuf::from("ffi_keep_test.TXT"));
+        assert!(exists);
+    }
+
+    #[test]
+    fn execute_in_mirror() {
+        let (source, mirror) = (Path::new("ffi_mirror_jpg"), Path::new("ffi_mirror_raw"));
+        for dir in [source, mirror] {
+            fs::create_dir_all(dir).unwrap();
+        }
+        fs::write(source.join("my_photo.JPG"), "").unwrap();
+        fs::write(mirror.join("my_photo.CR2"), "").unwrap();
+        fs::write(mirror.join("other.CR2"), "").unwrap();
+        let pipeline = parse_pipeline(PIPELINE).unwrap();
+        let result = execute_mirrored(&pipeline, &[source.join("my_photo.JPG")], source, mirror);
+        let exists = mirror.join("my photo.CR2").exists();
+        fs::remove_dir_all(source).ok();
+        fs::remove_dir_all(mirror).ok();
+        let result = result.unwrap();
+        assert!(result
+            .outcomes
+            .iter()
+            .all(|outcome| outcome.error.is_none()));
+        assert_eq!(result.outcomes.len(), 2);
+        assert_eq!(result.unmatched, [mirror.join("other.CR2")]);
+        assert!(exists);
+    }
+
+    #[test]
+    fn c_round_trip() {
+        let pipeline = CString::new(PIPELINE).unwrap();
+        let paths = CString::new(r#"["my_file.TXT"]"#).unwrap();
+        unsafe {
+            let names = mr_preview(pipeline.as_ptr(), paths.as_ptr());
+            assert!(mr_last_error().is_null());
+            assert_eq!(CStr::from_ptr(names).to_str(), Ok(r#"["my file.txt"]"#));
+            mr_string_free(names);
+        }
+    }
+
+    #[test]
+    fn c_reports_errors() {
+        let pipeline = CString::new(r#"[{"Unknown": 1}]"#).unwrap();
+        unsafe {
+            assert!(mr_preview(pipeline.as_ptr(), ptr::null()).is_null());
+            assert!(!mr_last_error().is_null());
+            assert!(mr_preview(ptr::null(), ptr::null()).is_null());
+            assert_eq!(
+                CStr::from_ptr(mr_last_error()).to_str(),
+                Ok("pipeline is a null pointer")
+            );
+        }
+    }
+}
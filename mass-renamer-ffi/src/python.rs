@@ -0,0 +1,76 @@
+use std::path::PathBuf;
+
+use pyo3::{exceptions::PyValueError, prelude::*};
+
+use crate::{parse_pipeline, FfiError, Outcome};
+
+impl From<FfiError> for PyErr {
+    fn from(err: FfiError) -> Self {
+        PyValueError::new_err(err.to_string())
+    }
+}
+
+/// Preview the new names for `paths` with a JSON pipeline. Nothing is renamed.
+#[pyfunction]
+fn preview(pipeline: &str, paths: Vec<PathBuf>) -> PyResult<Vec<PathBuf>> {
+    Ok(crate::preview(&parse_pipeline(pipeline)?, &paths)?)
+}
+
+type Outcomes = Vec<(PathBuf, PathBuf, Option<String>)>;
+
+fn outcomes(outcomes: Vec<Outcome>) -> Outcomes {
+    outcomes
+        .into_iter()
+        .map(|outcome| (outcome.from, outcome.to, outcome.error))
+        .collect()
+}
+
+/// Rename `paths` with a JSON pipeline. With `keep_originals` each file's old name is
+/// kept on the file for `restore`.
+/// Returns a `(from, to, error)` tuple per path, `error` is `None` on success.
+#[pyfunction]
+#[pyo3(signature = (pipeline, paths, keep_originals = false))]
+fn execute(pipeline: &str, paths: Vec<PathBuf>, keep_originals: bool) -> PyResult<Outcomes> {
+    let pipeline = parse_pipeline(pipeline)?;
+    Ok(outcomes(if keep_originals {
+        crate::execute_keeping_originals(&pipeline, &paths)
+    } else {
+        crate::execute(&pipeline, &paths)
+    }))
+}
+
+/// Rename `paths` with a JSON pipeline, and the files with the same stems in the `mirror`
+/// of the `source` folder. Returns the outcomes and the paths without a match.
+#[pyfunction]
+fn execute_mirrored(
+    pipeline: &str,
+    paths: Vec<PathBuf>,
+    source: PathBuf,
+    mirror: PathBuf,
+) -> PyResult<(Outcomes, Vec<PathBuf>)> {
+    let result = crate::execute_mirrored(&parse_pipeline(pipeline)?, &paths, &source, &mirror)?;
+    Ok((outcomes(result.outcomes), result.unmatched))
+}
+
+/// Rename `paths` back to their names before `execute` with `keep_originals`.
+#[pyfunction]
+fn restore(paths: Vec<PathBuf>) -> Outcomes {
+    outcomes(crate::restore(&paths))
+}
+
+/// Limit every following `execute` and `restore` to `per_second` renames a second,
+/// 0 for no limit.
+#[pyfunction]
+fn set_throttle(per_second: u32) {
+    crate::set_throttle(per_second);
+}
+
+#[pymodule]
+fn mass_renamer_ffi(_py: Python, module: &PyModule) -> PyResult<()> {
+    module.add_function(wrap_pyfunction!(preview, module)?)?;
+    module.add_function(wrap_pyfunction!(execute, module)?)?;
+    module.add_function(wrap_pyfunction!(restore, module)?)?;
+    module.add_function(wrap_pyfunction!(execute_mirrored, module)?)?;
+    module.add_function(wrap_pyfunction!(set_throttle, module)?)?;
+    Ok(())
+}
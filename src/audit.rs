@@ -0,0 +1,86 @@
+//! An append-only log of every file renamed, undone, redone or copied, for tracing changes to
+//! shared drives. Unlike the history, nothing is ever removed from it: each move is
+//! added as one line of JSON, an [Entry], so the log can be read with any JSON Lines tool.
+
+use std::{
+    fs::{self, OpenOptions},
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
+
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+
+/// How the `batch` of an [Entry] is formatted, the time the batch started.
+pub const BATCH_FORMAT: &str = "%Y%m%dT%H%M%S%.6f";
+
+/// What moved a file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Action {
+    Rename,
+    Undo,
+    Redo,
+    /// Copied to a new path, leaving the original, see [crate::file::DestinationOptions].
+    Copy,
+}
+
+/// One file moved. Every entry of a batch shares its `batch` id.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Entry {
+    pub time: DateTime<Local>,
+    pub batch: String,
+    pub action: Action,
+    pub old: PathBuf,
+    pub new: PathBuf,
+}
+
+/// Add the `(old, new)` moves of a batch to the end of the log at `path`, creating it
+/// and any missing folders.
+pub fn append<P: AsRef<Path>>(
+    path: P,
+    action: Action,
+    moves: &[(PathBuf, PathBuf)],
+) -> Result<(), io::Error> {
+    if moves.is_empty() {
+        return Ok(());
+    }
+    let path = path.as_ref();
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let time = Local::now();
+    let batch = time.format(BATCH_FORMAT).to_string();
+    let mut lines = String::new();
+    for (old, new) in moves {
+        let entry = Entry {
+            time,
+            batch: batch.clone(),
+            action,
+            old: old.clone(),
+            new: new.clone(),
+        };
+        lines.push_str(&serde_json::to_string(&entry)?);
+        lines.push('\n');
+    }
+    // One write per batch, so batches from two windows don't interleave.
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)?
+        .write_all(lines.as_bytes())
+}
+
+/// Read the log at `path`, oldest first. Lines that aren't entries are skipped, and a
+/// missing log has no entries.
+pub fn read<P: AsRef<Path>>(path: P) -> Result<Vec<Entry>, io::Error> {
+    let text = match fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(vec![]),
+        Err(err) => return Err(err),
+    };
+    Ok(text
+        .lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect())
+}
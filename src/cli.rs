@@ -0,0 +1,848 @@
+//! Rename from the command line, for scripts and SSH sessions where the window is no use:
+//! `mass_renamer --cli <FOLDER> [RULES] [--commit]`.
+//!
+//! Every file in the folder goes through the same rules as in the window, in the same
+//! order. The new names are printed as `old -> new` and the files are only renamed
+//! with `--commit`.
+
+use std::{
+    fs, io,
+    io::Write,
+    path::{Path, PathBuf},
+    sync::atomic::AtomicBool,
+};
+
+use clap::{Args, Parser, ValueEnum};
+
+#[cfg(feature = "mapping")]
+use crate::mapping;
+use crate::{
+    audit::{self, Action},
+    batch::{self, Resolution, Resolved, Throttle},
+    file::{
+        date::{parse_offset, DateZone},
+        AddOptions, Case, CaseOptions, DateFormat, DateMode, DateOptions, DatePrefix, DateSuffix,
+        DateType, ExtensionOptions, File, FolderMode, FolderOptions, NameOptions, NumberFormat,
+        NumberMode, NumberOptions, Options, RegexOptions, RemoveOptions, ReplaceOptions,
+    },
+    guard::Guard,
+    gui::{self, AUDIT_FILE, HISTORY_FILE, SETTINGS_FILE},
+    history::RenameHistory,
+    metadata::{Camera, CaptureTime, Dimensions, Extractor, FileTimes, Metadata},
+    policy::NamingPolicy,
+    references::{self, Links},
+    script::{self, Shell},
+    settings::Settings,
+};
+
+/// The first argument that starts the command line rather than the window.
+pub const FLAG: &str = "--cli";
+
+/// Rename the files in a folder. Without --commit the new names are only printed.
+#[derive(Debug, Parser)]
+#[command(bin_name = "mass_renamer --cli")]
+pub struct Cli {
+    /// The folder whose files are renamed.
+    pub folder: PathBuf,
+    /// Rename the files, rather than only printing the new names.
+    #[arg(long)]
+    pub commit: bool,
+    /// Rename in protected folders such as / or the home folder too, see `guard`.
+    #[arg(long)]
+    pub allow_protected: bool,
+    /// Print a script making the renames instead, to run where Mass Renamer isn't installed.
+    #[arg(long, value_name = "SHELL", conflicts_with = "commit")]
+    pub script: Option<ShellArg>,
+    /// Rename from a CSV or TSV table of old and new names instead of the rules.
+    #[cfg(feature = "mapping")]
+    #[arg(long, value_name = "TABLE")]
+    pub mapping: Option<PathBuf>,
+    /// Rewrite the links to renamed files in the text files with these extensions under
+    /// the folder, e.g. `md,html`.
+    #[arg(long, value_name = "EXT", value_delimiter = ',')]
+    pub update_links: Vec<String>,
+    /// Check the new names against a naming policy file, and rename nothing if any breaks
+    /// it, see `policy`.
+    #[arg(long, value_name = "FILE")]
+    pub policy: Option<PathBuf>,
+    /// What to do about files given the same new name, or the name of a file already
+    /// there: rename nothing, leave them out, or replace the files already there.
+    #[arg(long, value_name = "POLICY", default_value = "refuse")]
+    pub collisions: CollisionArg,
+    /// The settings folder the undo history and audit log are kept in, if not the
+    /// window's own.
+    #[arg(long, value_name = "FOLDER")]
+    pub config_dir: Option<PathBuf>,
+    #[command(flatten)]
+    regex: RegexArgs,
+    #[command(flatten)]
+    name: NameArgs,
+    #[command(flatten)]
+    replace: ReplaceArgs,
+    #[command(flatten)]
+    case: CaseArgs,
+    #[command(flatten)]
+    remove: RemoveArgs,
+    #[command(flatten)]
+    add: AddArgs,
+    #[command(flatten)]
+    date: DateArgs,
+    #[command(flatten)]
+    folder_name: FolderArgs,
+    #[command(flatten)]
+    number: NumberArgs,
+    #[command(flatten)]
+    extension: ExtensionArgs,
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum ShellArg {
+    Sh,
+    Powershell,
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum CollisionArg {
+    Refuse,
+    Skip,
+    Overwrite,
+}
+
+#[derive(Debug, Args)]
+#[command(next_help_heading = "Regex (1)")]
+struct RegexArgs {
+    /// Regular expression to match in the name.
+    #[arg(long, value_name = "EXP", default_value = "")]
+    regex: String,
+    /// What the match is replaced with, `$1` or `\1` etc. for groups, `\U`, `\L` and `\E`
+    /// to change their case.
+    #[arg(long, value_name = "REP", default_value = "")]
+    regex_with: String,
+    /// Match against the extension too.
+    #[arg(long)]
+    regex_extension: bool,
+}
+
+#[derive(Debug, Args)]
+#[command(next_help_heading = "Name (2)")]
+struct NameArgs {
+    /// Replace the name with this text, tokens such as {stem} included.
+    #[arg(long, value_name = "TEXT", group = "name_mode")]
+    name: Option<String>,
+    /// Remove the name, leaving what the other rules add.
+    #[arg(long, group = "name_mode")]
+    remove_name: bool,
+    /// Reverse the name.
+    #[arg(long, group = "name_mode")]
+    reverse_name: bool,
+}
+
+#[derive(Debug, Args)]
+#[command(next_help_heading = "Replace (3)")]
+struct ReplaceArgs {
+    /// Text to replace.
+    #[arg(long, value_name = "TEXT", default_value = "")]
+    replace: String,
+    /// What it is replaced with.
+    #[arg(long, value_name = "TEXT", default_value = "")]
+    with: String,
+    /// Only replace text in the same case.
+    #[arg(long)]
+    match_case: bool,
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum CaseArg {
+    Lower,
+    Upper,
+    Title,
+    Sentence,
+    Dictionary,
+}
+
+#[derive(Debug, Args)]
+#[command(next_help_heading = "Case (4)")]
+struct CaseArgs {
+    #[arg(long)]
+    case: Option<CaseArg>,
+    /// Replace spaces with underscores.
+    #[arg(long)]
+    snake: bool,
+    /// Words left in the case they are written, separated by spaces.
+    #[arg(long, value_name = "WORDS", default_value = "")]
+    case_except: String,
+    /// A word written as given by the dictionary case, e.g. iPhone. Repeat for more.
+    #[arg(long, value_name = "WORD")]
+    dictionary: Vec<String>,
+}
+
+#[derive(Debug, Args)]
+#[command(next_help_heading = "Remove (5)")]
+struct RemoveArgs {
+    /// Remove the first N characters.
+    #[arg(long, value_name = "N", default_value_t = 0)]
+    remove_first: usize,
+    /// Remove the last N characters.
+    #[arg(long, value_name = "N", default_value_t = 0)]
+    remove_last: usize,
+    /// Remove the characters from this position, 0 indexed...
+    #[arg(long, value_name = "N", default_value_t = 0)]
+    remove_from: usize,
+    /// ...to this one.
+    #[arg(long, value_name = "N", default_value_t = 0)]
+    remove_to: usize,
+    /// Remove each of these characters.
+    #[arg(long, value_name = "CHARS", default_value = "")]
+    remove_chars: String,
+    /// Remove these words, separated by spaces. `*` matches anything within a word.
+    #[arg(long, value_name = "WORDS", default_value = "")]
+    remove_words: String,
+    /// Remove everything before this text.
+    #[arg(long, value_name = "TEXT", group = "crop")]
+    crop_before: Option<String>,
+    /// Remove everything after this text.
+    #[arg(long, value_name = "TEXT", group = "crop")]
+    crop_after: Option<String>,
+    /// Remove the digits 0-9.
+    #[arg(long)]
+    remove_digits: bool,
+    /// Remove characters 128 to 255.
+    #[arg(long)]
+    remove_high: bool,
+    /// Remove leading and trailing spaces.
+    #[arg(long)]
+    trim: bool,
+    /// Replace double spaces with single ones.
+    #[arg(long)]
+    remove_double_spaces: bool,
+    /// Remove the letters a-z and A-Z.
+    #[arg(long)]
+    remove_letters: bool,
+    /// Remove symbols.
+    #[arg(long)]
+    remove_symbols: bool,
+    /// Remove dots at the start of the name.
+    #[arg(long)]
+    remove_lead_dots: bool,
+}
+
+#[derive(Debug, Args)]
+#[command(next_help_heading = "Add (6)")]
+struct AddArgs {
+    /// Text added before the name.
+    #[arg(long, value_name = "TEXT")]
+    prefix: Option<String>,
+    /// Text inserted at --insert-at.
+    #[arg(long, value_name = "TEXT", requires = "insert_at")]
+    insert: Option<String>,
+    /// Where --insert goes, 0 indexed, negative from the end.
+    #[arg(
+        long,
+        value_name = "POS",
+        allow_negative_numbers = true,
+        requires = "insert"
+    )]
+    insert_at: Option<i32>,
+    /// Text added after the name.
+    #[arg(long, value_name = "TEXT")]
+    suffix: Option<String>,
+    /// Put a space before each capital letter, keeping acronyms together.
+    #[arg(long)]
+    word_space: bool,
+    /// Put a space between numbers and the letters around them.
+    #[arg(long)]
+    digit_space: bool,
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum Placement {
+    Prefix,
+    Suffix,
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum DateTypeArg {
+    Created,
+    Modified,
+    Current,
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum DateFormatArg {
+    Dmy,
+    Mdy,
+    Ymd,
+    Week,
+    Quarter,
+    Day,
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum TimeArg {
+    Hm,
+    Hms,
+}
+
+#[derive(Debug, Args)]
+#[command(next_help_heading = "Auto Date (7)")]
+struct DateArgs {
+    /// Where the date goes.
+    #[arg(long)]
+    date: Option<Placement>,
+    #[arg(long, value_name = "TYPE", default_value = "created")]
+    date_type: DateTypeArg,
+    #[arg(long, value_name = "FORMAT", default_value = "dmy")]
+    date_format: DateFormatArg,
+    /// Add the time too.
+    #[arg(long, value_name = "TIME")]
+    date_time: Option<TimeArg>,
+    /// A custom chrono format, e.g. %Y-%m-%d, used instead of --date-format.
+    #[arg(long, value_name = "FORMAT")]
+    date_custom: Option<String>,
+    /// Between the parts of the date.
+    #[arg(long, value_name = "SEP", default_value = "")]
+    date_sep: String,
+    /// Between the date and the name.
+    #[arg(long, value_name = "SEG", default_value = "")]
+    date_seg: String,
+    /// Four digit years.
+    #[arg(long)]
+    full_year: bool,
+    /// The language of month and weekday names, e.g. fr_FR.
+    #[arg(long, value_name = "LOCALE", default_value = "")]
+    date_locale: String,
+    /// local, utc or an offset such as +05:30.
+    #[arg(long, value_name = "ZONE", default_value = "local", value_parser = zone)]
+    date_zone: DateZone,
+}
+
+#[derive(Debug, Args)]
+#[command(next_help_heading = "Append Folder Name (8)")]
+struct FolderArgs {
+    /// Where the folder name goes.
+    #[arg(long)]
+    folder_name: Option<Placement>,
+    /// Between the folder names and the name.
+    #[arg(long, value_name = "SEP", default_value = "")]
+    folder_sep: String,
+    /// How many parent folders.
+    #[arg(long, value_name = "N", default_value_t = 1)]
+    folder_levels: i32,
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum NumberFormatArg {
+    Decimal,
+    Binary,
+    Octal,
+    Hex,
+    HexLower,
+    Letters,
+    LettersLower,
+    Roman,
+    RomanLower,
+}
+
+#[derive(Debug, Args)]
+#[command(next_help_heading = "Numbering (9)")]
+struct NumberArgs {
+    /// Where the number goes.
+    #[arg(long, group = "number_mode")]
+    number: Option<Placement>,
+    /// Insert the number at this position instead, 0 indexed.
+    #[arg(long, value_name = "POS", group = "number_mode")]
+    number_at: Option<usize>,
+    #[arg(long, value_name = "N", default_value_t = 1)]
+    start: u32,
+    /// Added to the number from one file to the next.
+    #[arg(long, value_name = "N", default_value_t = 1)]
+    step: u32,
+    /// Start again from the first number once past this.
+    #[arg(long, value_name = "N")]
+    reset_at: Option<u32>,
+    /// Start again from the first number in each folder.
+    #[arg(long)]
+    reset_per_folder: bool,
+    /// The least number of digits.
+    #[arg(long, value_name = "N", default_value_t = 0)]
+    pad: usize,
+    /// Pad with this rather than 0, or A or a for letters.
+    #[arg(long, value_name = "CHAR")]
+    pad_char: Option<char>,
+    /// Between the number and the name, `:` is replaced by the number.
+    #[arg(long, value_name = "SEP", default_value = "")]
+    number_sep: String,
+    #[arg(long, value_name = "FORMAT", default_value = "decimal")]
+    number_format: NumberFormatArg,
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum ExtensionArg {
+    Lower,
+    Upper,
+    Title,
+    Remove,
+}
+
+#[derive(Debug, Args)]
+#[command(next_help_heading = "Extension (10)")]
+struct ExtensionArgs {
+    #[arg(long, group = "extension_mode")]
+    extension: Option<ExtensionArg>,
+    /// Replace the extension.
+    #[arg(long, value_name = "EXT", group = "extension_mode")]
+    new_extension: Option<String>,
+    /// Add another extension.
+    #[arg(long, value_name = "EXT", group = "extension_mode")]
+    extra_extension: Option<String>,
+    /// Change one extension into another whatever its case, e.g. `jpeg=jpg`. Repeat for
+    /// more pairs.
+    #[arg(long, value_name = "FROM=TO", value_parser = extension_pair, group = "extension_mode")]
+    map_extension: Vec<(String, String)>,
+}
+
+fn extension_pair(text: &str) -> Result<(String, String), String> {
+    text.split_once('=')
+        .map(|(from, to)| (from.to_owned(), to.to_owned()))
+        .ok_or_else(|| format!("expected FROM=TO, found \"{text}\""))
+}
+
+fn zone(text: &str) -> Result<DateZone, String> {
+    match text.to_lowercase().as_str() {
+        "local" => Ok(DateZone::Local),
+        "utc" => Ok(DateZone::Utc),
+        _ => parse_offset(text)
+            .map(|minutes| minutes.round() as i32)
+            .filter(|minutes| minutes.abs() <= DateZone::MAX_OFFSET)
+            .map(DateZone::Fixed)
+            .ok_or_else(|| format!("{text} is not local, utc or an offset such as +05:30")),
+    }
+}
+
+impl Cli {
+    /// The rules, in processing order.
+    pub fn options(&self) -> Vec<Options> {
+        vec![
+            Options::Regex(self.regex.build()),
+            Options::Name(self.name.build()),
+            Options::Replace(self.replace.build()),
+            Options::Case(self.case.build()),
+            Options::Remove(self.remove.build()),
+            Options::Add(self.add.build()),
+            Options::Date(self.date.build()),
+            Options::Folder(self.folder_name.build()),
+            Options::Number(self.number.build()),
+            Options::Extension(self.extension.build()),
+        ]
+    }
+}
+
+impl RegexArgs {
+    fn build(&self) -> RegexOptions {
+        RegexOptions {
+            exp: self.regex.clone(),
+            rep: self.regex_with.clone(),
+            extension: self.regex_extension,
+        }
+    }
+}
+
+impl NameArgs {
+    fn build(&self) -> NameOptions {
+        match &self.name {
+            Some(name) => NameOptions::Fixed(name.clone()),
+            None if self.remove_name => NameOptions::Remove,
+            None if self.reverse_name => NameOptions::Reverse,
+            None => NameOptions::Keep,
+        }
+    }
+}
+
+impl ReplaceArgs {
+    fn build(&self) -> ReplaceOptions {
+        ReplaceOptions {
+            replace: self.replace.clone(),
+            with: self.with.clone(),
+            case: self.match_case,
+        }
+    }
+}
+
+impl CaseArgs {
+    fn build(&self) -> CaseOptions {
+        CaseOptions {
+            case: match self.case {
+                None => Case::Keep,
+                Some(CaseArg::Lower) => Case::Lower,
+                Some(CaseArg::Upper) => Case::Upper,
+                Some(CaseArg::Title) => Case::Title,
+                Some(CaseArg::Sentence) => Case::Sentence,
+                Some(CaseArg::Dictionary) => Case::Dictionary,
+            },
+            snake: self.snake,
+            exceptions: self.case_except.clone(),
+            dictionary: self.dictionary.clone(),
+        }
+    }
+}
+
+impl RemoveArgs {
+    fn build(&self) -> RemoveOptions {
+        RemoveOptions {
+            first_n: self.remove_first,
+            last_n: self.remove_last,
+            range: (self.remove_from, self.remove_to),
+            characters: self.remove_chars.clone(),
+            words: self.remove_words.clone(),
+            crop: match (&self.crop_before, &self.crop_after) {
+                (Some(text), _) => (true, text.clone()),
+                (None, Some(text)) => (false, text.clone()),
+                (None, None) => (true, String::new()),
+            },
+            digits: self.remove_digits,
+            ascii_high: self.remove_high,
+            trim: self.trim,
+            double_space: self.remove_double_spaces,
+            chars: self.remove_letters,
+            symbols: self.remove_symbols,
+            lead_dots: self.remove_lead_dots,
+        }
+    }
+}
+
+impl AddArgs {
+    fn build(&self) -> AddOptions {
+        AddOptions {
+            prefix: self.prefix.clone(),
+            insert: self.insert_at.zip(self.insert.clone()),
+            suffix: self.suffix.clone(),
+            word_space: self.word_space,
+            digit_space: self.digit_space,
+        }
+    }
+}
+
+impl DateArgs {
+    fn build(&self) -> DateOptions {
+        let prefix = match self.date_format {
+            DateFormatArg::Dmy => DatePrefix::Dmy,
+            DateFormatArg::Mdy => DatePrefix::Mdy,
+            DateFormatArg::Ymd => DatePrefix::Ymd,
+            DateFormatArg::Week => DatePrefix::Week,
+            DateFormatArg::Quarter => DatePrefix::Quarter,
+            DateFormatArg::Day => DatePrefix::DayOfYear,
+        };
+        let suffix = self.date_time.map(|time| match time {
+            TimeArg::Hm => DateSuffix::Hm,
+            TimeArg::Hms => DateSuffix::Hms,
+        });
+        DateOptions {
+            date_mode: match self.date {
+                None => DateMode::None,
+                Some(Placement::Prefix) => DateMode::Prefix,
+                Some(Placement::Suffix) => DateMode::Suffix,
+            },
+            date_type: match self.date_type {
+                DateTypeArg::Created => DateType::Created,
+                DateTypeArg::Modified => DateType::Modified,
+                DateTypeArg::Current => DateType::Current,
+            },
+            fmt: match self.date_custom {
+                Some(_) => DateFormat::Custom,
+                None => DateFormat::Std((prefix, suffix)),
+            },
+            custom_fmt: self.date_custom.clone().unwrap_or_default(),
+            sep: self.date_sep.clone(),
+            seg: self.date_seg.clone(),
+            full_year: self.full_year,
+            locale: self.date_locale.clone(),
+            zone: self.date_zone,
+        }
+    }
+}
+
+impl FolderArgs {
+    fn build(&self) -> FolderOptions {
+        FolderOptions {
+            mode: match self.folder_name {
+                None => FolderMode::None,
+                Some(Placement::Prefix) => FolderMode::Prefix,
+                Some(Placement::Suffix) => FolderMode::Suffix,
+            },
+            sep: self.folder_sep.clone(),
+            levels: self.folder_levels,
+        }
+    }
+}
+
+impl NumberArgs {
+    fn build(&self) -> NumberOptions {
+        let format = match self.number_format {
+            NumberFormatArg::Decimal => NumberFormat::Decimal,
+            NumberFormatArg::Binary => NumberFormat::Binary,
+            NumberFormatArg::Octal => NumberFormat::Octal,
+            NumberFormatArg::Hex => NumberFormat::HexUpper,
+            NumberFormatArg::HexLower => NumberFormat::HexLower,
+            NumberFormatArg::Letters => NumberFormat::AsciiUpper,
+            NumberFormatArg::LettersLower => NumberFormat::AsciiLower,
+            NumberFormatArg::Roman => NumberFormat::RomanUpper,
+            NumberFormatArg::RomanLower => NumberFormat::RomanLower,
+        };
+        // The same defaults as the Numbering panel.
+        let char = self.pad_char.unwrap_or(match format {
+            NumberFormat::AsciiUpper => 'A',
+            NumberFormat::AsciiLower => 'a',
+            NumberFormat::RomanUpper | NumberFormat::RomanLower => ' ',
+            _ => '0',
+        });
+        NumberOptions {
+            mode: match (self.number, self.number_at) {
+                (_, Some(position)) => NumberMode::Insert(position),
+                (Some(Placement::Prefix), None) => NumberMode::Prefix,
+                (Some(Placement::Suffix), None) => NumberMode::Suffix,
+                (None, None) => NumberMode::None,
+            },
+            value: self.start,
+            step: self.step,
+            reset_at: self.reset_at,
+            reset_folder: self.reset_per_folder,
+            pad: self.pad,
+            char,
+            sep: self.number_sep.clone(),
+            format,
+        }
+    }
+}
+
+impl ExtensionArgs {
+    fn build(&self) -> ExtensionOptions {
+        match (self.extension, &self.new_extension, &self.extra_extension) {
+            (Some(ExtensionArg::Lower), _, _) => ExtensionOptions::Lower,
+            (Some(ExtensionArg::Upper), _, _) => ExtensionOptions::Upper,
+            (Some(ExtensionArg::Title), _, _) => ExtensionOptions::Title,
+            (Some(ExtensionArg::Remove), _, _) => ExtensionOptions::Remove,
+            (None, Some(extension), _) => ExtensionOptions::New(extension.clone()),
+            (None, None, Some(extension)) => ExtensionOptions::Extra(extension.clone()),
+            _ if !self.map_extension.is_empty() => {
+                ExtensionOptions::Map(self.map_extension.clone())
+            }
+            (None, None, None) => ExtensionOptions::Keep,
+        }
+    }
+}
+
+/// The `(original, new)` paths of the files in the folder the rules would change,
+/// in name order.
+pub fn plan(folder: &Path, options: &[Options]) -> Result<Vec<(PathBuf, PathBuf)>, io::Error> {
+    let mut paths = fs::read_dir(folder)?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<Result<Vec<_>, _>>()?;
+    paths.retain(|path| path.is_file());
+    paths.sort();
+    let needs_metadata = options.iter().any(Options::needs_metadata);
+    let (mut originals, mut files) = (vec![], vec![]);
+    for path in paths {
+        let Ok(mut file) = File::new(&path) else {
+            continue;
+        };
+        if needs_metadata {
+            let mut metadata = Metadata::default();
+            FileTimes.extract(&path, &mut metadata).ok();
+            CaptureTime.extract(&path, &mut metadata).ok();
+            Dimensions.extract(&path, &mut metadata).ok();
+            Camera.extract(&path, &mut metadata).ok();
+            file = file.with_metadata(metadata);
+        }
+        files.push(file);
+        originals.push(path);
+    }
+    batch::preview(&mut files, options);
+    Ok(originals
+        .into_iter()
+        .zip(&files)
+        .map(|(original, file)| (original, file.new_path()))
+        .filter(|(original, new)| original != new)
+        .collect())
+}
+
+/// Print the new names and, with `--commit`, rename the files.
+/// Returns whether every rename worked.
+pub fn run<W: Write>(cli: &Cli, out: &mut W) -> Result<bool, io::Error> {
+    #[cfg(feature = "mapping")]
+    let renames = match &cli.mapping {
+        Some(table) => {
+            let pairs = mapping::read(table).map_err(io::Error::from)?;
+            let plan = mapping::plan(&cli.folder, &pairs);
+            for err in &plan.errors {
+                writeln!(out, "⛔ {err}")?;
+            }
+            plan.renames
+        }
+        None => plan(&cli.folder, &cli.options())?,
+    };
+    #[cfg(not(feature = "mapping"))]
+    let renames = plan(&cli.folder, &cli.options())?;
+    if let Some(shell) = cli.script {
+        let shell = match shell {
+            ShellArg::Sh => Shell::Posix,
+            ShellArg::Powershell => Shell::PowerShell,
+        };
+        write!(out, "{}", script::script(&renames, shell))?;
+        return Ok(true);
+    }
+    if renames.is_empty() {
+        writeln!(out, "Nothing to rename")?;
+        return Ok(true);
+    }
+    for (original, new) in &renames {
+        writeln!(out, "{} -> {}", file_name(original), file_name(new))?;
+    }
+    if let Some(path) = &cli.policy {
+        let policy = NamingPolicy::read(path)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+        let violations = policy.check(&renames);
+        for violation in &violations {
+            writeln!(out, "⛔ {violation}")?;
+        }
+        if !violations.is_empty() {
+            writeln!(
+                out,
+                "{} policy violations, nothing renamed",
+                violations.len()
+            )?;
+            return Ok(false);
+        }
+    }
+    // Files that can't have their new names are found before any is renamed.
+    let found = batch::collisions(&renames);
+    for collision in &found {
+        writeln!(out, "⛔ {collision}")?;
+    }
+    let resolution = match cli.collisions {
+        _ if found.is_empty() => None,
+        CollisionArg::Refuse => {
+            writeln!(out, "{} collisions, nothing renamed", found.len())?;
+            return Ok(false);
+        }
+        CollisionArg::Skip => Some(Resolution::Skip),
+        CollisionArg::Overwrite => Some(Resolution::Overwrite),
+    };
+    let Resolved {
+        renames,
+        skipped,
+        replace,
+    } = match resolution {
+        Some(resolution) => batch::resolve(&renames, |_| resolution),
+        None => Resolved {
+            renames,
+            ..Default::default()
+        },
+    };
+    for original in &skipped {
+        writeln!(out, "{} skipped", file_name(original))?;
+    }
+    if !cli.commit {
+        if !cli.update_links.is_empty() {
+            let documents = references::documents(&cli.folder, &cli.update_links)?;
+            links(
+                &references::find(&documents, &renames),
+                "would be updated",
+                out,
+            )?;
+        }
+        writeln!(
+            out,
+            "{} files would be renamed, add --commit to rename them",
+            renames.len()
+        )?;
+        return Ok(true);
+    }
+    let guard = if cli.allow_protected {
+        Guard::none()
+    } else {
+        Guard::default()
+    };
+    let cancel = AtomicBool::new(false);
+    let results = batch::rename_watched(
+        &renames,
+        Throttle::default(),
+        &guard,
+        &replace,
+        &cancel,
+        |_, _| {},
+    );
+    let mut failed = 0;
+    let mut renamed = vec![];
+    for (rename, result) in renames.iter().zip(results) {
+        match result {
+            Ok(()) => renamed.push(rename.clone()),
+            Err(err) => {
+                failed += 1;
+                writeln!(out, "⛔ {}: {err}", file_name(&rename.0))?;
+            }
+        }
+    }
+    writeln!(
+        out,
+        "{} files renamed, {failed} failed",
+        renames.len() - failed
+    )?;
+    let config_dir = cli.config_dir.clone().or_else(gui::config_dir);
+    if let Some(dir) = config_dir.filter(|_| !renamed.is_empty()) {
+        failed += record(&dir, &renamed, out)?;
+    }
+    if !cli.update_links.is_empty() {
+        let documents = references::documents(&cli.folder, &cli.update_links)?;
+        let updated = references::update(&documents, &renamed);
+        links(&updated, "updated", out)?;
+        failed += updated.failed.len();
+    }
+    Ok(failed == 0)
+}
+
+/// Add the files renamed to the undo history kept in the settings folder `dir`, so the
+/// window can undo the batch, and to the audit log when it is turned on, as renaming in
+/// the window does. Returns how many of them couldn't be written.
+fn record<W: Write>(
+    dir: &Path,
+    renamed: &[(PathBuf, PathBuf)],
+    out: &mut W,
+) -> Result<usize, io::Error> {
+    let mut failed = 0;
+    // A history that can't be read is left as it is, rather than replaced.
+    let path = dir.join(HISTORY_FILE);
+    let recorded = RenameHistory::load(&path).and_then(|mut history| {
+        history.record(renamed);
+        history.save(&path)
+    });
+    if let Err(err) = recorded {
+        failed += 1;
+        writeln!(out, "⛔ Not added to the undo history: {err}")?;
+    }
+    let settings = Settings::load(dir.join(SETTINGS_FILE)).unwrap_or_default();
+    if settings.audit {
+        if let Err(err) = audit::append(dir.join(AUDIT_FILE), Action::Rename, renamed) {
+            failed += 1;
+            writeln!(out, "⛔ Not added to the audit log: {err}")?;
+        }
+    }
+    Ok(failed)
+}
+
+/// Print the links to renamed files and how many there are, and the documents that
+/// couldn't be searched or updated.
+fn links<W: Write>(found: &Links, done: &str, out: &mut W) -> Result<(), io::Error> {
+    for reference in &found.references {
+        writeln!(out, "{reference}")?;
+    }
+    for (document, err) in &found.failed {
+        writeln!(out, "⛔ {}: {err}", document.display())?;
+    }
+    writeln!(out, "{} links {done}", found.references.len())
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
@@ -1,383 +1,39 @@
-use std::{
-    cmp::Ordering,
-    fmt::Debug,
-    fs, io,
-    path::{Path, PathBuf},
-};
+//! The rename options from `mass_renamer_core`, along with the views used to edit them.
 
 pub mod add;
+pub mod blacklist;
 pub mod case;
 pub mod date;
+pub mod destination;
 pub mod extension;
 pub mod folder;
+pub mod lookup;
 pub mod name;
+pub mod normalize;
 pub mod number;
+pub mod pair;
 pub mod reg;
+pub mod release;
 pub mod remove;
 pub mod replace;
-
-use crate::{generate_path_as_string, PathString};
-use add::AddOptions;
-pub use case::{Case, CaseOptions};
-use chrono::{DateTime, Local};
-pub use date::{DateFormat, DateMode, DateOptions, DatePrefix, DateSuffix, DateType};
-use egui::{RichText, WidgetText};
-pub use extension::ExtensionOptions;
-pub use folder::{FolderMode, FolderOptions};
-pub use name::NameOptions;
-pub use number::{NumberFormat, NumberMode, NumberOptions};
-pub use reg::RegexOptions;
-pub use remove::RemoveOptions;
-pub use replace::ReplaceOptions;
-use thiserror::Error;
-
-pub trait Process {
-    fn process(&self, file: &mut File);
-}
+pub mod sanitize;
+pub mod swap;
+
+pub use mass_renamer_core::file::{
+    expand_tokens, read_word_list, AddOptions, BlacklistMode, BlacklistOptions, Case, CaseOptions,
+    CharPolicy, DateCreated, DateFormat, DateMode, DateModified, DateOptions, DatePrefix,
+    DateSuffix, DateType, DestinationOptions, Extension, ExtensionOptions, File, FileError,
+    Filename, FolderMode, FolderOptions, LookupOptions, NameOptions, NormalizeOptions,
+    NumberFormat, NumberMode, NumberOptions, Options, PairOptions, Process, RegexOptions,
+    ReleaseOptions, RemoveOptions, ReplaceOptions, SanitizeOptions, Scopes, Size, Step, SwapMode,
+    SwapOptions, Tokens,
+};
 
 pub trait OptionBuilder {
-    type Processor: Process;
+    type Processor;
 
     fn build(&self) -> Self::Processor;
-}
-
-#[derive(Debug, Default)]
-pub struct File {
-    stem: String,
-    valid_original: bool,
-    extension: Option<String>,
-    original: PathBuf,
-    add: Option<AddOptions>,
-    case: Option<CaseOptions>,
-    date: Option<DateOptions>,
-    ext: Option<ExtensionOptions>,
-    folder: Option<FolderOptions>,
-    name: Option<NameOptions>,
-    number: Option<NumberOptions>,
-    regex: Option<RegexOptions>,
-    remove: Option<RemoveOptions>,
-    replace: Option<RegexOptions>,
-}
-
-impl File {
-    /// Create a new File object from a Path.
-    /// No checking is performed to validate that the Path exists or is a file.
-    /// To perform this check use [File::try_from<&Path>], [File::try_from<&PathBuf>], or [File::try_from<PathBuf>]
-    pub fn new(path: &Path) -> Result<Self, FileError> {
-        let extension = {
-            generate_path_as_string(path.extension()).map(|e| match e {
-                PathString::Valid(s) => s,
-                PathString::Invalid(s) => s,
-            })
-        };
-        match generate_path_as_string(path.file_stem()) {
-            Some(stem) => {
-                let (stem, valid_original) = match stem {
-                    PathString::Valid(s) => (s, true),
-                    PathString::Invalid(s) => (s, false),
-                };
-                Ok(Self {
-                    stem,
-                    valid_original,
-                    extension,
-                    original: path.to_owned(),
-                    ..Default::default()
-                })
-            }
-            None => Err(FileError::BadStem),
-        }
-    }
-}
-
-impl TryFrom<&Path> for File {
-    type Error = FileError;
-
-    fn try_from(path: &Path) -> Result<Self, FileError> {
-        if !path.exists() {
-            return Err(FileError::NotExists);
-        }
-        let extension = {
-            generate_path_as_string(path.extension()).map(|e| match e {
-                PathString::Valid(s) => s,
-                PathString::Invalid(s) => s,
-            })
-        };
-        match generate_path_as_string(path.file_stem()) {
-            Some(stem) => {
-                let (stem, valid_original) = match stem {
-                    PathString::Valid(s) => (s, true),
-                    PathString::Invalid(s) => (s, false),
-                };
-                Ok(Self {
-                    stem,
-                    valid_original,
-                    extension,
-                    original: path.to_owned(),
-                    ..Default::default()
-                })
-            }
-            None => Err(FileError::BadStem),
-        }
-    }
-}
-
-impl TryFrom<PathBuf> for File {
-    type Error = FileError;
-
-    fn try_from(value: PathBuf) -> Result<Self, FileError> {
-        value.as_path().try_into()
-    }
-}
-
-impl TryFrom<&PathBuf> for File {
-    type Error = FileError;
-
-    fn try_from(value: &PathBuf) -> Result<Self, FileError> {
-        value.as_path().try_into()
-    }
-}
-impl From<&File> for WidgetText {
-    fn from(value: &File) -> Self {
-        Self::RichText(RichText::new(match &value.extension {
-            None => value.stem.clone(),
-            Some(ext) => format!("{}.{}", value.stem, ext),
-        }))
-    }
-}
-
-impl File {
-    /// Tool to rename a single file.
-    /// Takes the `&path` and various options (processed in order) to return a `PathBuf`
-    /// used to rename the file.
-    /// Options are
-    ///    -  1 RegEx
-    ///    -  2 Name
-    ///    -  3 Replace
-    ///    -  4 Case
-    ///    -  5 Remove
-    ///    -  6 Add
-    ///    -  7 Auto Date
-    ///    -  8 Append Folder Name
-    ///    -  9 Numbering
-    ///    - 10 Extension
-    ///
-    /// # Example
-    ///
-    /// ```
-    /// # use std::path::{Path, PathBuf};
-    /// # use bulk_file_renamer::file::{NameOptions, Case, CaseOptions, File, Process, Options};
-    /// let file = Path::new("file.txt");
-    /// let name = NameOptions::Fixed("new_name".into());
-    /// let case = CaseOptions{case: Case::Upper, snake: false, exceptions: "n".into()};
-    /// let mut rename = File::new(file).unwrap().with_option(Options::Name(name)).with_option(Options::Case(case));
-    /// let new_name = rename.preview();
-    /// assert_eq!(new_name, PathBuf::from("nEW_nAME.txt"));
-    /// ```
-    pub fn preview(&mut self) -> PathBuf {
-        let mut opts: Vec<Box<dyn Process>> = vec![];
-        if let Some(opt) = &self.regex {
-            opts.push(Box::new(opt.clone()));
-        };
-        if let Some(opt) = &self.name {
-            opts.push(Box::new(opt.clone()));
-        };
-        if let Some(opt) = &self.replace {
-            opts.push(Box::new(opt.clone()));
-        };
-        if let Some(opt) = &self.case {
-            opts.push(Box::new(opt.clone()));
-        };
-        if let Some(opt) = &self.remove {
-            opts.push(Box::new(opt.clone()));
-        };
-        if let Some(opt) = &self.add {
-            opts.push(Box::new(opt.clone()));
-        };
-        if let Some(opt) = &self.date {
-            opts.push(Box::new(opt.clone()));
-        };
-        if let Some(opt) = &self.folder {
-            opts.push(Box::new(opt.clone()));
-        };
-        if let Some(opt) = &self.number {
-            opts.push(Box::new(opt.clone()));
-        };
-        if let Some(opt) = &self.ext {
-            opts.push(Box::new(opt.clone()));
-        };
-        for opt in opts {
-            opt.process(self);
-        }
-        let mut new_name = match self.original.parent() {
-            None => PathBuf::from("/"),
-            Some(p) => PathBuf::from(p),
-        };
-        new_name.push(Path::new(&self.stem));
-        match &self.extension {
-            None => new_name,
-            Some(e) => new_name.with_extension(e),
-        }
-    }
-
-    /// Rename the file. Can not be undone.
-    pub fn rename(mut self) -> Result<(), FileError> {
-        let new_name = &self.preview();
-        fs::rename(&self.original, new_name)?;
-        Ok(())
-    }
-
-    /// Revert the previewed changes to a file.
-    pub fn revert(&mut self) {
-        let temp: &File = &self.original.clone().try_into().unwrap();
-        self.stem = temp.stem.clone();
-        self.extension = temp.extension.clone();
-    }
-
-    pub fn with_option(mut self, option: Options) -> Self {
-        use Options::*;
-        match option {
-            Regex(opt) => self.regex = Some(opt),
-            Name(opt) => self.name = Some(opt),
-            Case(opt) => self.case = Some(opt),
-            Remove(opt) => self.remove = Some(opt),
-            Add(opt) => self.add = Some(opt),
-            Date(opt) => self.date = Some(opt),
-            Folder(opt) => self.folder = Some(opt),
-            Number(opt) => self.number = Some(opt),
-            Extension(opt) => self.ext = Some(opt),
-        }
-        self
-    }
-
-    // Return the information on a file.
-    pub fn info(&self) -> (Filename, Extension, Size, DateModified, DateCreated) {
-        let mut size = None;
-        let mut modified = None;
-        let mut created = None;
-        if let Ok(data) = self.original.metadata() {
-            if self.original.is_file() {
-                size = Some(data.len())
-            };
-            if let Ok(dt) = data.modified() {
-                modified = Some(dt.into())
-            };
-            if let Ok(dt) = data.created() {
-                created = Some(dt.into())
-            };
-        };
-        (
-            &self.stem,
-            self.extension.as_deref(),
-            size,
-            modified,
-            created,
-        )
-    }
-
-    // Check if the original file was valid UTF-8
-    pub fn is_valid(&self) -> bool {
-        self.valid_original
-    }
-}
-
-pub type Filename<'a> = &'a str;
-pub type Extension<'a> = Option<&'a str>;
-pub type Size = Option<u64>;
-pub type DateCreated = Option<DateTime<Local>>;
-pub type DateModified = Option<DateTime<Local>>;
-
-#[derive(Debug, Error)]
-pub enum FileError {
-    #[error("File does not exist.")]
-    NotExists,
-    #[error("File does not have a stem.")]
-    BadStem,
-    #[error(transparent)]
-    Io(#[from] io::Error),
-}
-
-#[derive(Debug)]
-pub enum Options {
-    Regex(RegexOptions),
-    Name(NameOptions),
-    Case(CaseOptions),
-    Remove(RemoveOptions),
-    Add(AddOptions),
-    Date(DateOptions),
-    Folder(FolderOptions),
-    Number(NumberOptions),
-    Extension(ExtensionOptions),
-}
-
-impl Ord for File {
-    fn cmp(&self, other: &Self) -> Ordering {
-        match (self.original.is_dir(), other.original.is_dir()) {
-            (true, true) => self.stem.cmp(&other.stem),
-            (true, false) => Ordering::Less,
-            (false, true) => Ordering::Greater,
-            (false, false) => match (&self.extension, &other.extension) {
-                (None, None) => self.stem.cmp(&other.stem),
-                (None, Some(ext)) => {
-                    let mut rhs = other.stem.clone();
-                    rhs.push_str(ext);
-                    self.stem.cmp(&rhs)
-                }
-                (Some(ext), None) => {
-                    let mut lhs = self.stem.clone();
-                    lhs.push_str(ext);
-                    lhs.cmp(&other.stem)
-                }
-                (Some(self_ext), Some(other_ext)) => {
-                    let mut lhs = self.stem.clone();
-                    lhs.push_str(self_ext);
-                    let mut rhs = other.stem.clone();
-                    rhs.push_str(other_ext);
-                    lhs.cmp(&rhs)
-                }
-            },
-        }
-    }
-}
-
-impl PartialEq for File {
-    fn eq(&self, other: &Self) -> bool {
-        self.original == other.original
-    }
-}
-
-impl Eq for File {}
-
-impl PartialOrd for File {
-    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        Some(self.cmp(other))
-    }
-}
-
-#[cfg(test)]
-mod file_tests {
-    use super::*;
-
-    #[test]
-    fn test_regex() {
-        let file = Path::new("Testfile123.txt");
-        let expected = PathBuf::from("TestfileABC.txt");
-        let opt = RegexOptions {
-            exp: "123".into(),
-            rep: "ABC".into(),
-            extension: false,
-        };
-        let mut rename = File::new(file).unwrap().with_option(Options::Regex(opt));
-        let result = rename.preview();
-        assert_eq!(result, expected)
-    }
 
-    #[test]
-    fn test_name() {
-        let file = Path::new("file.txt");
-        let expected = PathBuf::from("new_name.txt");
-        let name = NameOptions::Fixed("new_name".into());
-        let mut rename = File::new(file).unwrap().with_option(Options::Name(name));
-        let new_name = rename.preview();
-        assert_eq!(new_name, expected)
-    }
+    /// Fill the view from existing options, the reverse of `build`.
+    fn load(&mut self, options: Self::Processor);
 }
@@ -1,60 +1,7 @@
-use egui::{Response, Ui, Widget};
-
-use super::{File, OptionBuilder, Process};
+use super::{OptionBuilder, Step};
 use crate::gui::{Arrows, Incrementer, ValText};
-
-/// Add a fixed `Prefix` or`Suffix` to the filename,
-/// or `Insert` text at a specific location (0 indexed, negative to index from the end).
-///
-/// You may also choose to add a `Word Space`. This will insert a space before any
-/// capital letter (except the first character), unless there's a space already there.
-#[derive(Default, Debug, Clone)]
-pub struct AddOptions {
-    pub prefix: Option<String>,
-    pub insert: Option<(i32, String)>,
-    pub suffix: Option<String>,
-    pub word_space: bool,
-}
-
-impl Process for AddOptions {
-    fn process(&self, file: &mut File) {
-        let file = &mut file.stem;
-        if let Some(prefix) = &self.prefix {
-            file.insert_str(0, prefix);
-        }
-
-        if let Some((pos, insert)) = &self.insert {
-            match pos {
-                p if p >= &(file.len() as i32) => file.push_str(insert),
-                p if p >= &0 => file.insert_str(*p as usize, insert),
-                p if -p >= file.len() as i32 => file.insert_str(0, insert),
-                _ => {
-                    let p = (file.len() as i32 + pos) as usize;
-                    file.insert_str(p, insert);
-                } // pos is negative
-            }
-        }
-
-        if let Some(suffix) = &self.suffix {
-            file.push_str(suffix);
-        }
-
-        if self.word_space {
-            let mut new = String::new();
-            let mut iter = file.chars();
-            if let Some(chr) = iter.next() {
-                new.push(chr)
-            }
-            for chr in iter {
-                if chr.is_uppercase() {
-                    new.push(' ');
-                }
-                new.push(chr);
-            }
-            *file = new
-        }
-    }
-}
+use egui::{Response, Ui, Widget};
+pub use mass_renamer_core::file::add::*;
 
 #[derive(Default)]
 pub struct AddView {
@@ -63,6 +10,7 @@ pub struct AddView {
     position: ValText<i32>,
     suffix: String,
     word_space: bool,
+    digit_space: bool,
     width: f32,
 }
 
@@ -95,7 +43,25 @@ impl OptionBuilder for AddView {
             insert,
             suffix,
             word_space: self.word_space,
+            digit_space: self.digit_space,
+        }
+    }
+
+    fn load(&mut self, options: AddOptions) {
+        self.prefix = options.prefix.unwrap_or_default();
+        match options.insert {
+            Some((position, insert)) => {
+                self.position.set_val(position);
+                self.insert = insert;
+            }
+            None => {
+                self.position.clear();
+                self.insert.clear();
+            }
         }
+        self.suffix = options.suffix.unwrap_or_default();
+        self.word_space = options.word_space;
+        self.digit_space = options.digit_space;
     }
 }
 
@@ -117,7 +83,7 @@ impl Widget for &mut AddView {
     fn ui(mut self, ui: &mut Ui) -> Response {
         ui.vertical(|ui| {
             ui.set_width(self.width);
-            ui.label("Add");
+            ui.label(Step::Add.to_string());
             ui.horizontal(|ui| {
                 ui.label("Prefix");
                 ui.text_edit_singleline(&mut self.prefix);
@@ -139,80 +105,13 @@ impl Widget for &mut AddView {
                 ui.label("Suffix");
                 ui.text_edit_singleline(&mut self.suffix);
             });
-            ui.checkbox(&mut self.word_space, "Word Space");
+            ui.horizontal(|ui| {
+                ui.checkbox(&mut self.word_space, "Word Space")
+                    .on_hover_text("Space before capitals, keeping acronyms together");
+                ui.checkbox(&mut self.digit_space, "Digit Space")
+                    .on_hover_text("Space between numbers and letters");
+            });
         })
         .response
     }
 }
-
-#[cfg(test)]
-mod add_tests {
-    use super::*;
-    use std::path::Path;
-
-    #[test]
-    fn add_all_options() {
-        let prefix = Some("prefix-".into());
-        let insert = Some((15, "-insert-".into()));
-        let suffix = Some("-suffix".into());
-        let word_space = true;
-        let file = Path::new("SomeTestFile");
-        let opt = AddOptions {
-            prefix,
-            insert,
-            suffix,
-            word_space,
-        };
-        let mut rename = File::new(file).unwrap();
-        opt.process(&mut rename);
-        assert_eq!(
-            rename.stem,
-            "prefix- Some Test-insert- File-suffix".to_owned()
-        )
-    }
-
-    #[test]
-    fn test_negative_insert() {
-        let insert = Some((-1, "!".into()));
-        let file = Path::new("Some Test File");
-        let opt = AddOptions {
-            prefix: None,
-            insert,
-            suffix: None,
-            word_space: false,
-        };
-        let mut rename = File::new(file).unwrap();
-        opt.process(&mut rename);
-        assert_eq!(rename.stem, "Some Test Fil!e".to_owned());
-    }
-
-    #[test]
-    fn test_insert_too_far_positive() {
-        let insert = Some((100, "!".into()));
-        let file = Path::new("Some Test File");
-        let opt = AddOptions {
-            prefix: None,
-            insert,
-            suffix: None,
-            word_space: false,
-        };
-        let mut rename = File::new(file).unwrap();
-        opt.process(&mut rename);
-        assert_eq!(rename.stem, "Some Test File!".to_owned());
-    }
-
-    #[test]
-    fn test_insert_too_far_negative() {
-        let insert = Some((-100, "!".into()));
-        let file = Path::new("Some Test File");
-        let opt = AddOptions {
-            prefix: None,
-            insert,
-            suffix: None,
-            word_space: false,
-        };
-        let mut rename = File::new(file).unwrap();
-        opt.process(&mut rename);
-        assert_eq!(rename.stem, "!Some Test File".to_owned());
-    }
-}
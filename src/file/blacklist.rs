@@ -0,0 +1,84 @@
+use super::{OptionBuilder, Step};
+use egui::{ComboBox, Response, Ui, Widget};
+pub use mass_renamer_core::file::blacklist::*;
+
+#[derive(Default)]
+pub struct BlacklistView {
+    options: BlacklistOptions,
+    source: String,
+    error: Option<String>,
+    width: f32,
+}
+
+impl BlacklistView {
+    pub fn new(width: f32) -> Self {
+        Self {
+            width,
+            ..Default::default()
+        }
+    }
+}
+
+impl OptionBuilder for BlacklistView {
+    type Processor = BlacklistOptions;
+
+    fn build(&self) -> BlacklistOptions {
+        self.options.clone()
+    }
+
+    fn load(&mut self, options: BlacklistOptions) {
+        self.options = options;
+        self.source.clear();
+        self.error = None;
+    }
+}
+
+impl Widget for &mut BlacklistView {
+    fn ui(self, ui: &mut Ui) -> Response {
+        ui.vertical(|ui| {
+            ui.set_width(self.width);
+            ui.label(Step::Blacklist.to_string());
+            ui.horizontal(|ui| {
+                if ui.small_button("Load").clicked() {
+                    if let Some(path) = rfd::FileDialog::new()
+                        .add_filter("Text", &["txt"])
+                        .pick_file()
+                    {
+                        match BlacklistOptions::read_words(&path) {
+                            Ok(words) => {
+                                self.options.words = words;
+                                self.source = path.display().to_string();
+                                self.error = None;
+                            }
+                            Err(err) => self.error = Some(err.to_string()),
+                        }
+                    }
+                };
+                if ui.small_button("Clear").clicked() {
+                    self.options.words.clear();
+                    self.source.clear();
+                    self.error = None;
+                };
+                ui.label(format!("{} words", self.options.words.len()))
+                    .on_hover_text(&self.source);
+            });
+            if let Some(err) = &self.error {
+                ui.label(err);
+            }
+            ui.horizontal(|ui| {
+                ComboBox::from_id_source("Blacklist Mode")
+                    .selected_text(format!("{:?}", self.options.mode))
+                    .show_ui(ui, |ui| {
+                        ui.selectable_value(&mut self.options.mode, BlacklistMode::Flag, "Flag");
+                        ui.selectable_value(
+                            &mut self.options.mode,
+                            BlacklistMode::Remove,
+                            "Remove",
+                        );
+                    });
+                ui.checkbox(&mut self.options.case, "Match Case");
+            });
+        })
+        .response
+    }
+}
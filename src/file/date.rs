@@ -1,189 +1,11 @@
-use egui::{ComboBox, Response, TextEdit, Ui, Widget};
-use std::{error::Error, fmt::Write, path::Path, time::SystemTime};
-
-use super::{File, OptionBuilder, Process};
-use chrono::{DateTime, Local};
-
-/// Use the prefix or suffix `Mode` to modify the filename with a date format.
-/// The `Date` that the file was created, modified, or the current date can be added in
-/// the format (`FMT`) selected. A `Sep`erator can be specified for the character(s)
-/// between the filename and the date as well as a format for setting the character(s)
-/// between date `Seg`ments. Select the `YYYY` box to display years as 4 digit instead
-/// of the default 2 (except for custom dates).
-///
-/// You also have the option to specify your own custom date formats using
-/// [chrono::format::strftime](https://docs.rs/chrono/0.4.31/chrono/format/strftime/index.html) specifiers.
-#[derive(Default, Debug, Clone)]
-pub struct DateOptions {
-    date_mode: DateMode,
-    date_type: DateType,
-    fmt: DateFormat,
-    custom_fmt: String,
-    sep: String,
-    seg: String,
-    full_year: bool,
-}
-
-impl Process for DateOptions {
-    fn process(&self, file: &mut File) {
-        if let Ok(datetime) = self.get_date(&file.original) {
-            let format = match &self.fmt {
-                DateFormat::Std((prefix, suffix)) => {
-                    let mut fmt = prefix.get_format(&self.seg, self.full_year);
-                    if let Some(suf) = suffix {
-                        fmt.push_str(&self.seg);
-                        fmt.push_str(&suf.get_format(&self.seg));
-                    }
-                    fmt
-                }
-                DateFormat::Custom => self.custom_fmt.clone(),
-            };
-            match self.date_mode {
-                DateMode::Prefix => file
-                    .stem
-                    .insert_str(0, &format!("{}{}", datetime.format(&format), self.sep)),
-                DateMode::Suffix => {
-                    write!(file.stem, "{}{}", self.sep, datetime.format(&format))
-                        .expect("Unexpected error appending to string.");
-                }
-                DateMode::None => {}
-            }
-        }
-    }
-}
-
-impl DateOptions {
-    fn get_date(&self, file: &Path) -> Result<DateTime<Local>, Box<dyn Error>> {
-        let metadata = file.metadata()?;
-        let dt = match self.date_type {
-            DateType::Created => metadata.created()?,
-            DateType::Modified => metadata.modified()?,
-            DateType::Current => SystemTime::now(),
-        };
-        let datetime: DateTime<Local> = dt.into();
-        Ok(datetime)
-    }
-}
-
-/// Select from
-/// `DateMode::Prefix`,
-/// `DateMode::Suffix`.
-#[derive(Default, PartialEq, Debug, Clone)]
-pub enum DateMode {
-    Prefix,
-    Suffix,
-    #[default]
-    None,
-}
-
-/// Select from
-/// - `DateType::Created` for the file creation date
-/// - `Datetype::Modified` for the date last modified
-/// - `DateType::Current` for today's date
-///
-/// Note, if an OS does not support `Created` or `Modified` this option will
-/// result in no change to the file name.
-#[derive(Default, PartialEq, Debug, Clone)]
-pub enum DateType {
-    #[default]
-    Created,
-    Modified,
-    Current,
-}
-
-/// Select from
-/// - `DateFormat::Std(DatePrefix, Option<DateSuffix>)` to use the standard options
-/// - `DateFormat::Custom` to use a custom `strftime` format
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub enum DateFormat {
-    Std((DatePrefix, Option<DateSuffix>)),
-    Custom,
-}
-
-impl Default for DateFormat {
-    fn default() -> Self {
-        Self::Std((DatePrefix::Dmy, None))
-    }
-}
-
-impl DateFormat {
-    fn format(&self) -> &str {
-        match self {
-            Self::Std((DatePrefix::Dmy, None)) => "DMY",
-            Self::Std((DatePrefix::Mdy, None)) => "MDY",
-            Self::Std((DatePrefix::Ymd, None)) => "YMD",
-            Self::Std((DatePrefix::Dmy, Some(DateSuffix::Hm))) => "DMY HM",
-            Self::Std((DatePrefix::Mdy, Some(DateSuffix::Hm))) => "MDY HM",
-            Self::Std((DatePrefix::Ymd, Some(DateSuffix::Hm))) => "YMD HM",
-            Self::Std((DatePrefix::Dmy, Some(DateSuffix::Hms))) => "DMY HMS",
-            Self::Std((DatePrefix::Mdy, Some(DateSuffix::Hms))) => "MDY HMS",
-            Self::Std((DatePrefix::Ymd, Some(DateSuffix::Hms))) => "YMD HMS",
-            Self::Custom => "Custom",
-        }
-    }
-
-    fn iter() -> impl Iterator<Item = DateFormat> {
-        [
-            Self::Std((DatePrefix::Dmy, None)),
-            Self::Std((DatePrefix::Mdy, None)),
-            Self::Std((DatePrefix::Ymd, None)),
-            Self::Std((DatePrefix::Dmy, Some(DateSuffix::Hm))),
-            Self::Std((DatePrefix::Mdy, Some(DateSuffix::Hm))),
-            Self::Std((DatePrefix::Ymd, Some(DateSuffix::Hm))),
-            Self::Std((DatePrefix::Dmy, Some(DateSuffix::Hms))),
-            Self::Std((DatePrefix::Mdy, Some(DateSuffix::Hms))),
-            Self::Std((DatePrefix::Ymd, Some(DateSuffix::Hms))),
-            Self::Custom,
-        ]
-        .iter()
-        .copied()
-    }
-}
-
-/// Select from
-/// - `DatePrefix::DMY` for Day Month Year
-/// - `DatePrefix::MDY` for Month Year Day
-/// - `DatePrefix::YMD` for Year Month Day
-#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
-pub enum DatePrefix {
-    #[default]
-    Dmy,
-    Mdy,
-    Ymd,
-}
-
-impl DatePrefix {
-    fn get_format(&self, sep: &str, full_year: bool) -> String {
-        let y = if full_year { "%Y" } else { "%y" };
-        match self {
-            Self::Dmy => format!("%d{sep}%m{sep}{y}"),
-            Self::Mdy => format!("%m{sep}%d{sep}{y}"),
-            Self::Ymd => format!("{y}{sep}%m{sep}%d"),
-        }
-    }
-}
-
-/// Select from
-/// - `DateSuffix::HM` for Hour Minute
-/// - `DateSuffix::HMS` for Hour Minute Second
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub enum DateSuffix {
-    Hm,
-    Hms,
-}
-
-impl DateSuffix {
-    fn get_format(&self, sep: &str) -> String {
-        match self {
-            Self::Hm => format!("%H{sep}%M{sep}"),
-            Self::Hms => format!("%H{sep}%M{sep}%S"),
-        }
-    }
-}
+use super::{OptionBuilder, Step};
+use egui::{ComboBox, DragValue, Response, TextEdit, Ui, Widget};
+pub use mass_renamer_core::file::date::*;
 
 #[derive(Default)]
 pub struct DateView {
     data: DateOptions,
+    bounds: Option<(String, String)>,
     width: f32,
 }
 
@@ -194,6 +16,11 @@ impl DateView {
             ..Default::default()
         }
     }
+
+    /// The earliest and latest dates for the selected files, shown below the options.
+    pub fn set_bounds(&mut self, bounds: Option<(String, String)>) {
+        self.bounds = bounds;
+    }
 }
 
 impl OptionBuilder for DateView {
@@ -202,13 +29,17 @@ impl OptionBuilder for DateView {
     fn build(&self) -> DateOptions {
         self.data.clone()
     }
+
+    fn load(&mut self, options: DateOptions) {
+        self.data = options;
+    }
 }
 
 impl Widget for &mut DateView {
     fn ui(self, ui: &mut Ui) -> Response {
         ui.vertical(|ui| {
             ui.set_width(self.width);
-            ui.label("Date");
+            ui.label(Step::Date.to_string());
             ui.horizontal(|ui| {
                 ui.set_width(self.width);
                 ui.label("Mode");
@@ -278,95 +109,81 @@ impl Widget for &mut DateView {
                 ui.add(TextEdit::singleline(&mut self.data.seg).desired_width(30.0));
             });
             ui.checkbox(&mut self.data.full_year, "4 Digit Year");
+            ui.horizontal(|ui| {
+                ui.set_width(self.width);
+                ui.label("Time Zone");
+                ComboBox::from_id_source("Date Zone")
+                    .selected_text(match self.data.zone {
+                        DateZone::Local => "Local",
+                        DateZone::Utc => "UTC",
+                        DateZone::Fixed(_) => "Offset",
+                    })
+                    .show_ui(ui, |ui| {
+                        ui.selectable_value(&mut self.data.zone, DateZone::Local, "Local");
+                        ui.selectable_value(&mut self.data.zone, DateZone::Utc, "UTC");
+                        if !matches!(self.data.zone, DateZone::Fixed(_)) {
+                            ui.selectable_value(&mut self.data.zone, DateZone::Fixed(0), "Offset");
+                        }
+                    })
+                    .response
+                    .on_hover_text(
+                        "Use UTC or an offset so synced files get the same names everywhere",
+                    );
+                if let DateZone::Fixed(minutes) = &mut self.data.zone {
+                    ui.add(
+                        DragValue::new(minutes)
+                            .clamp_range(-DateZone::MAX_OFFSET..=DateZone::MAX_OFFSET)
+                            .speed(15)
+                            .custom_formatter(|minutes, _| {
+                                let sign = if minutes < 0.0 { '-' } else { '+' };
+                                let minutes = minutes.abs() as i32;
+                                format!("UTC{sign}{:02}:{:02}", minutes / 60, minutes % 60)
+                            })
+                            .custom_parser(parse_offset),
+                    );
+                }
+            });
+            #[cfg(feature = "locales")]
+            ui.horizontal(|ui| {
+                ui.set_width(self.width);
+                ui.label("Language");
+                ComboBox::from_id_source("Date Locale")
+                    .selected_text(match self.data.locale.as_str() {
+                        "" => "English",
+                        locale => locale,
+                    })
+                    .show_ui(ui, |ui| {
+                        ui.selectable_value(&mut self.data.locale, String::new(), "English");
+                        for locale in LOCALES {
+                            ui.selectable_value(&mut self.data.locale, locale.into(), locale);
+                        }
+                    })
+                    .response
+                    .on_hover_text("The language of month and weekday names, e.g. %B and %A");
+            });
+            if let Some((first, last)) = &self.bounds {
+                if self.data.date_mode != DateMode::None {
+                    ui.label(format!("Range: {first} … {last}"));
+                }
+            }
         })
         .response
     }
 }
 
-#[cfg(test)]
-mod date_tests {
-    use super::*;
-    use crate::tester::run_test;
-    use std::path::Path;
-
-    #[test]
-    fn prefix_date_modified_hyphen_separator_full_year() {
-        run_test(&vec!["test file.txt"], || {
-            let mut file = File::new(Path::new("test file.txt")).unwrap();
-            let date_mode = DateMode::Prefix;
-            let date_type = DateType::Modified;
-            let fmt = DateFormat::Std((DatePrefix::Dmy, None));
-            let custom_fmt = String::new();
-            let sep = "-".into();
-            let seg = "_".into();
-            let full_year = true;
-            let opt = DateOptions {
-                date_mode,
-                date_type,
-                fmt,
-                custom_fmt,
-                sep,
-                seg,
-                full_year,
-            };
-            let date = format!("{}", chrono::Local::now().format("%d_%m_%Y"));
-            let expected = format!("{date}-test file");
-            opt.process(&mut file);
-            assert_eq!(file.stem, expected);
-        })
-    }
-
-    #[test]
-    fn suffix_date_created_no_separator() {
-        crate::tester::run_test(&vec!["test file.txt"], || {
-            let date = format!("{}", chrono::Local::now().format("%d_%m_%y_%H_%M_%S"));
-            let mut file = File::new(Path::new("test file.txt")).unwrap();
-            let date_mode = DateMode::Suffix;
-            let date_type = DateType::Created;
-            let fmt = DateFormat::Std((DatePrefix::Dmy, Some(DateSuffix::Hms)));
-            let custom_fmt = String::new();
-            let sep = "".into();
-            let seg = "_".into();
-            let full_year = false;
-            let opt = DateOptions {
-                date_mode,
-                date_type,
-                fmt,
-                custom_fmt,
-                sep,
-                seg,
-                full_year,
-            };
-            let expected = format!("test file{date}");
-            opt.process(&mut file);
-            assert_eq!(file.stem, expected);
-        })
-    }
-
-    #[test]
-    fn prefix_date_current_custom_format() {
-        crate::tester::run_test(&vec!["test file.txt"], || {
-            let mut file = File::new(Path::new("test file.txt")).unwrap();
-            let date_mode = DateMode::Prefix;
-            let date_type = DateType::Current;
-            let fmt = DateFormat::Custom;
-            let custom_fmt = String::from("%v++");
-            let sep = "~".into();
-            let seg = "_".into();
-            let full_year = true;
-            let opt = DateOptions {
-                date_mode,
-                date_type,
-                fmt,
-                custom_fmt,
-                sep,
-                seg,
-                full_year,
-            };
-            let date = format!("{}", chrono::Local::now().format("%v"));
-            let expected = format!("{date}++~test file");
-            opt.process(&mut file);
-            assert_eq!(file.stem, expected);
-        })
-    }
+/// Read an offset typed as `+05:30`, `UTC-4` or `-240` (minutes) into minutes.
+pub(crate) fn parse_offset(text: &str) -> Option<f64> {
+    let text = text.trim().trim_start_matches("UTC");
+    let (sign, text) = match text.strip_prefix('-') {
+        Some(rest) => (-1.0, rest),
+        None => (1.0, text.trim_start_matches('+')),
+    };
+    let minutes = match text.split_once(':') {
+        Some((hours, minutes)) => {
+            hours.parse::<f64>().ok()? * 60.0 + minutes.parse::<f64>().ok()?
+        }
+        None if text.len() <= 2 => text.parse::<f64>().ok()? * 60.0,
+        None => text.parse::<f64>().ok()?,
+    };
+    Some(sign * minutes)
 }
@@ -0,0 +1,67 @@
+use super::{DateType, OptionBuilder, Step};
+use egui::{ComboBox, Response, TextEdit, Ui, Widget};
+pub use mass_renamer_core::file::destination::*;
+
+#[derive(Default)]
+pub struct DestinationView {
+    options: DestinationOptions,
+    width: f32,
+}
+
+impl DestinationView {
+    pub fn new(width: f32) -> Self {
+        Self {
+            width,
+            ..Default::default()
+        }
+    }
+}
+
+impl OptionBuilder for DestinationView {
+    type Processor = DestinationOptions;
+
+    fn build(&self) -> DestinationOptions {
+        self.options.clone()
+    }
+
+    fn load(&mut self, options: DestinationOptions) {
+        self.options = options;
+    }
+}
+
+impl Widget for &mut DestinationView {
+    fn ui(self, ui: &mut Ui) -> Response {
+        ui.vertical(|ui| {
+            ui.set_width(self.width);
+            ui.label(Step::Destination.to_string());
+            ui.horizontal(|ui| {
+                ui.label("Folder");
+                ui.add(TextEdit::singleline(&mut self.options.folder).hint_text("{year}/{month}"))
+                    .on_hover_text(
+                        "Relative to each file's folder, or absolute. Missing folders are \
+                         created, left empty the files stay where they are",
+                    );
+            });
+            ui.horizontal(|ui| {
+                ui.label("Date");
+                ComboBox::from_id_source("Destination Date")
+                    .selected_text(match self.options.date_type {
+                        DateType::Created => "Created",
+                        DateType::Modified => "Modified",
+                        DateType::Current => "Now",
+                    })
+                    .show_ui(ui, |ui| {
+                        let date_type = &mut self.options.date_type;
+                        ui.selectable_value(date_type, DateType::Created, "Created");
+                        ui.selectable_value(date_type, DateType::Modified, "Modified");
+                        ui.selectable_value(date_type, DateType::Current, "Now");
+                    });
+            })
+            .response
+            .on_hover_text("The date {year}, {month} and {day} are taken from");
+            ui.checkbox(&mut self.options.copy, "Copy instead of move")
+                .on_hover_text("Leave the originals where they are");
+        })
+        .response
+    }
+}
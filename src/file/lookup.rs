@@ -0,0 +1,90 @@
+use super::{OptionBuilder, Step};
+use egui::{Response, Ui, Widget};
+pub use mass_renamer_core::file::lookup::*;
+
+#[derive(Default)]
+pub struct LookupView {
+    options: LookupOptions,
+    source: String,
+    error: Option<String>,
+    width: f32,
+}
+
+impl LookupView {
+    pub fn new(width: f32) -> Self {
+        Self {
+            width,
+            ..Default::default()
+        }
+    }
+}
+
+impl OptionBuilder for LookupView {
+    type Processor = LookupOptions;
+
+    fn build(&self) -> LookupOptions {
+        self.options.clone()
+    }
+
+    fn load(&mut self, options: LookupOptions) {
+        self.options = options;
+        self.source.clear();
+        self.error = None;
+    }
+}
+
+impl Widget for &mut LookupView {
+    fn ui(self, ui: &mut Ui) -> Response {
+        ui.vertical(|ui| {
+            ui.set_width(self.width);
+            ui.label(Step::Lookup.to_string());
+            ui.horizontal(|ui| {
+                if ui.small_button("Load").clicked() {
+                    if let Some(path) = rfd::FileDialog::new()
+                        .add_filter("Table", &["csv", "tsv"])
+                        .pick_file()
+                    {
+                        match LookupOptions::read_csv(&path) {
+                            Ok(options) => {
+                                self.options = LookupOptions {
+                                    key: self.options.key.clone(),
+                                    ..options
+                                };
+                                self.source = path.display().to_string();
+                                self.error = None;
+                            }
+                            Err(err) => self.error = Some(err.to_string()),
+                        }
+                    }
+                };
+                if ui.small_button("Clear").clicked() {
+                    self.options.columns.clear();
+                    self.options.rows.clear();
+                    self.source.clear();
+                    self.error = None;
+                };
+                ui.label(format!("{} rows", self.options.rows.len()))
+                    .on_hover_text(&self.source);
+            });
+            ui.horizontal(|ui| {
+                ui.label("Key:");
+                ui.text_edit_singleline(&mut self.options.key)
+                    .on_hover_text("Regex for the key, the whole file name when empty");
+            });
+            if !self.options.columns.is_empty() {
+                ui.label(
+                    self.options
+                        .columns
+                        .iter()
+                        .map(|column| format!("{{{column}}}"))
+                        .collect::<Vec<_>>()
+                        .join(" "),
+                );
+            }
+            if let Some(err) = &self.error {
+                ui.label(err);
+            }
+        })
+        .response
+    }
+}
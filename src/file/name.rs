@@ -1,55 +1,6 @@
-use std::slice::Iter;
-
-use super::{File, OptionBuilder, Process};
-use egui::{Response, RichText, Ui, Widget, WidgetText};
-
-/// Select from.
-/// - `NameOptions::Keep` - Do not change the original file name (default).
-/// - `NameOptions::Remove` - Completely erase the file from the selected items. This allows it to be rebuilt using components higher than (2).
-/// - `NameOptions::Fixed` - Specify a new file in the box for all selected items. Only really useful if you're also using the Numbering section.
-/// - `NameOptions::Reverse` - Reverse the name, e.g. 12345.txt becomes 54321.txt.
-#[derive(Default, Debug, Clone, PartialEq, Eq)]
-pub enum NameOptions {
-    #[default]
-    Keep,
-    Remove,
-    Fixed(String),
-    Reverse,
-}
-
-impl Process for NameOptions {
-    fn process(&self, file: &mut File) {
-        match self {
-            NameOptions::Keep => (),
-            NameOptions::Remove => file.stem = "".to_owned(),
-            NameOptions::Fixed(x) => file.stem = x.to_string(),
-            NameOptions::Reverse => file.stem = file.stem.chars().rev().collect::<String>(),
-        };
-    }
-}
-
-impl NameOptions {
-    fn iter() -> Iter<'static, Self> {
-        static OPTIONS: [NameOptions; 4] = [
-            NameOptions::Keep,
-            NameOptions::Remove,
-            NameOptions::Fixed(String::new()),
-            NameOptions::Reverse,
-        ];
-        OPTIONS.iter()
-    }
-}
-
-impl From<&NameOptions> for WidgetText {
-    fn from(value: &NameOptions) -> Self {
-        WidgetText::RichText(RichText::new(match value {
-            NameOptions::Keep => "Keep",
-            NameOptions::Remove => "Remove",
-            NameOptions::Fixed(_) => "Fixed",
-            NameOptions::Reverse => "Reverse",
-        }))
-    }
-}
+use super::{OptionBuilder, Step};
+use egui::{Response, Ui, Widget};
+pub use mass_renamer_core::file::name::*;
 
 #[derive(Default)]
 pub struct NameView {
@@ -76,15 +27,22 @@ impl OptionBuilder for NameView {
             _ => self.mode.clone(),
         }
     }
+
+    fn load(&mut self, options: NameOptions) {
+        (self.mode, self.value) = match options {
+            NameOptions::Fixed(value) => (NameOptions::Fixed(String::new()), value),
+            opt => (opt, String::new()),
+        };
+    }
 }
 
 impl Widget for &mut NameView {
     fn ui(self, ui: &mut Ui) -> Response {
         ui.vertical(|ui| {
             ui.set_width(self.width);
-            ui.label("Name");
+            ui.label(Step::Name.to_string());
             egui::ComboBox::new("Name Options", "")
-                .selected_text(&self.mode)
+                .selected_text(self.mode.to_string())
                 .show_ui(ui, |ui| {
                     for opt in NameOptions::iter() {
                         ui.selectable_value(&mut self.mode, opt.clone(), format!("{:?}", opt));
@@ -95,37 +53,3 @@ impl Widget for &mut NameView {
         .response
     }
 }
-#[cfg(test)]
-mod name_tests {
-    use super::*;
-    use std::path::Path;
-    #[test]
-    fn keep_name() {
-        let mut file = File::new(Path::new("file")).unwrap();
-        let opt = NameOptions::Keep;
-        opt.process(&mut file);
-        assert_eq!(&file.stem, "file");
-    }
-    #[test]
-    fn remove_name() {
-        let mut file = File::new(Path::new("file")).unwrap();
-        let opt = NameOptions::Remove;
-        opt.process(&mut file);
-        assert_eq!(&file.stem, "");
-    }
-    #[test]
-    fn fixed_name() {
-        let mut file = File::new(Path::new("file")).unwrap();
-        let new_name = "renamed_file";
-        let opt = NameOptions::Fixed(String::from(new_name));
-        opt.process(&mut file);
-        assert_eq!(file.stem, new_name);
-    }
-    #[test]
-    fn reverse_name() {
-        let mut file = File::new(Path::new("file")).unwrap();
-        let opt = NameOptions::Reverse;
-        opt.process(&mut file);
-        assert_eq!(&file.stem, "elif");
-    }
-}
@@ -0,0 +1,51 @@
+use super::{OptionBuilder, Step};
+use egui::{ComboBox, Response, Ui, Widget};
+pub use mass_renamer_core::file::normalize::*;
+
+#[derive(Default)]
+pub struct NormalizeView {
+    options: NormalizeOptions,
+    width: f32,
+}
+
+impl NormalizeView {
+    pub fn new(width: f32) -> Self {
+        Self {
+            width,
+            ..Default::default()
+        }
+    }
+}
+
+impl OptionBuilder for NormalizeView {
+    type Processor = NormalizeOptions;
+
+    fn build(&self) -> NormalizeOptions {
+        self.options
+    }
+
+    fn load(&mut self, options: NormalizeOptions) {
+        self.options = options;
+    }
+}
+
+impl Widget for &mut NormalizeView {
+    fn ui(self, ui: &mut Ui) -> Response {
+        ui.vertical(|ui| {
+            ui.set_width(self.width);
+            ui.label(Step::Normalize.to_string());
+            ComboBox::from_id_source("Normalize")
+                .selected_text(format!("{:?}", self.options).to_uppercase())
+                .show_ui(ui, |ui| {
+                    for opt in NormalizeOptions::iter() {
+                        ui.selectable_value(
+                            &mut self.options,
+                            opt,
+                            format!("{:?}", opt).to_uppercase(),
+                        );
+                    }
+                });
+        })
+        .response
+    }
+}
@@ -1,108 +1,8 @@
-use egui::{ComboBox, Response, TextEdit, Ui, Widget};
-
+use super::{OptionBuilder, Step};
 use crate::gui::{Arrows, Incrementer, ValText, NUM_WIDTH};
-
-use super::{File, OptionBuilder, Process};
-use std::fmt::Write;
-
-/// Add sequential numbers to the file.
-/// - `Mode` - Choose between prefix, suffix, both, or insert at a location (0 indexed).
-/// - `Value` - Specify a value number for the numbering.
-/// - `Step` - The number to be added to the previous.
-/// - `Pad` - The minimum number of digits occupied by the numeric element.
-/// - `Char` - The character to use for padding. By default, numeric bases will be padded with leading zeros; the a-z and A-Z options will be padded with "a" or "A" as appropriate.
-/// - `Sep`. - A character or characters that you wish to be inserted between the old filename and the number. If you enter the special character ":" (colon) in the Sep. box then this will be replaced with the auto-number. So a separator value of ABC:DEF: would result in ABC1DEF1, ABC2ABC2 etc.
-/// - `Format` - You can choose to append the auto-number in any various bases (binary, decimal, hex (upper and lower), octal), ASCII letters A-Z.
-#[derive(Default, Debug, Clone)]
-pub struct NumberOptions {
-    pub mode: NumberMode,
-    pub value: u32,
-    pub pad: usize,
-    pub char: char,
-    pub sep: String,
-    pub format: NumberFormat,
-}
-
-impl Process for NumberOptions {
-    fn process(&self, file: &mut File) {
-        let val = self.number_value();
-        match self.mode {
-            NumberMode::Prefix => file.stem.insert_str(0, &format!("{}{}", val, self.sep)),
-            NumberMode::Suffix => write!(file.stem, "{}{}", self.sep, val)
-                .expect("Unexpected error appending string."),
-            NumberMode::Insert(idx) => file
-                .stem
-                .insert_str(idx, &format!("{}{}{}", self.sep, val, self.sep)),
-        };
-    }
-}
-
-impl NumberOptions {
-    fn number_value(&self) -> String {
-        let replace = match &self.format {
-            NumberFormat::Decimal => format!("{}", self.value),
-            NumberFormat::Binary => format!("{:b}", self.value),
-            NumberFormat::Octal => format!("{:o}", self.value),
-            NumberFormat::HexUpper => format!("{:X}", self.value),
-            NumberFormat::HexLower => format!("{:x}", self.value),
-            f => {
-                let offset = match f {
-                    NumberFormat::AsciiLower => 96_u8,
-                    _ => 64_u8,
-                };
-                let mut res: Vec<char> = Vec::new();
-                let mut val = self.value;
-                while val > 0 {
-                    res.push(char::from((val % 26) as u8 + offset));
-                    val /= 26;
-                }
-                res.reverse();
-                res.into_iter().collect::<String>()
-            }
-        };
-        if self.pad > replace.len() {
-            let mut val = std::iter::repeat(self.char)
-                .take(self.pad - replace.len())
-                .collect::<String>();
-            val.push_str(&replace);
-            val
-        } else {
-            replace
-        }
-    }
-}
-
-/// Select from
-/// `NumberMode::Prefix`,
-/// `NumberMode::Suffix`, or
-/// `NumberMode::Insert(usize)`.
-#[derive(Default, PartialEq, Debug, Clone, Copy)]
-pub enum NumberMode {
-    #[default]
-    Prefix,
-    Suffix,
-    Insert(usize),
-}
-
-/// Select from
-/// `NumberFormat:Binary`,
-/// `NumberFormat:Decimal`,
-/// `NumberFormat:HexUpper`,
-/// `NumberFormat:HexLower`,
-/// `NumberFormat:Octal`,
-/// `NumberFormat:AsciiUpper`, or
-/// `NumberFormat:AsciiLower`
-#[derive(Default, PartialEq, Debug, Clone, Copy)]
-pub enum NumberFormat {
-    Binary,
-    #[default]
-    Decimal,
-    HexUpper,
-    HexLower,
-    Octal,
-    AsciiUpper,
-    AsciiLower,
-}
+use egui::{ComboBox, Response, TextEdit, Ui, Widget};
+pub use mass_renamer_core::file::number::*;
+use std::path::Path;
 
 #[derive(Default)]
 pub struct NumberView {
@@ -113,8 +13,12 @@ pub struct NumberView {
     pad: ValText<usize>,
     padding_char: ValText<char>,
     sep: String,
-    reset_pos: ValText<usize>,
+    reset_pos: ValText<u32>,
+    reset_folder: bool,
     format: NumberFormat,
+    count: usize,
+    auto_pad: bool,
+    detected_pad: Option<usize>,
     width: f32,
 }
 
@@ -125,6 +29,23 @@ impl NumberView {
             ..Default::default()
         }
     }
+
+    /// Number of files that will be numbered, used to show the range of numbers.
+    pub fn set_count(&mut self, count: usize) {
+        self.count = count;
+    }
+
+    /// Start numbering from `start`, as if it were typed in.
+    pub fn set_start(&mut self, start: u32) {
+        self.start.set_val(start);
+    }
+
+    /// Names already in the folder, used to detect the padding when `Auto` is ticked.
+    pub fn set_siblings<P: AsRef<Path>>(&mut self, names: &[P]) {
+        if self.auto_pad {
+            self.detected_pad = detect_pad(names);
+        }
+    }
 }
 
 impl OptionBuilder for NumberView {
@@ -134,7 +55,13 @@ impl OptionBuilder for NumberView {
         NumberOptions {
             mode: self.mode,
             value: self.start.get_val().unwrap_or(0),
-            pad: self.pad.get_val().unwrap_or(0),
+            step: self.increment.get_val().unwrap_or(1),
+            reset_at: self.reset_pos.get_val(),
+            reset_folder: self.reset_folder,
+            pad: match (self.auto_pad, self.detected_pad) {
+                (true, Some(pad)) => pad,
+                _ => self.pad.get_val().unwrap_or(0),
+            },
             char: self.padding_char.get_val().unwrap_or(match self.format {
                 NumberFormat::Binary
                 | NumberFormat::Decimal
@@ -143,13 +70,31 @@ impl OptionBuilder for NumberView {
                 | NumberFormat::Octal => '0',
                 NumberFormat::AsciiUpper => 'A',
                 NumberFormat::AsciiLower => 'a',
+                NumberFormat::RomanUpper | NumberFormat::RomanLower => ' ',
             }),
-            sep: self
-                .sep
-                .replace(':', &format!("{}", self.start.get_val().unwrap_or(0))),
+            sep: self.sep.clone(),
             format: self.format,
         }
     }
+
+    fn load(&mut self, options: NumberOptions) {
+        self.mode = options.mode;
+        match options.mode {
+            NumberMode::Insert(idx) => self.position.set_val(idx),
+            _ => self.position.clear(),
+        }
+        self.start.set_val(options.value);
+        self.increment.set_val(options.step);
+        match options.reset_at {
+            Some(reset) => self.reset_pos.set_val(reset),
+            None => self.reset_pos.clear(),
+        }
+        self.reset_folder = options.reset_folder;
+        self.pad.set_val(options.pad);
+        self.padding_char.set_val(options.char);
+        self.sep = options.sep;
+        self.format = options.format;
+    }
 }
 
 impl Incrementer for &mut NumberView {
@@ -202,17 +147,19 @@ impl Widget for &mut NumberView {
     fn ui(mut self, ui: &mut Ui) -> Response {
         ui.vertical(|ui| {
             ui.set_width(self.width);
-            ui.label("Numbering");
+            ui.label(Step::Number.to_string());
             ui.horizontal(|ui| {
                 ui.set_width(self.width);
                 ui.label("Mode");
                 let response = ComboBox::from_id_source("Number Mode")
                     .selected_text(match self.mode {
+                        NumberMode::None => "None",
                         NumberMode::Prefix => "Prefix",
                         NumberMode::Suffix => "Suffix",
                         NumberMode::Insert(_) => "Insert",
                     })
                     .show_ui(ui, |ui| {
+                        ui.selectable_value(&mut self.mode, NumberMode::None, "None");
                         ui.selectable_value(&mut self.mode, NumberMode::Prefix, "Prefix");
                         ui.selectable_value(&mut self.mode, NumberMode::Suffix, "Suffix");
                         ui.selectable_value(&mut self.mode, NumberMode::Insert(0), "Insert");
@@ -272,6 +219,13 @@ impl Widget for &mut NumberView {
                     self.pad.revert();
                 }
                 ui.add(Arrows::new("Number Pad", &mut self, "pad"));
+                ui.checkbox(&mut self.auto_pad, "Auto")
+                    .on_hover_text(match self.detected_pad {
+                        Some(pad) if self.auto_pad => {
+                            format!("Matching the {pad} digits used in this folder")
+                        }
+                        _ => "Match the padding already used in this folder".into(),
+                    });
                 ui.label("Char");
                 if ui
                     .add(
@@ -296,6 +250,8 @@ impl Widget for &mut NumberView {
                     self.reset_pos.revert();
                 }
                 ui.add(Arrows::new("Number Reset", &mut self, "reset_pos"));
+                ui.checkbox(&mut self.reset_folder, "Per folder")
+                    .on_hover_text("Start again from the first number in each folder");
             });
             ui.horizontal(|ui| {
                 ui.set_width(self.width);
@@ -309,6 +265,8 @@ impl Widget for &mut NumberView {
                         NumberFormat::Octal => "Octal",
                         NumberFormat::AsciiUpper => "A-Z",
                         NumberFormat::AsciiLower => "a-z",
+                        NumberFormat::RomanUpper => "I, II, III",
+                        NumberFormat::RomanLower => "i, ii, iii",
                     })
                     .show_ui(ui, |ui| {
                         ui.selectable_value(&mut self.format, NumberFormat::Binary, "Binary");
@@ -318,95 +276,22 @@ impl Widget for &mut NumberView {
                         ui.selectable_value(&mut self.format, NumberFormat::HexLower, "Hex Lower");
                         ui.selectable_value(&mut self.format, NumberFormat::AsciiUpper, "A-Z");
                         ui.selectable_value(&mut self.format, NumberFormat::AsciiLower, "a-z");
+                        ui.selectable_value(
+                            &mut self.format,
+                            NumberFormat::RomanUpper,
+                            "I, II, III",
+                        );
+                        ui.selectable_value(
+                            &mut self.format,
+                            NumberFormat::RomanLower,
+                            "i, ii, iii",
+                        );
                     });
             });
+            if let Some((first, last)) = self.build().bounds(self.count) {
+                ui.label(format!("Range: {first} … {last}"));
+            }
         })
         .response
     }
 }
-
-#[cfg(test)]
-mod numbering_test {
-    use super::*;
-    use std::path::Path;
-
-    fn vec_compare(va: &[String], vb: &[String]) -> bool {
-        (va.len() == vb.len()) &&  // zip stops at the shortest
-     va.iter()
-       .zip(vb)
-       .all(|(a,b)| (a == b))
-    }
-
-    #[test]
-    fn prefix_decimal_with_padding() {
-        let mut files = (0..10)
-            .map(|_| File::new(Path::new("TestFile.txt")).unwrap())
-            .collect::<Vec<File>>();
-        let pad = 2;
-        let char = '0';
-        let sep = "--";
-        for (value, file) in files.iter_mut().enumerate() {
-            let format = NumberFormat::Decimal;
-            let mode = NumberMode::Prefix;
-            let opt = NumberOptions {
-                mode,
-                value: (&value + 1) as u32,
-                pad,
-                char,
-                sep: String::from(sep),
-                format,
-            };
-            opt.process(file);
-        }
-        let expected = (1..=10)
-            .map(|i| format!("{i:02}--TestFile"))
-            .collect::<Vec<String>>();
-        let result = files
-            .iter()
-            .map(|f| f.stem.clone())
-            .collect::<Vec<String>>();
-        assert!(vec_compare(&result, &expected));
-    }
-
-    #[test]
-    fn suffix_binary_no_padding() {
-        let mut file = File::new(Path::new("TestFile.txt")).unwrap();
-        let format = NumberFormat::Binary;
-        let value = 5;
-        let pad = 0;
-        let char = '0';
-        let sep = ".".into();
-        let mode = NumberMode::Suffix;
-        let opt = NumberOptions {
-            mode,
-            value,
-            pad,
-            char,
-            sep,
-            format,
-        };
-        opt.process(&mut file);
-        assert_eq!(file.stem, "TestFile.101");
-    }
-
-    #[test]
-    fn insert_asciiupper() {
-        let mut file = File::new(Path::new("TestFile.txt")).unwrap();
-        let format = NumberFormat::AsciiUpper;
-        let value = 50;
-        let pad = 0;
-        let char = '0';
-        let sep = "_".into();
-        let mode = NumberMode::Insert(4);
-        let opt = NumberOptions {
-            mode,
-            value,
-            pad,
-            char,
-            sep,
-            format,
-        };
-        opt.process(&mut file);
-        assert_eq!(file.stem, "Test_AX_File");
-    }
-}
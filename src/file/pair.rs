@@ -0,0 +1,42 @@
+use super::{OptionBuilder, Step};
+use egui::{Response, Ui, Widget};
+pub use mass_renamer_core::file::pair::*;
+
+#[derive(Default)]
+pub struct PairView {
+    options: PairOptions,
+    width: f32,
+}
+
+impl PairView {
+    pub fn new(width: f32) -> Self {
+        Self {
+            width,
+            ..Default::default()
+        }
+    }
+}
+
+impl OptionBuilder for PairView {
+    type Processor = PairOptions;
+
+    fn build(&self) -> PairOptions {
+        self.options
+    }
+
+    fn load(&mut self, options: PairOptions) {
+        self.options = options;
+    }
+}
+
+impl Widget for &mut PairView {
+    fn ui(self, ui: &mut Ui) -> Response {
+        ui.vertical(|ui| {
+            ui.set_width(self.width);
+            ui.label(Step::Pair.to_string());
+            ui.checkbox(&mut self.options.keep, "Keep pairs together")
+                .on_hover_text("Give each JPEG the new name of the RAW file it was shot with");
+        })
+        .response
+    }
+}
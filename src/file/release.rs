@@ -0,0 +1,51 @@
+use super::{OptionBuilder, Step};
+use egui::{Response, Ui, Widget};
+pub use mass_renamer_core::file::release::*;
+
+#[derive(Default)]
+pub struct ReleaseView {
+    options: ReleaseOptions,
+    width: f32,
+}
+
+impl ReleaseView {
+    pub fn new(width: f32) -> Self {
+        Self {
+            width,
+            ..Default::default()
+        }
+    }
+}
+
+impl OptionBuilder for ReleaseView {
+    type Processor = ReleaseOptions;
+
+    fn build(&self) -> ReleaseOptions {
+        self.options
+    }
+
+    fn load(&mut self, options: ReleaseOptions) {
+        self.options = options;
+    }
+}
+
+impl Widget for &mut ReleaseView {
+    fn ui(self, ui: &mut Ui) -> Response {
+        ui.vertical(|ui| {
+            ui.set_width(self.width);
+            ui.label(Step::Release.to_string());
+            ui.horizontal(|ui| {
+                ui.checkbox(&mut self.options.resolution, "Resolution");
+                ui.checkbox(&mut self.options.source, "Source");
+                ui.checkbox(&mut self.options.codec, "Codec");
+            });
+            ui.horizontal(|ui| {
+                ui.checkbox(&mut self.options.audio, "Audio");
+                ui.checkbox(&mut self.options.tags, "Tags");
+                ui.checkbox(&mut self.options.group, "Group");
+            });
+            ui.checkbox(&mut self.options.dots, "Dots to Spaces");
+        })
+        .response
+    }
+}
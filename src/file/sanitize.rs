@@ -0,0 +1,61 @@
+use super::{OptionBuilder, Step};
+use egui::{ComboBox, Response, Ui, Widget};
+pub use mass_renamer_core::file::sanitize::*;
+
+#[derive(Default)]
+pub struct SanitizeView {
+    options: SanitizeOptions,
+    width: f32,
+}
+
+impl SanitizeView {
+    pub fn new(width: f32) -> Self {
+        Self {
+            width,
+            ..Default::default()
+        }
+    }
+}
+
+impl OptionBuilder for SanitizeView {
+    type Processor = SanitizeOptions;
+
+    fn build(&self) -> SanitizeOptions {
+        self.options
+    }
+
+    fn load(&mut self, options: SanitizeOptions) {
+        self.options = options;
+    }
+}
+
+impl Widget for &mut SanitizeView {
+    fn ui(self, ui: &mut Ui) -> Response {
+        ui.vertical(|ui| {
+            ui.set_width(self.width);
+            ui.label(Step::Sanitize.to_string());
+            for (label, policy) in [
+                ("Emoji", &mut self.options.emoji),
+                ("Zero Width", &mut self.options.zero_width),
+                ("Control", &mut self.options.control),
+                ("Bidi Marks", &mut self.options.bidi),
+            ] {
+                ui.horizontal(|ui| {
+                    ComboBox::from_id_source(format!("Sanitize {label}"))
+                        .selected_text(format!("{:?}", policy))
+                        .show_ui(ui, |ui| {
+                            for opt in CharPolicy::iter() {
+                                ui.selectable_value(policy, opt, format!("{:?}", opt));
+                            }
+                        });
+                    ui.label(label);
+                });
+            }
+            ui.checkbox(&mut self.options.web_safe, "Web Safe")
+                .on_hover_text("Transliterate, lowercase and keep only a-z 0-9 . _ -");
+            ui.checkbox(&mut self.options.windows, "Windows Safe")
+                .on_hover_text("Replace characters Windows doesn't allow and fix device names");
+        })
+        .response
+    }
+}
@@ -0,0 +1,54 @@
+use super::{OptionBuilder, Step};
+use egui::{ComboBox, Response, Ui, Widget};
+pub use mass_renamer_core::file::swap::*;
+
+#[derive(Default)]
+pub struct SwapView {
+    options: SwapOptions,
+    width: f32,
+}
+
+impl SwapView {
+    pub fn new(width: f32) -> Self {
+        Self {
+            width,
+            ..Default::default()
+        }
+    }
+}
+
+impl OptionBuilder for SwapView {
+    type Processor = SwapOptions;
+
+    fn build(&self) -> SwapOptions {
+        self.options.clone()
+    }
+
+    fn load(&mut self, options: SwapOptions) {
+        self.options = options;
+    }
+}
+
+impl Widget for &mut SwapView {
+    fn ui(self, ui: &mut Ui) -> Response {
+        ui.vertical(|ui| {
+            ui.set_width(self.width);
+            ui.label(Step::Swap.to_string());
+            ui.horizontal(|ui| {
+                ui.label("Mode");
+                ComboBox::from_id_source("Swap Mode")
+                    .selected_text(format!("{:?}", self.options.mode))
+                    .show_ui(ui, |ui| {
+                        for mode in [SwapMode::None, SwapMode::Swap, SwapMode::Rotate] {
+                            ui.selectable_value(&mut self.options.mode, mode, format!("{mode:?}"));
+                        }
+                    });
+            });
+            ui.horizontal(|ui| {
+                ui.label("Match:");
+                ui.text_edit_singleline(&mut self.options.exp);
+            });
+        })
+        .response
+    }
+}
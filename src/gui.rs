@@ -1,33 +1,112 @@
 use std::{
     cmp::Ordering,
-    fs,
+    collections::{hash_map::DefaultHasher, BTreeSet, HashMap, HashSet},
+    env, fs,
+    hash::{Hash, Hasher},
+    mem,
     path::{Path, PathBuf},
+    time::Duration,
 };
 
 use eframe::{
     egui::{
-        menu, Align, CentralPanel, Color32, Context, Frame, Key, Layout, Margin, Rounding,
-        ScrollArea, Stroke, TextEdit, TopBottomPanel, Visuals,
+        menu, Align, Align2, Button, CentralPanel, Color32, ComboBox, Context, DragValue, Frame,
+        Id, Key, Label, LayerId, Layout, Margin, Response, Rounding, ScrollArea, Sense, Stroke,
+        Style, TextEdit, TextStyle, TopBottomPanel, Ui, Visuals,
     },
-    run_native, App, CreationContext, NativeOptions,
+    glow, run_native, App, CreationContext, NativeOptions,
 };
 
 use crate::{
+    analysis::{
+        invalid_names, lint, path_lengths, similar_names, suggest, summarize_with, LengthError,
+        Lint, NameError, PathLimits, Platform, SimilarityWarning, Summary,
+    },
+    audit::{self, Action},
+    batch::{self, Collision, CollisionPolicy, Resolution, Resolved},
+    directory::{DirectoryError, EntryKind, ExtensionGroup, Filter, Override, Overrides},
     file::{
-        add::AddView, case::CaseView, date::DateView, extension::ExtensionView, folder::FolderView,
-        name::NameView, number::NumberView, reg::RegexView, remove::RemoveView,
+        add::AddView,
+        blacklist::BlacklistView,
+        case::CaseView,
+        date::DateView,
+        destination::DestinationView,
+        extension::ExtensionView,
+        folder::FolderView,
+        lookup::LookupView,
+        name::NameView,
+        normalize::NormalizeView,
+        number::NumberView,
+        pair::{self, PairView},
+        reg::RegexView,
+        release::ReleaseView,
+        remove::RemoveView,
         replace::ReplaceView,
+        sanitize::SanitizeView,
+        swap::SwapView,
+        FileError, OptionBuilder, Options, Step,
     },
-    File,
+    guard::Guard,
+    history::{Outcome, RenameHistory},
+    metadata::{cache::MetadataCache, Extraction, Metadata, Scheduler},
+    mirror::{Mirror, MirrorPlan},
+    playlist,
+    policy::{NamingPolicy, Violation},
+    references::{self, Reference},
+    runs::LastRuns,
+    script::{copy_script, script, Shell},
+    settings::{Settings, Theme, SIMPLE_STEPS},
+    snapshot::Snapshot,
+    sort::{SortColumn, SortError, SortExpression},
+    stats::Statistics,
+    File, Selected,
 };
 
+mod about;
+#[cfg(feature = "zip")]
+mod archive;
+mod changes;
+mod conflicts;
+mod conform;
 mod files;
 mod increment_decrement;
+#[cfg(feature = "ingest")]
+mod ingest;
+mod navigation;
+mod queue;
+mod rotate;
+mod sample;
+mod schedule;
+mod scratchpad;
+mod stats;
 mod valid_text;
+mod worker;
 
+#[cfg(feature = "report")]
+use crate::report::{self, ReportError, ReportFormat, ReportRow};
+#[cfg(feature = "watch")]
+use crate::watch::FolderWatch;
+#[cfg(feature = "mapping")]
+use crate::{mapping, merge::Merge};
+use about::AboutWindow;
+#[cfg(feature = "zip")]
+use archive::{is_zip, ArchiveWindow};
+use changes::ChangesWindow;
+use conflicts::ConflictsWindow;
+use conform::ConformWindow;
 use files::*;
 pub use increment_decrement::{Arrows, Incrementer};
+#[cfg(feature = "ingest")]
+use ingest::IngestWindow;
+use navigation::{breadcrumbs, Visited};
+use queue::{Finished, QueueWindow};
+use rotate::RotateWindow;
+use sample::SampleWindow;
+use schedule::PreviewSchedule;
+use scratchpad::ScratchpadWindow;
+use stats::StatisticsWindow;
 pub use valid_text::ValText;
+use worker::RenameWorker;
 
 const FRAME_MARGIN: f32 = 5.0;
 const FRAME_RADIUS: f32 = 10.0;
@@ -35,6 +114,23 @@ const FILES_HEIGHT: f32 = 495.0;
 const FILES_WIDTH: f32 = 1200.0;
 pub const NUM_WIDTH: f32 = 15.0;
 const COL_WIDTH: f32 = 450.0;
+const SORT_WIDTH: f32 = 200.0;
+const METADATA_REFRESH: Duration = Duration::from_millis(100);
+/// How often the folder is checked for changes made by other programs.
+#[cfg(feature = "watch")]
+const WATCH_REFRESH: Duration = Duration::from_millis(500);
+/// How long previewing may take each frame, the rest of a huge batch waits for later frames.
+const PREVIEW_BUDGET: Duration = Duration::from_millis(8);
+/// The folder kept in the platform's config folder, see [config_dir].
+const CONFIG_DIR: &str = "mass-renamer";
+/// The folder in the home folder older versions kept everything in.
+const LEGACY_CONFIG_DIR: &str = ".mass-renamer";
+const CACHE_FILE: &str = "metadata-cache.json";
+pub(crate) const SETTINGS_FILE: &str = "settings.json";
+const STATISTICS_FILE: &str = "statistics.json";
+const RUNS_FILE: &str = "last-runs.json";
+pub(crate) const HISTORY_FILE: &str = "history.json";
+pub(crate) const AUDIT_FILE: &str = "audit.jsonl";
 
 pub fn run() -> eframe::Result<()> {
     let native_options = NativeOptions::default();
@@ -49,18 +145,189 @@ pub fn run() -> eframe::Result<()> {
 pub struct Renamer {
     cwd: String,
     cwd_path: PathBuf,
+    visited: Visited,
+    /// Notices other programs changing the folder, so the list is read again.
+    #[cfg(feature = "watch")]
+    watch: Option<FolderWatch>,
     files: Vec<FileListing>,
     columns: (Columns, Order, Columns), // 3rd field is previous
+    grouping: Grouping,
+    collapsed: HashSet<String>,
+    /// Where the keyboard is in the file list.
+    cursor: Cursor,
+    /// The custom sort expression being typed, saved in the settings once it is valid.
+    sort: String,
+    sort_error: Option<String>,
+    /// Which entries are listed, and the filter being edited, applied once it is valid.
+    filter: Filter,
+    filter_edit: Filter,
+    filter_error: Option<String>,
+    selection: Selected,
+    /// Files named or left out by hand, see [Renamer::set_override].
+    overrides: Overrides,
+    settings: Settings,
+    mirror: Option<Mirror>,
+    mirror_plan: MirrorPlan,
     add: AddView,
+    blacklist: BlacklistView,
     case: CaseView,
     date: DateView,
     extension: ExtensionView,
     folder: FolderView,
+    lookup: LookupView,
     name: NameView,
+    normalize: NormalizeView,
     number: NumberView,
     reg_exp: RegexView,
+    release: ReleaseView,
     remove: RemoveView,
     replace: ReplaceView,
+    sanitize: SanitizeView,
+    swap: SwapView,
+    pair: PairView,
+    destination: DestinationView,
+    sample: SampleWindow,
+    scratchpad: ScratchpadWindow,
+    conform: ConformWindow,
+    changes: ChangesWindow,
+    conflicts: ConflictsWindow,
+    queue: QueueWindow,
+    rotate: RotateWindow,
+    /// The batch being renamed, see [Renamer::rename].
+    renaming: Option<Renaming>,
+    #[cfg(feature = "zip")]
+    archive: ArchiveWindow,
+    #[cfg(feature = "ingest")]
+    ingest: IngestWindow,
+    about: AboutWindow,
+    statistics: Statistics,
+    last_runs: LastRuns,
+    history: RenameHistory,
+    /// Leave out files the last run in the folder renamed, when running it again.
+    skip_repeated: bool,
+    /// Rename in the protected folders too, until the app is closed.
+    allow_protected: bool,
+    /// The Numbering start set by hand and the one renamed batches carried it on to, while
+    /// the numbering continues across folders.
+    carried: Option<(u32, u32)>,
+    /// The step being dragged in the Order menu.
+    dragged_step: Option<Step>,
+    /// The list the selection was imported from or exported to, which is given the new
+    /// paths of the files renamed, undone or redone.
+    playlist: Option<PathBuf>,
+    statistics_window: StatisticsWindow,
+    metadata: Scheduler,
+    cache: MetadataCache,
+    warnings: Vec<SimilarityWarning>,
+    flagged: Vec<String>,
+    split: Vec<PathBuf>,
+    too_long: Vec<LengthError>,
+    /// The new names the system won't take, see [Settings::windows_names].
+    invalid: Vec<NameError>,
+    /// The naming policy in the settings, read when it is chosen.
+    policy: Option<NamingPolicy>,
+    /// Why the naming policy couldn't be read. Nothing is renamed until it is fixed or cleared.
+    policy_error: Option<String>,
+    /// The new names the last preview gives against the naming policy.
+    violations: Vec<Violation>,
+    collisions: Vec<Collision>,
+    failed: Vec<String>,
+    /// The files the last batch left out as their new names were taken, see
+    /// [CollisionPolicy::Skip].
+    skipped: Vec<PathBuf>,
+    /// The links rewritten after the last rename, undo or redo.
+    references: Vec<Reference>,
+    lints: Vec<Lint>,
+    /// What the last finished preview will do, for the Summary menu.
+    summary: Summary,
+    /// What the last preview was made from, see [Renamer::fingerprint].
+    applied: Option<String>,
+    /// The preview key of the last finished batch checked, see [Renamer::analyze].
+    analyzed: Option<u64>,
+    schedule: PreviewSchedule,
+    /// The listed files on screen in the last frame, which are previewed first.
+    visible: HashSet<PathBuf>,
+    /// Where the settings, statistics and metadata cache are kept. Nothing is kept without it.
+    config_dir: Option<PathBuf>,
+}
+
+/// A batch being renamed, with what is needed to finish it off once it's done.
+struct Renaming {
+    worker: RenameWorker,
+    renames: Vec<(PathBuf, PathBuf)>,
+    /// Whether each file was selected.
+    selected: Vec<bool>,
+    /// The override of each file, given back if it isn't renamed.
+    overridden: Vec<Option<Override>>,
+    options: Vec<Options>,
+    /// How many of the renames are of listed files, the rest are mirror files.
+    files: usize,
+    /// What the folder held before, to check what the batch changed.
+    before: Option<Snapshot>,
+    /// Whether the files are copied, see [crate::file::DestinationOptions].
+    copy: bool,
+    /// The files left out as their new names were taken.
+    skipped: Vec<PathBuf>,
+    /// The number after the last one the batch gives, to carry the numbering on from.
+    next_number: Option<u32>,
+}
+
+/// Use the theme and text size from the settings.
+fn apply_appearance(ctx: &Context, settings: &Settings) {
+    let mut style = Style {
+        visuals: match settings.theme {
+            Theme::Dark => Visuals::dark(),
+            Theme::Light => Visuals::light(),
+        },
+        ..Default::default()
+    };
+    let scale = settings.text_scale as f32 / 100.0;
+    for font in style.text_styles.values_mut() {
+        font.size *= scale;
+    }
+    ctx.set_style(style);
+}
+
+/// Edit the extension groups, returning whether they changed.
+fn groups_editor(ui: &mut Ui, groups: &mut Vec<ExtensionGroup>) -> bool {
+    let mut changed = false;
+    let mut remove = None;
+    for (idx, group) in groups.iter_mut().enumerate() {
+        ui.horizontal(|ui| {
+            if ui
+                .small_button("✖")
+                .on_hover_text("Remove the group")
+                .clicked()
+            {
+                remove = Some(idx);
+            }
+            changed |= ui
+                .add(TextEdit::singleline(&mut group.name).desired_width(COL_WIDTH / 4.0))
+                .changed();
+            changed |= ui
+                .add(TextEdit::singleline(&mut group.extensions).hint_text("jpg png"))
+                .changed();
+        });
+    }
+    if let Some(idx) = remove {
+        groups.remove(idx);
+        changed = true;
+    }
+    ui.horizontal(|ui| {
+        if ui.button("Add Group").clicked() {
+            groups.push(ExtensionGroup::new("New", ""));
+            changed = true;
+        }
+        if ui
+            .button("Reset")
+            .on_hover_text("Go back to Images, Audio, Video and Docs")
+            .clicked()
+        {
+            *groups = ExtensionGroup::defaults();
+            changed = true;
+        }
+    });
+    changed
 }
 
 /// Custom ordering for files. Directories at the start or end.
@@ -75,9 +342,6 @@ fn cmp(rhs: &Path, lhs: &Path) -> Ordering {
 impl Renamer {
     //! Called once before the first frame.
     pub fn new(cc: &CreationContext) -> Self {
-        // This is also where you can customized the look at feel of egui using
-        // `cc.egui_ctx.set_visuals` and `cc.egui_ctx.set_fonts`.
-        cc.egui_ctx.set_visuals(Visuals::dark());
         let mut app = Renamer {
             reg_exp: RegexView::new(COL_WIDTH),
             name: NameView::new(COL_WIDTH),
@@ -89,35 +353,505 @@ impl Renamer {
             add: AddView::new(COL_WIDTH / 2.0),
             date: DateView::new(COL_WIDTH / 2.0),
             number: NumberView::new(COL_WIDTH / 2.0),
+            normalize: NormalizeView::new(COL_WIDTH / 2.0),
+            sanitize: SanitizeView::new(COL_WIDTH / 2.0),
+            blacklist: BlacklistView::new(COL_WIDTH / 2.0),
+            release: ReleaseView::new(COL_WIDTH / 2.0),
+            swap: SwapView::new(COL_WIDTH / 2.0),
+            pair: PairView::new(COL_WIDTH / 2.0),
+            destination: DestinationView::new(COL_WIDTH / 2.0),
+            lookup: LookupView::new(COL_WIDTH / 2.0),
+            config_dir: config_dir(),
             ..Default::default()
         };
-        let cwd_path = match home::home_dir() {
-            Some(dir) => dir,
-            None => PathBuf::default(),
+        if let Some(path) = app.config_path(CACHE_FILE) {
+            app.cache = MetadataCache::load(path).unwrap_or_default();
+        }
+        if let Some(path) = app.config_path(SETTINGS_FILE) {
+            app.settings = Settings::load(path).unwrap_or_default();
+        }
+        apply_appearance(&cc.egui_ctx, &app.settings);
+        // Start where the app was left, unless that folder has gone since.
+        let cwd_path = match (&app.settings.last_folder, home::home_dir()) {
+            (Some(dir), _) if dir.is_dir() => dir.clone(),
+            (_, Some(dir)) => dir,
+            (_, None) => PathBuf::default(),
         };
         app.cwd_path = cwd_path.clone();
         app.cwd = cwd_path.display().to_string();
+        app.load_policy();
+        app.sort = app.settings.sort.clone();
+        if let Some(path) = app.config_path(STATISTICS_FILE) {
+            app.statistics = Statistics::load(path).unwrap_or_default();
+        }
+        if let Some(path) = app.config_path(RUNS_FILE) {
+            app.last_runs = LastRuns::load(path).unwrap_or_default();
+        }
+        if let Some(path) = app.config_path(HISTORY_FILE) {
+            app.history = RenameHistory::load(path).unwrap_or_default();
+        }
         app.file_list();
+        app.auto_select();
         app
     }
+    /// Show the files in `dir`, as entering it in the folder box does.
+    pub fn open<P: AsRef<Path>>(&mut self, dir: P) {
+        self.cwd = dir.as_ref().display().to_string();
+        self.change_dir();
+    }
+
+    /// Tick or untick a listed file, as its checkbox does.
+    pub fn select(&mut self, path: &Path, selected: bool) {
+        for listing in self.files.iter_mut().filter(|listing| listing.name == path) {
+            listing.selected = selected;
+            self.selection.set(&listing.name, selected);
+        }
+    }
+
+    /// Name a file by hand, or leave it out, until it is renamed or the override is
+    /// cleared with `None`. Changing the options or the filter keeps it.
+    pub fn set_override(&mut self, path: &Path, over: Option<Override>) {
+        self.overrides.set(path, over);
+        for listing in self.files.iter_mut() {
+            listing.overridden = self.overrides.get(&listing.name).cloned();
+        }
+    }
+
+    /// Name the files of the current folder from a table of old and new names, see
+    /// [crate::mapping]. Each file the table names is selected and given its new name
+    /// as an override, rows that can't be used are listed as failures.
+    #[cfg(feature = "mapping")]
+    pub fn import_mapping(&mut self, path: &Path) {
+        let pairs = match mapping::read(path) {
+            Ok(pairs) => pairs,
+            Err(err) => {
+                self.failed = vec![format!("{}: {err}", path.display())];
+                return;
+            }
+        };
+        let plan = mapping::plan(&self.cwd_path, &pairs);
+        for (from, to) in &plan.renames {
+            let name = to.file_name().unwrap_or_default().to_string_lossy();
+            self.set_override(from, Some(Override::Name(name.into_owned())));
+            self.select(from, true);
+        }
+        self.failed = plan.errors.iter().map(ToString::to_string).collect();
+    }
+
+    /// Name the selected files, in the order they are listed, with the names the merge
+    /// makes row by row, see [crate::merge]. Each file keeps its extension, and files
+    /// past the last row are left as they are.
+    #[cfg(feature = "mapping")]
+    pub fn mail_merge(&mut self, merge: &Merge) {
+        let files = self
+            .files
+            .iter()
+            .filter(|listing| listing.selected)
+            .filter(|listing| listing.overridden != Some(Override::Exclude))
+            .map(|listing| (listing.name.clone(), listing.extension.clone()))
+            .collect::<Vec<_>>();
+        for ((path, extension), name) in files.into_iter().zip(merge.names()) {
+            let name = match extension {
+                Some(extension) => format!("{name}.{extension}"),
+                None => name,
+            };
+            self.set_override(&path, Some(Override::Name(name)));
+        }
+    }
+
+    /// Select the files of a list of paths, e.g. an `.m3u` playlist, in place of the
+    /// current selection, see [crate::playlist]. The list follows the renames after this.
+    pub fn import_list(&mut self, path: &Path) {
+        let paths = match playlist::read(path) {
+            Ok(paths) => paths,
+            Err(err) => {
+                self.failed = vec![format!("{}: {err}", path.display())];
+                return;
+            }
+        };
+        self.selection.clear();
+        for file in paths {
+            self.selection.add(file);
+        }
+        for listing in self.files.iter_mut() {
+            listing.selected = self.selection.contains(&listing.name);
+        }
+        self.playlist = Some(path.to_path_buf());
+    }
+
+    /// Select files dropped onto the window, adding them to the selection. A dropped
+    /// folder adds the files in it. The folder of the first one dropped is shown, or the
+    /// folder itself when a folder comes first.
+    pub fn drop_paths(&mut self, paths: &[PathBuf]) {
+        let mut shown = None;
+        for path in paths {
+            if !path.is_dir() {
+                shown.get_or_insert_with(|| path.parent().map(Path::to_path_buf));
+                self.selection.add(path.clone());
+                continue;
+            }
+            shown.get_or_insert_with(|| Some(path.clone()));
+            match path.read_dir() {
+                Ok(dir) => dir
+                    .flatten()
+                    .map(|entry| entry.path())
+                    .filter(|file| file.is_file())
+                    .for_each(|file| self.selection.add(file)),
+                Err(err) => self.failed.push(format!("{}: {err}", path.display())),
+            }
+        }
+        if let Some(dir) = shown.flatten().filter(|dir| *dir != self.cwd_path) {
+            self.open(dir);
+        }
+        for listing in self.files.iter_mut() {
+            listing.selected = self.selection.contains(&listing.name);
+        }
+    }
+
+    /// Write the selected files, in the order they are listed, as a list of paths.
+    /// The list follows the renames after this.
+    pub fn export_list(&mut self, path: &Path) {
+        let selected = self
+            .files
+            .iter()
+            .filter(|listing| listing.selected)
+            .map(|listing| listing.name.as_path())
+            .collect::<Vec<_>>();
+        match playlist::write(path, &selected) {
+            Ok(()) => self.playlist = Some(path.to_path_buf()),
+            Err(err) => self.failed = vec![format!("{}: {err}", path.display())],
+        }
+    }
+
+    /// Rewrite the links to the files moved in the chosen kinds of text files under the
+    /// folder, see [Settings::references].
+    fn update_references(&mut self, moved: &[(PathBuf, PathBuf)]) {
+        self.references.clear();
+        let extensions = self
+            .settings
+            .references
+            .split_whitespace()
+            .collect::<Vec<_>>();
+        if extensions.is_empty() || moved.is_empty() {
+            return;
+        }
+        let updated = references::documents(&self.cwd_path, &extensions)
+            .map(|documents| references::update(&documents, moved));
+        match updated {
+            Ok(updated) => {
+                self.references = updated.references;
+                for (document, err) in updated.failed {
+                    let document = document.display();
+                    self.failed
+                        .push(format!("Links in {document} not updated: {err}"));
+                }
+            }
+            Err(err) => self.failed.push(format!("Links not updated: {err}")),
+        }
+    }
+
+    /// Give the files moved their new paths in the imported or exported list.
+    fn update_list(&mut self, moved: &[(PathBuf, PathBuf)]) {
+        let Some(list) = &self.playlist else {
+            return;
+        };
+        if let Err(err) = playlist::update(list, moved) {
+            self.failed.push(format!("{}: {err}", list.display()));
+        }
+    }
+
+    pub fn overrides(&self) -> &Overrides {
+        &self.overrides
+    }
+
+    /// The `(original, new)` paths of the selected files from the last preview,
+    /// excluded files left out.
+    pub fn previewed(&self) -> Vec<(PathBuf, PathBuf)> {
+        self.files
+            .iter()
+            .filter(|listing| listing.selected)
+            .filter(|listing| listing.overridden != Some(Override::Exclude))
+            .map(|listing| (listing.name.clone(), listing.renamed.new_path()))
+            .collect()
+    }
+
+    /// A script making the renames [Renamer::rename] would make, mirror files included,
+    /// for running where Mass Renamer isn't installed. When the options copy, so does the
+    /// script.
+    pub fn script(&mut self, shell: Shell) -> String {
+        if self.schedule.pending() {
+            self.preview();
+        }
+        let mut renames = self
+            .previewed()
+            .into_iter()
+            .filter(|(from, to)| from != to)
+            .collect::<Vec<_>>();
+        renames.extend(self.mirror_plan.renames.iter().cloned());
+        match copies(&self.options()) {
+            true => copy_script(&renames, shell),
+            false => script(&renames, shell),
+        }
+    }
+
+    /// Queue the renames [Renamer::rename] would make, mirror files included, to run
+    /// later with the batches of other folders, see [QueueWindow]. Nothing is queued
+    /// while the naming policy can't be read.
+    pub fn enqueue(&mut self) {
+        if self.schedule.pending() {
+            self.preview();
+        }
+        if let Some(err) = &self.policy_error {
+            self.failed = vec![format!("The naming policy can't be read: {err}")];
+            return;
+        }
+        let mut renames = self
+            .previewed()
+            .into_iter()
+            .filter(|(from, to)| from != to)
+            .collect::<Vec<_>>();
+        renames.extend(self.mirror_plan.renames.iter().cloned());
+        if !renames.is_empty() {
+            let options = self.options();
+            let copy = copies(&options);
+            self.queue
+                .add(self.cwd_path.clone(), &options, renames, copy);
+        }
+    }
+
+    /// Keep renames made outside [Renamer::rename], e.g. a finished queue run, in the
+    /// history as one batch, and list the folder again. Copies leave the originals, so
+    /// there is nothing to undo.
+    fn batch_finished(&mut self, finished: Finished) {
+        if !finished.renamed.is_empty() {
+            self.history.record(&finished.renamed);
+            self.save_history();
+            self.audit(Action::Rename, &finished.renamed);
+        }
+        if !finished.copied.is_empty() {
+            self.audit(Action::Copy, &finished.copied);
+        }
+        self.file_list();
+    }
+
+    /// What [Renamer::rename] would do to each selected file, excluded files left out.
+    #[cfg(feature = "report")]
+    pub fn report(&mut self) -> Vec<ReportRow> {
+        if self.schedule.pending() {
+            self.preview();
+        }
+        report::rows(
+            self.files
+                .iter()
+                .filter(|listing| listing.selected)
+                .filter(|listing| listing.overridden != Some(Override::Exclude))
+                .map(|listing| &listing.renamed),
+        )
+    }
+
+    /// Why files couldn't be renamed by the last [Renamer::rename].
+    pub fn failed(&self) -> &[String] {
+        &self.failed
+    }
+
+    /// The listed files found to be gone by [Renamer::rename].
+    pub fn missing(&self) -> Vec<&Path> {
+        self.files
+            .iter()
+            .filter(|listing| listing.missing)
+            .map(|listing| listing.name.as_path())
+            .collect()
+    }
+
+    /// Whether the current options are the ones the folder was last renamed with.
+    pub fn repeated(&self) -> bool {
+        self.last_runs
+            .repeated(&self.cwd_path, &self.options())
+            .is_some()
+    }
+
+    /// What the last preview will do when renamed, e.g. `3 files will be renamed, ...`.
+    pub fn summary(&self) -> Summary {
+        self.summary
+    }
+
+    /// The files the last preview gives the same name, or the name of a file already there.
+    pub fn collisions(&self) -> &[Collision] {
+        &self.collisions
+    }
+
+    /// The files the last batch left out as their new names were taken, see
+    /// [CollisionPolicy::Skip].
+    pub fn skipped(&self) -> &[PathBuf] {
+        &self.skipped
+    }
+
+    /// The new names the last preview gives against the naming policy, see
+    /// [Renamer::set_policy].
+    pub fn violations(&self) -> &[Violation] {
+        &self.violations
+    }
+
+    /// Check every new name against the policy file at `path` before renaming, see
+    /// [crate::policy], or stop checking with `None`.
+    pub fn set_policy(&mut self, path: Option<PathBuf>) {
+        self.settings.policy = path;
+        self.save_settings();
+        self.load_policy();
+        self.applied = None;
+        self.analyzed = None;
+    }
+
+    fn load_policy(&mut self) {
+        let policy = self.settings.policy.as_ref().map(NamingPolicy::read);
+        (self.policy, self.policy_error) = match policy {
+            Some(Ok(policy)) => (Some(policy), None),
+            Some(Err(err)) => (None, Some(err.to_string())),
+            None => (None, None),
+        };
+    }
+
+    /// Apply a step only to files with one of the `extensions`, e.g. `.jpg;.png`, or to
+    /// every file when they are empty, see [crate::file::File::with_scopes].
+    pub fn set_scope(&mut self, step: Step, extensions: &str) {
+        match extensions.trim() {
+            "" => self.settings.scopes.remove(&step),
+            _ => self.settings.scopes.insert(step, extensions.to_owned()),
+        };
+        self.save_settings();
+    }
+
+    /// Choose what to do about clashing names, see [Renamer::collisions].
+    pub fn set_collisions(&mut self, policy: CollisionPolicy) {
+        self.settings.collisions = policy;
+        self.save_settings();
+    }
+
+    /// Carry the numbering on from each batch renamed, so the files of the next folder
+    /// follow on from the last. Otherwise every folder is numbered from the Numbering
+    /// start, and any numbering carried on is restarted.
+    pub fn continue_numbering(&mut self, on: bool) {
+        self.settings.continue_numbering = on;
+        self.save_settings();
+        if !on {
+            self.restart_numbering();
+        }
+    }
+
+    /// Put the Numbering start back to the one set by hand, unless it has been changed
+    /// since the numbering was carried on.
+    pub fn restart_numbering(&mut self) {
+        if let Some((start, carried)) = self.carried.take() {
+            if self.number.build().value == carried {
+                self.number.set_start(start);
+            }
+        }
+    }
+
+    /// Leave out the files the last run renamed when the same batch is renamed again.
+    pub fn skip_repeated(&mut self, skip: bool) {
+        self.skip_repeated = skip;
+    }
+
+    /// Protect a folder, or stop protecting it, see [crate::guard].
+    pub fn protect(&mut self, folder: &Path, protected: bool) {
+        self.settings.guard.paths.retain(|path| path != folder);
+        if protected {
+            self.settings.guard.paths.push(folder.to_path_buf());
+        }
+        self.save_settings();
+    }
+
+    /// Rename in the protected folders too, until the app is closed.
+    pub fn allow_protected(&mut self, allow: bool) {
+        self.allow_protected = allow;
+    }
+
+    /// Drop the rows of missing files from the list.
+    pub fn remove_missing(&mut self) {
+        for listing in self.files.iter().filter(|listing| listing.missing) {
+            self.selection.remove(&listing.name);
+        }
+        self.files.retain(|listing| !listing.missing);
+    }
+
     fn change_dir(&mut self) {
-        self.cwd_path = PathBuf::from(&self.cwd);
+        let dir = PathBuf::from(&self.cwd);
+        if dir != self.cwd_path {
+            self.visited.leave(&self.cwd_path);
+        }
+        self.show_dir(dir);
+    }
+
+    /// Go back to the folder shown before this one, as a browser does.
+    pub fn back(&mut self) {
+        if let Some(dir) = self.visited.back(&self.cwd_path) {
+            self.show_dir(dir);
+        }
+    }
+
+    /// Go forward to the folder left by going back.
+    pub fn forward(&mut self) {
+        if let Some(dir) = self.visited.forward(&self.cwd_path) {
+            self.show_dir(dir);
+        }
+    }
+
+    /// List `dir` without adding it to the folders visited. The selection is kept by path,
+    /// so the files ticked in each folder are still ticked on coming back.
+    fn show_dir(&mut self, dir: PathBuf) {
+        self.cwd = dir.display().to_string();
+        self.cwd_path = dir;
+        if !self.settings.continue_numbering {
+            self.restart_numbering();
+        }
         self.file_list();
+        self.auto_select();
+        if self.cwd_path.is_dir() && self.settings.last_folder.as_ref() != Some(&self.cwd_path) {
+            self.settings.last_folder = Some(self.cwd_path.clone());
+            self.save_settings();
+        }
+    }
+
+    /// Select the listed files matching the saved mask, e.g. `*.tmp;*.part` to clean up
+    /// after downloads. Files already selected stay so.
+    pub fn auto_select(&mut self) {
+        if self.settings.auto_select.is_empty() {
+            return;
+        }
+        let filter = Filter {
+            mask: self.settings.auto_select.clone(),
+            ..Default::default()
+        };
+        let Ok(matcher) = filter.matcher_with(&self.settings.groups) else {
+            return;
+        };
+        for listing in self.files.iter_mut() {
+            if matcher.matches(&listing.name) {
+                listing.selected = true;
+                self.selection.add(listing.name.clone());
+            }
+        }
     }
 
     fn up_one(&mut self) {
-        if let Some(dir) = self.cwd_path.parent() {
-            self.cwd_path = PathBuf::from(dir);
-            self.cwd = self.cwd_path.display().to_string();
-            self.file_list();
+        if let Some(dir) = self.cwd_path.parent().map(Path::to_path_buf) {
+            self.open(dir);
         };
     }
 
     fn file_list(&mut self) {
         if let Ok(dir) = self.cwd_path.read_dir() {
+            let matcher = self.filter.matcher_with(&self.settings.groups).ok();
             let mut file_listing = Vec::new();
             for file in dir.flatten() {
                 let name = file.path();
+                if matcher
+                    .as_ref()
+                    .is_some_and(|matcher| !matcher.matches(&name))
+                {
+                    continue;
+                }
                 let extension = name
                     .extension()
                     .map(|ext| ext.to_string_lossy().to_string());
@@ -141,6 +875,8 @@ impl Renamer {
                     };
                 }
                 if let Ok(renamed) = renamed {
+                    let selected = self.selection.contains(&name);
+                    let overridden = self.overrides.get(&name).cloned();
                     file_listing.push(FileListing {
                         name,
                         renamed,
@@ -148,24 +884,862 @@ impl Renamer {
                         size,
                         modified,
                         created,
-                        selected: false,
+                        captured: None,
+                        selected,
+                        metadata_error: None,
+                        path_error: None,
+                        name_error: None,
+                        policy_error: None,
+                        conflict: None,
+                        missing: false,
+                        pending: false,
+                        overridden,
+                        override_changed: false,
+                        name_edit: String::new(),
                     });
                 }
             }
             file_listing.sort_unstable_by(|lhs, rhs| cmp(&lhs.name, &rhs.name));
+            if let Some(expression) = self.sort_expression() {
+                sort_by_expression(&mut file_listing, &expression);
+                self.columns = (Columns::Custom, Order::Forward, Columns::Custom);
+            }
             self.files = file_listing;
+            #[cfg(feature = "watch")]
+            if self.watch.as_ref().map(FolderWatch::folder) != Some(self.cwd_path.as_path()) {
+                self.watch = FolderWatch::new(&self.cwd_path).ok();
+            }
+            self.applied = None;
+            self.schedule.reset();
+            self.sample.invalidate();
+            self.metadata.clear();
+            if let Some(mirror) = &self.mirror {
+                self.set_mirror(mirror.root().to_path_buf());
+            }
+        }
+    }
+
+    /// The saved custom sort, unless there is none.
+    fn sort_expression(&self) -> Option<SortExpression> {
+        self.settings
+            .sort
+            .parse::<SortExpression>()
+            .ok()
+            .filter(|expression| !expression.is_empty())
+    }
+
+    /// List, and so number, the files in a custom order, kept for later sessions.
+    /// An empty expression goes back to sorting by name.
+    pub fn set_sort(&mut self, expression: &str) -> Result<(), SortError> {
+        let parsed = expression.parse::<SortExpression>()?;
+        self.settings.sort = parsed.to_string();
+        self.save_settings();
+        if !parsed.is_empty() {
+            sort_by_expression(&mut self.files, &parsed);
+            self.columns = (Columns::Custom, Order::Forward, Columns::Custom);
+        } else if self.columns.2 == Columns::Custom {
+            self.files
+                .sort_unstable_by(|lhs, rhs| cmp(&lhs.name, &rhs.name));
+            self.columns = Default::default();
+        }
+        Ok(())
+    }
+
+    /// List only the entries matching the filter, so rules only apply to them. Ticked
+    /// files filtered out stay ticked for when they are listed again.
+    pub fn set_filter(&mut self, filter: Filter) -> Result<(), DirectoryError> {
+        filter.matcher_with(&self.settings.groups)?;
+        self.filter_edit = filter.clone();
+        self.filter = filter;
+        self.file_list();
+        Ok(())
+    }
+
+    /// Repeat the renames in a mirror of the current folder, e.g. a folder of RAW files.
+    fn set_mirror(&mut self, root: PathBuf) {
+        self.mirror = Mirror::read(&self.cwd_path, root).ok();
+        self.analyzed = None;
+    }
+
+    /// Fill the panel matching the option.
+    /// A hidden panel is pinned so the option can be seen.
+    pub fn load_option(&mut self, option: Options) {
+        if !self.settings.shows(option.step()) {
+            self.settings.pinned.insert(option.step());
+            self.save_settings();
+        }
+        match option {
+            Options::Regex(opt) => self.reg_exp.load(opt),
+            Options::Name(opt) => self.name.load(opt),
+            Options::Replace(opt) => self.replace.load(opt),
+            Options::Case(opt) => self.case.load(opt),
+            Options::Remove(opt) => self.remove.load(opt),
+            Options::Add(opt) => self.add.load(opt),
+            Options::Date(opt) => self.date.load(opt),
+            Options::Folder(opt) => self.folder.load(opt),
+            Options::Number(opt) => self.number.load(opt),
+            Options::Extension(opt) => self.extension.load(opt),
+            Options::Normalize(opt) => self.normalize.load(opt),
+            Options::Sanitize(opt) => self.sanitize.load(opt),
+            Options::Blacklist(opt) => self.blacklist.load(opt),
+            Options::Release(opt) => self.release.load(opt),
+            Options::Swap(opt) => self.swap.load(opt),
+            Options::Lookup(opt) => self.lookup.load(opt),
+            Options::Pair(opt) => self.pair.load(opt),
+            Options::Destination(opt) => self.destination.load(opt),
+            _ => (),
+        }
+    }
+
+    /// Where a file kept between sessions, e.g. the metadata cache, is kept.
+    fn config_path(&self, file: &str) -> Option<PathBuf> {
+        self.config_dir.as_ref().map(|dir| dir.join(file))
+    }
+
+    fn save_settings(&self) {
+        if let Some(path) = self.config_path(SETTINGS_FILE) {
+            self.settings.save(path).ok();
+        }
+    }
+
+    fn save_statistics(&self) {
+        if let Some(path) = self.config_path(STATISTICS_FILE) {
+            self.statistics.save(path).ok();
+        }
+    }
+
+    /// Rename the selected files, and their mirror files, to the previewed names, waiting
+    /// for the batch to finish. Returns the `(original, new)` paths of the files renamed.
+    /// The batch is kept in the history, so [Renamer::undo] can give the files their names
+    /// back.
+    pub fn rename(&mut self) -> Vec<(PathBuf, PathBuf)> {
+        if self.renaming.is_some() {
+            return vec![];
+        }
+        self.start_rename();
+        let Some(mut renaming) = self.renaming.take() else {
+            return vec![];
+        };
+        let results = renaming.worker.wait();
+        self.renamed(renaming, results)
+    }
+
+    /// Start renaming as [Renamer::rename] does, without waiting. The batch runs on its
+    /// own thread and is finished off by [Renamer::renamed] once [Renamer::update] finds
+    /// it done.
+    fn start_rename(&mut self) {
+        self.start_resolved(None);
+    }
+
+    /// Start renaming, with the files the [ConflictsWindow] chose to overwrite with, if it
+    /// was asked.
+    fn start_resolved(&mut self, overwrite: Option<HashSet<PathBuf>>) {
+        if self.renaming.is_some() {
+            return;
+        }
+        if self.schedule.pending() {
+            self.preview();
+        }
+        let options = self.options();
+        let last_run = self
+            .last_runs
+            .repeated(&self.cwd_path, &options)
+            .filter(|_| self.skip_repeated);
+        let mut renames = self
+            .previewed()
+            .into_iter()
+            .filter(|(from, to)| from != to)
+            .filter(|(from, _)| !last_run.is_some_and(|run| run.produced(from)))
+            .collect::<Vec<_>>();
+        let listed = renames
+            .iter()
+            .map(|(from, _)| from.clone())
+            .collect::<HashSet<_>>();
+        let numbered = self
+            .files
+            .iter()
+            .filter(|listing| listing.selected && listing.overridden != Some(Override::Exclude))
+            .filter(|listing| listing.renamed.counter().is_some())
+            .count();
+        let next_number = options.iter().find_map(|opt| match opt {
+            Options::Number(number) if numbered > 0 => Some(number.nth(numbered)),
+            _ => None,
+        });
+        renames.extend(self.mirror_plan.renames.iter().cloned());
+        if let Some(err) = &self.policy_error {
+            self.failed = vec![format!("The naming policy can't be read: {err}")];
+            return;
+        }
+        if let Some(policy) = &self.policy {
+            let violations = policy.check(&renames);
+            if !violations.is_empty() {
+                self.failed = violations.iter().map(Violation::to_string).collect();
+                return;
+            }
+        }
+        let found = batch::collisions(&renames);
+        let decide = |overwrite: bool| match overwrite {
+            true => Resolution::Overwrite,
+            false => Resolution::Skip,
+        };
+        let resolved = match (self.settings.collisions, overwrite) {
+            _ if found.is_empty() => Resolved {
+                renames,
+                ..Default::default()
+            },
+            // Nothing is renamed while any name clashes, rather than failing midway.
+            (CollisionPolicy::Refuse | CollisionPolicy::Number, _) => {
+                self.failed = found.iter().map(Collision::to_string).collect();
+                return;
+            }
+            (CollisionPolicy::Skip, _) => batch::resolve(&renames, |_| decide(false)),
+            (CollisionPolicy::Overwrite, _) => batch::resolve(&renames, |_| decide(true)),
+            (CollisionPolicy::Prompt, None) => {
+                self.conflicts.ask(found);
+                return;
+            }
+            (CollisionPolicy::Prompt, Some(overwrite)) => batch::resolve(&renames, |collision| {
+                decide(overwrite.contains(&collision.original))
+            }),
+        };
+        let Resolved {
+            renames,
+            skipped,
+            replace,
+        } = resolved;
+        let files = renames
+            .iter()
+            .filter(|(from, _)| listed.contains(from))
+            .count();
+        // Selected paths are looked up while they still exist, then follow the files.
+        let selected = renames
+            .iter()
+            .map(|(from, _)| self.selection.contains(from))
+            .collect::<Vec<_>>();
+        for (from, _) in &renames {
+            self.selection.remove(from);
+        }
+        // Overrides are done with once a file is renamed, so only those that fail keep theirs.
+        let overridden = renames
+            .iter()
+            .map(|(from, _)| self.overrides.take(from))
+            .collect::<Vec<_>>();
+        self.failed.clear();
+        let copy = copies(&options);
+        // Copies leave the originals, which the comparison would take for failed renames.
+        let before = Snapshot::take(&self.cwd_path).ok().filter(|_| !copy);
+        let worker = RenameWorker::start(
+            renames.clone(),
+            self.settings.throttle,
+            self.guard(),
+            self.settings.background,
+            copy,
+            replace,
+        );
+        self.renaming = Some(Renaming {
+            worker,
+            renames,
+            selected,
+            overridden,
+            options,
+            files,
+            before,
+            copy,
+            skipped,
+            next_number,
+        });
+    }
+
+    /// Finish off a batch started renaming, once every file is done. Returns the
+    /// `(original, new)` paths of the files renamed, or copied. Copies leave nothing to
+    /// undo, so they are only added to the audit log.
+    fn renamed(
+        &mut self,
+        renaming: Renaming,
+        results: Vec<Result<(), FileError>>,
+    ) -> Vec<(PathBuf, PathBuf)> {
+        let Renaming {
+            renames,
+            selected,
+            overridden,
+            options,
+            files,
+            before,
+            copy,
+            skipped,
+            next_number,
+            ..
+        } = renaming;
+        self.skipped = skipped;
+        if let Some(next) = next_number.filter(|_| self.settings.continue_numbering) {
+            if results.iter().any(Result::is_ok) {
+                // A start changed by hand since it was carried on is the new one to restart from.
+                let start = match (self.carried, self.number.build().value) {
+                    (Some((start, carried)), value) if value == carried => start,
+                    (_, value) => value,
+                };
+                self.carried = Some((start, next));
+                self.number.set_start(next);
+            }
+        }
+        // Before the links and lists in the folder are updated.
+        if let Some(before) = before {
+            if let Ok(after) = Snapshot::take(before.folder()) {
+                let comparison = before.compare(&after, &renames);
+                self.changes.set(before.folder(), comparison);
+            }
+        }
+        // Files a cancelled batch didn't get to aren't counted.
+        let files = results
+            .iter()
+            .take(files)
+            .filter(|result| !matches!(result, Err(FileError::Cancelled(_))))
+            .count();
+        let mut renamed = vec![];
+        let mut missing = HashSet::new();
+        for ((((from, to), result), selected), over) in
+            renames.iter().zip(results).zip(selected).zip(overridden)
+        {
+            let path = match result {
+                // The original is still there, to rename or copy again.
+                Ok(()) if copy => {
+                    renamed.push((from.clone(), to.clone()));
+                    self.overrides.set(from, over);
+                    from
+                }
+                Ok(()) => {
+                    renamed.push((from.clone(), to.clone()));
+                    to
+                }
+                Err(FileError::Missing(path)) => {
+                    self.failed.push(FileError::Missing(path).to_string());
+                    missing.insert(from);
+                    from
+                }
+                Err(err) => {
+                    // Cancelled files weren't tried, so they aren't failures.
+                    if !matches!(err, FileError::Cancelled(_)) {
+                        self.failed.push(format!("{}: {err}", from.display()));
+                    }
+                    self.overrides.set(from, over);
+                    from
+                }
+            };
+            if selected {
+                self.selection.add(path.clone());
+            }
+        }
+        if self.settings.statistics && files > 0 {
+            self.statistics.record(&options, files);
+            self.save_statistics();
+        }
+        if copy {
+            self.audit(Action::Copy, &renamed);
+        } else if !renamed.is_empty() {
+            self.last_runs.record(&self.cwd_path, &options, &renamed);
+            if let Some(path) = self.config_path(RUNS_FILE) {
+                self.last_runs.save(path).ok();
+            }
+            self.history.record(&renamed);
+            self.save_history();
+            self.audit(Action::Rename, &renamed);
+            self.update_list(&renamed);
+        }
+        // Links only need updating to files that moved.
+        match copy {
+            true => self.references.clear(),
+            false => self.update_references(&renamed),
+        }
+        for listing in self.files.iter_mut() {
+            listing.missing |= missing.contains(&listing.name);
+        }
+        self.relist();
+        renamed
+    }
+
+    /// Read the folder again. Missing files keep their rows, marked, until they are removed.
+    fn relist(&mut self) {
+        let mut kept = mem::take(&mut self.files);
+        kept.retain(|listing| listing.missing);
+        self.file_list();
+        for listing in kept {
+            if self.files.iter().any(|other| other.name == listing.name) {
+                continue;
+            }
+            self.files.push(listing);
+        }
+        self.files
+            .sort_unstable_by(|lhs, rhs| cmp(&lhs.name, &rhs.name));
+    }
+
+    /// Give the files of the last batch renamed their names back, even one renamed
+    /// before the app was last closed. Returns the `(from, to)` paths of the files moved.
+    pub fn undo(&mut self) -> Vec<(PathBuf, PathBuf)> {
+        let selected = self.unselect(&self.history.undo_moves());
+        let outcome = self.history.undo(self.settings.throttle, &self.guard());
+        let moved = self.reverted(outcome, selected);
+        self.audit(Action::Undo, &moved);
+        self.update_list(&moved);
+        self.update_references(&moved);
+        moved
+    }
+
+    /// Rename the files of the last batch undone again.
+    /// Returns the `(from, to)` paths of the files moved.
+    pub fn redo(&mut self) -> Vec<(PathBuf, PathBuf)> {
+        let selected = self.unselect(&self.history.redo_moves());
+        let outcome = self.history.redo(self.settings.throttle, &self.guard());
+        let moved = self.reverted(outcome, selected);
+        self.audit(Action::Redo, &moved);
+        self.update_list(&moved);
+        self.update_references(&moved);
+        moved
+    }
+
+    pub fn can_undo(&self) -> bool {
+        self.history.can_undo()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        self.history.can_redo()
+    }
+
+    /// Take the files to be moved out of the selection while they still exist, returning
+    /// which were selected, for [Renamer::reverted] to select them where they end up.
+    fn unselect(&mut self, moves: &[(PathBuf, PathBuf)]) -> Vec<bool> {
+        let selected = moves
+            .iter()
+            .map(|(from, _)| self.selection.contains(from))
+            .collect();
+        for (from, _) in moves {
+            self.selection.remove(from);
+        }
+        selected
+    }
+
+    /// Follow the files an undo or redo moved with the selection, and list them again.
+    fn reverted(&mut self, outcome: Outcome, selected: Vec<bool>) -> Vec<(PathBuf, PathBuf)> {
+        self.failed.clear();
+        let mut moved = vec![];
+        for (((from, to), result), selected) in outcome.into_iter().zip(selected) {
+            let path = match result {
+                Ok(()) => {
+                    moved.push((from, to.clone()));
+                    to
+                }
+                Err(err) => {
+                    self.failed.push(format!("{}: {err}", from.display()));
+                    from
+                }
+            };
+            if selected {
+                self.selection.add(path);
+            }
+        }
+        self.save_history();
+        self.file_list();
+        moved
+    }
+
+    /// Add the files moved to the audit log, when it is turned on.
+    fn audit(&mut self, action: Action, moved: &[(PathBuf, PathBuf)]) {
+        if !self.settings.audit {
+            return;
+        }
+        if let Some(path) = self.config_path(AUDIT_FILE) {
+            if let Err(err) = audit::append(path, action, moved) {
+                self.failed
+                    .push(format!("Not added to the audit log: {err}"));
+            }
+        }
+    }
+
+    fn save_history(&self) {
+        if let Some(path) = self.config_path(HISTORY_FILE) {
+            self.history.save(path).ok();
+        }
+    }
+
+    /// The folders nothing may be renamed in, none once renaming in them is allowed.
+    fn guard(&self) -> Guard {
+        if self.allow_protected {
+            Guard::none()
+        } else {
+            self.settings.guard.clone()
+        }
+    }
+
+    /// The options from every shown panel, in the order they are applied in, see
+    /// [Settings::ordered].
+    fn options(&self) -> Vec<Options> {
+        let mut options = [
+            Options::Regex(self.reg_exp.build()),
+            Options::Name(self.name.build()),
+            Options::Replace(self.replace.build()),
+            Options::Case(self.case.build()),
+            Options::Remove(self.remove.build()),
+            Options::Add(self.add.build()),
+            Options::Date(self.date.build()),
+            Options::Folder(self.folder.build()),
+            Options::Number(self.number.build()),
+            Options::Extension(self.extension.build()),
+            Options::Normalize(self.normalize.build()),
+            Options::Sanitize(self.sanitize.build()),
+            Options::Blacklist(self.blacklist.build()),
+            Options::Release(self.release.build()),
+            Options::Swap(self.swap.build()),
+            Options::Lookup(self.lookup.build()),
+            Options::Pair(self.pair.build()),
+            Options::Destination(self.destination.build()),
+        ]
+        .into_iter()
+        .filter(|opt| self.settings.shows(opt.step()))
+        .collect::<Vec<_>>();
+        let order = self.settings.ordered();
+        options.sort_by_key(|opt| order.iter().position(|step| *step == opt.step()));
+        options
+    }
+
+    /// The options, selection and overrides a preview is made from, to tell when a staged
+    /// preview is out of date.
+    fn fingerprint(&self) -> String {
+        let selected = self
+            .files
+            .iter()
+            .filter(|listing| listing.selected)
+            .map(|listing| (&listing.name, &listing.overridden))
+            .collect::<BTreeSet<_>>();
+        serde_json::to_string(&(self.options(), &self.settings.scopes, selected))
+            .unwrap_or_default()
+    }
+
+    /// Apply the current options to the selected files and check the resulting names.
+    /// Names needing metadata fill in over later previews as it is read.
+    pub fn preview(&mut self) {
+        self.preview_for(Duration::MAX);
+    }
+
+    /// [Renamer::preview] for about `budget`, leaving the rest of a huge batch to later
+    /// calls. The files on screen are previewed first, and at least one file each call.
+    pub fn preview_for(&mut self, budget: Duration) {
+        self.number.set_siblings(
+            &self
+                .files
+                .iter()
+                .map(|listing| listing.name.as_path())
+                .collect::<Vec<_>>(),
+        );
+        let options = self.options();
+        let needs_metadata = options.iter().any(Options::needs_metadata);
+        // Sorting by capture time needs every file's metadata, not just the selected ones.
+        let custom = self
+            .sort_expression()
+            .filter(|_| self.columns.2 == Columns::Custom);
+        let by_captured = self.columns.2 == Columns::Captured
+            || custom
+                .as_ref()
+                .is_some_and(|expression| expression.uses(SortColumn::Captured));
+        if by_captured {
+            for listing in &self.files {
+                fetch_metadata(&mut self.metadata, &mut self.cache, &listing.name);
+            }
+        }
+        let arrived = self.metadata.poll();
+        for path in &arrived {
+            if let Some(Extraction::Ready(metadata)) = self.metadata.get(path) {
+                self.cache.insert(path, metadata.clone());
+            }
+        }
+        for listing in self.files.iter_mut() {
+            if let Some(Extraction::Ready(metadata)) = self.metadata.get(&listing.name) {
+                listing.captured = metadata.captured;
+            }
+        }
+        if by_captured && !arrived.is_empty() {
+            match &custom {
+                Some(expression) => sort_by_expression(&mut self.files, expression),
+                None => sort_by_captured(&mut self.files, &self.columns.1),
+            }
+        }
+        if needs_metadata && !arrived.is_empty() {
+            self.schedule.reset();
+        }
+        let key = self.preview_key(&options);
+        if !self.schedule.is_current(key) {
+            self.analyzed = None;
+            self.schedule_preview(key, &options, needs_metadata);
+        }
+        for (idx, file) in self.schedule.run(&self.visible, budget) {
+            let listing = &mut self.files[idx];
+            listing.renamed = file.clone();
+            listing.pending = false;
+            if let Some(Override::Name(name)) = &listing.overridden {
+                listing.renamed.set_name(name);
+            }
+        }
+        // The finished batch is checked once, not on every frame it is shown.
+        if self.schedule.pending() || self.analyzed == Some(key) {
+            return;
+        }
+        self.analyzed = Some(key);
+        self.analyze(&options);
+    }
+
+    /// Check the finished preview: its collisions, names and paths the system won't take,
+    /// naming policy violations, near-duplicates and blacklisted words.
+    fn analyze(&mut self, options: &[Options]) {
+        if self.settings.collisions == CollisionPolicy::Number {
+            batch::number_collisions(
+                self.files
+                    .iter_mut()
+                    .filter(|listing| listing.selected)
+                    .map(|listing| &mut listing.renamed),
+            );
+        }
+        let mut positions = HashMap::new();
+        for (idx, listing) in self.files.iter_mut().enumerate() {
+            listing.path_error = None;
+            listing.name_error = None;
+            listing.policy_error = None;
+            listing.conflict = None;
+            positions.insert(listing.name.clone(), idx);
+        }
+        let batch = self.previewed();
+        self.mirror_plan = self
+            .mirror
+            .as_ref()
+            .map(|mirror| mirror.plan(&batch))
+            .unwrap_or_default();
+        let (platform, limits) = match self.settings.windows_names {
+            true => (Platform::Windows, PathLimits::WINDOWS),
+            false => (Platform::default(), PathLimits::default()),
+        };
+        self.too_long = path_lengths(&batch, limits);
+        for err in &self.too_long {
+            if let Some(&idx) = positions.get(&err.original) {
+                self.files[idx].path_error = Some(err.to_string());
+            }
         }
+        // Mirror files aren't listed, so they are only counted in the status bar.
+        self.too_long
+            .extend(path_lengths(&self.mirror_plan.renames, limits));
+        self.invalid = invalid_names(&batch, platform);
+        for err in &self.invalid {
+            if let Some(&idx) = positions.get(&err.original) {
+                self.files[idx].name_error = Some(err.to_string());
+            }
+        }
+        self.invalid
+            .extend(invalid_names(&self.mirror_plan.renames, platform));
+        self.violations = self.policy.as_ref().map_or_else(Vec::new, |policy| {
+            let renamed = batch
+                .iter()
+                .filter(|(from, to)| from != to)
+                .cloned()
+                .collect::<Vec<_>>();
+            policy.check(&renamed)
+        });
+        for violation in &self.violations {
+            if let Some(&idx) = positions.get(&violation.original) {
+                let error = self.files[idx].policy_error.get_or_insert_with(String::new);
+                if !error.is_empty() {
+                    error.push('\n');
+                }
+                error.push_str(&violation.to_string());
+            }
+        }
+        if let Some(policy) = &self.policy {
+            self.violations
+                .extend(policy.check(&self.mirror_plan.renames));
+        }
+        self.collisions = batch::collisions(&batch);
+        for collision in &self.collisions {
+            if let Some(&idx) = positions.get(&collision.original) {
+                self.files[idx].conflict = Some(collision.to_string());
+            }
+        }
+        let conflicts = self.collisions.len();
+        self.collisions
+            .extend(batch::collisions(&self.mirror_plan.renames));
+        self.number.set_count(batch.len());
+        self.split = if self.settings.shows(Step::Pair) && self.pair.build().keep {
+            let all = self
+                .files
+                .iter()
+                .map(|listing| listing.name.as_path())
+                .collect::<Vec<_>>();
+            let chosen = batch.iter().map(|(from, _)| from).collect::<Vec<_>>();
+            pair::split(&chosen, &all)
+        } else {
+            vec![]
+        };
+        self.lints = lint(options);
+        self.warnings = similar_names(&batch);
+        self.summary = summarize_with(&batch, conflicts);
+        // A blacklist that won't compile is reported with each file's errors.
+        let blacklist = self
+            .settings
+            .shows(Step::Blacklist)
+            .then(|| self.blacklist.build().compile().ok())
+            .flatten();
+        self.flagged = blacklist.map_or_else(Vec::new, |blacklist| {
+            batch
+                .iter()
+                .filter_map(|(original, new)| {
+                    let words = blacklist
+                        .find(&new.file_name()?.to_string_lossy())
+                        .join(", ");
+                    (!words.is_empty()).then(|| format!("{}: {words}", original.display()))
+                })
+                .collect()
+        });
+        self.applied = Some(self.fingerprint());
+    }
+
+    /// The options and every listed file in order, as numbering follows the order, with
+    /// their overrides.
+    fn preview_key(&self, options: &[Options]) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        serde_json::to_string(options)
+            .unwrap_or_default()
+            .hash(&mut hasher);
+        self.settings.collisions.hash(&mut hasher);
+        self.settings.scopes.hash(&mut hasher);
+        self.settings.windows_names.hash(&mut hasher);
+        for listing in &self.files {
+            (&listing.name, listing.selected, &listing.overridden).hash(&mut hasher);
+        }
+        hasher.finish()
+    }
+
+    /// Whether new names are made for Windows, trimming what it would drop from them.
+    fn windows_names(&self) -> bool {
+        cfg!(windows) || self.settings.windows_names
+    }
+
+    /// Start previewing the selected files over the next frames.
+    fn schedule_preview(&mut self, key: u64, options: &[Options], needs_metadata: bool) {
+        let windows_names = self.windows_names();
+        let mut selected = vec![];
+        for (idx, listing) in self.files.iter_mut().enumerate() {
+            let Ok(mut file) = File::new(&listing.name) else {
+                continue;
+            };
+            listing.metadata_error = None;
+            if !listing.selected || listing.overridden == Some(Override::Exclude) {
+                listing.renamed = file;
+                listing.pending = false;
+                continue;
+            }
+            if needs_metadata {
+                // Names fill in as the metadata arrives.
+                fetch_metadata(&mut self.metadata, &mut self.cache, &listing.name);
+                let metadata = match self.metadata.get(&listing.name) {
+                    Some(Extraction::Ready(metadata)) => metadata.clone(),
+                    Some(Extraction::Failed(err)) => {
+                        listing.metadata_error = Some(err.clone());
+                        Metadata::default()
+                    }
+                    _ => Metadata::default(),
+                };
+                file = file.with_metadata(metadata);
+            }
+            listing.pending = true;
+            let file = file
+                .with_scopes(self.settings.scopes.clone())
+                .with_windows_names(windows_names);
+            selected.push((idx, listing.name.clone(), file));
+        }
+        let bounds = self
+            .date
+            .build()
+            .bounds(selected.iter().map(|(_, _, file)| file));
+        self.date.set_bounds(bounds);
+        self.schedule.start(key, selected, options);
     }
 
-    fn _process_selected(&mut self) {
-        for (_cnt, file) in self.files.iter().enumerate() {
-            if file.selected {
-                let mut _orig = &file.name;
-                let mut _renamed = &file.renamed;
-                // self.add.make_options().process(&mut renamed);
+    /// Keep the overrides changed from the rows of the file list.
+    fn store_overrides(&mut self) {
+        for listing in self.files.iter_mut() {
+            if mem::take(&mut listing.override_changed) {
+                self.overrides
+                    .set(&listing.name, listing.overridden.clone());
             }
         }
     }
+
+    /// The number of selected files previewed so far, and selected.
+    pub fn progress(&self) -> (usize, usize) {
+        self.schedule.progress()
+    }
+}
+
+/// Use the cached metadata for a file, or queue it to be read, unless it is already known.
+fn fetch_metadata(metadata: &mut Scheduler, cache: &mut MetadataCache, path: &Path) {
+    if metadata.get(path).is_none() {
+        match cache.get(path) {
+            Some(cached) => metadata.insert(path, cached),
+            None => metadata.request(path),
+        }
+    }
+}
+
+/// Where the settings, history and the like are kept: the [CONFIG_DIR] in `%APPDATA%` on
+/// Windows, `~/Library/Application Support` on macOS, and `$XDG_CONFIG_HOME` or
+/// `~/.config` elsewhere. The folder older versions kept in the home folder is moved
+/// there, or still used if it can't be moved.
+pub(crate) fn config_dir() -> Option<PathBuf> {
+    let home = home::home_dir();
+    let base = if cfg!(windows) {
+        env::var_os("APPDATA").map(PathBuf::from)
+    } else if cfg!(target_os = "macos") {
+        home.as_ref()
+            .map(|home| home.join("Library").join("Application Support"))
+    } else {
+        env::var_os("XDG_CONFIG_HOME")
+            .map(PathBuf::from)
+            .filter(|dir| dir.is_absolute())
+            .or_else(|| home.as_ref().map(|home| home.join(".config")))
+    };
+    let legacy = home.map(|home| home.join(LEGACY_CONFIG_DIR));
+    let Some(dir) = base.map(|base| base.join(CONFIG_DIR)) else {
+        return legacy;
+    };
+    match legacy {
+        Some(legacy) if legacy.is_dir() && !dir.exists() => {
+            let moved = dir
+                .parent()
+                .map_or(Ok(()), fs::create_dir_all)
+                .and_then(|_| fs::rename(&legacy, &dir));
+            match moved {
+                Ok(()) => Some(dir),
+                Err(_) => Some(legacy),
+            }
+        }
+        _ => Some(dir),
+    }
+}
+
+/// Whether the options copy the files rather than rename them, see
+/// [crate::file::DestinationOptions].
+fn copies(options: &[Options]) -> bool {
+    options
+        .iter()
+        .any(|opt| matches!(opt, Options::Destination(dest) if dest.copy))
+}
+
+/// The selected files, or the whole folder when nothing is selected.
+fn inspected(files: &[FileListing]) -> Vec<&Path> {
+    let selected = files
+        .iter()
+        .filter(|listing| listing.selected)
+        .map(|listing| listing.name.as_path())
+        .collect::<Vec<_>>();
+    if selected.is_empty() {
+        files.iter().map(|listing| listing.name.as_path()).collect()
+    } else {
+        selected
+    }
 }
 
 fn frame() -> Frame {
@@ -176,31 +1750,962 @@ fn frame() -> Frame {
 }
 
 impl App for Renamer {
+    fn on_exit(&mut self, _gl: Option<&glow::Context>) {
+        if let Some(path) = self.config_path(CACHE_FILE) {
+            self.cache.save(path).ok();
+        }
+    }
+
     /// Called each time the UI needs repainting, which may be many times per second.
     /// Put your widgets into a `SidePanel`, `TopPanel`, `CentralPanel`, `Window` or `Area`.
     fn update(&mut self, ctx: &Context, _frame: &mut eframe::Frame) {
         // let Self { label, value } = self;
+        if let Some(renaming) = &mut self.renaming {
+            if let Some(results) = renaming.worker.poll() {
+                let renaming = self.renaming.take().expect("The batch is being renamed.");
+                self.renamed(renaming, results);
+            }
+            ctx.request_repaint();
+        }
+        let dropped: Vec<_> = ctx.input(|input| {
+            let files = input.raw.dropped_files.iter();
+            files.filter_map(|file| file.path.clone()).collect()
+        });
+        if !dropped.is_empty() {
+            self.drop_paths(&dropped);
+        }
+        // F2 renames, as Rename does, unless it is typed into a box.
+        if ctx.input(|input| input.key_pressed(Key::F2))
+            && ctx.memory(|memory| memory.focus().is_none())
+        {
+            self.start_rename();
+        }
+        if ctx.input(|input| !input.raw.hovered_files.is_empty()) {
+            let layer = LayerId::new(egui::Order::Foreground, Id::new("drop files"));
+            let painter = ctx.layer_painter(layer);
+            let screen = ctx.screen_rect();
+            painter.rect_filled(screen, 0.0, Color32::from_black_alpha(160));
+            painter.text(
+                screen.center(),
+                Align2::CENTER_CENTER,
+                "Drop files or folders to select them",
+                TextStyle::Heading.resolve(&ctx.style()),
+                Color32::WHITE,
+            );
+        }
+        #[cfg(feature = "watch")]
+        if let Some(watch) = &self.watch {
+            // The batch's own renames are listed once it is done.
+            if watch.changed() && self.renaming.is_none() {
+                self.relist();
+            }
+            ctx.request_repaint_after(WATCH_REFRESH);
+        }
+        if self.scratchpad.open {
+            let options = self.options();
+            let windows_names = self.windows_names();
+            self.scratchpad
+                .refresh(&options, &self.settings.scopes, windows_names);
+            self.scratchpad.show(ctx);
+        }
+        if self.sample.open {
+            // Only rename the sample while it is being tested.
+            let options = self.options();
+            let files = &self.files;
+            self.sample.refresh(|| inspected(files), &options);
+            self.sample.show(ctx);
+        } else if !self.settings.staged
+            || self.applied.is_none()
+            || self.metadata.pending() > 0
+            || self.schedule.pending()
+        {
+            // Staged previews still fill in as the metadata they asked for arrives.
+            self.preview_for(PREVIEW_BUDGET);
+            if self.schedule.pending() {
+                ctx.request_repaint();
+            } else if self.metadata.pending() > 0 {
+                ctx.request_repaint_after(METADATA_REFRESH);
+            }
+        }
+
+        if self.conform.open {
+            let batch = self
+                .files
+                .iter()
+                .map(|listing| {
+                    let new = if listing.selected {
+                        listing.renamed.new_path()
+                    } else {
+                        listing.name.clone()
+                    };
+                    (listing.name.clone(), new)
+                })
+                .collect::<Vec<_>>();
+            self.conform.refresh(&batch);
+            self.conform.show(ctx);
+        }
+
+        if self.queue.running() {
+            let (throttle, guard) = (self.settings.throttle, self.guard());
+            let (collisions, background) = (self.settings.collisions, self.settings.background);
+            let policy = self.policy.as_ref();
+            if let Some(finished) = self
+                .queue
+                .step(throttle, &guard, policy, collisions, background)
+            {
+                self.batch_finished(finished);
+            }
+            ctx.request_repaint();
+        }
+        if self.queue.open {
+            self.queue.show(ctx);
+        }
+        if self.changes.open {
+            self.changes.show(ctx);
+        }
+        if self.conflicts.open {
+            if let Some(overwrite) = self.conflicts.show(ctx) {
+                self.start_resolved(Some(overwrite));
+            }
+        }
+        if self.rotate.open {
+            let (throttle, guard) = (self.settings.throttle, self.guard());
+            if let Some(renamed) = self.rotate.show(ctx, &self.cwd_path, throttle, &guard) {
+                self.batch_finished(Finished {
+                    renamed,
+                    ..Default::default()
+                });
+            }
+        }
+
+        #[cfg(feature = "zip")]
+        if self.archive.open {
+            self.archive.refresh(&self.options());
+            self.archive.show(ctx);
+        }
+
+        #[cfg(feature = "ingest")]
+        if self.ingest.open {
+            self.ingest
+                .show(ctx, &self.options(), self.settings.throttle);
+            if self.ingest.pending() {
+                ctx.request_repaint_after(METADATA_REFRESH);
+            }
+        }
+
+        if self.statistics_window.open
+            && self
+                .statistics_window
+                .show(ctx, &mut self.settings, &mut self.statistics)
+        {
+            self.save_settings();
+            self.save_statistics();
+        }
+
+        if self.about.open {
+            self.about.show(ctx);
+            if self.about.pending() {
+                ctx.request_repaint_after(METADATA_REFRESH);
+            }
+        }
 
         TopBottomPanel::top("top_panel").show(ctx, |ui| {
             menu::bar(ui, |ui| {
                 ui.menu_button("File", |ui| {
+                    let log = self
+                        .config_path(AUDIT_FILE)
+                        .map(|path| path.display().to_string())
+                        .unwrap_or_default();
+                    if ui
+                        .checkbox(&mut self.settings.audit, "Audit Log")
+                        .on_hover_text(format!("Add every rename, undo and redo to {log}"))
+                        .changed()
+                    {
+                        self.save_settings();
+                    }
+                    if ui
+                        .button("Import Selection")
+                        .on_hover_text("Select the files of a list of paths or an .m3u playlist")
+                        .clicked()
+                    {
+                        ui.close_menu();
+                        if let Some(path) = rfd::FileDialog::new()
+                            .add_filter("List", &["txt", "m3u", "m3u8"])
+                            .pick_file()
+                        {
+                            self.import_list(&path);
+                        }
+                    }
+                    if ui
+                        .button("Export Selection")
+                        .on_hover_text("Save the selected files as a list of paths or a playlist")
+                        .clicked()
+                    {
+                        ui.close_menu();
+                        if let Some(path) = rfd::FileDialog::new()
+                            .set_directory(&self.cwd_path)
+                            .set_file_name("selection.txt")
+                            .add_filter("List", &["txt", "m3u", "m3u8"])
+                            .save_file()
+                        {
+                            self.export_list(&path);
+                        }
+                    }
+                    ui.menu_button("Export Script", |ui| {
+                        let shells = [(Shell::Posix, "Shell"), (Shell::PowerShell, "PowerShell")];
+                        for (shell, name) in shells {
+                            if ui.button(name).clicked() {
+                                ui.close_menu();
+                                if let Some(path) = rfd::FileDialog::new()
+                                    .set_directory(&self.cwd_path)
+                                    .set_file_name(format!("rename.{}", shell.extension()))
+                                    .save_file()
+                                {
+                                    let script = self.script(shell);
+                                    if let Err(err) = fs::write(&path, script) {
+                                        self.failed = vec![format!("{}: {err}", path.display())];
+                                    }
+                                }
+                            }
+                        }
+                    })
+                    .response
+                    .on_hover_text("Save the renames as a script to review or run elsewhere");
+                    #[cfg(feature = "report")]
+                    ui.menu_button("Export Report", |ui| {
+                        let formats = [(ReportFormat::Csv, "CSV"), (ReportFormat::Json, "JSON")];
+                        for (format, name) in formats {
+                            if ui.button(name).clicked() {
+                                ui.close_menu();
+                                if let Some(path) = rfd::FileDialog::new()
+                                    .set_directory(&self.cwd_path)
+                                    .set_file_name(format!("preview.{}", format.extension()))
+                                    .save_file()
+                                {
+                                    let rows = self.report();
+                                    let written = fs::File::create(&path)
+                                        .map_err(ReportError::from)
+                                        .and_then(|out| report::write(&rows, format, out));
+                                    if let Err(err) = written {
+                                        self.failed = vec![format!("{}: {err}", path.display())];
+                                    }
+                                }
+                            }
+                        }
+                    })
+                    .response
+                    .on_hover_text("Save the new names, their status and conflicts for review");
                     if ui.button("Quit").clicked() {
                         _frame.close();
                     }
                 });
+                if ui.button("Test on Sample").clicked() {
+                    self.sample.show_sample();
+                };
+                if ui
+                    .button("Scratchpad")
+                    .on_hover_text("Try the rules on names typed or pasted in")
+                    .clicked()
+                {
+                    self.scratchpad.open = true;
+                };
+                if ui
+                    .button("Queue")
+                    .on_hover_text("Batches from other folders waiting to be renamed")
+                    .clicked()
+                {
+                    self.queue.open = true;
+                };
+                if ui
+                    .button("Conform Check")
+                    .on_hover_text("Check the names against a naming scheme")
+                    .clicked()
+                {
+                    self.conform.open = true;
+                };
+                if ui
+                    .button("Changes on Disk")
+                    .on_hover_text("What the last rename changed, and anything else that did")
+                    .clicked()
+                {
+                    self.changes.open = true;
+                };
+                if ui
+                    .button("Swap Extensions")
+                    .on_hover_text("Swap extensions between files of the same name")
+                    .clicked()
+                {
+                    self.rotate.open = true;
+                };
+                #[cfg(feature = "ingest")]
+                if ui
+                    .button("Ingest")
+                    .on_hover_text("Copy a memory card with the new names and a checksum manifest")
+                    .clicked()
+                {
+                    self.ingest.open = true;
+                };
+                #[cfg(feature = "zip")]
+                {
+                    let selected = self
+                        .files
+                        .iter()
+                        .filter(|listing| listing.selected)
+                        .collect::<Vec<_>>();
+                    let zip = match selected[..] {
+                        [listing] if is_zip(&listing.name) => Some(listing.name.clone()),
+                        _ => None,
+                    };
+                    if ui
+                        .add_enabled(zip.is_some(), egui::Button::new("Rename in Zip"))
+                        .on_hover_text("Rename the entries inside the selected zip archive")
+                        .on_disabled_hover_text("Select one zip archive")
+                        .clicked()
+                    {
+                        if let Some(zip) = zip {
+                            self.archive.show_archive(&zip);
+                        }
+                    };
+                }
+                ui.menu_button("Cache", |ui| {
+                    ui.label(format!("{} files cached", self.cache.len()));
+                    ui.horizontal(|ui| {
+                        let mut max_entries = self.cache.max_entries();
+                        if ui.add(DragValue::new(&mut max_entries)).changed() {
+                            self.cache.set_max_entries(max_entries);
+                        };
+                        ui.label("Max Files");
+                    });
+                    if ui.button("Clear").clicked() {
+                        self.cache.clear();
+                        ui.close_menu();
+                    };
+                });
+                #[cfg(debug_assertions)]
+                ui.menu_button("Debug", |ui| {
+                    if ui.button("Generate Sandbox").clicked() {
+                        if let Some(dir) = rfd::FileDialog::new()
+                            .set_directory(&self.cwd_path)
+                            .pick_folder()
+                        {
+                            if crate::fixtures::generate(&dir).is_ok() {
+                                self.cwd = dir.join(crate::fixtures::SANDBOX).display().to_string();
+                                self.change_dir();
+                            }
+                        }
+                        ui.close_menu();
+                    }
+                });
+                ui.menu_button("Suggestions", |ui| {
+                    let suggestions = suggest(&inspected(&self.files));
+                    if suggestions.is_empty() {
+                        ui.label("No suggestions");
+                    }
+                    for suggestion in suggestions {
+                        if ui.button(&suggestion.message).clicked() {
+                            self.load_option(suggestion.option);
+                            ui.close_menu();
+                        }
+                    }
+                });
+                ui.menu_button("Mirror", |ui| {
+                    match &self.mirror {
+                        Some(mirror) => ui.label(mirror.root().display().to_string()),
+                        None => ui.label("No mirror folder"),
+                    };
+                    if ui.button("Choose Folder").clicked() {
+                        if let Some(dir) = rfd::FileDialog::new()
+                            .set_directory(&self.cwd_path)
+                            .pick_folder()
+                        {
+                            self.set_mirror(dir);
+                        }
+                        ui.close_menu();
+                    }
+                    if ui.button("Clear").clicked() {
+                        self.mirror = None;
+                        self.analyzed = None;
+                        ui.close_menu();
+                    }
+                });
+                ui.menu_button("View", |ui| {
+                    let mut changed = ui
+                        .checkbox(&mut self.settings.advanced, "Advanced")
+                        .on_hover_text("Show every panel")
+                        .changed();
+                    changed |= ui
+                        .checkbox(&mut self.settings.staged, "Staged Preview")
+                        .on_hover_text(
+                            "Only update the new names when Apply is pressed, for very large folders",
+                        )
+                        .changed();
+                    changed |= ui
+                        .checkbox(&mut self.settings.number_column, "Number Column")
+                        .on_hover_text("Show the number each file is given, to check steps and resets")
+                        .changed();
+                    if ui
+                        .checkbox(&mut self.settings.windows_names, "Windows Names")
+                        .on_hover_text(
+                            "Check new names against Windows' rules and path limit, and trim \
+                            the dots and spaces it drops, e.g. for files synced to Windows",
+                        )
+                        .changed()
+                    {
+                        self.applied = None;
+                        changed = true;
+                    }
+                    let mut carry = self.settings.continue_numbering;
+                    if ui
+                        .checkbox(&mut carry, "Continue Numbering")
+                        .on_hover_text(
+                            "Number the next folder on from the last batch renamed, \
+                            rather than from the start each time",
+                        )
+                        .changed()
+                    {
+                        self.continue_numbering(carry);
+                    }
+                    if ui
+                        .add_enabled(self.carried.is_some(), Button::new("Restart Numbering"))
+                        .on_hover_text("Number from the start set by hand again")
+                        .clicked()
+                    {
+                        self.restart_numbering();
+                        ui.close_menu();
+                    }
+                    ui.separator();
+                    let mut appearance = false;
+                    ui.horizontal(|ui| {
+                        let theme = &mut self.settings.theme;
+                        appearance |= ui.radio_value(theme, Theme::Dark, "Dark").changed();
+                        appearance |= ui.radio_value(theme, Theme::Light, "Light").changed();
+                    });
+                    ui.horizontal(|ui| {
+                        ui.label("Text Size");
+                        let scale = DragValue::new(&mut self.settings.text_scale)
+                            .clamp_range(50..=200)
+                            .suffix("%");
+                        appearance |= ui.add(scale).changed();
+                    });
+                    ui.horizontal(|ui| {
+                        ui.label("Column Width");
+                        let width = DragValue::new(&mut self.settings.column_width)
+                            .clamp_range(20..=400);
+                        changed |= ui
+                            .add(width)
+                            .on_hover_text("The narrowest the columns of the file list are")
+                            .changed();
+                    });
+                    if appearance {
+                        apply_appearance(ui.ctx(), &self.settings);
+                        changed = true;
+                    }
+                    if !self.settings.advanced {
+                        ui.separator();
+                        ui.label("Also show");
+                        for step in Step::ALL
+                            .into_iter()
+                            .filter(|step| step.enabled() && !SIMPLE_STEPS.contains(step))
+                        {
+                            let mut pinned = self.settings.pinned.contains(&step);
+                            if ui.checkbox(&mut pinned, step.to_string()).changed() {
+                                if pinned {
+                                    self.settings.pinned.insert(step);
+                                } else {
+                                    self.settings.pinned.remove(&step);
+                                }
+                                changed = true;
+                            }
+                        }
+                    }
+                    if changed {
+                        self.save_settings();
+                    }
+                });
+                ui.menu_button("Order", |ui| {
+                    ui.label("Drag the steps into the order they are applied in");
+                    let mut order = self.settings.ordered();
+                    let mut moved = None;
+                    // Swap, Lookup and Pairs work on the whole batch, outside the order.
+                    for step in order.iter().copied().filter(|step| {
+                        self.settings.shows(*step)
+                            && !matches!(step, Step::Swap | Step::Lookup | Step::Pair)
+                    }) {
+                        let label = format!("☰ {}", step.name());
+                        let row = ui.add(Label::new(label).sense(Sense::drag()));
+                        if row.drag_started() {
+                            self.dragged_step = Some(step);
+                        }
+                        // Dragging over another step takes its place.
+                        let over = ui.rect_contains_pointer(row.rect);
+                        let dragged = self.dragged_step.filter(|dragged| over && *dragged != step);
+                        if let Some(dragged) = dragged {
+                            moved = Some((dragged, step));
+                        }
+                    }
+                    if ui.input(|input| input.pointer.any_released()) {
+                        self.dragged_step = None;
+                    }
+                    let mut changed = false;
+                    if let Some((dragged, onto)) = moved {
+                        let position = |step| order.iter().position(|other| *other == step);
+                        if let (Some(from), Some(to)) = (position(dragged), position(onto)) {
+                            let step = order.remove(from);
+                            order.insert(to, step);
+                            self.settings.order = order;
+                            changed = true;
+                        }
+                    }
+                    if ui
+                        .add_enabled(!self.settings.order.is_empty(), Button::new("Reset"))
+                        .clicked()
+                    {
+                        self.settings.order.clear();
+                        changed = true;
+                    }
+                    if changed {
+                        self.save_settings();
+                    }
+                })
+                .response
+                .on_hover_text("The order the steps are applied in");
+                ui.menu_button("Scope", |ui| {
+                    ui.label("The extensions each step is limited to, e.g. .jpg;.png");
+                    // Swap and Pairs work on the whole batch, so they can't be limited.
+                    let steps = self
+                        .settings
+                        .ordered()
+                        .into_iter()
+                        .filter(|step| {
+                            self.settings.shows(*step) && !matches!(step, Step::Swap | Step::Pair)
+                        })
+                        .collect::<Vec<_>>();
+                    for step in steps {
+                        ui.horizontal(|ui| {
+                            ui.label(step.name());
+                            let mut extensions =
+                                self.settings.scopes.get(&step).cloned().unwrap_or_default();
+                            let edit = TextEdit::singleline(&mut extensions)
+                                .hint_text("Every file")
+                                .desired_width(COL_WIDTH / 2.0);
+                            if ui.add(edit).changed() {
+                                self.set_scope(step, &extensions);
+                            }
+                        });
+                    }
+                })
+                .response
+                .on_hover_text("Apply steps only to some kinds of file");
+                ui.menu_button("Throttle", |ui| {
+                    ui.horizontal(|ui| {
+                        let throttle = &mut self.settings.throttle.per_second;
+                        if ui.add(DragValue::new(throttle)).changed() {
+                            self.save_settings();
+                        };
+                        ui.label("Renames per second");
+                    })
+                    .response
+                    .on_hover_text("Slow renames down for network drives, 0 for no limit");
+                    if ui
+                        .checkbox(&mut self.settings.background, "Low priority")
+                        .on_hover_text(
+                            "Rename in the background, so huge batches don't slow down other \
+                             programs",
+                        )
+                        .changed()
+                    {
+                        self.save_settings();
+                    }
+                });
+                ui.menu_button("Links", |ui| {
+                    ui.horizontal(|ui| {
+                        let extensions = TextEdit::singleline(&mut self.settings.references)
+                            .hint_text("md html")
+                            .desired_width(COL_WIDTH / 2.0);
+                        if ui.add(extensions).changed() {
+                            self.save_settings();
+                        }
+                        ui.label("Extensions");
+                    })
+                    .response
+                    .on_hover_text("The text files whose links to renamed files are rewritten");
+                })
+                .response
+                .on_hover_text("Keep links in documentation working after renaming");
+                ui.menu_button("Auto Select", |ui| {
+                    ui.horizontal(|ui| {
+                        let mask = TextEdit::singleline(&mut self.settings.auto_select)
+                            .hint_text("*.tmp;*.part")
+                            .desired_width(COL_WIDTH / 2.0);
+                        if ui.add(mask).changed() {
+                            self.save_settings();
+                        }
+                        ui.label("Mask");
+                    })
+                    .response
+                    .on_hover_text("Separate masks with ;, or use a group such as @Images");
+                    let filter = Filter {
+                        mask: self.settings.auto_select.clone(),
+                        ..Default::default()
+                    };
+                    if let Err(err) = filter.matcher_with(&self.settings.groups) {
+                        ui.colored_label(Color32::RED, format!("⛔ {err}"));
+                    }
+                    if ui
+                        .add_enabled(
+                            !self.settings.auto_select.is_empty(),
+                            Button::new("Select Now"),
+                        )
+                        .clicked()
+                    {
+                        self.auto_select();
+                    }
+                })
+                .response
+                .on_hover_text("Select the matching files whenever a folder is opened");
+                ui.menu_button("Conflicts", |ui| {
+                    let policy = &mut self.settings.collisions;
+                    let refuse = ui
+                        .radio_value(policy, CollisionPolicy::Refuse, "Rename nothing")
+                        .on_hover_text("Until the new names are fixed");
+                    let number = ui
+                        .radio_value(policy, CollisionPolicy::Number, "Number them")
+                        .on_hover_text("Add (2), (3) and so on to the names that clash");
+                    let skip = ui
+                        .radio_value(policy, CollisionPolicy::Skip, "Skip them")
+                        .on_hover_text("Rename the rest, leaving the files that clash");
+                    let overwrite = ui
+                        .radio_value(policy, CollisionPolicy::Overwrite, "Overwrite")
+                        .on_hover_text("Replace the files already there");
+                    let prompt = ui
+                        .radio_value(policy, CollisionPolicy::Prompt, "Ask")
+                        .on_hover_text("Choose for each file when renaming");
+                    let changed = [refuse, number, skip, overwrite, prompt]
+                        .iter()
+                        .any(Response::changed);
+                    if changed {
+                        self.save_settings();
+                    }
+                })
+                .response
+                .on_hover_text("What to do about files given the same name");
+                ui.menu_button("Naming Policy", |ui| {
+                    ui.horizontal(|ui| {
+                        if ui.button("Load").clicked() {
+                            ui.close_menu();
+                            if let Some(path) = rfd::FileDialog::new().pick_file() {
+                                self.set_policy(Some(path));
+                            }
+                        }
+                        if ui.button("Clear").clicked() {
+                            self.set_policy(None);
+                        }
+                    });
+                    match &self.settings.policy {
+                        Some(path) => ui.label(path.display().to_string()),
+                        None => ui.label("No policy"),
+                    };
+                    if let Some(policy) = &self.policy {
+                        for rule in policy.describe() {
+                            ui.monospace(rule);
+                        }
+                    }
+                    if let Some(err) = &self.policy_error {
+                        ui.colored_label(Color32::RED, err);
+                    }
+                })
+                .response
+                .on_hover_text("The rules every new name must follow, shared by a team");
+                ui.menu_button(format!("Overrides ({})", self.overrides.len()), |ui| {
+                    let mut clear = None;
+                    for (path, over) in self.overrides.iter() {
+                        ui.horizontal(|ui| {
+                            if ui
+                                .small_button("✖")
+                                .on_hover_text("Clear the override")
+                                .clicked()
+                            {
+                                clear = Some(path.to_path_buf());
+                            }
+                            let file = path.file_name().unwrap_or_default().to_string_lossy();
+                            match over {
+                                Override::Name(name) => ui.label(format!("{file} → {name}")),
+                                Override::Exclude => ui.label(format!("{file} excluded")),
+                            }
+                            .on_hover_text(path.display().to_string());
+                        });
+                    }
+                    if let Some(path) = clear {
+                        self.set_override(&path, None);
+                    }
+                    #[cfg(feature = "mapping")]
+                    if ui
+                        .button("Import Mapping")
+                        .on_hover_text("Name files from a CSV or TSV table of old and new names")
+                        .clicked()
+                    {
+                        ui.close_menu();
+                        if let Some(path) = rfd::FileDialog::new()
+                            .add_filter("Table", &["csv", "tsv"])
+                            .pick_file()
+                        {
+                            self.import_mapping(&path);
+                        }
+                    }
+                    #[cfg(feature = "mapping")]
+                    if ui
+                        .button("Mail Merge")
+                        .on_hover_text(
+                            "Name the selected files in order from a template and a table, \
+                             e.g. {region}_{seq}",
+                        )
+                        .clicked()
+                    {
+                        ui.close_menu();
+                        let template = rfd::FileDialog::new()
+                            .set_title("Template")
+                            .add_filter("Text", &["txt"])
+                            .pick_file();
+                        let table = template.as_ref().and_then(|_| {
+                            rfd::FileDialog::new()
+                                .set_title("Table")
+                                .add_filter("Table", &["csv", "tsv"])
+                                .pick_file()
+                        });
+                        if let (Some(template), Some(table)) = (template, table) {
+                            match Merge::read(&template, &table) {
+                                Ok(merge) => self.mail_merge(&merge),
+                                Err(err) => self.failed = vec![format!("Mail merge: {err}")],
+                            }
+                        }
+                    }
+                    if ui
+                        .add_enabled(!self.overrides.is_empty(), Button::new("Clear All"))
+                        .clicked()
+                    {
+                        self.overrides.clear();
+                        for listing in self.files.iter_mut() {
+                            listing.overridden = None;
+                        }
+                        ui.close_menu();
+                    }
+                })
+                .response
+                .on_hover_text("Files named or left out by hand, right click a new name to add one");
+                ui.menu_button("Protected", |ui| {
+                    let mut unprotect = None;
+                    for path in &self.settings.guard.paths {
+                        ui.horizontal(|ui| {
+                            if ui
+                                .small_button("✖")
+                                .on_hover_text("Stop protecting it")
+                                .clicked()
+                            {
+                                unprotect = Some(path.clone());
+                            }
+                            ui.label(path.display().to_string());
+                        });
+                    }
+                    if let Some(path) = unprotect {
+                        self.protect(&path, false);
+                    }
+                    if ui.button("Add Folder").clicked() {
+                        if let Some(dir) = rfd::FileDialog::new().pick_folder() {
+                            self.protect(&dir, true);
+                        }
+                        ui.close_menu();
+                    }
+                    ui.separator();
+                    ui.checkbox(&mut self.allow_protected, "Rename in them anyway")
+                        .on_hover_text("Until the app is closed");
+                })
+                .response
+                .on_hover_text("Folders nothing is renamed in, or directly inside");
+                ui.menu_button("Help", |ui| {
+                    if ui.button("Usage Statistics").clicked() {
+                        self.statistics_window.open = true;
+                        ui.close_menu();
+                    }
+                    if ui.button("About").clicked() {
+                        self.about.open = true;
+                        ui.close_menu();
+                    }
+                });
             });
         });
 
         TopBottomPanel::bottom("bottom_panel").show(ctx, |ui| {
             // Status bar.
             ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
-                ui.label("Status: Ready");
+                let pending = self.metadata.pending();
+                let (previewed, selected) = self.schedule.progress();
+                if self.warnings.is_empty()
+                    && self.flagged.is_empty()
+                    && self.split.is_empty()
+                    && self.too_long.is_empty()
+                    && self.invalid.is_empty()
+                    && self.violations.is_empty()
+                    && self.collisions.is_empty()
+                    && self.failed.is_empty()
+                    && self.references.is_empty()
+                    && self.lints.is_empty()
+                    && self.mirror.is_none()
+                    && self.renaming.is_none()
+                    && pending == 0
+                    && previewed == selected
+                {
+                    ui.label("Status: Ready");
+                } else {
+                    if let Some(renaming) = &self.renaming {
+                        renaming.worker.show(ui);
+                    }
+                    if previewed < selected {
+                        ui.label(format!("{previewed} of {selected} previewed"))
+                            .on_hover_text("The files on screen are previewed first");
+                    }
+                    if pending > 0 {
+                        ui.label(format!("Reading metadata for {pending} files"));
+                    }
+                    if !self.flagged.is_empty() {
+                        ui.label(format!("{} blacklisted names", self.flagged.len()))
+                            .on_hover_text(self.flagged.join("\n"));
+                    }
+                    if !self.warnings.is_empty() {
+                        ui.label(format!("{} near-duplicate names", self.warnings.len()))
+                            .on_hover_text(
+                                self.warnings
+                                    .iter()
+                                    .map(|warning| warning.to_string())
+                                    .collect::<Vec<_>>()
+                                    .join("\n"),
+                            );
+                    }
+                    if !self.failed.is_empty() {
+                        ui.label(format!("⛔ {} renames failed", self.failed.len()))
+                            .on_hover_text(self.failed.join("\n"));
+                    }
+                    if !self.skipped.is_empty() {
+                        ui.label(format!("{} skipped", self.skipped.len()))
+                            .on_hover_text(
+                                self.skipped
+                                    .iter()
+                                    .map(|path| path.display().to_string())
+                                    .collect::<Vec<_>>()
+                                    .join("\n"),
+                            );
+                    }
+                    if !self.references.is_empty() {
+                        ui.label(format!("{} links updated", self.references.len()))
+                            .on_hover_text(
+                                self.references
+                                    .iter()
+                                    .map(|reference| reference.to_string())
+                                    .collect::<Vec<_>>()
+                                    .join("\n"),
+                            );
+                    }
+                    if !self.collisions.is_empty() {
+                        ui.label(format!("⛔ {} name conflicts", self.collisions.len()))
+                            .on_hover_text(
+                                self.collisions
+                                    .iter()
+                                    .map(|collision| collision.to_string())
+                                    .collect::<Vec<_>>()
+                                    .join("\n"),
+                            );
+                    }
+                    if !self.too_long.is_empty() {
+                        ui.label(format!("⛔ {} paths too long", self.too_long.len()))
+                            .on_hover_text(
+                                self.too_long
+                                    .iter()
+                                    .map(|err| err.to_string())
+                                    .collect::<Vec<_>>()
+                                    .join("\n"),
+                            );
+                    }
+                    if !self.invalid.is_empty() {
+                        ui.label(format!("⛔ {} names not allowed", self.invalid.len()))
+                            .on_hover_text(
+                                self.invalid
+                                    .iter()
+                                    .map(|err| err.to_string())
+                                    .collect::<Vec<_>>()
+                                    .join("\n"),
+                            );
+                    }
+                    if !self.violations.is_empty() {
+                        ui.label(format!("⛔ {} policy violations", self.violations.len()))
+                            .on_hover_text(
+                                self.violations
+                                    .iter()
+                                    .map(|violation| violation.to_string())
+                                    .collect::<Vec<_>>()
+                                    .join("\n"),
+                            );
+                    }
+                    if !self.split.is_empty() {
+                        ui.label(format!(
+                            "{} RAW+JPEG pairs split by the selection",
+                            self.split.len()
+                        ))
+                        .on_hover_text(
+                            self.split
+                                .iter()
+                                .map(|path| path.display().to_string())
+                                .collect::<Vec<_>>()
+                                .join("\n"),
+                        );
+                    }
+                    if !self.mirror_plan.unmatched.is_empty() {
+                        ui.label(format!(
+                            "{} unmatched in mirror",
+                            self.mirror_plan.unmatched.len()
+                        ))
+                        .on_hover_text(
+                            self.mirror_plan
+                                .unmatched
+                                .iter()
+                                .map(|path| path.display().to_string())
+                                .collect::<Vec<_>>()
+                                .join("\n"),
+                        );
+                    }
+                    if self.mirror.is_some() {
+                        ui.label(format!("{} mirror renames", self.mirror_plan.renames.len()));
+                    }
+                    if !self.lints.is_empty() {
+                        ui.label(format!("{} rule warnings", self.lints.len()))
+                            .on_hover_text(
+                                self.lints
+                                    .iter()
+                                    .map(|lint| lint.to_string())
+                                    .collect::<Vec<_>>()
+                                    .join("\n"),
+                            );
+                    }
+                    ui.label("Status:");
+                }
             });
         });
 
         CentralPanel::default().show(ctx, |ui| {
             ui.with_layout(Layout::top_down(Align::Center), |ui| {
                 ui.horizontal(|ui| {
+                    if ui
+                        .add_enabled(self.visited.can_go_back(), Button::new("⏴").small())
+                        .on_hover_text("Back")
+                        .clicked()
+                    {
+                        self.back();
+                    }
+                    if ui
+                        .add_enabled(self.visited.can_go_forward(), Button::new("⏵").small())
+                        .on_hover_text("Forward")
+                        .clicked()
+                    {
+                        self.forward();
+                    }
                     if ui.small_button("Select Folder").clicked() {
                         if let Some(dir) = rfd::FileDialog::new()
                             .set_directory(&self.cwd_path)
@@ -219,37 +2724,318 @@ impl App for Renamer {
                         self.change_dir()
                     };
                 });
+                ui.horizontal(|ui| {
+                    for (idx, (name, dir)) in breadcrumbs(&self.cwd_path).into_iter().enumerate() {
+                        if idx > 0 {
+                            ui.label("›");
+                        }
+                        if ui.small_button(name).clicked() {
+                            self.open(dir);
+                        }
+                    }
+                });
                 ui.horizontal(|ui| {
                     // ui.with_layout(Layout::top_down_justified(Align::Center),
+                    let settings = self.settings.clone();
+                    let shows = |step| settings.shows(step);
                     ui.vertical(|ui| {
-                        frame().show(ui, |ui| ui.add(&mut self.reg_exp));
-                        frame().show(ui, |ui| ui.add(&mut self.name));
-                        frame().show(ui, |ui| ui.add(&mut self.folder));
-                        frame().show(ui, |ui| ui.add(&mut self.replace));
+                        if shows(Step::Regex) {
+                            frame().show(ui, |ui| ui.add(&mut self.reg_exp));
+                        }
+                        if shows(Step::Name) {
+                            frame().show(ui, |ui| ui.add(&mut self.name));
+                        }
+                        if shows(Step::Folder) {
+                            frame().show(ui, |ui| ui.add(&mut self.folder));
+                        }
+                        if shows(Step::Replace) {
+                            frame().show(ui, |ui| ui.add(&mut self.replace));
+                        }
+                        ui.horizontal(|ui| {
+                            if shows(Step::Case) {
+                                frame().show(ui, |ui| ui.add(&mut self.case));
+                            }
+                            if shows(Step::Extension) {
+                                frame().show(ui, |ui| ui.add(&mut self.extension));
+                            }
+                        });
                         ui.horizontal(|ui| {
-                            frame().show(ui, |ui| ui.add(&mut self.case));
-                            frame().show(ui, |ui| ui.add(&mut self.extension));
+                            if shows(Step::Remove) {
+                                frame().show(ui, |ui| ui.add(&mut self.remove));
+                            }
+                            if shows(Step::Number) {
+                                frame().show(ui, |ui| ui.add(&mut self.number));
+                            }
                         });
                         ui.horizontal(|ui| {
-                            frame().show(ui, |ui| ui.add(&mut self.remove));
-                            frame().show(ui, |ui| ui.add(&mut self.number));
+                            if shows(Step::Date) {
+                                frame().show(ui, |ui| ui.add(&mut self.date));
+                            }
+                            if shows(Step::Add) {
+                                frame().show(ui, |ui| ui.add(&mut self.add));
+                            }
                         });
                         ui.horizontal(|ui| {
-                            frame().show(ui, |ui| ui.add(&mut self.date));
-                            frame().show(ui, |ui| ui.add(&mut self.add));
+                            if shows(Step::Normalize) {
+                                frame().show(ui, |ui| ui.add(&mut self.normalize));
+                            }
+                            if shows(Step::Sanitize) {
+                                frame().show(ui, |ui| ui.add(&mut self.sanitize));
+                            }
+                        });
+                        ui.horizontal(|ui| {
+                            if shows(Step::Blacklist) {
+                                frame().show(ui, |ui| ui.add(&mut self.blacklist));
+                            }
+                            if shows(Step::Release) {
+                                frame().show(ui, |ui| ui.add(&mut self.release));
+                            }
+                        });
+                        ui.horizontal(|ui| {
+                            if shows(Step::Swap) {
+                                frame().show(ui, |ui| ui.add(&mut self.swap));
+                            }
+                            if shows(Step::Lookup) {
+                                frame().show(ui, |ui| ui.add(&mut self.lookup));
+                            }
+                        });
+                        ui.horizontal(|ui| {
+                            if shows(Step::Pair) {
+                                frame().show(ui, |ui| ui.add(&mut self.pair));
+                            }
+                            if shows(Step::Destination) {
+                                frame().show(ui, |ui| ui.add(&mut self.destination));
+                            }
                         });
                     });
                     ui.add_space(FRAME_MARGIN);
                     frame().show(ui, |ui| {
-                        ScrollArea::vertical()
-                            .max_height(FILES_HEIGHT)
-                            .show(ui, |ui| {
-                                ui.add(FileView::new(
-                                    &mut self.files,
-                                    &mut self.columns,
-                                    FILES_WIDTH,
-                                ))
+                        ui.vertical(|ui| {
+                            ui.horizontal(|ui| {
+                                ui.label("Group By");
+                                ComboBox::from_id_source("Group By")
+                                    .selected_text(format!("{:?}", self.grouping))
+                                    .show_ui(ui, |ui| {
+                                        for opt in Grouping::iter() {
+                                            if ui
+                                                .selectable_value(
+                                                    &mut self.grouping,
+                                                    opt,
+                                                    format!("{:?}", opt),
+                                                )
+                                                .changed()
+                                            {
+                                                self.collapsed.clear();
+                                            };
+                                        }
+                                    });
+                                ui.label("Sort By");
+                                let sort = ui
+                                    .add(
+                                        TextEdit::singleline(&mut self.sort)
+                                            .hint_text("extension, number")
+                                            .desired_width(SORT_WIDTH),
+                                    )
+                                    .on_hover_text(
+                                        "The columns to list, and number, the files by: name, stem, \
+                                        extension, size, modified, created, captured or number, \
+                                        each optionally followed by desc",
+                                    );
+                                if sort.changed() {
+                                    let expression = self.sort.clone();
+                                    self.sort_error =
+                                        self.set_sort(&expression).err().map(|err| err.to_string());
+                                }
+                                if let Some(err) = &self.sort_error {
+                                    ui.colored_label(Color32::RED, "⛔").on_hover_text(err);
+                                }
+                                let title = if self.filter == Filter::default() {
+                                    "Filter"
+                                } else {
+                                    "Filter ✔"
+                                };
+                                ui.menu_button(title, |ui| {
+                                    let edit = &mut self.filter_edit;
+                                    let mut changed = false;
+                                    ui.horizontal(|ui| {
+                                        ui.label("Mask");
+                                        changed |= ui
+                                            .add(
+                                                TextEdit::singleline(&mut edit.mask)
+                                                    .hint_text("*.jpg;*.png"),
+                                            )
+                                            .changed();
+                                    });
+                                    ui.horizontal(|ui| {
+                                        ui.label("Regex");
+                                        changed |=
+                                            ui.text_edit_singleline(&mut edit.regex).changed();
+                                    });
+                                    ui.horizontal(|ui| {
+                                        for kind in EntryKind::iter() {
+                                            changed |= ui
+                                                .radio_value(
+                                                    &mut edit.kind,
+                                                    kind,
+                                                    format!("{:?}", kind),
+                                                )
+                                                .changed();
+                                        }
+                                    });
+                                    changed |= ui
+                                        .checkbox(&mut edit.skip_hidden, "Skip hidden")
+                                        .changed();
+                                    ui.horizontal(|ui| {
+                                        ui.label("Groups");
+                                        for group in &self.settings.groups {
+                                            let add = ui
+                                                .small_button(&group.name)
+                                                .on_hover_text(&group.extensions);
+                                            if add.clicked() {
+                                                if !edit.mask.trim().is_empty() {
+                                                    edit.mask.push(';');
+                                                }
+                                                edit.mask.push_str(&format!("@{}", group.name));
+                                                changed = true;
+                                            }
+                                        }
+                                    });
+                                    ui.collapsing("Edit Groups", |ui| {
+                                        changed |= groups_editor(ui, &mut self.settings.groups);
+                                    });
+                                    if changed {
+                                        let filter = edit.clone();
+                                        self.save_settings();
+                                        self.filter_error =
+                                            self.set_filter(filter).err().map(|err| err.to_string());
+                                    }
+                                })
+                                .response
+                                .on_hover_text("List only the files matching a mask or expression");
+                                if let Some(err) = &self.filter_error {
+                                    ui.colored_label(Color32::RED, "⛔").on_hover_text(err);
+                                }
+                                ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+                                    let idle = self.renaming.is_none();
+                                    if ui
+                                        .add_enabled(idle, egui::Button::new("Rename"))
+                                        .on_hover_text("Rename the selected files")
+                                        .clicked()
+                                    {
+                                        self.start_rename();
+                                    }
+                                    if ui
+                                        .button("Queue")
+                                        .on_hover_text(
+                                            "Rename the selected files later, with batches \
+                                             from other folders",
+                                        )
+                                        .clicked()
+                                    {
+                                        self.enqueue();
+                                    }
+                                    if ui
+                                        .add_enabled(
+                                            idle && self.can_redo(),
+                                            egui::Button::new("Redo"),
+                                        )
+                                        .on_hover_text("Rename the files of the last undo again")
+                                        .clicked()
+                                    {
+                                        self.redo();
+                                    }
+                                    if ui
+                                        .add_enabled(
+                                            idle && self.can_undo(),
+                                            egui::Button::new("Undo"),
+                                        )
+                                        .on_hover_text(
+                                            "Give the files of the last rename their names back",
+                                        )
+                                        .clicked()
+                                    {
+                                        self.undo();
+                                    }
+                                    ui.menu_button("Summary", |ui| {
+                                        let summary = self.summary.to_string();
+                                        ui.add(
+                                            TextEdit::multiline(&mut summary.as_str())
+                                                .desired_rows(2),
+                                        );
+                                        if ui.button("Copy").clicked() {
+                                            ui.output_mut(|output| output.copied_text = summary);
+                                            ui.close_menu();
+                                        }
+                                    })
+                                    .response
+                                    .on_hover_text(
+                                        "What renaming will do, in a sentence a screen reader can read",
+                                    );
+                                    if self.repeated() {
+                                        ui.checkbox(&mut self.skip_repeated, "Skip its files")
+                                            .on_hover_text(
+                                                "Leave out the files the last rename gave their names",
+                                            );
+                                        ui.colored_label(
+                                            Color32::YELLOW,
+                                            "⚠ Same rules as the last rename here",
+                                        )
+                                        .on_hover_text(
+                                            "Renaming again may add dates or numbers a second time",
+                                        );
+                                    }
+                                    if self.files.iter().any(|listing| listing.missing)
+                                        && ui
+                                            .button("Remove Missing")
+                                            .on_hover_text(
+                                                "Remove the files that no longer exist from the list",
+                                            )
+                                            .clicked()
+                                    {
+                                        self.remove_missing();
+                                    }
+                                    if self.settings.staged {
+                                        if ui
+                                            .button("Apply")
+                                            .on_hover_text("Update the new names")
+                                            .clicked()
+                                        {
+                                            self.preview_for(PREVIEW_BUDGET);
+                                        }
+                                        if self.applied.as_ref() != Some(&self.fingerprint()) {
+                                            ui.colored_label(
+                                                Color32::YELLOW,
+                                                "⚠ Preview out of date",
+                                            );
+                                        }
+                                    }
+                                });
                             });
+                            let mut opened = None;
+                            ScrollArea::vertical()
+                                .max_height(FILES_HEIGHT)
+                                .show(ui, |ui| {
+                                    ui.add(
+                                        FileView::new(
+                                            &mut self.files,
+                                            &mut self.selection,
+                                            &mut self.columns,
+                                            self.grouping,
+                                            &mut self.collapsed,
+                                            &mut self.visible,
+                                            FILES_WIDTH,
+                                        )
+                                        .with_number_column(self.settings.number_column)
+                                        .with_column_width(self.settings.column_width as f32)
+                                        .with_navigation(self.cwd_path.parent(), &mut opened)
+                                        .with_keyboard(&mut self.cursor),
+                                    )
+                                });
+                            if let Some(dir) = opened {
+                                self.open(dir);
+                            }
+                            self.store_overrides();
+                        });
                     });
                 });
             })
@@ -0,0 +1,76 @@
+use std::{
+    sync::mpsc::{channel, Receiver},
+    thread,
+};
+
+use egui::{Button, Context, Hyperlink, Window};
+
+use crate::update::{build_info, check, Release, UpdateError, RELEASES_URL};
+
+/// Shows the version and build, and checks for a newer release when asked.
+#[derive(Default)]
+pub struct AboutWindow {
+    pub open: bool,
+    checking: Option<Receiver<Result<Option<Release>, UpdateError>>>,
+    status: Option<Result<Option<Release>, UpdateError>>,
+}
+
+impl AboutWindow {
+    /// Whether an update check is still running.
+    pub fn pending(&self) -> bool {
+        self.checking.is_some()
+    }
+
+    fn start_check(&mut self) {
+        let (sender, receiver) = channel();
+        thread::spawn(move || sender.send(check()));
+        self.checking = Some(receiver);
+        self.status = None;
+    }
+
+    pub fn show(&mut self, ctx: &Context) {
+        if let Some(receiver) = &self.checking {
+            if let Ok(status) = receiver.try_recv() {
+                self.status = Some(status);
+                self.checking = None;
+            }
+        }
+        let mut open = self.open;
+        Window::new("About")
+            .open(&mut open)
+            .resizable(false)
+            .show(ctx, |ui| {
+                ui.heading("Mass Renamer");
+                ui.label(format!("Version {}", build_info()));
+                ui.add(Hyperlink::from_label_and_url("Release notes", RELEASES_URL));
+                ui.separator();
+                ui.horizontal(|ui| {
+                    if ui
+                        .add_enabled(!self.pending(), Button::new("Check for Updates"))
+                        .clicked()
+                    {
+                        self.start_check();
+                    }
+                    match &self.status {
+                        _ if self.pending() => {
+                            ui.spinner();
+                        }
+                        Some(Ok(Some(release))) => {
+                            ui.add(Hyperlink::from_label_and_url(
+                                format!("{} is available", release.version),
+                                &release.url,
+                            ));
+                        }
+                        Some(Ok(None)) => {
+                            ui.label("Up to date");
+                        }
+                        Some(Err(err)) => {
+                            ui.label(err.to_string());
+                        }
+                        None => (),
+                    }
+                });
+            });
+        self.open = open;
+    }
+}
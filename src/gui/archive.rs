@@ -0,0 +1,110 @@
+use std::path::{Path, PathBuf};
+
+use egui::{Context, Grid, ScrollArea, Window};
+
+use crate::{archive, file::Options};
+
+const RESULTS_HEIGHT: f32 = 300.0;
+
+/// Renames the entries inside a zip archive with the current rules, without extracting it.
+#[derive(Default)]
+pub struct ArchiveWindow {
+    pub open: bool,
+    path: PathBuf,
+    copy: bool,
+    entries: Vec<String>,
+    results: Vec<(String, String)>,
+    message: Option<String>,
+}
+
+/// Whether the file looks like a zip archive.
+pub fn is_zip(path: &Path) -> bool {
+    path.is_file()
+        && path
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("zip"))
+}
+
+impl ArchiveWindow {
+    /// Open the window on the archive, listing its entries.
+    pub fn show_archive(&mut self, path: &Path) {
+        self.open = true;
+        self.path = path.to_path_buf();
+        self.read();
+    }
+
+    fn read(&mut self) {
+        self.results.clear();
+        match archive::entries(&self.path) {
+            Ok(entries) => {
+                self.entries = entries;
+                self.message = None;
+            }
+            Err(err) => {
+                self.entries.clear();
+                self.message = Some(err.to_string());
+            }
+        }
+    }
+
+    /// Apply the options to the entry names.
+    pub fn refresh(&mut self, options: &[Options]) {
+        self.results = archive::preview(&self.entries, options);
+    }
+
+    /// Where a copy of the archive is written, beside it.
+    fn copy_path(&self) -> PathBuf {
+        let stem = self.path.file_stem().unwrap_or_default().to_string_lossy();
+        self.path.with_file_name(format!("{stem} renamed.zip"))
+    }
+
+    pub fn show(&mut self, ctx: &Context) {
+        let mut open = self.open;
+        let title = format!(
+            "Rename in {}",
+            self.path.file_name().unwrap_or_default().to_string_lossy()
+        );
+        Window::new(title)
+            .id("Rename in Zip".into())
+            .open(&mut open)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.checkbox(&mut self.copy, "Write to a copy")
+                        .on_hover_text(format!(
+                            "Leave the archive as it is and write {}",
+                            self.copy_path().display()
+                        ));
+                    if ui.button("Rename Entries").clicked() {
+                        let copy = self.copy.then(|| self.copy_path());
+                        match archive::rewrite(&self.path, &self.results, copy.as_deref()) {
+                            Ok(()) if self.copy => {
+                                self.message = Some(format!("Wrote {}", copy.unwrap().display()))
+                            }
+                            Ok(()) => {
+                                self.read();
+                                self.message = Some(String::from("Renamed"));
+                            }
+                            Err(err) => self.message = Some(err.to_string()),
+                        }
+                    };
+                });
+                if let Some(message) = &self.message {
+                    ui.label(message);
+                }
+                ui.separator();
+                ScrollArea::vertical()
+                    .max_height(RESULTS_HEIGHT)
+                    .show(ui, |ui| {
+                        Grid::new("Archive Entries").striped(true).show(ui, |ui| {
+                            for (original, renamed) in &self.results {
+                                ui.label(original);
+                                ui.label("→");
+                                ui.label(renamed);
+                                ui.end_row();
+                            }
+                        });
+                    });
+            });
+        self.open = open;
+    }
+}
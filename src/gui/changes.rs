@@ -0,0 +1,80 @@
+use std::path::{Path, PathBuf};
+
+use egui::{CollapsingHeader, Context, ScrollArea, Window};
+
+use crate::snapshot::{Change, Comparison};
+
+const CHANGES_HEIGHT: f32 = 300.0;
+
+/// Lists what the last batch changed on disk next to what it was expected to, so
+/// anything else, e.g. a file another program wrote while renaming, stands out.
+#[derive(Default)]
+pub struct ChangesWindow {
+    pub open: bool,
+    folder: PathBuf,
+    comparison: Option<Comparison>,
+}
+
+impl ChangesWindow {
+    /// Show the comparison for a batch renamed in `folder`, opening the window when the
+    /// folder didn't change as expected.
+    pub fn set(&mut self, folder: &Path, comparison: Comparison) {
+        self.open |= !comparison.as_expected();
+        self.folder = folder.to_path_buf();
+        self.comparison = Some(comparison);
+    }
+
+    pub fn show(&mut self, ctx: &Context) {
+        let mut open = self.open;
+        Window::new("Changes on Disk")
+            .open(&mut open)
+            .show(ctx, |ui| {
+                let Some(comparison) = &self.comparison else {
+                    ui.label("Nothing renamed yet");
+                    return;
+                };
+                ui.label(self.folder.display().to_string());
+                if comparison.as_expected() {
+                    ui.label(format!(
+                        "✔ {} files renamed, nothing else changed",
+                        comparison.renamed.len()
+                    ));
+                }
+                ui.separator();
+                ScrollArea::vertical()
+                    .max_height(CHANGES_HEIGHT)
+                    .show(ui, |ui| {
+                        let unexpected = comparison.unexpected.iter().map(Change::to_string);
+                        let missed = comparison
+                            .missed
+                            .iter()
+                            .map(|(from, to)| format!("{} → {}", name(from), name(to)));
+                        let renamed = comparison
+                            .renamed
+                            .iter()
+                            .map(|(from, to)| format!("{} → {}", name(from), name(to)));
+                        for (label, lines, open) in [
+                            ("⛔ Other changes", unexpected.collect::<Vec<_>>(), true),
+                            ("⛔ Not renamed", missed.collect(), true),
+                            ("Renamed as expected", renamed.collect(), false),
+                        ] {
+                            CollapsingHeader::new(format!("{label}: {}", lines.len()))
+                                .id_source(label)
+                                .default_open(open && !lines.is_empty())
+                                .show(ui, |ui| {
+                                    for line in lines {
+                                        ui.label(line);
+                                    }
+                                });
+                        }
+                    });
+            });
+        self.open = open;
+    }
+}
+
+fn name(path: &Path) -> String {
+    path.file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
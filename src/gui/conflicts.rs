@@ -0,0 +1,89 @@
+use std::{collections::HashSet, path::PathBuf};
+
+use egui::{Context, ScrollArea, Window};
+
+use crate::batch::Collision;
+
+const CONFLICTS_HEIGHT: f32 = 300.0;
+
+/// Asks what to do about each file whose new name is taken before a batch is renamed,
+/// see [crate::batch::CollisionPolicy::Prompt].
+#[derive(Default)]
+pub struct ConflictsWindow {
+    pub open: bool,
+    collisions: Vec<Collision>,
+    /// The original paths of the files to overwrite with, the rest are skipped.
+    overwrite: HashSet<PathBuf>,
+}
+
+impl ConflictsWindow {
+    /// Ask about the `collisions` of a batch about to be renamed.
+    pub fn ask(&mut self, collisions: Vec<Collision>) {
+        self.open = true;
+        self.collisions = collisions;
+        self.overwrite.clear();
+    }
+
+    /// Show the conflicts. Returns the files chosen to overwrite with once `Rename` is
+    /// clicked, the batch should then be renamed again with them.
+    pub fn show(&mut self, ctx: &Context) -> Option<HashSet<PathBuf>> {
+        let mut open = self.open;
+        let mut answer = None;
+        Window::new("Name Conflicts")
+            .open(&mut open)
+            .show(ctx, |ui| {
+                ui.label(format!(
+                    "{} files can't have their new names",
+                    self.collisions.len()
+                ));
+                ui.horizontal(|ui| {
+                    if ui.button("Skip All").clicked() {
+                        self.overwrite.clear();
+                    }
+                    if ui.button("Overwrite All").clicked() {
+                        self.overwrite = self
+                            .collisions
+                            .iter()
+                            .filter(|collision| collision.other.is_none())
+                            .map(|collision| collision.original.clone())
+                            .collect();
+                    }
+                });
+                ui.separator();
+                ScrollArea::vertical()
+                    .max_height(CONFLICTS_HEIGHT)
+                    .show(ui, |ui| {
+                        for collision in &self.collisions {
+                            if collision.other.is_some() {
+                                ui.label(collision.to_string()).on_hover_text(
+                                    "The first file given the name keeps it, the rest are skipped",
+                                );
+                                continue;
+                            }
+                            let mut overwrite = self.overwrite.contains(&collision.original);
+                            if ui
+                                .checkbox(&mut overwrite, collision.to_string())
+                                .on_hover_text("Overwrite the file already there, or skip")
+                                .changed()
+                            {
+                                match overwrite {
+                                    true => self.overwrite.insert(collision.original.clone()),
+                                    false => self.overwrite.remove(&collision.original),
+                                };
+                            }
+                        }
+                    });
+                ui.separator();
+                ui.horizontal(|ui| {
+                    if ui.button("Rename").clicked() {
+                        answer = Some(self.overwrite.clone());
+                    }
+                    if ui.button("Cancel").clicked() {
+                        self.collisions.clear();
+                    }
+                });
+            });
+        self.open = open && answer.is_none() && !self.collisions.is_empty();
+        answer
+    }
+}
@@ -0,0 +1,75 @@
+use std::path::{Path, PathBuf};
+
+use egui::{CollapsingHeader, Context, ScrollArea, TextEdit, Window};
+
+use crate::analysis::{conform, Conformance};
+
+const RESULTS_HEIGHT: f32 = 300.0;
+
+/// Reports which files fit a naming scheme now, which will once renamed and which won't.
+#[derive(Default)]
+pub struct ConformWindow {
+    pub open: bool,
+    scheme: String,
+    report: Option<Result<Conformance, String>>,
+}
+
+impl ConformWindow {
+    /// Check the `(original, new)` paths against the scheme.
+    pub fn refresh(&mut self, batch: &[(PathBuf, PathBuf)]) {
+        self.report = (!self.scheme.is_empty())
+            .then(|| conform(batch, &self.scheme).map_err(|err| err.to_string()));
+    }
+
+    pub fn show(&mut self, ctx: &Context) {
+        let mut open = self.open;
+        Window::new("Conform Check")
+            .open(&mut open)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label("Scheme");
+                    ui.add(
+                        TextEdit::singleline(&mut self.scheme).hint_text(r"\d{4}-\d{2}-\d{2} .+"),
+                    )
+                    .on_hover_text(
+                        "A regular expression the whole file name, extension included, must match",
+                    );
+                });
+                ui.separator();
+                match &self.report {
+                    None => {
+                        ui.label("Enter the naming scheme to check the files against");
+                    }
+                    Some(Err(err)) => {
+                        ui.label(err);
+                    }
+                    Some(Ok(report)) => {
+                        ScrollArea::vertical()
+                            .max_height(RESULTS_HEIGHT)
+                            .show(ui, |ui| {
+                                for (label, files) in [
+                                    ("Already conform", &report.already),
+                                    ("Conform after renaming", &report.after),
+                                    ("Still don't conform", &report.never),
+                                ] {
+                                    CollapsingHeader::new(format!("{label}: {}", files.len()))
+                                        .id_source(label)
+                                        .show(ui, |ui| {
+                                            for file in files {
+                                                ui.label(file_name(file));
+                                            }
+                                        });
+                                }
+                            });
+                    }
+                }
+            });
+        self.open = open;
+    }
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
@@ -1,13 +1,18 @@
 use std::{
     borrow::Cow,
-    cmp::Ordering,
+    cmp::{Ordering, Reverse},
+    collections::{BTreeMap, HashSet},
     path::{Path, PathBuf},
 };
 
 use chrono::{DateTime, Local};
-use egui::{Grid, Response, Ui, Widget};
+use egui::{Button, Grid, Key, Label, Response, RichText, Sense, Ui, Widget};
 
-use crate::File;
+use crate::{
+    directory::Override,
+    sort::{SortExpression, SortFields},
+    File, Selected,
+};
 
 pub struct FileListing {
     pub name: PathBuf,
@@ -16,7 +21,28 @@ pub struct FileListing {
     pub size: Option<u64>,
     pub modified: Option<DateTime<Local>>,
     pub created: Option<DateTime<Local>>,
+    /// When the photo was taken or the song recorded, once the metadata has been read.
+    pub captured: Option<DateTime<Local>>,
     pub selected: bool,
+    pub metadata_error: Option<String>,
+    /// Why the new name can't be used, e.g. it is over the path length limit.
+    pub path_error: Option<String>,
+    /// Why the system won't take the new name, e.g. it is kept for a device.
+    pub name_error: Option<String>,
+    /// Another file is given the same name, or already has it.
+    pub conflict: Option<String>,
+    /// The rules of the naming policy the new name breaks, see [crate::policy].
+    pub policy_error: Option<String>,
+    /// The file was gone when it was to be renamed.
+    pub missing: bool,
+    /// Selected, but not previewed yet.
+    pub pending: bool,
+    /// The change made to the file by hand, if any.
+    pub overridden: Option<Override>,
+    /// Set when the override is changed from the row, to be stored by the app.
+    pub override_changed: bool,
+    /// The name being typed in the row's menu.
+    pub name_edit: String,
 }
 
 #[derive(Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
@@ -25,10 +51,88 @@ pub enum Columns {
     Name,
     NewName,
     Extension,
+    /// The extension the file is given.
+    NewExtension,
     Size,
     Created,
     Modified,
+    Captured,
+    /// The custom sort expression, see [crate::sort].
+    Custom,
+}
+/// Collapsible groups of rows in the file list.
+/// - `None` - A flat list (default).
+/// - `Folder` - Group by parent folder.
+/// - `Extension` - Group by file type.
+/// - `Status` - Group by what will happen to the file when renamed.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Grouping {
+    #[default]
+    None,
+    Folder,
+    Extension,
+    Status,
 }
+
+impl Grouping {
+    pub fn iter() -> impl Iterator<Item = Grouping> {
+        [Self::None, Self::Folder, Self::Extension, Self::Status]
+            .iter()
+            .copied()
+    }
+
+    /// The name of the group a file belongs to.
+    fn key(&self, item: &FileListing) -> String {
+        match self {
+            Grouping::None => String::new(),
+            Grouping::Folder => item
+                .name
+                .parent()
+                .map(|parent| parent.display().to_string())
+                .unwrap_or_default(),
+            Grouping::Extension => match (&item.extension, item.name.is_dir()) {
+                (_, true) => String::from("(folder)"),
+                (Some(ext), false) => ext.to_lowercase(),
+                (None, false) => String::from("(none)"),
+            },
+            Grouping::Status => String::from(if item.missing {
+                "Missing"
+            } else if item.conflict.is_some() {
+                "Conflict"
+            } else if item.path_error.is_some() {
+                "Path too long"
+            } else if item.name_error.is_some() {
+                "Invalid name"
+            } else if item.policy_error.is_some() {
+                "Against policy"
+            } else if item.metadata_error.is_some() {
+                "Metadata unavailable"
+            } else if !item.renamed.errors().is_empty() {
+                "Steps failed"
+            } else if item.overridden == Some(Override::Exclude) {
+                "Excluded"
+            } else if !item.selected {
+                "Not selected"
+            } else if file_no_parents(&item.name) == item.renamed.to_string() {
+                "Unchanged"
+            } else {
+                "Renamed"
+            }),
+        }
+    }
+}
+
+/// Where the keyboard is in the file list, see [FileView::with_keyboard].
+#[derive(Debug, Default)]
+pub struct Cursor {
+    /// The row the arrow keys are on, which Space ticks and Enter opens.
+    row: Option<PathBuf>,
+    /// The row last ticked, a Shift+click ticks the rows from it too.
+    anchor: Option<PathBuf>,
+    /// The row moved to, to scroll it into view.
+    moved: bool,
+}
+
 #[derive(Debug, Default)]
 pub enum Order {
     #[default]
@@ -38,8 +142,22 @@ pub enum Order {
 
 pub struct FileView<'a> {
     files: &'a mut Vec<FileListing>,
+    selection: &'a mut Selected,
     columns: &'a mut (Columns, Order, Columns),
+    grouping: Grouping,
+    /// Show the number each file is given, see [File::counter].
+    number_column: bool,
+    /// The folder above, listed first as `..`.
+    parent: Option<&'a Path>,
+    /// Set to the folder double-clicked, to open it.
+    open: Option<&'a mut Option<PathBuf>>,
+    cursor: Option<&'a mut Cursor>,
+    collapsed: &'a mut HashSet<String>,
+    /// Filled with the files whose rows are on screen.
+    visible: &'a mut HashSet<PathBuf>,
     width: f32,
+    /// The narrowest a column is.
+    column_width: Option<f32>,
 }
 
 /// Return the datetime as a localized date and time.
@@ -71,160 +189,542 @@ fn cmp(rhs: &Path, lhs: &Path) -> Ordering {
     }
 }
 
+/// Sort by capture time, files without one first when sorting forward.
+/// Files captured at the same time keep their order.
+pub fn sort_by_captured(files: &mut [FileListing], order: &Order) {
+    match order {
+        Order::Forward => files.sort_by_key(|file| file.captured),
+        Order::Reverse => files.sort_by_key(|file| Reverse(file.captured)),
+    }
+}
+
+pub fn sort_by_expression(files: &mut [FileListing], expression: &SortExpression) {
+    files.sort_by(|lhs, rhs| expression.compare(&lhs.sort_fields(), &rhs.sort_fields()));
+}
+
+impl FileListing {
+    fn sort_fields(&self) -> SortFields<'_> {
+        SortFields {
+            path: &self.name,
+            size: self.size,
+            modified: self.modified,
+            created: self.created,
+            captured: self.captured,
+        }
+    }
+}
+
 impl<'a> FileView<'a> {
     pub fn new(
         files: &'a mut Vec<FileListing>,
+        selection: &'a mut Selected,
         columns: &'a mut (Columns, Order, Columns),
+        grouping: Grouping,
+        collapsed: &'a mut HashSet<String>,
+        visible: &'a mut HashSet<PathBuf>,
         width: f32,
     ) -> Self {
+        visible.clear();
         Self {
             files,
+            selection,
             columns,
+            grouping,
+            number_column: false,
+            parent: None,
+            open: None,
+            cursor: None,
+            collapsed,
+            visible,
             width,
+            column_width: None,
+        }
+    }
+
+    /// Show the number each file is given in a column after the new name.
+    pub fn with_number_column(mut self, show: bool) -> Self {
+        self.number_column = show;
+        self
+    }
+
+    /// Keep every column at least `width` wide.
+    pub fn with_column_width(mut self, width: f32) -> Self {
+        self.column_width = Some(width);
+        self
+    }
+
+    /// Open folders on a double-click by setting `open` to them, with `parent` listed
+    /// first as `..` to go up a folder.
+    pub fn with_navigation(
+        mut self,
+        parent: Option<&'a Path>,
+        open: &'a mut Option<PathBuf>,
+    ) -> Self {
+        self.parent = parent;
+        self.open = Some(open);
+        self
+    }
+
+    /// Move through the rows with the arrow keys, tick the row on with Space, open it with
+    /// Enter if it is a folder, and tick every row with Ctrl+A. Shift+clicking a checkbox
+    /// ticks or unticks the rows from the one last ticked as well. Keys typed into a box
+    /// are left alone.
+    pub fn with_keyboard(mut self, cursor: &'a mut Cursor) -> Self {
+        self.cursor = Some(cursor);
+        self
+    }
+
+    /// The rows by group, in the order they are shown. Rows aren't grouped by default.
+    fn groups(&self) -> Vec<(String, Vec<usize>)> {
+        let mut groups: BTreeMap<String, Vec<usize>> = BTreeMap::new();
+        // Groups keep the current sort order within them.
+        for (idx, item) in self.files.iter().enumerate() {
+            groups.entry(self.grouping.key(item)).or_default().push(idx);
+        }
+        groups.into_iter().collect()
+    }
+
+    /// Act on the keys pressed, given the rows shown in order.
+    fn keyboard(&mut self, ui: &Ui, shown: &[usize]) {
+        let Some(cursor) = self.cursor.as_deref_mut() else {
+            return;
+        };
+        if ui.memory(|memory| memory.focus().is_some()) {
+            return;
+        }
+        let (down, up, tick, enter, all) = ui.input(|input| {
+            (
+                input.num_presses(Key::ArrowDown),
+                input.num_presses(Key::ArrowUp),
+                input.key_pressed(Key::Space),
+                input.key_pressed(Key::Enter),
+                input.modifiers.command && input.key_pressed(Key::A),
+            )
+        });
+        if all {
+            for item in self.files.iter_mut() {
+                item.selected = true;
+                self.selection.add(item.name.clone());
+            }
+        }
+        let files = &mut *self.files;
+        let mut at = cursor
+            .row
+            .as_ref()
+            .and_then(|row| shown.iter().position(|idx| files[*idx].name == *row));
+        if down + up > 0 && !shown.is_empty() {
+            let moved = match at {
+                Some(at) => (at + down).saturating_sub(up).min(shown.len() - 1),
+                None => 0,
+            };
+            at = Some(moved);
+            cursor.row = Some(files[shown[moved]].name.clone());
+            cursor.moved = true;
+        }
+        let item = match at {
+            Some(at) => &mut files[shown[at]],
+            None => return,
+        };
+        if tick {
+            item.selected = !item.selected;
+            self.selection.set(&item.name, item.selected);
+            cursor.anchor = Some(item.name.clone());
+        }
+        if let Some(open) = self.open.as_deref_mut().filter(|_| enter) {
+            if item.name.is_dir() {
+                *open = Some(item.name.clone());
+            }
+        }
+    }
+
+    /// The row at `clicked` in `shown` was ticked or unticked by hand. With Shift held,
+    /// the rows from the one last ticked are given the same state.
+    fn ticked(&mut self, ui: &Ui, shown: &[usize], clicked: usize) {
+        let name = self.files[clicked].name.clone();
+        let Some(cursor) = self.cursor.as_deref_mut() else {
+            return;
+        };
+        let anchor = cursor.anchor.replace(name.clone());
+        cursor.row = Some(name);
+        if !ui.input(|input| input.modifiers.shift) {
+            return;
+        }
+        let from = anchor.and_then(|anchor| {
+            let mut rows = shown.iter();
+            rows.position(|idx| self.files[*idx].name == anchor)
+        });
+        let to = shown.iter().position(|idx| *idx == clicked);
+        let (Some(from), Some(to)) = (from, to) else {
+            return;
+        };
+        let selected = self.files[clicked].selected;
+        for idx in &shown[from.min(to)..=from.max(to)] {
+            self.files[*idx].selected = selected;
+            self.selection.set(&self.files[*idx].name, selected);
+        }
+    }
+}
+
+/// Show a file's row, returning whether its checkbox was clicked. The row the keyboard
+/// is on, if any, is highlighted.
+fn show_row(
+    ui: &mut Ui,
+    item: &mut FileListing,
+    number_column: bool,
+    selection: &mut Selected,
+    visible: &mut HashSet<PathBuf>,
+    open: Option<&mut Option<PathBuf>>,
+    cursor: Option<&mut Cursor>,
+) -> bool {
+    let checkbox = ui.checkbox(&mut item.selected, "");
+    if checkbox.changed() {
+        selection.set(&item.name, item.selected);
+    };
+    if ui.is_rect_visible(checkbox.rect) {
+        visible.insert(item.name.clone());
+    }
+    let mut text = RichText::new(file_no_parents(&item.name));
+    let cursor = cursor.filter(|cursor| cursor.row.as_ref() == Some(&item.name));
+    if cursor.is_some() {
+        text = text.background_color(ui.visuals().selection.bg_fill);
+    }
+    let name = ui.add(Label::new(text).sense(Sense::click()));
+    if let Some(cursor) = cursor.filter(|cursor| cursor.moved) {
+        name.scroll_to_me(None);
+        cursor.moved = false;
+    }
+    if let Some(open) = open.filter(|_| item.name.is_dir()) {
+        if name.on_hover_text("Double-click to open").double_clicked() {
+            *open = Some(item.name.clone());
+        }
+    }
+    // Steps that couldn't be applied leave the name partly processed, so they are flagged.
+    let warnings = item
+        .metadata_error
+        .iter()
+        .map(|err| format!("Metadata unavailable: {err}"))
+        .chain(item.renamed.errors().iter().cloned())
+        .collect::<Vec<_>>();
+    let manual = match &item.overridden {
+        Some(Override::Name(_)) => "✏ ",
+        _ => "",
+    };
+    let error = item
+        .conflict
+        .as_ref()
+        .or(item.path_error.as_ref())
+        .or(item.name_error.as_ref())
+        .or(item.policy_error.as_ref());
+    match error {
+        _ if item.missing => ui
+            .label(format!("⛔ {}", item.renamed))
+            .on_hover_text("Missing, the file no longer exists"),
+        _ if item.overridden == Some(Override::Exclude) => ui
+            .weak(format!("⊘ {}", item.renamed))
+            .on_hover_text("Excluded, it won't be renamed"),
+        _ if item.pending => ui.weak("…").on_hover_text("Not previewed yet"),
+        Some(err) => ui
+            .label(format!("⛔ {manual}{}", item.renamed))
+            .on_hover_text(err),
+        None if !warnings.is_empty() => ui
+            .label(format!("⚠ {manual}{}", item.renamed))
+            .on_hover_text(warnings.join("\n")),
+        None => ui.label(format!("{manual}{}", item.renamed)),
+    }
+    .context_menu(|ui| override_menu(ui, item));
+    if number_column {
+        // Files left out of the batch have no number.
+        let counter = item.renamed.counter().filter(|_| !item.pending);
+        ui.label(counter.unwrap_or_default());
+    }
+    ui.label(if let Some(ext) = &item.extension {
+        ext.as_str()
+    } else {
+        ""
+    });
+    new_extension(ui, item);
+    ui.label(if let Some(size) = &item.size {
+        format!("{}", &size)
+    } else {
+        String::new()
+    });
+    if let Some(time) = &item.modified {
+        ui.label(datetime_to_string(time));
+    }
+    if let Some(time) = &item.created {
+        ui.label(datetime_to_string(time));
+    }
+    ui.label(
+        item.captured
+            .as_ref()
+            .map(datetime_to_string)
+            .unwrap_or_default(),
+    );
+    ui.end_row();
+    checkbox.changed()
+}
+
+/// Show the extension the file is given, standing out when it isn't the one it has.
+fn new_extension(ui: &mut Ui, item: &FileListing) {
+    match item.renamed.extension() {
+        _ if item.pending => ui.label(""),
+        new if new == item.extension.as_deref() => ui.label(new.unwrap_or_default()),
+        new => {
+            let text = RichText::new(new.unwrap_or("(none)")).strong();
+            let was = match &item.extension {
+                Some(ext) => format!("Was .{ext}"),
+                None => String::from("Had no extension"),
+            };
+            ui.label(text.color(ui.visuals().warn_fg_color))
+                .on_hover_text(was)
         }
+    };
+}
+
+/// Name a file by hand, leave it out, or go back to what the options do to it.
+fn override_menu(ui: &mut Ui, item: &mut FileListing) {
+    if item.name_edit.is_empty() {
+        item.name_edit = item.renamed.to_string();
+    }
+    ui.horizontal(|ui| {
+        ui.text_edit_singleline(&mut item.name_edit);
+        if ui.button("Set Name").clicked() && !item.name_edit.is_empty() {
+            item.overridden = Some(Override::Name(item.name_edit.clone()));
+            item.override_changed = true;
+            ui.close_menu();
+        }
+    });
+    if ui.button("Exclude").clicked() {
+        item.overridden = Some(Override::Exclude);
+        item.override_changed = true;
+        ui.close_menu();
+    }
+    if ui
+        .add_enabled(item.overridden.is_some(), Button::new("Clear Override"))
+        .clicked()
+    {
+        item.overridden = None;
+        item.name_edit.clear();
+        item.override_changed = true;
+        ui.close_menu();
     }
 }
 
 impl<'a> Widget for FileView<'a> {
-    fn ui(self, ui: &mut Ui) -> Response {
-        Grid::new("Files")
-            .striped(true)
-            .show(ui, |ui| {
-                ui.set_width(self.width);
-                ui.label("Sel");
-                if ui
-                    .selectable_value(&mut self.columns.0, Columns::Name, "Name")
-                    .clicked()
-                {
-                    match self.columns {
-                        (_, Order::Forward, Columns::Name) => {
-                            self.files
-                                .sort_unstable_by(|lhs, rhs| cmp(&rhs.name, &lhs.name));
-                            self.columns.1 = Order::Reverse;
-                        }
-                        _ => {
-                            self.files
-                                .sort_unstable_by(|lhs, rhs| cmp(&lhs.name, &rhs.name));
-                            self.columns.1 = Order::Forward;
-                        }
-                    };
-                    self.columns.2 = Columns::Name;
+    fn ui(mut self, ui: &mut Ui) -> Response {
+        let mut grid = Grid::new("Files").striped(true);
+        if let Some(width) = self.column_width {
+            grid = grid.min_col_width(width);
+        }
+        grid.show(ui, |ui| {
+            ui.set_width(self.width);
+            ui.label("Sel");
+            if ui
+                .selectable_value(&mut self.columns.0, Columns::Name, "Name")
+                .clicked()
+            {
+                match self.columns {
+                    (_, Order::Forward, Columns::Name) => {
+                        self.files
+                            .sort_unstable_by(|lhs, rhs| cmp(&rhs.name, &lhs.name));
+                        self.columns.1 = Order::Reverse;
+                    }
+                    _ => {
+                        self.files
+                            .sort_unstable_by(|lhs, rhs| cmp(&lhs.name, &rhs.name));
+                        self.columns.1 = Order::Forward;
+                    }
                 };
-                if ui
-                    .selectable_value(&mut self.columns.0, Columns::NewName, "New Name")
-                    .clicked()
-                {
-                    match self.columns {
-                        (_, Order::Forward, Columns::NewName) => {
-                            self.files
-                                .sort_unstable_by(|lhs, rhs| rhs.renamed.cmp(&lhs.renamed));
-                            self.columns.1 = Order::Reverse;
-                        }
-                        _ => {
-                            self.files
-                                .sort_unstable_by(|lhs, rhs| lhs.renamed.cmp(&rhs.renamed));
-                            self.columns.1 = Order::Forward;
-                        }
-                    };
-                    self.columns.2 = Columns::NewName;
+                self.columns.2 = Columns::Name;
+            };
+            if ui
+                .selectable_value(&mut self.columns.0, Columns::NewName, "New Name")
+                .clicked()
+            {
+                match self.columns {
+                    (_, Order::Forward, Columns::NewName) => {
+                        self.files
+                            .sort_unstable_by(|lhs, rhs| rhs.renamed.cmp(&lhs.renamed));
+                        self.columns.1 = Order::Reverse;
+                    }
+                    _ => {
+                        self.files
+                            .sort_unstable_by(|lhs, rhs| lhs.renamed.cmp(&rhs.renamed));
+                        self.columns.1 = Order::Forward;
+                    }
                 };
-                if ui
-                    .selectable_value(&mut self.columns.0, Columns::Extension, "Type")
-                    .clicked()
-                {
-                    match self.columns {
-                        (_, Order::Forward, Columns::Extension) => {
-                            self.files
-                                .sort_unstable_by(|lhs, rhs| rhs.extension.cmp(&lhs.extension));
-                            self.columns.1 = Order::Reverse;
-                        }
-                        _ => {
-                            self.files
-                                .sort_unstable_by(|lhs, rhs| lhs.extension.cmp(&rhs.extension));
-                            self.columns.1 = Order::Forward;
-                        }
-                    };
-                    self.columns.2 = Columns::Extension;
+                self.columns.2 = Columns::NewName;
+            };
+            if self.number_column {
+                ui.label("No.")
+                    .on_hover_text("The number each file is given, in the order they are listed");
+            }
+            if ui
+                .selectable_value(&mut self.columns.0, Columns::Extension, "Type")
+                .clicked()
+            {
+                match self.columns {
+                    (_, Order::Forward, Columns::Extension) => {
+                        self.files
+                            .sort_unstable_by(|lhs, rhs| rhs.extension.cmp(&lhs.extension));
+                        self.columns.1 = Order::Reverse;
+                    }
+                    _ => {
+                        self.files
+                            .sort_unstable_by(|lhs, rhs| lhs.extension.cmp(&rhs.extension));
+                        self.columns.1 = Order::Forward;
+                    }
                 };
-                if ui
-                    .selectable_value(&mut self.columns.0, Columns::Size, "Size")
-                    .clicked()
-                {
-                    match self.columns {
-                        (_, Order::Forward, Columns::Size) => {
-                            self.files
-                                .sort_unstable_by(|lhs, rhs| rhs.size.cmp(&lhs.size));
-                            self.columns.1 = Order::Reverse;
-                        }
-                        _ => {
-                            self.files
-                                .sort_unstable_by(|lhs, rhs| lhs.size.cmp(&rhs.size));
-                            self.columns.1 = Order::Forward;
-                        }
-                    };
-                    self.columns.2 = Columns::Size;
+                self.columns.2 = Columns::Extension;
+            };
+            if ui
+                .selectable_value(&mut self.columns.0, Columns::NewExtension, "New Ext")
+                .on_hover_text("The extension each file is given")
+                .clicked()
+            {
+                let new = |item: &FileListing| item.renamed.extension().map(str::to_owned);
+                match self.columns {
+                    (_, Order::Forward, Columns::NewExtension) => {
+                        self.files.sort_by_cached_key(|item| Reverse(new(item)));
+                        self.columns.1 = Order::Reverse;
+                    }
+                    _ => {
+                        self.files.sort_by_cached_key(new);
+                        self.columns.1 = Order::Forward;
+                    }
                 };
-                if ui
-                    .selectable_value(&mut self.columns.0, Columns::Modified, "Modified")
-                    .clicked()
-                {
-                    match self.columns {
-                        (_, Order::Forward, Columns::Modified) => {
-                            self.files
-                                .sort_unstable_by(|lhs, rhs| rhs.modified.cmp(&lhs.modified));
-                            self.columns.1 = Order::Reverse;
-                        }
-                        _ => {
-                            self.files
-                                .sort_unstable_by(|lhs, rhs| lhs.modified.cmp(&rhs.modified));
-                            self.columns.1 = Order::Forward;
-                        }
-                    };
-                    self.columns.2 = Columns::Modified;
+                self.columns.2 = Columns::NewExtension;
+            };
+            if ui
+                .selectable_value(&mut self.columns.0, Columns::Size, "Size")
+                .clicked()
+            {
+                match self.columns {
+                    (_, Order::Forward, Columns::Size) => {
+                        self.files
+                            .sort_unstable_by(|lhs, rhs| rhs.size.cmp(&lhs.size));
+                        self.columns.1 = Order::Reverse;
+                    }
+                    _ => {
+                        self.files
+                            .sort_unstable_by(|lhs, rhs| lhs.size.cmp(&rhs.size));
+                        self.columns.1 = Order::Forward;
+                    }
+                };
+                self.columns.2 = Columns::Size;
+            };
+            if ui
+                .selectable_value(&mut self.columns.0, Columns::Modified, "Modified")
+                .clicked()
+            {
+                match self.columns {
+                    (_, Order::Forward, Columns::Modified) => {
+                        self.files
+                            .sort_unstable_by(|lhs, rhs| rhs.modified.cmp(&lhs.modified));
+                        self.columns.1 = Order::Reverse;
+                    }
+                    _ => {
+                        self.files
+                            .sort_unstable_by(|lhs, rhs| lhs.modified.cmp(&rhs.modified));
+                        self.columns.1 = Order::Forward;
+                    }
+                };
+                self.columns.2 = Columns::Modified;
+            };
+            if ui
+                .selectable_value(&mut self.columns.0, Columns::Created, "Created")
+                .clicked()
+            {
+                match self.columns {
+                    (_, Order::Forward, Columns::Created) => {
+                        self.files
+                            .sort_unstable_by(|lhs, rhs| rhs.created.cmp(&lhs.created));
+                        self.columns.1 = Order::Reverse;
+                    }
+                    _ => {
+                        self.files
+                            .sort_unstable_by(|lhs, rhs| lhs.created.cmp(&rhs.created));
+                        self.columns.1 = Order::Forward;
+                    }
+                };
+                self.columns.2 = Columns::Created;
+            };
+            if ui
+                .selectable_value(&mut self.columns.0, Columns::Captured, "Captured")
+                .on_hover_text("When the photo was taken or the song recorded")
+                .clicked()
+            {
+                self.columns.1 = match self.columns {
+                    (_, Order::Forward, Columns::Captured) => Order::Reverse,
+                    _ => Order::Forward,
                 };
-                if ui
-                    .selectable_value(&mut self.columns.0, Columns::Created, "Created")
-                    .clicked()
-                {
-                    match self.columns {
-                        (_, Order::Forward, Columns::Created) => {
-                            self.files
-                                .sort_unstable_by(|lhs, rhs| rhs.created.cmp(&lhs.created));
-                            self.columns.1 = Order::Reverse;
+                sort_by_captured(self.files, &self.columns.1);
+                self.columns.2 = Columns::Captured;
+            };
+            ui.end_row();
+
+            if let (Some(parent), Some(open)) = (self.parent, self.open.as_deref_mut()) {
+                ui.label("");
+                let up = ui
+                    .add(Label::new("🗀..").sense(Sense::click()))
+                    .on_hover_text("Double-click to go up a folder");
+                if up.double_clicked() {
+                    *open = Some(parent.to_path_buf());
+                }
+                ui.end_row();
+            }
+            let groups = self.groups();
+            let shown = groups
+                .iter()
+                .filter(|(group, _)| !self.collapsed.contains(group))
+                .flat_map(|(_, rows)| rows.iter().copied())
+                .collect::<Vec<_>>();
+            self.keyboard(ui, &shown);
+            let mut clicked = None;
+            for (group, rows) in groups {
+                if self.grouping != Grouping::None {
+                    let mut selected = rows.iter().all(|idx| self.files[*idx].selected);
+                    if ui.checkbox(&mut selected, "").changed() {
+                        for idx in &rows {
+                            self.files[*idx].selected = selected;
+                            self.selection.set(&self.files[*idx].name, selected);
                         }
-                        _ => {
-                            self.files
-                                .sort_unstable_by(|lhs, rhs| lhs.created.cmp(&rhs.created));
-                            self.columns.1 = Order::Forward;
+                    };
+                    let collapsed = self.collapsed.contains(&group);
+                    let arrow = if collapsed { "⏵" } else { "⏷" };
+                    if ui
+                        .selectable_label(false, format!("{arrow} {group} ({})", rows.len()))
+                        .clicked()
+                    {
+                        if collapsed {
+                            self.collapsed.remove(&group);
+                        } else {
+                            self.collapsed.insert(group);
                         }
                     };
-                    self.columns.2 = Columns::Created;
-                };
-                ui.end_row();
-
-                for item in self.files.iter_mut() {
-                    ui.checkbox(&mut item.selected, "");
-                    ui.label(file_no_parents(&item.name));
-                    ui.label(&item.renamed);
-                    ui.label(if let Some(ext) = &item.extension {
-                        ext.as_str()
-                    } else {
-                        ""
-                    });
-                    ui.label(if let Some(size) = &item.size {
-                        format!("{}", &size)
-                    } else {
-                        String::new()
-                    });
-                    if let Some(time) = &item.modified {
-                        ui.label(datetime_to_string(time));
-                    }
-                    if let Some(time) = &item.created {
-                        ui.label(datetime_to_string(time));
-                    }
                     ui.end_row();
+                    if collapsed {
+                        continue;
+                    }
                 }
-            })
-            .response
+                for idx in rows {
+                    if show_row(
+                        ui,
+                        &mut self.files[idx],
+                        self.number_column,
+                        self.selection,
+                        self.visible,
+                        self.open.as_deref_mut(),
+                        self.cursor.as_deref_mut(),
+                    ) {
+                        clicked = Some(idx);
+                    }
+                }
+            }
+            if let Some(idx) = clicked {
+                self.ticked(ui, &shown, idx);
+            }
+        })
+        .response
     }
 }
@@ -1,4 +1,13 @@
-use egui::{self, Id, Response, Ui, Widget};
+use egui::{self, EventFilter, Id, Key, Response, Ui, Widget};
+
+/// How long an arrow is held before it starts repeating, in seconds.
+const REPEAT_DELAY: f64 = 0.4;
+/// The time between repeats once they start, which shortens the longer the arrow is held.
+const REPEAT_INTERVAL: f64 = 0.15;
+/// The shortest time between repeats.
+const FASTEST_INTERVAL: f64 = 0.02;
+/// How many steps an arrow takes at once while Shift is held.
+const SHIFT_STEPS: usize = 10;
 
 pub trait Incrementer {
     fn increment(&mut self, field: &str);
@@ -6,6 +15,10 @@ pub trait Incrementer {
 }
 
 /// A set of increment decrement arrows stacked vertically.
+///
+/// Holding an arrow repeats it, faster the longer it's held, and Shift steps by 10.
+/// The arrows can be reached with Tab: Enter or Space presses the focused one, and the
+/// up and down keys step either way.
 pub struct Arrows<'a, I: Incrementer> {
     pub id: Id,
     pub value: &'a mut I,
@@ -26,13 +39,60 @@ impl<I: Incrementer> Widget for Arrows<'_, I> {
     fn ui(self, ui: &mut Ui) -> Response {
         ui.vertical(|ui| {
             ui.set_width(10.0);
-            if ui.button("^").clicked() {
-                self.value.increment(self.field)
+            let up = ui.button("^");
+            let down = ui.button("v");
+            let mut steps = presses(ui, &up, self.id.with("up")) as isize
+                - presses(ui, &down, self.id.with("down")) as isize;
+            for arrow in [&up, &down].into_iter().filter(|arrow| arrow.has_focus()) {
+                // The up and down keys step rather than move the focus away.
+                let filter = EventFilter {
+                    arrows: true,
+                    ..Default::default()
+                };
+                ui.memory_mut(|memory| memory.set_focus_lock_filter(arrow.id, filter));
+                ui.input(|input| {
+                    steps += input.num_presses(Key::ArrowUp) as isize;
+                    steps -= input.num_presses(Key::ArrowDown) as isize;
+                });
+            }
+            let times = match ui.input(|input| input.modifiers.shift) {
+                true => SHIFT_STEPS,
+                false => 1,
             };
-            if ui.button("v").clicked() {
-                self.value.decrement(self.field)
+            for _ in 0..steps.unsigned_abs() * times {
+                match steps > 0 {
+                    true => self.value.increment(self.field),
+                    false => self.value.decrement(self.field),
+                }
             }
         })
         .response
     }
 }
+
+/// How many times an arrow is pressed this frame: once when the pointer goes down on it
+/// or it is pressed from the keyboard, then again every so often while it is held.
+fn presses(ui: &Ui, arrow: &Response, id: Id) -> usize {
+    let now = ui.input(|input| input.time);
+    let keyboard = arrow.has_focus()
+        && ui.input(|input| input.num_presses(Key::Enter) + input.num_presses(Key::Space)) > 0;
+    if !arrow.is_pointer_button_down_on() {
+        // When the arrow was first pressed, and when it next repeats.
+        ui.data_mut(|data| data.remove::<(f64, f64)>(id));
+        return usize::from(keyboard);
+    }
+    ui.ctx().request_repaint();
+    let Some((since, next)) = ui.data(|data| data.get_temp::<(f64, f64)>(id)) else {
+        ui.data_mut(|data| data.insert_temp(id, (now, now + REPEAT_DELAY)));
+        return 1;
+    };
+    let mut next = next;
+    let mut presses = 0;
+    while next <= now {
+        presses += 1;
+        let interval = REPEAT_INTERVAL / (1.0 + now - since);
+        next += interval.max(FASTEST_INTERVAL);
+    }
+    ui.data_mut(|data| data.insert_temp(id, (since, next)));
+    presses
+}
@@ -0,0 +1,118 @@
+use std::{
+    path::PathBuf,
+    sync::mpsc::{channel, Receiver},
+    thread,
+};
+
+use egui::{Button, Context, Grid, ScrollArea, TextEdit, Window};
+
+use crate::{
+    batch::Throttle,
+    file::Options,
+    ingest::{ingest, IngestError, Report, MANIFEST},
+};
+
+const RESULTS_HEIGHT: f32 = 200.0;
+const PATH_WIDTH: f32 = 300.0;
+
+/// Copies a memory card to a folder with the current rules, writing and checking a
+/// checksum manifest, see [crate::ingest].
+#[derive(Default)]
+pub struct IngestWindow {
+    pub open: bool,
+    source: String,
+    destination: String,
+    running: Option<Receiver<Result<Report, IngestError>>>,
+    report: Option<Result<Report, IngestError>>,
+}
+
+impl IngestWindow {
+    /// Whether an ingest is still running.
+    pub fn pending(&self) -> bool {
+        self.running.is_some()
+    }
+
+    fn start(&mut self, options: Vec<Options>, throttle: Throttle) {
+        let (sender, receiver) = channel();
+        let (source, destination) = (self.source.clone(), self.destination.clone());
+        thread::spawn(move || sender.send(ingest(source, destination, &options, throttle)));
+        self.running = Some(receiver);
+        self.report = None;
+    }
+
+    pub fn show(&mut self, ctx: &Context, options: &[Options], throttle: Throttle) {
+        if let Some(receiver) = &self.running {
+            if let Ok(report) = receiver.try_recv() {
+                self.report = Some(report);
+                self.running = None;
+            }
+        }
+        let mut open = self.open;
+        Window::new("Ingest")
+            .open(&mut open)
+            .show(ctx, |ui| {
+                Grid::new("Ingest Folders").show(ui, |ui| {
+                    for (label, path) in [
+                        ("Card", &mut self.source),
+                        ("Copy to", &mut self.destination),
+                    ] {
+                        ui.label(label);
+                        ui.add(TextEdit::singleline(path).desired_width(PATH_WIDTH));
+                        if ui.button("Browse").clicked() {
+                            if let Some(dir) = rfd::FileDialog::new()
+                                .set_directory(PathBuf::from(path.as_str()))
+                                .pick_folder()
+                            {
+                                *path = dir.display().to_string();
+                            }
+                        }
+                        ui.end_row();
+                    }
+                });
+                ui.horizontal(|ui| {
+                    let ready = !self.pending()
+                        && !self.source.is_empty()
+                        && !self.destination.is_empty();
+                    if ui
+                        .add_enabled(ready, Button::new("Ingest"))
+                        .on_hover_text(format!(
+                            "Copy every file on the card with its new name, then write and check {MANIFEST}"
+                        ))
+                        .clicked()
+                    {
+                        self.start(options.to_vec(), throttle);
+                    }
+                    match &self.report {
+                        _ if self.pending() => {
+                            ui.spinner();
+                        }
+                        Some(Ok(report)) => {
+                            ui.label(format!(
+                                "{} copied, {} failed, {} not verified",
+                                report.copied.len(),
+                                report.failed.len(),
+                                report.unverified.len()
+                            ));
+                        }
+                        Some(Err(err)) => {
+                            ui.label(err.to_string());
+                        }
+                        None => (),
+                    }
+                });
+                if let Some(Ok(report)) = &self.report {
+                    ScrollArea::vertical()
+                        .max_height(RESULTS_HEIGHT)
+                        .show(ui, |ui| {
+                            for (original, err) in &report.failed {
+                                ui.label(format!("⛔ {}: {err}", original.display()));
+                            }
+                            for copy in &report.unverified {
+                                ui.label(format!("⛔ {} doesn't match {MANIFEST}", copy.display()));
+                            }
+                        });
+                }
+            });
+        self.open = open;
+    }
+}
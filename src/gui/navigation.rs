@@ -0,0 +1,59 @@
+use std::path::{Path, PathBuf};
+
+/// The folders visited before and after the current one, for going back and forward as
+/// in a browser. Going to a new folder forgets the ones ahead.
+#[derive(Debug, Default)]
+pub struct Visited {
+    back: Vec<PathBuf>,
+    forward: Vec<PathBuf>,
+}
+
+impl Visited {
+    /// Leave `current` for another folder.
+    pub fn leave(&mut self, current: &Path) {
+        if self.back.last().map(PathBuf::as_path) != Some(current) {
+            self.back.push(current.to_path_buf());
+        }
+        self.forward.clear();
+    }
+
+    /// The folder before `current`, which becomes one ahead.
+    pub fn back(&mut self, current: &Path) -> Option<PathBuf> {
+        let previous = self.back.pop()?;
+        self.forward.push(current.to_path_buf());
+        Some(previous)
+    }
+
+    /// The folder ahead of `current`, which becomes one before.
+    pub fn forward(&mut self, current: &Path) -> Option<PathBuf> {
+        let next = self.forward.pop()?;
+        self.back.push(current.to_path_buf());
+        Some(next)
+    }
+
+    pub fn can_go_back(&self) -> bool {
+        !self.back.is_empty()
+    }
+
+    pub fn can_go_forward(&self) -> bool {
+        !self.forward.is_empty()
+    }
+}
+
+/// Each folder from the root down to `dir`, with the name shown for it, e.g. `/`, `home`
+/// and `me` for `/home/me`.
+pub fn breadcrumbs(dir: &Path) -> Vec<(String, PathBuf)> {
+    let mut crumbs = dir
+        .ancestors()
+        .filter(|folder| !folder.as_os_str().is_empty())
+        .map(|folder| {
+            let name = match folder.file_name() {
+                Some(name) => name.to_string_lossy().into_owned(),
+                None => folder.display().to_string(),
+            };
+            (name, folder.to_path_buf())
+        })
+        .collect::<Vec<_>>();
+    crumbs.reverse();
+    crumbs
+}
@@ -0,0 +1,278 @@
+use std::path::PathBuf;
+
+use egui::{Button, Context, Grid, ProgressBar, ScrollArea, Window};
+
+use super::worker::RenameWorker;
+use crate::{
+    batch::{self, Collision, CollisionPolicy, Resolution, Resolved, Throttle},
+    file::{FileError, Options},
+    guard::Guard,
+    policy::{NamingPolicy, Violation},
+};
+
+const BATCHES_HEIGHT: f32 = 300.0;
+
+/// Batches prepared in different folders, each with its own rules, renamed one after the
+/// other. A run is kept in the history as a single batch, so one undo reverts all of it.
+/// Batches queued to copy leave the originals, so they are left out of the history.
+/// Each batch is checked against the naming policy and for clashing names as it comes
+/// up, see [crate::policy] and [CollisionPolicy], then renamed on its own thread.
+#[derive(Default)]
+pub struct QueueWindow {
+    pub open: bool,
+    batches: Vec<Queued>,
+    /// The batch renamed next, while the queue runs.
+    next: Option<usize>,
+    /// The batch being renamed, with the `(original, new)` paths given to the worker.
+    renaming: Option<(RenameWorker, Vec<(PathBuf, PathBuf)>)>,
+    /// What the run has renamed and copied so far.
+    finished: Finished,
+}
+
+/// The `(original, new)` paths of the files a run renamed, and of those it copied.
+#[derive(Default)]
+pub struct Finished {
+    pub renamed: Vec<(PathBuf, PathBuf)>,
+    pub copied: Vec<(PathBuf, PathBuf)>,
+}
+
+struct Queued {
+    folder: PathBuf,
+    /// The steps the new names were made with, for telling the batches apart.
+    steps: String,
+    renames: Vec<(PathBuf, PathBuf)>,
+    /// Whether the files are copied, see [crate::file::DestinationOptions].
+    copy: bool,
+    status: Status,
+}
+
+enum Status {
+    Waiting,
+    /// Nothing was renamed as some new names clash.
+    Refused(Vec<String>),
+    /// Nothing was renamed as some new names break the naming policy.
+    Violations(Vec<String>),
+    /// The files left out as their new names clash, while the rest are renamed.
+    Running(Vec<String>),
+    /// Why files couldn't be renamed, and the files left out.
+    Done {
+        failed: Vec<String>,
+        skipped: Vec<String>,
+    },
+}
+
+impl QueueWindow {
+    /// Queue the `(original, new)` paths previewed in `folder` with the options, to be
+    /// copied rather than renamed when `copy` is set.
+    pub fn add(
+        &mut self,
+        folder: PathBuf,
+        options: &[Options],
+        renames: Vec<(PathBuf, PathBuf)>,
+        copy: bool,
+    ) {
+        let steps = options
+            .iter()
+            .map(|option| option.step().name())
+            .collect::<Vec<_>>()
+            .join(", ");
+        self.batches.push(Queued {
+            folder,
+            steps,
+            renames,
+            copy,
+            status: Status::Waiting,
+        });
+        self.open = true;
+    }
+
+    pub fn running(&self) -> bool {
+        self.next.is_some()
+    }
+
+    /// Move the run on: take in the files the batch being renamed has done, or start
+    /// the next waiting batch. Clashing names are dealt with as `collisions` says, a
+    /// batch that would ask is refused as a run can't stop to. Once the last batch is
+    /// done, or one is cancelled, returns every file the run renamed or copied.
+    pub fn step(
+        &mut self,
+        throttle: Throttle,
+        guard: &Guard,
+        policy: Option<&NamingPolicy>,
+        collisions: CollisionPolicy,
+        background: bool,
+    ) -> Option<Finished> {
+        let idx = self.next?;
+        if let Some((worker, renames)) = &mut self.renaming {
+            let results = worker.poll()?;
+            let cancelled = worker.cancelled();
+            let queued = &mut self.batches[idx];
+            let done = match queued.copy {
+                true => &mut self.finished.copied,
+                false => &mut self.finished.renamed,
+            };
+            let mut failed = vec![];
+            for ((from, to), result) in renames.iter().zip(results) {
+                match result {
+                    Ok(()) => done.push((from.clone(), to.clone())),
+                    // Cancelled files weren't tried, so they aren't failures.
+                    Err(FileError::Cancelled(_)) => (),
+                    Err(err) => failed.push(format!("{}: {err}", from.display())),
+                }
+            }
+            let skipped = match &mut queued.status {
+                Status::Running(skipped) => std::mem::take(skipped),
+                _ => vec![],
+            };
+            queued.status = Status::Done { failed, skipped };
+            self.renaming = None;
+            self.next = Some(idx + 1).filter(|_| !cancelled);
+            return self
+                .next
+                .is_none()
+                .then(|| std::mem::take(&mut self.finished));
+        }
+        let Some(queued) = self.batches.get_mut(idx) else {
+            self.next = None;
+            return Some(std::mem::take(&mut self.finished));
+        };
+        self.next = Some(idx + 1);
+        if !matches!(queued.status, Status::Waiting) {
+            return None;
+        }
+        let violations = policy.map_or_else(Vec::new, |policy| policy.check(&queued.renames));
+        if !violations.is_empty() {
+            queued.status =
+                Status::Violations(violations.iter().map(Violation::to_string).collect());
+            return None;
+        }
+        let found = batch::collisions(&queued.renames);
+        let resolution = match collisions {
+            _ if found.is_empty() => None,
+            CollisionPolicy::Skip => Some(Resolution::Skip),
+            CollisionPolicy::Overwrite => Some(Resolution::Overwrite),
+            // The names were numbered when previewed, so any clash left is refused.
+            CollisionPolicy::Refuse | CollisionPolicy::Number | CollisionPolicy::Prompt => {
+                queued.status = Status::Refused(found.iter().map(Collision::to_string).collect());
+                return None;
+            }
+        };
+        let Resolved {
+            renames,
+            skipped,
+            replace,
+        } = match resolution {
+            Some(resolution) => batch::resolve(&queued.renames, |_| resolution),
+            None => Resolved {
+                renames: queued.renames.clone(),
+                ..Default::default()
+            },
+        };
+        let skipped = skipped
+            .iter()
+            .map(|path| path.display().to_string())
+            .collect();
+        queued.status = Status::Running(skipped);
+        let worker = RenameWorker::start(
+            renames.clone(),
+            throttle,
+            guard.clone(),
+            background,
+            queued.copy,
+            replace,
+        );
+        self.next = Some(idx);
+        self.renaming = Some((worker, renames));
+        None
+    }
+
+    pub fn show(&mut self, ctx: &Context) {
+        let mut open = self.open;
+        Window::new("Rename Queue").open(&mut open).show(ctx, |ui| {
+            let total = self.batches.len();
+            let done = self
+                .batches
+                .iter()
+                .filter(|queued| !matches!(queued.status, Status::Waiting | Status::Running(_)))
+                .count();
+            let progress = match total {
+                0 => 0.0,
+                _ => done as f32 / total as f32,
+            };
+            ui.add(ProgressBar::new(progress).text(format!("{done} of {total} batches")));
+            if let Some((worker, _)) = &self.renaming {
+                ui.horizontal(|ui| worker.show(ui));
+            }
+            let mut remove = None;
+            ScrollArea::vertical()
+                .max_height(BATCHES_HEIGHT)
+                .show(ui, |ui| {
+                    Grid::new("Queue").striped(true).show(ui, |ui| {
+                        for (idx, queued) in self.batches.iter().enumerate() {
+                            if ui
+                                .add_enabled(!self.running(), Button::new("✖").small())
+                                .on_hover_text("Take the batch off the queue")
+                                .clicked()
+                            {
+                                remove = Some(idx);
+                            }
+                            ui.label(queued.folder.display().to_string())
+                                .on_hover_text(&queued.steps);
+                            match queued.copy {
+                                true => ui.label(format!("{} files to copy", queued.renames.len())),
+                                false => ui.label(format!("{} files", queued.renames.len())),
+                            };
+                            match &queued.status {
+                                Status::Waiting => ui.label("Waiting"),
+                                Status::Refused(clashes) => ui
+                                    .label(format!("⛔ {} name conflicts", clashes.len()))
+                                    .on_hover_text(clashes.join("\n")),
+                                Status::Violations(broken) => ui
+                                    .label(format!("⛔ {} names break the policy", broken.len()))
+                                    .on_hover_text(broken.join("\n")),
+                                Status::Running(_) => ui.label("Renaming"),
+                                Status::Done { failed, skipped } => {
+                                    let mut text = match failed.len() {
+                                        0 => "Done".to_owned(),
+                                        count => format!("⛔ {count} renames failed"),
+                                    };
+                                    if !skipped.is_empty() {
+                                        text.push_str(&format!(", {} skipped", skipped.len()));
+                                    }
+                                    let listed = failed.iter().chain(skipped);
+                                    ui.label(text).on_hover_text(
+                                        listed.cloned().collect::<Vec<_>>().join("\n"),
+                                    )
+                                }
+                            };
+                            ui.end_row();
+                        }
+                    });
+                });
+            if let Some(idx) = remove {
+                self.batches.remove(idx);
+            }
+            ui.horizontal(|ui| {
+                let waiting = self
+                    .batches
+                    .iter()
+                    .any(|queued| matches!(queued.status, Status::Waiting));
+                if ui
+                    .add_enabled(waiting && !self.running(), Button::new("Run"))
+                    .on_hover_text("Rename the waiting batches one after the other")
+                    .clicked()
+                {
+                    self.next = Some(0);
+                }
+                if ui
+                    .add_enabled(!self.running(), Button::new("Clear Finished"))
+                    .clicked()
+                {
+                    self.batches
+                        .retain(|queued| matches!(queued.status, Status::Waiting));
+                }
+            });
+        });
+        self.open = open;
+    }
+}
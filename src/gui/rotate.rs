@@ -0,0 +1,157 @@
+use std::path::{Path, PathBuf};
+
+use egui::{Button, Context, ScrollArea, TextEdit, Window};
+
+use crate::{
+    batch::{self, Collision, Throttle},
+    directory,
+    guard::Guard,
+    rotate::Rotation,
+};
+
+const PLAN_HEIGHT: f32 = 300.0;
+
+/// Swaps extensions between files of the same name, e.g. `.yml` and `.yaml`, in a few
+/// steps: pick the extensions, check the renames found, then make them as one batch.
+#[derive(Default)]
+pub struct RotateWindow {
+    pub open: bool,
+    extensions: String,
+    subfolders: bool,
+    complete: bool,
+    /// The `(from, to)` paths found, none until Find is pressed.
+    plan: Option<Vec<(PathBuf, PathBuf)>>,
+    collisions: Vec<Collision>,
+    /// Why files couldn't be renamed by the last swap.
+    failed: Vec<String>,
+    renamed: usize,
+}
+
+impl RotateWindow {
+    /// Look for the files to rename in `folder`.
+    fn find(&mut self, folder: &Path) {
+        let files = match self.subfolders {
+            true => directory::walk(folder, false)
+                .map(|folders| folders.into_iter().flat_map(|found| found.files).collect()),
+            false => folder.read_dir().map(|entries| {
+                entries
+                    .flatten()
+                    .map(|entry| entry.path())
+                    .filter(|path| path.is_file())
+                    .collect::<Vec<_>>()
+            }),
+        };
+        let files = match files {
+            Ok(files) => files,
+            Err(err) => {
+                self.failed = vec![format!("{}: {err}", folder.display())];
+                return;
+            }
+        };
+        let plan = Rotation::new(&self.extensions, self.complete).plan(&files);
+        self.collisions = batch::collisions(&plan);
+        self.plan = Some(plan);
+        self.failed.clear();
+        self.renamed = 0;
+    }
+
+    /// Rename the files found, returning the `(original, new)` paths of those renamed.
+    fn swap(&mut self, throttle: Throttle, guard: &Guard) -> Vec<(PathBuf, PathBuf)> {
+        let plan = self.plan.take().unwrap_or_default();
+        let mut renamed = vec![];
+        for ((from, to), result) in plan
+            .iter()
+            .zip(batch::rename_guarded(&plan, throttle, guard))
+        {
+            match result {
+                Ok(()) => renamed.push((from.clone(), to.clone())),
+                Err(err) => self.failed.push(format!("{}: {err}", from.display())),
+            }
+        }
+        self.renamed = renamed.len();
+        renamed
+    }
+
+    /// Show the wizard for the files in `folder`. Returns the `(original, new)` paths of
+    /// the files renamed, once they are.
+    pub fn show(
+        &mut self,
+        ctx: &Context,
+        folder: &Path,
+        throttle: Throttle,
+        guard: &Guard,
+    ) -> Option<Vec<(PathBuf, PathBuf)>> {
+        let mut open = self.open;
+        let mut renamed = None;
+        Window::new("Swap Extensions")
+            .open(&mut open)
+            .show(ctx, |ui| {
+                ui.label("1. The extensions to swap, or to rotate through");
+                ui.horizontal(|ui| {
+                    let extensions =
+                        TextEdit::singleline(&mut self.extensions).hint_text("yml yaml");
+                    if ui.add(extensions).changed() {
+                        self.plan = None;
+                    }
+                    ui.label("Extensions");
+                })
+                .response
+                .on_hover_text("Each extension is given the next one, the last the first");
+                let mut changed = ui
+                    .checkbox(&mut self.subfolders, "Include subfolders")
+                    .changed();
+                changed |= ui
+                    .checkbox(&mut self.complete, "Only names with every extension")
+                    .on_hover_text("Leave a lone config.yml alone, rather than make it .yaml")
+                    .changed();
+                if changed {
+                    self.plan = None;
+                }
+                ui.separator();
+                ui.label("2. Check the files found");
+                if ui.button("Find").clicked() {
+                    self.find(folder);
+                }
+                if let Some(plan) = &self.plan {
+                    ui.label(format!("{} files to rename", plan.len()));
+                    ScrollArea::vertical()
+                        .max_height(PLAN_HEIGHT)
+                        .show(ui, |ui| {
+                            for (from, to) in plan {
+                                let from = from.strip_prefix(folder).unwrap_or(from);
+                                let to = to.file_name().unwrap_or_default().to_string_lossy();
+                                ui.label(format!("{} → {to}", from.display()));
+                            }
+                        });
+                    if !self.collisions.is_empty() {
+                        ui.label(format!("⛔ {} name conflicts", self.collisions.len()))
+                            .on_hover_text(
+                                self.collisions
+                                    .iter()
+                                    .map(Collision::to_string)
+                                    .collect::<Vec<_>>()
+                                    .join("\n"),
+                            );
+                    }
+                }
+                ui.separator();
+                ui.label("3. Rename them, as one batch to undo");
+                let ready = self
+                    .plan
+                    .as_ref()
+                    .is_some_and(|plan| !plan.is_empty() && self.collisions.is_empty());
+                if ui.add_enabled(ready, Button::new("Swap")).clicked() {
+                    renamed = Some(self.swap(throttle, guard));
+                }
+                if self.renamed > 0 {
+                    ui.label(format!("{} files renamed", self.renamed));
+                }
+                if !self.failed.is_empty() {
+                    ui.label(format!("⛔ {} renames failed", self.failed.len()))
+                        .on_hover_text(self.failed.join("\n"));
+                }
+            });
+        self.open = open;
+        renamed
+    }
+}
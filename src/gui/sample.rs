@@ -0,0 +1,124 @@
+use std::{
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use egui::{Context, DragValue, Grid, ScrollArea, Window};
+
+use crate::{analysis::sample, file::Options, File};
+
+const DEFAULT_SIZE: usize = 20;
+const MAX_SIZE: usize = 500;
+const RESULTS_HEIGHT: f32 = 300.0;
+
+/// Dry-run the rules on a small subset of the files. While the window is open only the
+/// sample is renamed each frame, so rules can be tweaked quickly on very large folders.
+pub struct SampleWindow {
+    pub open: bool,
+    size: usize,
+    random: bool,
+    seed: u64,
+    stale: bool,
+    names: Vec<PathBuf>,
+    results: Vec<(PathBuf, PathBuf)>,
+}
+
+impl Default for SampleWindow {
+    fn default() -> Self {
+        Self {
+            open: false,
+            size: DEFAULT_SIZE,
+            random: false,
+            seed: 0,
+            stale: true,
+            names: vec![],
+            results: vec![],
+        }
+    }
+}
+
+impl SampleWindow {
+    /// Open the window with a fresh sample.
+    pub fn show_sample(&mut self) {
+        self.open = true;
+        self.reseed();
+    }
+
+    /// Draw a new sample on the next refresh, e.g. after the file list changed.
+    pub fn invalidate(&mut self) {
+        self.stale = true;
+    }
+
+    fn reseed(&mut self) {
+        self.seed = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|time| time.as_nanos() as u64)
+            .unwrap_or_default();
+        self.stale = true;
+    }
+
+    /// Apply the options to the sample, drawing it from `names` first if needed.
+    pub fn refresh<'a, F>(&mut self, names: F, options: &[Options])
+    where
+        F: FnOnce() -> Vec<&'a Path>,
+    {
+        if self.stale {
+            self.names = sample(&names(), self.size, self.random.then_some(self.seed))
+                .into_iter()
+                .map(Path::to_path_buf)
+                .collect();
+            self.stale = false;
+        }
+        self.results = self
+            .names
+            .iter()
+            .filter_map(|name| {
+                let mut file = File::new(name).ok()?.with_pipeline(options);
+                Some((name.clone(), file.preview()))
+            })
+            .collect();
+    }
+
+    pub fn show(&mut self, ctx: &Context) {
+        let mut open = self.open;
+        Window::new("Test on Sample")
+            .open(&mut open)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label("Files");
+                    if ui
+                        .add(DragValue::new(&mut self.size).clamp_range(1..=MAX_SIZE))
+                        .changed()
+                    {
+                        self.stale = true;
+                    };
+                    if ui.checkbox(&mut self.random, "Random").changed() {
+                        self.stale = true;
+                    };
+                    if ui.small_button("Resample").clicked() {
+                        self.reseed();
+                    };
+                });
+                ui.separator();
+                ScrollArea::vertical()
+                    .max_height(RESULTS_HEIGHT)
+                    .show(ui, |ui| {
+                        Grid::new("Sample Results").striped(true).show(ui, |ui| {
+                            for (original, renamed) in &self.results {
+                                ui.label(file_name(original));
+                                ui.label("→");
+                                ui.label(file_name(renamed));
+                                ui.end_row();
+                            }
+                        });
+                    });
+            });
+        self.open = open;
+    }
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
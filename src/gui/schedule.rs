@@ -0,0 +1,103 @@
+use std::{
+    collections::{HashMap, HashSet},
+    path::PathBuf,
+    time::{Duration, Instant},
+};
+
+use crate::{batch, file::Options, File};
+
+/// Previews a batch over as many frames as it takes, each within a time budget, so the
+/// window stays responsive with huge selections. Rows on screen are previewed first.
+#[derive(Default)]
+pub struct PreviewSchedule {
+    /// What the batch was made from, to tell when it has to start over.
+    key: Option<u64>,
+    options: Vec<Options>,
+    /// The index of each file's listing.
+    indices: Vec<usize>,
+    positions: HashMap<PathBuf, usize>,
+    files: Vec<File>,
+    done: Vec<bool>,
+    previewed: usize,
+    /// Files before it are all previewed.
+    next: usize,
+}
+
+impl PreviewSchedule {
+    /// Whether the batch was made from the same key.
+    pub fn is_current(&self, key: u64) -> bool {
+        self.key == Some(key)
+    }
+
+    /// Start previewing the files, given with the index and name of their listing.
+    pub fn start(&mut self, key: u64, batch: Vec<(usize, PathBuf, File)>, options: &[Options]) {
+        self.key = Some(key);
+        self.options = options.to_vec();
+        self.indices.clear();
+        self.positions.clear();
+        self.files.clear();
+        for (position, (idx, name, file)) in batch.into_iter().enumerate() {
+            self.indices.push(idx);
+            self.positions.insert(name, position);
+            self.files.push(file);
+        }
+        batch::prepare(&mut self.files, &self.options);
+        self.done = vec![false; self.files.len()];
+        self.previewed = 0;
+        self.next = 0;
+    }
+
+    /// Forget the batch, so the next preview starts over.
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+
+    /// Preview files until the budget is spent, the `visible` ones first, and at least
+    /// one. The batch is finished once the last file is previewed.
+    /// Returns the listing index and file of each name that changed.
+    pub fn run(&mut self, visible: &HashSet<PathBuf>, budget: Duration) -> Vec<(usize, &File)> {
+        let started = Instant::now();
+        let first = visible
+            .iter()
+            .filter_map(|name| self.positions.get(name).copied())
+            .filter(|&position| !self.done[position])
+            .collect::<Vec<_>>();
+        let mut changed = vec![];
+        let mut first = first.into_iter();
+        while self.pending() && (changed.is_empty() || started.elapsed() < budget) {
+            let position = match first.next() {
+                Some(position) if !self.done[position] => position,
+                Some(_) => continue,
+                None => {
+                    while self.done[self.next] {
+                        self.next += 1;
+                    }
+                    self.next
+                }
+            };
+            self.files[position].preview();
+            self.done[position] = true;
+            self.previewed += 1;
+            changed.push(position);
+        }
+        if !changed.is_empty() && !self.pending() {
+            // Swap and Pairs can change any name, so every file is handed back.
+            batch::finish(&mut self.files, &self.options);
+            changed = (0..self.files.len()).collect();
+        }
+        changed
+            .into_iter()
+            .map(|position| (self.indices[position], &self.files[position]))
+            .collect()
+    }
+
+    /// Whether files are still to be previewed.
+    pub fn pending(&self) -> bool {
+        self.previewed < self.files.len()
+    }
+
+    /// The number of files previewed and in the batch.
+    pub fn progress(&self) -> (usize, usize) {
+        (self.previewed, self.files.len())
+    }
+}
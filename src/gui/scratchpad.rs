@@ -0,0 +1,114 @@
+use std::{
+    collections::hash_map::DefaultHasher,
+    hash::{Hash, Hasher},
+    path::Path,
+};
+
+use egui::{Context, Grid, ScrollArea, TextEdit, Window};
+
+use crate::{
+    batch,
+    file::{Options, Scopes},
+    File,
+};
+
+const NAMES_ROWS: usize = 8;
+const RESULTS_HEIGHT: f32 = 300.0;
+
+/// Try the rules on names typed or pasted in, one per line, without touching any files,
+/// e.g. to work out the rules before opening the real folder. A name can start with its
+/// folders, e.g. `Trip/IMG_0001.JPG`, for the steps that use them.
+#[derive(Default)]
+pub struct ScratchpadWindow {
+    pub open: bool,
+    names: String,
+    /// Each name, its new name, and why steps couldn't be applied to it.
+    results: Vec<(String, String, Vec<String>)>,
+    /// The names and rules the results were worked out for.
+    key: Option<u64>,
+}
+
+impl ScratchpadWindow {
+    /// Apply the options to the names as one batch, so they are numbered, swapped and
+    /// paired as the files of a folder would be. Nothing is redone until the names or
+    /// the rules change.
+    pub fn refresh(&mut self, options: &[Options], scopes: &Scopes, windows_names: bool) {
+        let mut hasher = DefaultHasher::new();
+        self.names.hash(&mut hasher);
+        serde_json::to_string(options)
+            .unwrap_or_default()
+            .hash(&mut hasher);
+        scopes.hash(&mut hasher);
+        windows_names.hash(&mut hasher);
+        let key = hasher.finish();
+        if self.key == Some(key) {
+            return;
+        }
+        self.key = Some(key);
+        let mut files = self
+            .names
+            .lines()
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .filter_map(|name| File::new(Path::new(name)).ok())
+            .map(|file| {
+                file.with_scopes(scopes.clone())
+                    .with_windows_names(windows_names)
+            })
+            .collect::<Vec<_>>();
+        batch::preview(&mut files, options);
+        self.results = files
+            .iter()
+            .map(|file| {
+                let errors = file.errors().to_vec();
+                (
+                    file_name(file.original()),
+                    file_name(&file.new_path()),
+                    errors,
+                )
+            })
+            .collect();
+    }
+
+    pub fn show(&mut self, ctx: &Context) {
+        let mut open = self.open;
+        Window::new("Scratchpad").open(&mut open).show(ctx, |ui| {
+            ui.label("Names to try the rules on, one per line. No files are renamed.");
+            ui.add(
+                TextEdit::multiline(&mut self.names)
+                    .desired_rows(NAMES_ROWS)
+                    .hint_text("IMG_0001.JPG\nTrip/IMG_0002.JPG"),
+            );
+            if ui.small_button("Clear").clicked() {
+                self.names.clear();
+            }
+            ui.separator();
+            ScrollArea::vertical()
+                .max_height(RESULTS_HEIGHT)
+                .show(ui, |ui| {
+                    Grid::new("Scratchpad Results")
+                        .striped(true)
+                        .show(ui, |ui| {
+                            for (original, renamed, errors) in &self.results {
+                                ui.label(original);
+                                ui.label("→");
+                                match errors.is_empty() {
+                                    true => ui.label(renamed),
+                                    false => ui
+                                        .label(format!("⚠ {renamed}"))
+                                        .on_hover_text(errors.join("\n")),
+                                };
+                                ui.end_row();
+                            }
+                        });
+                });
+        });
+        self.open = open;
+    }
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
@@ -0,0 +1,59 @@
+use egui::{Context, Grid, Window};
+
+use crate::{
+    settings::Settings,
+    stats::{Statistics, SIZE_LABELS},
+};
+
+/// Shows the usage statistics, and turns recording them on and off.
+#[derive(Default)]
+pub struct StatisticsWindow {
+    pub open: bool,
+}
+
+impl StatisticsWindow {
+    /// Returns whether the settings or statistics changed and should be saved.
+    pub fn show(&mut self, ctx: &Context, settings: &mut Settings, stats: &mut Statistics) -> bool {
+        let mut changed = false;
+        let mut open = self.open;
+        Window::new("Usage Statistics")
+            .open(&mut open)
+            .resizable(false)
+            .show(ctx, |ui| {
+                changed |= ui
+                    .checkbox(&mut settings.statistics, "Record usage statistics")
+                    .changed();
+                ui.label(
+                    "Counts the panels used and the number of files renamed at once, never any \
+                     names. Kept only on this computer, nothing is ever sent.",
+                );
+                ui.separator();
+                ui.label(format!(
+                    "{} files renamed in {} batches",
+                    stats.files, stats.batches
+                ));
+                Grid::new("Statistics Steps").striped(true).show(ui, |ui| {
+                    for (step, count) in stats.most_used() {
+                        ui.label(step.to_string());
+                        ui.label(count.to_string());
+                        ui.end_row();
+                    }
+                });
+                ui.separator();
+                ui.label("Files per batch");
+                Grid::new("Statistics Sizes").striped(true).show(ui, |ui| {
+                    for (label, count) in SIZE_LABELS.iter().zip(stats.sizes) {
+                        ui.label(*label);
+                        ui.label(count.to_string());
+                        ui.end_row();
+                    }
+                });
+                if ui.button("Clear").clicked() {
+                    *stats = Statistics::default();
+                    changed = true;
+                }
+            });
+        self.open = open;
+        changed
+    }
+}
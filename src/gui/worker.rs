@@ -0,0 +1,183 @@
+use std::{
+    collections::HashSet,
+    io,
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::{channel, Receiver},
+        Arc,
+    },
+    thread::{self, JoinHandle},
+    time::Duration,
+};
+
+use egui::{Button, ProgressBar, Ui};
+
+use crate::{
+    batch::{self, Throttle},
+    file::FileError,
+    guard::Guard,
+    priority,
+};
+
+const PROGRESS_WIDTH: f32 = 200.0;
+/// How often [RenameWorker::wait] checks whether the batch is done.
+const WAIT_INTERVAL: Duration = Duration::from_millis(10);
+
+/// A batch renamed on its own thread, so the window keeps responding during thousands
+/// of renames. Each file is reported as soon as it is done, and the batch can be
+/// cancelled part way, see [batch::rename_watched].
+pub struct RenameWorker {
+    total: usize,
+    done: usize,
+    /// Why files couldn't be renamed, as they are reported.
+    failed: Vec<String>,
+    cancel: Arc<AtomicBool>,
+    /// Whether the batch should run at low priority, see [priority].
+    background: Arc<AtomicBool>,
+    /// Whether it does, which may differ as the worker only changes it between files, and
+    /// not every platform can.
+    lowered: Arc<AtomicBool>,
+    progress: Receiver<Option<String>>,
+    handle: Option<JoinHandle<Vec<Result<(), FileError>>>>,
+}
+
+impl RenameWorker {
+    /// Start renaming the `(from, to)` paths, or copying them if `copy`, at low priority
+    /// if `background`. Files at the new names in `replace` are replaced, see
+    /// [batch::resolve].
+    pub fn start(
+        renames: Vec<(PathBuf, PathBuf)>,
+        throttle: Throttle,
+        guard: Guard,
+        background: bool,
+        copy: bool,
+        replace: HashSet<PathBuf>,
+    ) -> Self {
+        let (sender, progress) = channel();
+        let cancel = Arc::new(AtomicBool::new(false));
+        let background = Arc::new(AtomicBool::new(background));
+        let lowered = Arc::new(AtomicBool::new(false));
+        let total = renames.len();
+        let stop = cancel.clone();
+        let (wanted, low) = (background.clone(), lowered.clone());
+        // The priority is the thread's own, so it is changed from the thread.
+        let reprioritize = move || {
+            let wanted = wanted.load(Ordering::Relaxed);
+            if wanted != low.load(Ordering::Relaxed) && priority::set_background(wanted).is_ok() {
+                low.store(wanted, Ordering::Relaxed);
+            }
+        };
+        let handle = thread::spawn(move || {
+            reprioritize();
+            let progress = |idx: usize, result: &Result<(), FileError>| {
+                reprioritize();
+                let err = result
+                    .as_ref()
+                    .err()
+                    .map(|err| format!("{}: {err}", renames[idx].0.display()));
+                // The window only stops listening once the batch is done.
+                sender.send(err).ok();
+            };
+            match copy {
+                true => batch::copy_watched(&renames, throttle, &guard, &stop, progress),
+                false => {
+                    batch::rename_watched(&renames, throttle, &guard, &replace, &stop, progress)
+                }
+            }
+        });
+        Self {
+            total,
+            done: 0,
+            failed: vec![],
+            cancel,
+            background,
+            lowered,
+            progress,
+            handle: Some(handle),
+        }
+    }
+
+    /// Take in the files reported since the last call. Once the batch is done, returns
+    /// a result for each of its `(from, to)` paths. Never blocks.
+    pub fn poll(&mut self) -> Option<Vec<Result<(), FileError>>> {
+        for err in self.progress.try_iter() {
+            self.done += 1;
+            self.failed.extend(err);
+        }
+        if !self.handle.as_ref()?.is_finished() {
+            return None;
+        }
+        let results = self.handle.take()?.join().unwrap_or_else(|_| {
+            (0..self.total)
+                .map(|_| Err(io::Error::other("The renaming stopped unexpectedly.").into()))
+                .collect()
+        });
+        Some(results)
+    }
+
+    /// Wait for the batch to finish, returning a result for each of its `(from, to)` paths.
+    pub fn wait(&mut self) -> Vec<Result<(), FileError>> {
+        loop {
+            if let Some(results) = self.poll() {
+                return results;
+            }
+            thread::sleep(WAIT_INTERVAL);
+        }
+    }
+
+    /// Stop starting renames. Files already moved to a temporary name still get their new
+    /// names, so nothing is left half renamed.
+    pub fn cancel(&self) {
+        self.cancel.store(true, Ordering::Relaxed);
+    }
+
+    pub fn cancelled(&self) -> bool {
+        self.cancel.load(Ordering::Relaxed)
+    }
+
+    /// Give the batch its normal priority back, from the next file on.
+    pub fn boost(&self) {
+        self.background.store(false, Ordering::Relaxed);
+    }
+
+    /// Show how far the batch is, with a button to cancel it.
+    pub fn show(&self, ui: &mut Ui) {
+        if ui
+            .add_enabled(!self.cancelled(), Button::new("Cancel"))
+            .on_hover_text("Stop renaming, the files already renamed keep their new names")
+            .clicked()
+        {
+            self.cancel();
+        }
+        if !self.failed.is_empty() {
+            ui.label(format!("⛔ {} failed", self.failed.len()))
+                .on_hover_text(self.failed.join("\n"));
+        }
+        if self.lowered.load(Ordering::Relaxed) {
+            let boosting = !self.background.load(Ordering::Relaxed);
+            if ui
+                .add_enabled(!boosting, Button::new("Boost"))
+                .on_hover_text("Rename at normal priority, which is faster")
+                .clicked()
+            {
+                self.boost();
+            }
+            ui.label("🐢 Background")
+                .on_hover_text("Renaming at low priority, so other programs aren't slowed down");
+        }
+        let progress = match self.total {
+            0 => 1.0,
+            total => self.done as f32 / total as f32,
+        };
+        let text = match self.cancelled() {
+            true => format!("Cancelling, {} of {} done", self.done, self.total),
+            false => format!("Renaming {} of {}", self.done, self.total),
+        };
+        ui.add(
+            ProgressBar::new(progress)
+                .desired_width(PROGRESS_WIDTH)
+                .text(text),
+        );
+    }
+}
@@ -0,0 +1,131 @@
+//! A journal of the batches renamed, kept on disk so a bad batch can be undone, and the
+//! undo redone, even after the app has been closed.
+
+use std::{
+    io,
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    batch::{self, Throttle},
+    file::FileError,
+    guard::Guard,
+    persist,
+};
+
+/// Batches remembered, the oldest are forgotten first.
+pub const MAX_BATCHES: usize = 50;
+
+/// The renames of one batch, as `(original, new)` paths.
+pub type Batch = Vec<(PathBuf, PathBuf)>;
+
+/// What a batch undone or redone did: each move made, as `(from, to)`, and how it went.
+pub type Outcome = Vec<((PathBuf, PathBuf), Result<(), FileError>)>;
+
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RenameHistory {
+    /// The batches that can be undone, most recent last.
+    done: Vec<Batch>,
+    /// The batches that can be redone, most recently undone last.
+    undone: Vec<Batch>,
+}
+
+impl RenameHistory {
+    /// Read a history saved with [RenameHistory::save]. A missing file gives no history.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, io::Error> {
+        Ok(persist::read(path, |text| serde_json::from_str::<Self>(text))?.unwrap_or_default())
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), io::Error> {
+        persist::write(path, serde_json::to_string_pretty(self)?)
+    }
+
+    /// Remember a batch of `(original, new)` renames. Batches undone can't be redone after.
+    pub fn record(&mut self, renamed: &[(PathBuf, PathBuf)]) {
+        if renamed.is_empty() {
+            return;
+        }
+        self.done.push(renamed.to_vec());
+        self.undone.clear();
+        let extra = self.done.len().saturating_sub(MAX_BATCHES);
+        self.done.drain(..extra);
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.done.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.undone.is_empty()
+    }
+
+    /// The `(from, to)` moves the next [RenameHistory::undo] makes.
+    pub fn undo_moves(&self) -> Batch {
+        self.done.last().map(reversed).unwrap_or_default()
+    }
+
+    /// The `(from, to)` moves the next [RenameHistory::redo] makes.
+    pub fn redo_moves(&self) -> Batch {
+        self.undone.last().cloned().unwrap_or_default()
+    }
+
+    /// Give the files of the last batch their original names back.
+    /// Files that couldn't be renamed back stay in the history, to be tried again.
+    pub fn undo(&mut self, throttle: Throttle, guard: &Guard) -> Outcome {
+        let Some(batch) = self.done.pop() else {
+            return vec![];
+        };
+        let outcome = apply(reversed(&batch), throttle, guard);
+        let (undone, left) = split(&batch, &outcome);
+        if !left.is_empty() {
+            self.done.push(left);
+        }
+        if !undone.is_empty() {
+            self.undone.push(undone);
+        }
+        outcome
+    }
+
+    /// Rename the files of the last batch undone again.
+    pub fn redo(&mut self, throttle: Throttle, guard: &Guard) -> Outcome {
+        let Some(batch) = self.undone.pop() else {
+            return vec![];
+        };
+        let outcome = apply(batch.clone(), throttle, guard);
+        let (redone, left) = split(&batch, &outcome);
+        if !left.is_empty() {
+            self.undone.push(left);
+        }
+        if !redone.is_empty() {
+            self.done.push(redone);
+        }
+        outcome
+    }
+}
+
+fn reversed(batch: &Batch) -> Batch {
+    batch
+        .iter()
+        .map(|(original, new)| (new.clone(), original.clone()))
+        .collect()
+}
+
+fn apply(moves: Batch, throttle: Throttle, guard: &Guard) -> Outcome {
+    let results = batch::rename_guarded(&moves, throttle, guard);
+    moves.into_iter().zip(results).collect()
+}
+
+/// The renames of the batch whose moves worked, and those whose moves failed.
+fn split(batch: &Batch, outcome: &Outcome) -> (Batch, Batch) {
+    let (worked, failed): (Vec<_>, Vec<_>) = batch
+        .iter()
+        .zip(outcome)
+        .partition(|(_, (_, result))| result.is_ok());
+    let pairs = |half: Vec<(&(PathBuf, PathBuf), _)>| {
+        half.into_iter().map(|(pair, _)| pair.clone()).collect()
+    };
+    (pairs(worked), pairs(failed))
+}
@@ -1,6 +1,18 @@
-use bulk_file_renamer::{directory::get_initial_directory, gui, RenamerError, Selected};
+use clap::Parser;
+use mass_renamer::{
+    cli::{self, Cli},
+    directory::get_initial_directory,
+    gui, RenamerError, Selected,
+};
 
 fn main() -> Result<(), RenamerError> {
+    if std::env::args().nth(1).as_deref() == Some(cli::FLAG) {
+        let cli = Cli::parse_from(std::env::args().skip(1));
+        if !cli::run(&cli, &mut std::io::stdout())? {
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
     let _initial = get_initial_directory(std::env::args().nth(1))?;
     let mut _files = Selected::default();
     Ok(gui::run()?)
@@ -0,0 +1,80 @@
+//! The last batch renamed in each folder, to warn before the same batch is run again,
+//! which would e.g. add a date or a number to the names a second time.
+
+use std::{
+    collections::BTreeSet,
+    io,
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{file::Options, persist};
+
+/// Folders remembered, the least recently renamed are forgotten first.
+pub const MAX_FOLDERS: usize = 100;
+
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LastRuns {
+    /// Most recently renamed last.
+    runs: Vec<LastRun>,
+}
+
+/// The rules a folder was last renamed with, and the names they gave.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LastRun {
+    pub folder: PathBuf,
+    /// The options, as JSON.
+    pub options: String,
+    pub names: BTreeSet<String>,
+}
+
+impl LastRun {
+    /// Whether the last run gave the file its name.
+    pub fn produced(&self, path: &Path) -> bool {
+        path.file_name()
+            .is_some_and(|name| self.names.contains(&*name.to_string_lossy()))
+    }
+}
+
+impl LastRuns {
+    /// Read runs saved with [LastRuns::save]. A missing file gives no runs.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, io::Error> {
+        Ok(persist::read(path, |text| serde_json::from_str::<Self>(text))?.unwrap_or_default())
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), io::Error> {
+        persist::write(path, serde_json::to_string_pretty(self)?)
+    }
+
+    /// Remember the `(original, new)` renames made in the folder with the options.
+    pub fn record(&mut self, folder: &Path, options: &[Options], renamed: &[(PathBuf, PathBuf)]) {
+        self.runs.retain(|run| run.folder != folder);
+        let names = renamed
+            .iter()
+            .filter_map(|(_, to)| to.file_name())
+            .map(|name| name.to_string_lossy().into_owned())
+            .collect();
+        self.runs.push(LastRun {
+            folder: folder.to_path_buf(),
+            options: fingerprint(options),
+            names,
+        });
+        let extra = self.runs.len().saturating_sub(MAX_FOLDERS);
+        self.runs.drain(..extra);
+    }
+
+    /// The last run in the folder, if it used the same options.
+    pub fn repeated(&self, folder: &Path, options: &[Options]) -> Option<&LastRun> {
+        self.runs
+            .iter()
+            .find(|run| run.folder == folder)
+            .filter(|run| run.options == fingerprint(options))
+    }
+}
+
+fn fingerprint(options: &[Options]) -> String {
+    serde_json::to_string(options).unwrap_or_default()
+}
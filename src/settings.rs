@@ -0,0 +1,147 @@
+//! Preferences kept between sessions.
+
+use std::{
+    collections::BTreeSet,
+    io,
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    batch::{CollisionPolicy, Throttle},
+    directory::ExtensionGroup,
+    file::{Scopes, Step},
+    guard::Guard,
+    persist,
+};
+
+/// The panels shown in simple mode.
+pub const SIMPLE_STEPS: [Step; 4] = [Step::Replace, Step::Case, Step::Number, Step::Date];
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    /// Show every panel, rather than only the [SIMPLE_STEPS] and `pinned` ones.
+    pub advanced: bool,
+    /// Extra panels shown in simple mode.
+    pub pinned: BTreeSet<Step>,
+    /// How fast renames go, e.g. slower for a NAS.
+    pub throttle: Throttle,
+    /// Rename at low priority, so huge batches don't slow down other programs, see
+    /// [crate::priority].
+    pub background: bool,
+    /// Only preview when Apply is pressed rather than on every change, for very large folders.
+    pub staged: bool,
+    /// Record local usage statistics, see [crate::stats]. Off unless chosen.
+    pub statistics: bool,
+    /// The custom order files are listed, and so numbered, in, see [crate::sort].
+    pub sort: String,
+    /// Folders nothing is renamed in, see [crate::guard].
+    pub guard: Guard,
+    /// What to do about files given the same name, see [crate::batch::collisions].
+    pub collisions: CollisionPolicy,
+    /// Add every rename, undo and redo to the audit log, see [crate::audit]. Off unless chosen.
+    pub audit: bool,
+    /// Show the number each file is given in a column of its own.
+    pub number_column: bool,
+    /// Check new names against Windows' rules and path limit, and trim the dots and spaces
+    /// it drops from their ends, e.g. for files synced to it. They always are on Windows,
+    /// see [crate::analysis::invalid_names] and [crate::file::File::with_windows_names].
+    pub windows_names: bool,
+    /// Carry the numbering on into the next batch, in this folder or another, rather than
+    /// numbering each folder from the start, see [crate::gui::Renamer::continue_numbering].
+    pub continue_numbering: bool,
+    /// The order the steps are applied in, see [crate::file::File::with_pipeline]. Steps
+    /// left out follow in [Step] order.
+    pub order: Vec<Step>,
+    /// The extensions each step is limited to, see [crate::file::File::with_scopes].
+    pub scopes: Scopes,
+    /// The extensions of the text files under the folder whose links to renamed files are
+    /// rewritten, separated by spaces, e.g. `md html`. Empty to leave them alone, see
+    /// [crate::references].
+    pub references: String,
+    /// The kinds of file filtered on with `@` and their name, see [ExtensionGroup].
+    pub groups: Vec<ExtensionGroup>,
+    /// The files selected whenever a folder is opened, as a mask of the
+    /// [crate::directory::Filter], e.g. `*.tmp;*.part`. Empty to select nothing more.
+    pub auto_select: String,
+    /// The naming policy file every new name must follow before anything is renamed, see
+    /// [crate::policy]. None for no policy.
+    pub policy: Option<PathBuf>,
+    /// Whether the app is dark or light.
+    pub theme: Theme,
+    /// The size of the text, as a percentage of the usual size.
+    pub text_scale: u32,
+    /// The narrowest the columns of the file list are, in points.
+    pub column_width: u32,
+    /// The folder open when the app was last closed, opened again on start.
+    pub last_folder: Option<PathBuf>,
+}
+
+/// The colours of the app.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Theme {
+    #[default]
+    Dark,
+    Light,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            advanced: Default::default(),
+            pinned: Default::default(),
+            throttle: Default::default(),
+            background: Default::default(),
+            staged: Default::default(),
+            statistics: Default::default(),
+            sort: Default::default(),
+            guard: Default::default(),
+            collisions: Default::default(),
+            audit: Default::default(),
+            number_column: Default::default(),
+            windows_names: Default::default(),
+            continue_numbering: Default::default(),
+            order: Default::default(),
+            scopes: Default::default(),
+            references: Default::default(),
+            groups: ExtensionGroup::defaults(),
+            auto_select: Default::default(),
+            policy: Default::default(),
+            theme: Default::default(),
+            text_scale: 100,
+            column_width: 40,
+            last_folder: Default::default(),
+        }
+    }
+}
+
+impl Settings {
+    /// Read settings saved with [Settings::save]. A missing file gives the defaults.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, io::Error> {
+        Ok(persist::read(path, |text| serde_json::from_str::<Self>(text))?.unwrap_or_default())
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), io::Error> {
+        persist::write(path, serde_json::to_string_pretty(self)?)
+    }
+
+    /// Every step, in the order they are applied.
+    pub fn ordered(&self) -> Vec<Step> {
+        let mut steps = self.order.clone();
+        steps.extend(
+            Step::ALL
+                .into_iter()
+                .filter(|step| !self.order.contains(step)),
+        );
+        steps
+    }
+
+    /// Whether the step's panel is shown, and so whether its rule is applied.
+    /// Steps left out of the build are never shown.
+    pub fn shows(&self, step: Step) -> bool {
+        step.enabled()
+            && (self.advanced || SIMPLE_STEPS.contains(&step) || self.pinned.contains(&step))
+    }
+}
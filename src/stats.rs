@@ -0,0 +1,93 @@
+//! Usage statistics, kept only on this computer and never sent anywhere.
+//! They record which panels are used and how many files are renamed at once, never names.
+
+use std::{collections::BTreeMap, io, path::Path};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    file::{Options, Step},
+    persist,
+};
+
+/// Labels for the batch sizes counted in [Statistics::sizes].
+pub const SIZE_LABELS: [&str; 5] = ["1", "2 to 9", "10 to 99", "100 to 999", "1000 or more"];
+
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Statistics {
+    /// Batches renamed.
+    pub batches: u64,
+    /// Files renamed, over every batch.
+    pub files: u64,
+    /// Batches each step was used in.
+    pub steps: BTreeMap<Step, u64>,
+    /// Batches of each size, see [SIZE_LABELS].
+    pub sizes: [u64; 5],
+}
+
+impl Statistics {
+    /// Read statistics saved with [Statistics::save]. A missing file gives empty statistics.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, io::Error> {
+        Ok(persist::read(path, |text| serde_json::from_str::<Self>(text))?.unwrap_or_default())
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), io::Error> {
+        persist::write(path, serde_json::to_string_pretty(self)?)
+    }
+
+    /// Count a batch of `files` renamed with the options. Options left at their defaults
+    /// aren't counted as used.
+    pub fn record(&mut self, options: &[Options], files: usize) {
+        self.batches += 1;
+        self.files += files as u64;
+        for opt in options.iter().filter(|opt| in_use(opt)) {
+            *self.steps.entry(opt.step()).or_default() += 1;
+        }
+        let size = match files {
+            0..=1 => 0,
+            2..=9 => 1,
+            10..=99 => 2,
+            100..=999 => 3,
+            _ => 4,
+        };
+        self.sizes[size] += 1;
+    }
+
+    /// The steps used, most used first.
+    pub fn most_used(&self) -> Vec<(Step, u64)> {
+        let mut steps = self
+            .steps
+            .iter()
+            .map(|(step, count)| (*step, *count))
+            .collect::<Vec<_>>();
+        steps.sort_by_key(|(step, count)| (std::cmp::Reverse(*count), *step));
+        steps
+    }
+}
+
+/// Whether an option differs from its panel's defaults.
+fn in_use(option: &Options) -> bool {
+    let default = match option {
+        Options::Regex(_) => Options::Regex(Default::default()),
+        Options::Name(_) => Options::Name(Default::default()),
+        Options::Replace(_) => Options::Replace(Default::default()),
+        Options::Case(_) => Options::Case(Default::default()),
+        Options::Remove(_) => Options::Remove(Default::default()),
+        Options::Add(_) => Options::Add(Default::default()),
+        Options::Date(_) => Options::Date(Default::default()),
+        Options::Folder(_) => Options::Folder(Default::default()),
+        Options::Number(_) => Options::Number(Default::default()),
+        Options::Extension(_) => Options::Extension(Default::default()),
+        Options::Normalize(_) => Options::Normalize(Default::default()),
+        Options::Sanitize(_) => Options::Sanitize(Default::default()),
+        Options::Blacklist(_) => Options::Blacklist(Default::default()),
+        Options::Release(_) => Options::Release(Default::default()),
+        Options::Swap(_) => Options::Swap(Default::default()),
+        Options::Lookup(_) => Options::Lookup(Default::default()),
+        Options::Pair(_) => Options::Pair(Default::default()),
+        Options::Destination(_) => Options::Destination(Default::default()),
+        _ => return true,
+    };
+    serde_json::to_value(option).ok() != serde_json::to_value(default).ok()
+}
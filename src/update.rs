@@ -0,0 +1,93 @@
+//! Version information and an optional check for newer releases on GitHub.
+//!
+//! The check is only compiled in with the `update-check` feature and only runs when
+//! asked to, nothing is sent otherwise.
+
+use thiserror::Error;
+
+pub const VERSION: &str = env!("CARGO_PKG_VERSION");
+pub const RELEASES_URL: &str = "https://github.com/MrRobotMan/mass-renamer/releases";
+#[cfg(feature = "update-check")]
+const LATEST_URL: &str = "https://api.github.com/repos/MrRobotMan/mass-renamer/releases/latest";
+
+/// A published release.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Release {
+    pub version: String,
+    pub url: String,
+}
+
+#[derive(Debug, Error)]
+pub enum UpdateError {
+    #[error("This build can't check for updates.")]
+    Unsupported,
+    #[cfg(feature = "update-check")]
+    #[error(transparent)]
+    Http(#[from] Box<ureq::Error>),
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error("GitHub sent an unexpected response.")]
+    Response,
+}
+
+/// Version, build profile and target, e.g. `0.1.0 (release, linux x86_64)`.
+pub fn build_info() -> String {
+    let profile = if cfg!(debug_assertions) {
+        "debug"
+    } else {
+        "release"
+    };
+    format!(
+        "{VERSION} ({profile}, {} {})",
+        std::env::consts::OS,
+        std::env::consts::ARCH
+    )
+}
+
+/// Whether `latest` is a later version than `current`. Versions are compared number by
+/// number, so `v0.10.0` is later than `0.9.2`. Anything after a `-` is ignored.
+pub fn is_newer(latest: &str, current: &str) -> bool {
+    fn parts(version: &str) -> Vec<u64> {
+        version
+            .trim_start_matches('v')
+            .split('-')
+            .next()
+            .unwrap_or_default()
+            .split('.')
+            .map(|part| part.parse().unwrap_or(0))
+            .collect()
+    }
+    parts(latest) > parts(current)
+}
+
+/// Ask GitHub for the latest release. Returns it if it is newer than this build.
+#[cfg(feature = "update-check")]
+pub fn check() -> Result<Option<Release>, UpdateError> {
+    let response: serde_json::Value = ureq::get(LATEST_URL)
+        .set(
+            "User-Agent",
+            concat!("mass-renamer/", env!("CARGO_PKG_VERSION")),
+        )
+        .timeout(std::time::Duration::from_secs(10))
+        .call()
+        .map_err(Box::new)?
+        .into_json()?;
+    let field = |name| {
+        response
+            .get(name)
+            .and_then(|value: &serde_json::Value| value.as_str())
+            .map(String::from)
+            .ok_or(UpdateError::Response)
+    };
+    let release = Release {
+        version: field("tag_name")?,
+        url: field("html_url")?,
+    };
+    Ok(is_newer(&release.version, VERSION).then_some(release))
+}
+
+/// Ask GitHub for the latest release. Returns it if it is newer than this build.
+#[cfg(not(feature = "update-check"))]
+pub fn check() -> Result<Option<Release>, UpdateError> {
+    Err(UpdateError::Unsupported)
+}
@@ -0,0 +1,228 @@
+//! The command line, run as `mass_renamer --cli` would run it.
+
+mod common;
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use clap::Parser;
+use common::fixture;
+use mass_renamer::{
+    cli::{run, Cli},
+    history::RenameHistory,
+};
+
+fn names(dir: &Path) -> Vec<String> {
+    let mut names = fs::read_dir(dir)
+        .unwrap()
+        .flatten()
+        .map(|entry| entry.file_name().to_string_lossy().into_owned())
+        .collect::<Vec<_>>();
+    names.sort();
+    names
+}
+
+/// Run the command line on `dir`, keeping its history in a settings folder beside it,
+/// see [config_dir], rather than the real one. The folder is removed after unless the
+/// test made it.
+fn cli(dir: &Path, args: &[&str]) -> (bool, String) {
+    let config = config_dir(dir);
+    let made = config.exists();
+    let (dir, config_arg) = (dir.display().to_string(), config.display().to_string());
+    let command = ["--cli", dir.as_str(), "--config-dir", config_arg.as_str()];
+    let cli = Cli::try_parse_from(command.iter().chain(args)).unwrap();
+    let mut out = vec![];
+    let ok = run(&cli, &mut out).unwrap();
+    if !made {
+        fs::remove_dir_all(&config).ok();
+    }
+    (ok, String::from_utf8(out).unwrap())
+}
+
+/// The settings folder the tests on `dir` use, beside it.
+fn config_dir(dir: &Path) -> PathBuf {
+    let name = dir.file_name().unwrap_or_default().to_string_lossy();
+    dir.with_file_name(format!("{name}_config"))
+}
+
+#[test]
+fn previews_without_commit() {
+    let dir = fixture("preview", &["my_cat.JPG", "notes.txt"]);
+    let args = [
+        "--replace",
+        "_",
+        "--with",
+        " ",
+        "--case",
+        "title",
+        "--extension",
+        "lower",
+    ];
+    let (ok, out) = cli(&dir, &args);
+    let after = names(&dir);
+    fs::remove_dir_all(&dir).ok();
+    assert!(ok);
+    assert_eq!(
+        out,
+        "my_cat.JPG -> My Cat.jpg\nnotes.txt -> Notes.txt\n2 files would be renamed, add --commit to rename them\n"
+    );
+    assert_eq!(after, ["my_cat.JPG", "notes.txt"]);
+}
+
+#[test]
+fn commit_renames() {
+    let dir = fixture("commit", &["a.txt", "b.txt"]);
+    let (ok, out) = cli(&dir, &["--prefix", "new_", "--commit"]);
+    let after = names(&dir);
+    let (_, again) = cli(&dir, &["--remove-first", "0"]);
+    fs::remove_dir_all(&dir).ok();
+    assert!(ok);
+    assert!(out.ends_with("2 files renamed, 0 failed\n"));
+    assert_eq!(after, ["new_a.txt", "new_b.txt"]);
+    assert_eq!(again, "Nothing to rename\n");
+}
+
+#[test]
+fn commits_recorded_for_undo() {
+    let dir = fixture("recorded", &["a.txt"]);
+    let config = config_dir(&dir);
+    fs::create_dir_all(&config).unwrap();
+    fs::write(config.join("settings.json"), r#"{"audit": true}"#).unwrap();
+    let (ok, _) = cli(&dir, &["--prefix", "new_", "--commit"]);
+    let history = RenameHistory::load(config.join("history.json"));
+    let audit = fs::read_to_string(config.join("audit.jsonl"));
+    fs::remove_dir_all(&dir).ok();
+    fs::remove_dir_all(&config).ok();
+    assert!(ok);
+    assert!(history.unwrap().can_undo());
+    assert_eq!(audit.unwrap().lines().count(), 1);
+}
+
+#[test]
+fn bad_flags_are_refused() {
+    assert!(Cli::try_parse_from(["--cli", ".", "--case", "shouting"]).is_err());
+    assert!(Cli::try_parse_from(["--cli", ".", "--date-zone", "+15:00"]).is_err());
+    assert!(Cli::try_parse_from(["--cli", ".", "--insert", "x"]).is_err());
+}
+
+#[test]
+fn script_instead_of_renaming() {
+    let dir = fixture("script", &["it's.txt"]);
+    let (ok, out) = cli(&dir, &["--replace", "'", "--with", " i", "--script", "sh"]);
+    let after = names(&dir);
+    fs::remove_dir_all(&dir).ok();
+    assert!(ok);
+    let (from, to) = (dir.join("it's.txt"), dir.join("it is.txt"));
+    let line = format!(
+        "mv -- '{}' '{}'\n",
+        from.display().to_string().replace('\'', r"'\''"),
+        to.display()
+    );
+    assert!(out.starts_with("#!/bin/sh\n"));
+    assert!(out.ends_with(&line));
+    assert_eq!(after, ["it's.txt"]);
+    assert!(Cli::try_parse_from(["--cli", ".", "--script", "sh", "--commit"]).is_err());
+}
+
+#[test]
+fn links_follow_renames() {
+    let dir = fixture("links", &["a b.png", "index.md"]);
+    fs::write(dir.join("index.md"), "![a](a%20b.png)\n").unwrap();
+    let args = ["--replace", " ", "--with", "_", "--update-links", "md"];
+    let (_, preview) = cli(&dir, &args);
+    let (ok, out) = cli(&dir, &[&args[..], &["--commit"]].concat());
+    let text = fs::read_to_string(dir.join("index.md"));
+    fs::remove_dir_all(&dir).ok();
+    assert!(ok);
+    assert!(preview.contains("index.md:1: a%20b.png -> a_b.png\n1 links would be updated\n"));
+    assert!(out.ends_with("index.md:1: a%20b.png -> a_b.png\n1 links updated\n"));
+    assert_eq!(text.unwrap(), "![a](a_b.png)\n");
+}
+
+#[test]
+fn extensions_mapped() {
+    let dir = fixture("map", &["a.JPEG", "b.tif", "c.png"]);
+    let args = [
+        "--map-extension",
+        "jpeg=jpg",
+        "--map-extension",
+        "tif=tiff",
+        "--commit",
+    ];
+    let (ok, _) = cli(&dir, &args);
+    let after = names(&dir);
+    fs::remove_dir_all(&dir).ok();
+    assert!(ok);
+    assert_eq!(after, ["a.jpg", "b.tiff", "c.png"]);
+}
+
+#[test]
+fn naming_policy_enforced() {
+    let dir = fixture("policy", &["a b.txt", "c d.txt"]);
+    let rules = fixture("policy_rules", &[]);
+    let policy = rules.join("policy.txt");
+    fs::write(&policy, "charset = a-z_.\nforbidden = d\n").unwrap();
+    let policy = policy.display().to_string();
+    let args = [
+        "--replace",
+        " ",
+        "--with",
+        "_",
+        "--policy",
+        &policy,
+        "--commit",
+    ];
+    let (ok, out) = cli(&dir, &args);
+    let after = names(&dir);
+    fs::remove_dir_all(&dir).ok();
+    fs::remove_dir_all(&rules).ok();
+    assert!(!ok);
+    assert!(out.ends_with("c_d.txt contains \"d\"\n1 policy violations, nothing renamed\n"));
+    assert_eq!(after, ["a b.txt", "c d.txt"]);
+}
+
+#[test]
+fn collisions_refused_or_skipped() {
+    let dir = fixture("collisions", &["a.txt", "b.txt", "c.md"]);
+    let args = ["--name", "same", "--extension", "lower"];
+    let (refused, preview) = cli(&dir, &args);
+    let (ok, out) = cli(
+        &dir,
+        &[&args[..], &["--collisions", "skip", "--commit"]].concat(),
+    );
+    let after = names(&dir);
+    fs::remove_dir_all(&dir).ok();
+    assert!(!refused);
+    assert!(preview.contains("b.txt: same.txt is also the new name of"));
+    assert!(preview.ends_with(
+        "2 collisions, nothing renamed
+"
+    ));
+    assert!(ok);
+    assert!(out.contains(
+        "b.txt skipped
+"
+    ));
+    assert!(out.ends_with(
+        "2 files renamed, 0 failed
+"
+    ));
+    assert_eq!(after, ["b.txt", "same.md", "same.txt"]);
+}
+
+#[cfg(feature = "mapping")]
+#[test]
+fn rename_from_mapping() {
+    let dir = fixture("mapping", &["a.txt", "b.txt"]);
+    let table = dir.join("names.csv");
+    fs::write(&table, "a.txt,apple.txt\nmissing.txt,x.txt\n").unwrap();
+    let table = table.display().to_string();
+    let (ok, out) = cli(&dir, &["--mapping", &table, "--commit"]);
+    let after = names(&dir);
+    fs::remove_dir_all(&dir).ok();
+    assert!(ok);
+    assert!(out.starts_with("⛔ missing.txt: no such file\na.txt -> apple.txt\n"));
+    assert_eq!(after, ["apple.txt", "b.txt", "names.csv"]);
+}
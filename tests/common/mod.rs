@@ -0,0 +1,16 @@
+//! Helpers shared by the integration tests.
+
+use std::{env, fs, path::PathBuf, process};
+
+/// A fresh folder of files, each holding its own name so moves can be followed. The
+/// folder is unique to the test and to this run, so test binaries running at the same
+/// time, or the leftovers of another run, don't get in the way.
+pub fn fixture(name: &str, files: &[&str]) -> PathBuf {
+    let dir = env::temp_dir().join(format!("mass_renamer_{name}_{}", process::id()));
+    fs::remove_dir_all(&dir).ok();
+    fs::create_dir_all(&dir).unwrap();
+    for file in files {
+        fs::write(dir.join(file), file).unwrap();
+    }
+    dir
+}
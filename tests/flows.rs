@@ -0,0 +1,619 @@
+//! Whole batches driven through the GUI's [Renamer], as clicking through the window does:
+//! open a folder, tick files, fill in panels, preview, rename and undo.
+
+mod common;
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use common::fixture;
+use mass_renamer::{
+    audit::{self, Action},
+    batch::{self, CollisionPolicy},
+    directory::{Filter, Override},
+    file::{
+        AddOptions, Case, CaseOptions, NameOptions, NumberMode, NumberOptions, Options,
+        PairOptions, ReplaceOptions, Step, SwapMode, SwapOptions,
+    },
+    gui::Renamer,
+    history::RenameHistory,
+};
+
+/// A renamer showing `dir` with the `files` ticked.
+fn open(dir: &Path, files: &[&str]) -> Renamer {
+    let mut renamer = Renamer::default();
+    renamer.open(dir);
+    for file in files {
+        renamer.select(&dir.join(file), true);
+    }
+    renamer
+}
+
+/// The folder's files and what they hold, in name order.
+fn contents(dir: &Path) -> Vec<(String, String)> {
+    let mut files = fs::read_dir(dir)
+        .unwrap()
+        .flatten()
+        .map(|entry| {
+            let name = entry.file_name().to_string_lossy().into_owned();
+            (name, fs::read_to_string(entry.path()).unwrap_or_default())
+        })
+        .collect::<Vec<_>>();
+    files.sort();
+    files
+}
+
+/// Put the renamed files back, the reverse of the batch.
+fn undo(renamed: &[(PathBuf, PathBuf)]) {
+    let back = renamed
+        .iter()
+        .map(|(from, to)| (to, from))
+        .collect::<Vec<_>>();
+    assert!(batch::rename(&back).iter().all(Result::is_ok));
+}
+
+fn pairs(items: &[(&str, &str)]) -> Vec<(String, String)> {
+    items
+        .iter()
+        .map(|(name, text)| (name.to_string(), text.to_string()))
+        .collect()
+}
+
+#[test]
+fn rename_and_undo() {
+    let dir = fixture("rename", &["my_cat.jpg", "my_dog.jpg", "skip_me.jpg"]);
+    let mut renamer = open(&dir, &["my_cat.jpg", "my_dog.jpg"]);
+    renamer.load_option(Options::Replace(ReplaceOptions {
+        replace: "_".into(),
+        with: " ".into(),
+        case: true,
+    }));
+    renamer.load_option(Options::Case(CaseOptions {
+        case: Case::Title,
+        ..Default::default()
+    }));
+    renamer.preview();
+    let renamed = renamer.rename();
+    let after = contents(&dir);
+    undo(&renamed);
+    let restored = contents(&dir);
+    fs::remove_dir_all(&dir).ok();
+    assert!(renamer.failed().is_empty());
+    assert_eq!(
+        after,
+        pairs(&[
+            ("My Cat.jpg", "my_cat.jpg"),
+            ("My Dog.jpg", "my_dog.jpg"),
+            ("skip_me.jpg", "skip_me.jpg"),
+        ])
+    );
+    assert_eq!(
+        restored,
+        pairs(&[
+            ("my_cat.jpg", "my_cat.jpg"),
+            ("my_dog.jpg", "my_dog.jpg"),
+            ("skip_me.jpg", "skip_me.jpg"),
+        ])
+    );
+}
+
+#[test]
+fn renamed_files_stay_selected() {
+    let dir = fixture("selected", &["a_1.txt", "b_1.txt"]);
+    let mut renamer = open(&dir, &["a_1.txt"]);
+    renamer.load_option(Options::Replace(ReplaceOptions {
+        replace: "_1".into(),
+        with: "_2".into(),
+        case: true,
+    }));
+    renamer.preview();
+    renamer.rename();
+    renamer.preview();
+    let previewed = renamer.previewed();
+    fs::remove_dir_all(&dir).ok();
+    assert_eq!(previewed, [(dir.join("a_2.txt"), dir.join("a_2.txt"))]);
+}
+
+#[test]
+fn existing_names_are_not_overwritten() {
+    let dir = fixture("exists", &["a.txt", "b.txt", "ca.txt"]);
+    let mut renamer = open(&dir, &["a.txt", "ca.txt"]);
+    renamer.load_option(Options::Replace(ReplaceOptions {
+        replace: "a".into(),
+        with: "b".into(),
+        case: true,
+    }));
+    renamer.preview();
+    let conflicts = renamer.collisions().len();
+    let renamed = renamer.rename();
+    let after = contents(&dir);
+    fs::remove_dir_all(&dir).ok();
+    // The clash stops the whole batch, not just the file, until it is fixed.
+    assert_eq!(conflicts, 1);
+    assert!(renamed.is_empty());
+    assert_eq!(renamer.failed().len(), 1);
+    assert_eq!(
+        after,
+        pairs(&[("a.txt", "a.txt"), ("b.txt", "b.txt"), ("ca.txt", "ca.txt")])
+    );
+}
+
+#[test]
+fn batch_steps_and_undo() {
+    let files = ["a.CR3", "a.jpg", "b.CR3", "b.jpg"];
+    let dir = fixture("batch", &files);
+    let mut renamer = open(&dir, &files);
+    renamer.load_option(Options::Swap(SwapOptions {
+        exp: r"\.CR3$".into(),
+        mode: SwapMode::Swap,
+    }));
+    renamer.load_option(Options::Pair(PairOptions { keep: true }));
+    renamer.preview();
+    let renamed = renamer.rename();
+    let after = contents(&dir);
+    undo(&renamed);
+    let restored = contents(&dir);
+    fs::remove_dir_all(&dir).ok();
+    // The RAW files swap names, and each JPEG follows its RAW file.
+    assert_eq!(
+        after,
+        pairs(&[
+            ("a.CR3", "b.CR3"),
+            ("a.jpg", "b.jpg"),
+            ("b.CR3", "a.CR3"),
+            ("b.jpg", "a.jpg"),
+        ])
+    );
+    assert_eq!(
+        restored,
+        files.map(|name| (name.to_string(), name.to_string()))
+    );
+}
+
+#[test]
+fn deleted_files_are_marked_missing() {
+    let dir = fixture("missing", &["a_1.txt", "b_1.txt", "c_1.txt"]);
+    let mut renamer = open(&dir, &["a_1.txt", "b_1.txt", "c_1.txt"]);
+    renamer.load_option(Options::Replace(ReplaceOptions {
+        replace: "_1".into(),
+        with: "_2".into(),
+        case: true,
+    }));
+    renamer.preview();
+    fs::remove_file(dir.join("b_1.txt")).unwrap();
+    renamer.rename();
+    let after = contents(&dir);
+    let missing = renamer
+        .missing()
+        .into_iter()
+        .map(Path::to_path_buf)
+        .collect::<Vec<_>>();
+    let failed = renamer.failed().len();
+    renamer.remove_missing();
+    let cleared = renamer.missing().is_empty();
+    fs::remove_dir_all(&dir).ok();
+    assert_eq!(
+        after,
+        pairs(&[("a_2.txt", "a_1.txt"), ("c_2.txt", "c_1.txt")])
+    );
+    assert_eq!(missing, [dir.join("b_1.txt")]);
+    assert_eq!(failed, 1);
+    assert!(cleared);
+}
+
+#[test]
+fn repeated_batches_skip_renamed_files() {
+    let dir = fixture("repeated", &["a.txt", "b.txt"]);
+    let mut renamer = open(&dir, &["a.txt", "b.txt"]);
+    let add = AddOptions {
+        suffix: Some("_v2".into()),
+        ..Default::default()
+    };
+    renamer.load_option(Options::Add(add));
+    let first_time = renamer.repeated();
+    renamer.preview();
+    renamer.rename();
+    fs::write(dir.join("c.txt"), "c.txt").unwrap();
+    renamer.open(&dir);
+    renamer.select(&dir.join("c.txt"), true);
+    let second_time = renamer.repeated();
+    renamer.skip_repeated(true);
+    renamer.preview();
+    renamer.rename();
+    let after = contents(&dir);
+    fs::remove_dir_all(&dir).ok();
+    assert!(!first_time);
+    assert!(second_time);
+    assert_eq!(
+        after,
+        pairs(&[
+            ("a_v2.txt", "a.txt"),
+            ("b_v2.txt", "b.txt"),
+            ("c_v2.txt", "c.txt"),
+        ])
+    );
+}
+
+#[test]
+fn huge_batches_preview_over_several_frames() {
+    let dir = fixture("budget", &["a.txt", "b.txt", "c.txt"]);
+    let mut renamer = open(&dir, &["a.txt", "b.txt", "c.txt"]);
+    let add = AddOptions {
+        prefix: Some("new_".into()),
+        ..Default::default()
+    };
+    renamer.load_option(Options::Add(add));
+    // With no time to spare each call still previews one file.
+    renamer.preview_for(Duration::ZERO);
+    let first = renamer.progress();
+    renamer.preview_for(Duration::ZERO);
+    let second = renamer.progress();
+    // Renaming finishes the preview first.
+    renamer.rename();
+    let after = contents(&dir);
+    fs::remove_dir_all(&dir).ok();
+    assert_eq!(first, (1, 3));
+    assert_eq!(second, (2, 3));
+    assert_eq!(
+        after,
+        pairs(&[
+            ("new_a.txt", "a.txt"),
+            ("new_b.txt", "b.txt"),
+            ("new_c.txt", "c.txt"),
+        ])
+    );
+}
+
+#[test]
+fn custom_sort_sets_the_numbering_order() {
+    let dir = fixture("sort", &["b_10.jpg", "a_2.png", "b_9.jpg"]);
+    let mut renamer = open(&dir, &["b_10.jpg", "a_2.png", "b_9.jpg"]);
+    let unknown = renamer.set_sort("extension, colour").is_err();
+    renamer.set_sort("ext, then number").unwrap();
+    let order = |renamer: &mut Renamer| {
+        renamer.preview();
+        renamer
+            .previewed()
+            .into_iter()
+            .map(|(from, _)| from)
+            .collect::<Vec<_>>()
+    };
+    let sorted = order(&mut renamer);
+    // The order is kept when the folder is read again.
+    renamer.open(&dir);
+    let reopened = order(&mut renamer);
+    fs::remove_dir_all(&dir).ok();
+    assert!(unknown);
+    assert_eq!(
+        sorted,
+        ["b_9.jpg", "b_10.jpg", "a_2.png"].map(|file| dir.join(file))
+    );
+    assert_eq!(reopened, sorted);
+}
+
+#[test]
+fn protected_folders_need_an_override() {
+    let dir = fixture("protected", &["a.txt"]);
+    let mut renamer = open(&dir, &["a.txt"]);
+    renamer.protect(&dir, true);
+    let add = AddOptions {
+        prefix: Some("new_".into()),
+        ..Default::default()
+    };
+    renamer.load_option(Options::Add(add));
+    renamer.preview();
+    let refused = renamer.rename();
+    let failed = renamer.failed().len();
+    renamer.allow_protected(true);
+    renamer.preview();
+    renamer.rename();
+    let after = contents(&dir);
+    fs::remove_dir_all(&dir).ok();
+    assert!(refused.is_empty());
+    assert_eq!(failed, 1);
+    assert_eq!(after, pairs(&[("new_a.txt", "a.txt")]));
+}
+
+#[test]
+fn history_undoes_and_redoes_batches() {
+    let dir = fixture("history", &["a.txt", "b.txt"]);
+    let mut renamer = open(&dir, &["a.txt", "b.txt"]);
+    let add = AddOptions {
+        prefix: Some("new_".into()),
+        ..Default::default()
+    };
+    renamer.load_option(Options::Add(add));
+    renamer.preview();
+    let renamed = renamer.rename();
+    let undone = renamer.undo();
+    let after_undo = contents(&dir);
+    let redone = renamer.redo();
+    let after_redo = contents(&dir);
+    // A restarted app reads the history back, and can still undo the batch.
+    let journal = dir.join("history.json");
+    let mut history = RenameHistory::default();
+    history.record(&renamed);
+    history.save(&journal).unwrap();
+    let mut history = RenameHistory::load(&journal).unwrap();
+    fs::remove_file(&journal).unwrap();
+    history.undo(Default::default(), &Default::default());
+    let after_restart = contents(&dir);
+    fs::remove_dir_all(&dir).ok();
+    assert_eq!(undone.len(), 2);
+    assert_eq!(redone, renamed);
+    assert!(renamer.can_undo() && !renamer.can_redo());
+    assert_eq!(after_undo, pairs(&[("a.txt", "a.txt"), ("b.txt", "b.txt")]));
+    assert_eq!(
+        after_redo,
+        pairs(&[("new_a.txt", "a.txt"), ("new_b.txt", "b.txt")])
+    );
+    assert_eq!(after_restart, after_undo);
+}
+
+#[test]
+fn summary_reads_out_the_batch() {
+    let dir = fixture("summary", &["a.txt", "b.txt", "c.txt"]);
+    let mut renamer = open(&dir, &["a.txt", "b.txt", "c.txt"]);
+    let replace = ReplaceOptions {
+        replace: "a".into(),
+        with: "b".into(),
+        case: true,
+    };
+    renamer.load_option(Options::Replace(replace));
+    renamer.preview();
+    let summary = renamer.summary().to_string();
+    fs::remove_dir_all(&dir).ok();
+    assert_eq!(
+        summary,
+        "1 file will be renamed, 2 unchanged, 1 conflict, extensions unchanged"
+    );
+}
+
+#[test]
+fn clashing_names_can_be_numbered() {
+    let dir = fixture("numbered", &["a.txt", "b.txt", "c.txt"]);
+    let mut renamer = open(&dir, &["a.txt", "b.txt", "c.txt"]);
+    renamer.set_collisions(CollisionPolicy::Number);
+    renamer.load_option(Options::Name(NameOptions::Fixed("b".into())));
+    renamer.preview();
+    let conflicts = renamer.collisions().len();
+    renamer.rename();
+    let after = contents(&dir);
+    fs::remove_dir_all(&dir).ok();
+    assert_eq!(conflicts, 0);
+    assert_eq!(
+        after,
+        pairs(&[
+            ("b (2).txt", "a.txt"),
+            ("b (3).txt", "c.txt"),
+            ("b.txt", "b.txt")
+        ])
+    );
+}
+
+#[test]
+fn clashing_names_can_be_skipped_or_overwritten() {
+    let dir = fixture("skipped", &["a.txt", "b.txt", "c.txt", "x.txt"]);
+    let mut renamer = open(&dir, &["a.txt", "b.txt"]);
+    renamer.set_collisions(CollisionPolicy::Skip);
+    renamer.load_option(Options::Name(NameOptions::Fixed("x".into())));
+    renamer.preview();
+    let renamed = renamer.rename();
+    let skipped = renamer.skipped().to_vec();
+    let after_skip = contents(&dir);
+    let mut renamer = open(&dir, &["c.txt"]);
+    renamer.set_collisions(CollisionPolicy::Overwrite);
+    renamer.load_option(Options::Name(NameOptions::Fixed("x".into())));
+    renamer.preview();
+    renamer.rename();
+    let after_overwrite = contents(&dir);
+    fs::remove_dir_all(&dir).ok();
+    assert!(renamed.is_empty());
+    assert_eq!(skipped, [dir.join("a.txt"), dir.join("b.txt")]);
+    assert_eq!(after_skip.len(), 4);
+    assert_eq!(
+        after_overwrite,
+        pairs(&[("a.txt", "a.txt"), ("b.txt", "b.txt"), ("x.txt", "c.txt")])
+    );
+}
+
+#[test]
+fn names_against_the_policy_are_not_renamed() {
+    let dir = fixture("policy", &["a.txt", "b.txt"]);
+    let policy = dir.with_extension("policy");
+    fs::write(&policy, "max_length = 8\n").unwrap();
+    let mut renamer = open(&dir, &["a.txt", "b.txt"]);
+    renamer.set_policy(Some(policy.clone()));
+    renamer.load_option(Options::Add(AddOptions {
+        prefix: Some("long_".into()),
+        ..Default::default()
+    }));
+    renamer.preview();
+    let violations = renamer.violations().len();
+    let renamed = renamer.rename();
+    let after = contents(&dir);
+    fs::remove_dir_all(&dir).ok();
+    fs::remove_file(&policy).ok();
+    assert_eq!(violations, 2);
+    assert!(renamed.is_empty());
+    assert_eq!(after, pairs(&[("a.txt", "a.txt"), ("b.txt", "b.txt")]));
+}
+
+#[test]
+fn scoped_steps_leave_other_extensions() {
+    let dir = fixture("scope", &["a.jpg", "a.xmp"]);
+    let mut renamer = open(&dir, &["a.jpg", "a.xmp"]);
+    renamer.set_scope(Step::Add, ".jpg");
+    renamer.load_option(Options::Add(AddOptions {
+        prefix: Some("x_".into()),
+        ..Default::default()
+    }));
+    renamer.preview();
+    renamer.rename();
+    let after = contents(&dir);
+    fs::remove_dir_all(&dir).ok();
+    assert_eq!(after, pairs(&[("a.xmp", "a.xmp"), ("x_a.jpg", "a.jpg")]));
+}
+
+#[test]
+fn filtered_out_files_are_not_renamed() {
+    let dir = fixture("filter", &["a.jpg", "b.png", "c.txt"]);
+    let mut renamer = open(&dir, &["a.jpg", "b.png", "c.txt"]);
+    renamer
+        .set_filter(Filter {
+            mask: "*.jpg;*.png".into(),
+            ..Default::default()
+        })
+        .unwrap();
+    renamer.load_option(Options::Add(AddOptions {
+        prefix: Some("x_".into()),
+        ..Default::default()
+    }));
+    renamer.preview();
+    renamer.rename();
+    let after = contents(&dir);
+    fs::remove_dir_all(&dir).ok();
+    assert_eq!(
+        after,
+        pairs(&[
+            ("c.txt", "c.txt"),
+            ("x_a.jpg", "a.jpg"),
+            ("x_b.png", "b.png")
+        ])
+    );
+}
+
+#[test]
+fn overrides_outlast_option_and_filter_changes() {
+    let dir = fixture("overrides", &["a.jpg", "b.jpg", "c.jpg"]);
+    let mut renamer = open(&dir, &["a.jpg", "b.jpg", "c.jpg"]);
+    renamer.set_override(
+        &dir.join("a.jpg"),
+        Some(Override::Name("manual.jpg".into())),
+    );
+    renamer.set_override(&dir.join("b.jpg"), Some(Override::Exclude));
+    renamer
+        .set_filter(Filter {
+            mask: "*.jpg".into(),
+            ..Default::default()
+        })
+        .unwrap();
+    renamer.load_option(Options::Add(AddOptions {
+        prefix: Some("x_".into()),
+        ..Default::default()
+    }));
+    renamer.preview();
+    renamer.rename();
+    let after = contents(&dir);
+    let left = renamer.overrides().len();
+    fs::remove_dir_all(&dir).ok();
+    assert_eq!(
+        after,
+        pairs(&[
+            ("b.jpg", "b.jpg"),
+            ("manual.jpg", "a.jpg"),
+            ("x_c.jpg", "c.jpg")
+        ])
+    );
+    // The renamed file's override is done with, the excluded file keeps its own.
+    assert_eq!(left, 1);
+}
+
+#[test]
+fn audit_log_only_grows() {
+    let dir = fixture("audit", &[]);
+    let log = dir.join("logs").join("audit.jsonl");
+    let first = [(dir.join("a.txt"), dir.join("b.txt"))];
+    let second = [
+        (dir.join("b.txt"), dir.join("a.txt")),
+        (dir.join("c.txt"), dir.join("d.txt")),
+    ];
+    audit::append(&log, Action::Rename, &first).unwrap();
+    audit::append(&log, Action::Undo, &second).unwrap();
+    audit::append(&log, Action::Redo, &[]).unwrap();
+    let entries = audit::read(&log).unwrap();
+    fs::remove_dir_all(&dir).ok();
+    let moves = entries
+        .iter()
+        .map(|entry| (entry.action, entry.old.clone(), entry.new.clone()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        moves,
+        [
+            (Action::Rename, first[0].0.clone(), first[0].1.clone()),
+            (Action::Undo, second[0].0.clone(), second[0].1.clone()),
+            (Action::Undo, second[1].0.clone(), second[1].1.clone()),
+        ]
+    );
+    assert_ne!(entries[0].batch, entries[1].batch);
+    assert_eq!(entries[1].batch, entries[2].batch);
+}
+
+#[test]
+fn numbering_continues_only_when_chosen() {
+    let dir = fixture("continue_numbering", &[]);
+    for (folder, files) in [("one", ["a.txt", "b.txt"]), ("two", ["c.txt", "d.txt"])] {
+        fs::create_dir(dir.join(folder)).unwrap();
+        for file in files {
+            fs::write(dir.join(folder).join(file), file).unwrap();
+        }
+    }
+    let names = |folder: &str| {
+        let files = contents(&dir.join(folder));
+        files.into_iter().map(|(name, _)| name).collect::<Vec<_>>()
+    };
+    let mut renamer = Renamer::default();
+    renamer.load_option(Options::Number(NumberOptions {
+        mode: NumberMode::Prefix,
+        value: 1,
+        sep: "_".into(),
+        ..Default::default()
+    }));
+    let rename_all = |renamer: &mut Renamer, folder: &str| {
+        renamer.open(dir.join(folder));
+        for name in names(folder) {
+            renamer.select(&dir.join(folder).join(name), true);
+        }
+        renamer.rename();
+    };
+    renamer.continue_numbering(true);
+    rename_all(&mut renamer, "one");
+    rename_all(&mut renamer, "two");
+    assert_eq!(names("one"), ["1_a.txt", "2_b.txt"]);
+    assert_eq!(names("two"), ["3_c.txt", "4_d.txt"]);
+    // Each folder starts from the start set by hand again.
+    renamer.continue_numbering(false);
+    rename_all(&mut renamer, "one");
+    rename_all(&mut renamer, "two");
+    let (one, two) = (names("one"), names("two"));
+    fs::remove_dir_all(&dir).ok();
+    assert_eq!(one, ["1_1_a.txt", "2_2_b.txt"]);
+    assert_eq!(two, ["1_3_c.txt", "2_4_d.txt"]);
+}
+
+#[test]
+fn dropped_folders_select_their_files() {
+    let dir = fixture("drop", &["x.txt", "y.txt"]);
+    let photos = dir.join("photos");
+    fs::create_dir(&photos).unwrap();
+    for file in ["a.jpg", "b.jpg"] {
+        fs::write(photos.join(file), file).unwrap();
+    }
+    let mut renamer = Renamer::default();
+    renamer.drop_paths(std::slice::from_ref(&photos));
+    let from_folder = renamer.previewed();
+    renamer.drop_paths(&[dir.join("x.txt")]);
+    let from_file = renamer.previewed();
+    fs::remove_dir_all(&dir).ok();
+    let originals = |previewed: Vec<(PathBuf, PathBuf)>| {
+        let names = previewed
+            .into_iter()
+            .map(|(from, _)| from.file_name().unwrap().to_owned());
+        names.collect::<Vec<_>>()
+    };
+    assert_eq!(originals(from_folder), ["a.jpg", "b.jpg"]);
+    assert_eq!(originals(from_file), ["x.txt"]);
+}